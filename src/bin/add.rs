@@ -0,0 +1,83 @@
+use cargo::core::Workspace;
+use cargo::core::dependency::Kind;
+use cargo::ops;
+use cargo::util::{CliResult, Config};
+use cargo::util::important_paths::find_root_manifest_for_wd;
+
+#[derive(Deserialize)]
+pub struct Options {
+    flag_package: Option<String>,
+    flag_features: Vec<String>,
+    flag_dev: bool,
+    flag_build: bool,
+    flag_optional: bool,
+    flag_target: Option<String>,
+    flag_manifest_path: Option<String>,
+    flag_verbose: u32,
+    flag_quiet: Option<bool>,
+    flag_color: Option<String>,
+    flag_frozen: bool,
+    flag_locked: bool,
+    arg_crate: String,
+}
+
+pub const USAGE: &'static str = "
+Add a dependency to a Cargo.toml manifest file
+
+Usage:
+    cargo add [options] <crate>
+    cargo add -h | --help
+
+Options:
+    -h, --help                   Print this message
+    -p SPEC, --package SPEC      Package to add the dependency to
+    --features FEATURES          Comma-separated list of features to enable
+    --dev                        Add as a development dependency
+    --build                      Add as a build dependency
+    --optional                   Mark the dependency as optional
+    --target TRIPLE              Add as a dependency of the given target platform
+    --manifest-path PATH         Path to the manifest to add the dependency to
+    -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
+    -q, --quiet                  No output printed to stdout
+    --color WHEN                 Coloring: auto, always, never
+    --frozen                     Require Cargo.lock and cache are up to date
+    --locked                     Require Cargo.lock is up to date
+
+The <crate> is the name of a crate on crates.io, optionally followed by `@`
+and a version requirement, such as `serde@1.0`. Without a version requirement
+the latest version of the crate is added.
+
+The manifest is edited in place, keeping its formatting and comments.
+";
+
+pub fn execute(options: Options, config: &Config) -> CliResult {
+    config.configure(options.flag_verbose,
+                     options.flag_quiet,
+                     &options.flag_color,
+                     options.flag_frozen,
+                     options.flag_locked)?;
+    let root = find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+    let ws = Workspace::new(&root, config)?;
+
+    let kind = if options.flag_dev {
+        Kind::Development
+    } else if options.flag_build {
+        Kind::Build
+    } else {
+        Kind::Normal
+    };
+    let features = options.flag_features.iter().flat_map(|s| {
+        s.split(|c: char| c == ',' || c == ' ')
+    }).filter(|s| !s.is_empty()).map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let opts = ops::AddOptions {
+        spec: options.flag_package.as_ref().map(|s| &s[..]),
+        krate: &options.arg_crate,
+        features: &features,
+        kind: kind,
+        optional: options.flag_optional,
+        target: options.flag_target.as_ref().map(|s| &s[..]),
+    };
+    ops::add(&ws, &opts)?;
+    Ok(())
+}
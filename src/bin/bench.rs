@@ -17,6 +17,7 @@ pub struct Options {
     flag_quiet: Option<bool>,
     flag_color: Option<String>,
     flag_message_format: MessageFormat,
+    flag_timings: bool,
     flag_lib: bool,
     flag_bin: Vec<String>,
     flag_bins: bool,
@@ -27,6 +28,7 @@ pub struct Options {
     flag_bench: Vec<String>,
     flag_benches: bool,
     flag_no_fail_fast: bool,
+    flag_profile: Option<String>,
     flag_frozen: bool,
     flag_locked: bool,
     arg_args: Vec<String>,
@@ -59,12 +61,14 @@ Options:
     --features FEATURES          Space-separated list of features to also build
     --all-features               Build all available features
     --no-default-features        Do not build the `default` feature
+    --profile NAME               Build artifacts with the specified custom profile
     --target TRIPLE              Build for the target triple
     --manifest-path PATH         Path to the manifest to build benchmarks for
     -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet                  No output printed to stdout
     --color WHEN                 Coloring: auto, always, never
     --message-format FMT         Error format: human, json [default: human]
+    --timings                    Write a report of the time spent on each unit
     --no-fail-fast               Run all benchmarks regardless of failure
     --frozen                     Require Cargo.lock and cache are up to date
     --locked                     Require Cargo.lock is up to date
@@ -99,6 +103,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                      &options.flag_color,
                      options.flag_frozen,
                      options.flag_locked)?;
+    let target = options.flag_target.map_or_else(Vec::new, |t| vec![t]);
     let ops = ops::TestOptions {
         no_run: options.flag_no_run,
         no_fail_fast: options.flag_no_fail_fast,
@@ -106,12 +111,13 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         compile_opts: ops::CompileOptions {
             config: config,
             jobs: options.flag_jobs,
-            target: options.flag_target.as_ref().map(|s| &s[..]),
+            target: &target,
             features: &options.flag_features,
             all_features: options.flag_all_features,
             no_default_features: options.flag_no_default_features,
             spec: spec,
-            release: true,
+            release: options.flag_profile.is_none(),
+            profile: options.flag_profile.as_ref().map(|s| &s[..]),
             mode: ops::CompileMode::Bench,
             filter: ops::CompileFilter::new(options.flag_lib,
                                             &options.flag_bin, options.flag_bins,
@@ -119,6 +125,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                                             &options.flag_example, options.flag_examples,
                                             &options.flag_bench, options.flag_benches,),
             message_format: options.flag_message_format,
+            timings: options.flag_timings,
             target_rustdoc_args: None,
             target_rustc_args: None,
         },
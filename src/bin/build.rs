@@ -12,13 +12,15 @@ pub struct Options {
     flag_features: Vec<String>,
     flag_all_features: bool,
     flag_no_default_features: bool,
-    flag_target: Option<String>,
+    flag_target: Vec<String>,
     flag_manifest_path: Option<String>,
     flag_verbose: u32,
     flag_quiet: Option<bool>,
     flag_color: Option<String>,
     flag_message_format: MessageFormat,
+    flag_timings: bool,
     flag_release: bool,
+    flag_profile: Option<String>,
     flag_lib: bool,
     flag_bin: Vec<String>,
     flag_bins: bool,
@@ -56,15 +58,17 @@ Options:
     --bench NAME                 Build only the specified bench target
     --benches                    Build all benches
     --release                    Build artifacts in release mode, with optimizations
+    --profile NAME               Build artifacts with the specified custom profile
     --features FEATURES          Space-separated list of features to also build
     --all-features               Build all available features
     --no-default-features        Do not build the `default` feature
-    --target TRIPLE              Build for the target triple
+    --target TRIPLE ...          Build for the target triples
     --manifest-path PATH         Path to the manifest to compile
     -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet                  No output printed to stdout
     --color WHEN                 Coloring: auto, always, never
     --message-format FMT         Error format: human, json [default: human]
+    --timings                    Write a report of the time spent on each unit
     --frozen                     Require Cargo.lock and cache are up to date
     --locked                     Require Cargo.lock is up to date
 
@@ -79,7 +83,8 @@ Note that `--exclude` has to be specified in conjunction with the `--all` flag.
 
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
-the --release flag will use the `release` profile instead.
+the --release flag will use the `release` profile instead. Any other profile
+defined in the manifest can be selected with `--profile NAME`.
 ";
 
 pub fn execute(options: Options, config: &Config) -> CliResult {
@@ -100,19 +105,21 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
     let opts = CompileOptions {
         config: config,
         jobs: options.flag_jobs,
-        target: options.flag_target.as_ref().map(|t| &t[..]),
+        target: &options.flag_target,
         features: &options.flag_features,
         all_features: options.flag_all_features,
         no_default_features: options.flag_no_default_features,
         spec: spec,
         mode: ops::CompileMode::Build,
         release: options.flag_release,
+        profile: options.flag_profile.as_ref().map(|s| &s[..]),
         filter: ops::CompileFilter::new(options.flag_lib,
                                         &options.flag_bin, options.flag_bins,
                                         &options.flag_test, options.flag_tests,
                                         &options.flag_example, options.flag_examples,
                                         &options.flag_bench, options.flag_benches,),
         message_format: options.flag_message_format,
+        timings: options.flag_timings,
         target_rustdoc_args: None,
         target_rustc_args: None,
     };
@@ -50,6 +50,8 @@ Options:
     --color WHEN        Coloring: auto, always, never
     --frozen            Require Cargo.lock and cache are up to date
     --locked            Require Cargo.lock is up to date
+    --config CONFIG     Override a configuration value, given as KEY=VALUE
+                        (e.g. `build.jobs=4`) or as the path to a config file
 
 Some common cargo commands are (see all commands with --list):
     build       Compile the current project
@@ -88,22 +90,25 @@ fn main() {
                 })
             })
             .collect());
-        let rest = &args;
-        cargo::call_main_without_stdin(execute, &config, USAGE, rest, true)
+        let (cli_config, rest) = split_config_args(args);
+        config.set_cli_config(&cli_config)?;
+        cargo::call_main_without_stdin(execute, &config, USAGE, &rest, true)
     })();
 
     match result {
         Err(e) => cargo::exit_with_error(e, &mut *config.shell()),
-        Ok(()) => {}
+        Ok(()) => cargo::ops::auto_gc(&config),
     }
 }
 
 macro_rules! each_subcommand{
     ($mac:ident) => {
+        $mac!(add);
         $mac!(bench);
         $mac!(build);
         $mac!(check);
         $mac!(clean);
+        $mac!(config);
         $mac!(doc);
         $mac!(fetch);
         $mac!(generate_lockfile);
@@ -120,13 +125,16 @@ macro_rules! each_subcommand{
         $mac!(pkgid);
         $mac!(publish);
         $mac!(read_manifest);
+        $mac!(remove);
         $mac!(run);
         $mac!(rustc);
         $mac!(rustdoc);
         $mac!(search);
         $mac!(test);
+        $mac!(tree);
         $mac!(uninstall);
         $mac!(update);
+        $mac!(vendor);
         $mac!(verify_project);
         $mac!(version);
         $mac!(yank);
@@ -213,7 +221,7 @@ fn execute(flags: Flags, config: &Config) -> CliResult {
             default_alias.insert("b", "build".to_string());
             default_alias.insert("t", "test".to_string());
             default_alias.insert("r", "run".to_string());
-            let mut args: Vec<String> = env::args().collect();
+            let (_, mut args) = split_config_args(env::args().collect());
             if let Some(new_command) = default_alias.get(&args[1][..]) {
                 args[1] = new_command.clone();
             }
@@ -246,6 +254,33 @@ fn execute(flags: Flags, config: &Config) -> CliResult {
     execute_external_subcommand(config, &args[1], &args)
 }
 
+/// Splits the global `--config` arguments, which come before the subcommand,
+/// off the rest of the command line. Everything from the subcommand on is
+/// left to the subcommand, even if it looks like a `--config` argument.
+fn split_config_args(args: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut config = Vec::new();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    rest.extend(args.next());
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            config.extend(args.next());
+        } else if arg.starts_with("--config=") {
+            config.push(arg["--config=".len()..].to_string());
+        } else if arg == "--color" || arg == "--explain" {
+            rest.push(arg);
+            rest.extend(args.next());
+        } else if arg.starts_with('-') && arg != "--" {
+            rest.push(arg);
+        } else {
+            rest.push(arg);
+            rest.extend(args);
+            break
+        }
+    }
+    (config, rest)
+}
+
 fn try_execute_builtin_command(config: &Config, args: &[String]) -> Option<CliResult> {
     macro_rules! cmd {
         ($name:ident) => (if args[1] == stringify!($name).replace("_", "-") {
@@ -27,15 +27,17 @@ Options:
     --bench NAME                 Check only the specified bench target
     --benches                    Check all benches
     --release                    Check artifacts in release mode, with optimizations
+    --profile NAME               Check artifacts with the specified custom profile
     --features FEATURES          Space-separated list of features to also check
     --all-features               Check all available features
     --no-default-features        Do not check the `default` feature
-    --target TRIPLE              Check for the target triple
+    --target TRIPLE ...          Check for the target triples
     --manifest-path PATH         Path to the manifest to compile
     -v, --verbose ...            Use verbose output
     -q, --quiet                  No output printed to stdout
     --color WHEN                 Coloring: auto, always, never
     --message-format FMT         Error format: human, json [default: human]
+    --timings                    Write a report of the time spent on each unit
     --frozen                     Require Cargo.lock and cache are up to date
     --locked                     Require Cargo.lock is up to date
 
@@ -46,7 +48,8 @@ current package is built. For more information on SPEC and its format, see the
 
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
-the --release flag will use the `release` profile instead.
+the --release flag will use the `release` profile instead. Any other profile
+defined in the manifest can be selected with `--profile NAME`.
 ";
 
 #[derive(Deserialize)]
@@ -56,13 +59,15 @@ pub struct Options {
     flag_features: Vec<String>,
     flag_all_features: bool,
     flag_no_default_features: bool,
-    flag_target: Option<String>,
+    flag_target: Vec<String>,
     flag_manifest_path: Option<String>,
     flag_verbose: u32,
     flag_quiet: Option<bool>,
     flag_color: Option<String>,
     flag_message_format: MessageFormat,
+    flag_timings: bool,
     flag_release: bool,
+    flag_profile: Option<String>,
     flag_lib: bool,
     flag_bin: Vec<String>,
     flag_bins: bool,
@@ -98,19 +103,21 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
     let opts = CompileOptions {
         config: config,
         jobs: options.flag_jobs,
-        target: options.flag_target.as_ref().map(|t| &t[..]),
+        target: &options.flag_target,
         features: &options.flag_features,
         all_features: options.flag_all_features,
         no_default_features: options.flag_no_default_features,
         spec: spec,
         mode: ops::CompileMode::Check,
         release: options.flag_release,
+        profile: options.flag_profile.as_ref().map(|s| &s[..]),
         filter: ops::CompileFilter::new(options.flag_lib,
                                         &options.flag_bin, options.flag_bins,
                                         &options.flag_test, options.flag_tests,
                                         &options.flag_example, options.flag_examples,
                                         &options.flag_bench, options.flag_benches,),
         message_format: options.flag_message_format,
+        timings: options.flag_timings,
         target_rustdoc_args: None,
         target_rustc_args: None,
     };
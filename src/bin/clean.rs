@@ -7,6 +7,9 @@ use cargo::util::important_paths::{find_root_manifest_for_wd};
 
 #[derive(Deserialize)]
 pub struct Options {
+    cmd_gc: bool,
+    flag_max_age: Option<String>,
+    flag_max_size: Option<String>,
     flag_package: Vec<String>,
     flag_target: Option<String>,
     flag_manifest_path: Option<String>,
@@ -23,6 +26,7 @@ Remove artifacts that cargo has generated in the past
 
 Usage:
     cargo clean [options]
+    cargo clean gc [options]
 
 Options:
     -h, --help                   Print this message
@@ -30,6 +34,8 @@ Options:
     --manifest-path PATH         Path to the manifest to the package to clean
     --target TRIPLE              Target triple to clean output for (default all)
     --release                    Whether or not to clean release artifacts
+    --max-age AGE                With `gc`, remove cache entries unused for AGE
+    --max-size SIZE              With `gc`, shrink the cache to at most SIZE
     -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet                  No output printed to stdout
     --color WHEN                 Coloring: auto, always, never
@@ -40,6 +46,13 @@ If the --package argument is given, then SPEC is a package id specification
 which indicates which package's artifacts should be cleaned out. If it is not
 given, then all packages' artifacts are removed. For more information on SPEC
 and its format, see the `cargo help pkgid` command.
+
+`cargo clean gc` instead cleans the global package cache in CARGO_HOME, which
+holds downloaded crates and git repositories. Entries which haven't been used
+for AGE (such as `30d` or `12h`) are removed, and then the least recently used
+entries until the cache takes up at most SIZE (such as `5GB` or `500MiB`). If
+neither is given, the `gc.max-age` and `gc.max-size` configuration is used,
+removing entries unused for 90 days by default.
 ";
 
 pub fn execute(options: Options, config: &Config) -> CliResult {
@@ -50,6 +63,26 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                      options.flag_frozen,
                      options.flag_locked)?;
 
+    if options.cmd_gc {
+        let opts = if options.flag_max_age.is_none() &&
+                      options.flag_max_size.is_none() {
+            ops::GcOptions::from_config(config)?
+        } else {
+            ops::GcOptions {
+                max_age: match options.flag_max_age {
+                    Some(ref age) => Some(ops::parse_age(age)?),
+                    None => None,
+                },
+                max_size: match options.flag_max_size {
+                    Some(ref size) => Some(ops::parse_size(size)?),
+                    None => None,
+                },
+            }
+        };
+        ops::gc(config, &opts)?;
+        return Ok(())
+    }
+
     let root = find_root_manifest_for_wd(options.flag_manifest_path, config.cwd())?;
     let opts = ops::CleanOptions {
         config: config,
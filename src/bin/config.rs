@@ -0,0 +1,52 @@
+use cargo::ops;
+use cargo::util::{CliResult, Config};
+
+#[derive(Deserialize)]
+pub struct Options {
+    flag_verbose: u32,
+    flag_quiet: Option<bool>,
+    flag_color: Option<String>,
+    flag_frozen: bool,
+    flag_locked: bool,
+    flag_format: ops::ConfigFormat,
+    flag_show_origin: bool,
+    arg_key: Option<String>,
+}
+
+pub const USAGE: &'static str = "
+Inspect configuration values
+
+Usage:
+    cargo config get [options] [<key>]
+    cargo config -h | --help
+
+Options:
+    -h, --help               Print this message
+    --format FMT             Output format: toml, json [default: toml]
+    --show-origin            Display where each value was defined
+    -v, --verbose ...        Use verbose output (-vv very verbose/build.rs output)
+    -q, --quiet              No output printed to stdout
+    --color WHEN             Coloring: auto, always, never
+    --frozen                 Require Cargo.lock and cache are up to date
+    --locked                 Require Cargo.lock is up to date
+
+The `get` subcommand prints the configuration merged from all the config files
+and `--config` arguments, or only the value of <key> (such as `build.jobs`) and
+the values below it. Configuration set through `CARGO_*` environment variables
+is not included.
+";
+
+pub fn execute(options: Options, config: &Config) -> CliResult {
+    config.configure(options.flag_verbose,
+                     options.flag_quiet,
+                     &options.flag_color,
+                     options.flag_frozen,
+                     options.flag_locked)?;
+    let opts = ops::GetConfigOptions {
+        key: options.arg_key.as_ref().map(|s| &s[..]),
+        format: options.flag_format,
+        show_origin: options.flag_show_origin,
+    };
+    ops::get_config(config, &opts)?;
+    Ok(())
+}
@@ -5,7 +5,7 @@ use cargo::util::important_paths::{find_root_manifest_for_wd};
 
 #[derive(Deserialize)]
 pub struct Options {
-    flag_target: Option<String>,
+    flag_target: Vec<String>,
     flag_features: Vec<String>,
     flag_all_features: bool,
     flag_jobs: Option<u32>,
@@ -47,7 +47,7 @@ Options:
     --features FEATURES          Space-separated list of features to also build
     --all-features               Build all available features
     --no-default-features        Do not build the `default` feature
-    --target TRIPLE              Build for the target triple
+    --target TRIPLE ...          Build for the target triples
     --manifest-path PATH         Path to the manifest to document
     -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet                  No output printed to stdout
@@ -89,7 +89,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         compile_opts: ops::CompileOptions {
             config: config,
             jobs: options.flag_jobs,
-            target: options.flag_target.as_ref().map(|t| &t[..]),
+            target: &options.flag_target,
             features: &options.flag_features,
             all_features: options.flag_all_features,
             no_default_features: options.flag_no_default_features,
@@ -100,7 +100,9 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                                             &empty, false,
                                             &empty, false),
             message_format: options.flag_message_format,
+            timings: false,
             release: options.flag_release,
+            profile: None,
             mode: ops::CompileMode::Doc {
                 deps: !options.flag_no_deps,
             },
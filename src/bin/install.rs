@@ -9,6 +9,7 @@ pub struct Options {
     flag_all_features: bool,
     flag_no_default_features: bool,
     flag_debug: bool,
+    flag_profile: Option<String>,
     flag_bin: Vec<String>,
     flag_bins: bool,
     flag_example: Vec<String>,
@@ -56,6 +57,7 @@ Build and install options:
     --all-features            Build all available features
     --no-default-features     Do not build the `default` feature
     --debug                   Build in debug mode instead of release mode
+    --profile NAME            Build with the specified custom profile
     --bin NAME                Install only the specified binary
     --bins                    Install all binaries
     --example NAME            Install only the specified example
@@ -108,19 +110,21 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
     let compile_opts = ops::CompileOptions {
         config: config,
         jobs: options.flag_jobs,
-        target: None,
+        target: &[],
         features: &options.flag_features,
         all_features: options.flag_all_features,
         no_default_features: options.flag_no_default_features,
         spec: ops::Packages::Packages(&[]),
         mode: ops::CompileMode::Build,
-        release: !options.flag_debug,
+        release: !options.flag_debug && options.flag_profile.is_none(),
+        profile: options.flag_profile.as_ref().map(|s| &s[..]),
         filter: ops::CompileFilter::new(false,
                                         &options.flag_bin, options.flag_bins,
                                         &[], false,
                                         &options.flag_example, options.flag_examples,
                                         &[], false),
         message_format: ops::MessageFormat::Human,
+        timings: false,
         target_rustc_args: None,
         target_rustdoc_args: None,
     };
@@ -14,6 +14,7 @@ pub struct Options {
     flag_list: bool,
     flag_frozen: bool,
     flag_locked: bool,
+    flag_registry: Option<String>,
 }
 
 pub const USAGE: &'static str = "
@@ -29,6 +30,7 @@ Options:
     -l, --list               List owners of a crate
     --index INDEX            Registry index to modify owners for
     --token TOKEN            API token to use when authenticating
+    --registry REGISTRY      Registry to use
     -v, --verbose ...        Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet              No output printed to stdout
     --color WHEN             Coloring: auto, always, never
@@ -57,6 +59,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         to_add: options.flag_add,
         to_remove: options.flag_remove,
         list: options.flag_list,
+        registry: options.flag_registry,
     };
     ops::modify_owners(config, &opts)?;
     Ok(())
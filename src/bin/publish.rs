@@ -18,6 +18,7 @@ pub struct Options {
     flag_dry_run: bool,
     flag_frozen: bool,
     flag_locked: bool,
+    flag_registry: Option<String>,
 }
 
 pub const USAGE: &'static str = "
@@ -41,6 +42,7 @@ Options:
     --color WHEN             Coloring: auto, always, never
     --frozen                 Require Cargo.lock and cache are up to date
     --locked                 Require Cargo.lock is up to date
+    --registry REGISTRY      Registry to publish to
 
 ";
 
@@ -62,6 +64,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         flag_allow_dirty: allow_dirty,
         flag_jobs: jobs,
         flag_dry_run: dry_run,
+        flag_registry: registry,
         ..
     } = options;
 
@@ -94,6 +97,7 @@ about this warning.";
         allow_dirty: allow_dirty,
         jobs: jobs,
         dry_run: dry_run,
+        registry: registry,
     })?;
     Ok(())
 }
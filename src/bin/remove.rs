@@ -0,0 +1,70 @@
+use cargo::core::Workspace;
+use cargo::core::dependency::Kind;
+use cargo::ops;
+use cargo::util::{CliResult, Config};
+use cargo::util::important_paths::find_root_manifest_for_wd;
+
+#[derive(Deserialize)]
+pub struct Options {
+    flag_package: Option<String>,
+    flag_dev: bool,
+    flag_build: bool,
+    flag_target: Option<String>,
+    flag_manifest_path: Option<String>,
+    flag_verbose: u32,
+    flag_quiet: Option<bool>,
+    flag_color: Option<String>,
+    flag_frozen: bool,
+    flag_locked: bool,
+    arg_crate: String,
+}
+
+pub const USAGE: &'static str = "
+Remove a dependency from a Cargo.toml manifest file
+
+Usage:
+    cargo remove [options] <crate>
+    cargo remove -h | --help
+
+Options:
+    -h, --help                   Print this message
+    -p SPEC, --package SPEC      Package to remove the dependency from
+    --dev                        Remove a development dependency
+    --build                      Remove a build dependency
+    --target TRIPLE              Remove a dependency of the given target platform
+    --manifest-path PATH         Path to the manifest to remove the dependency from
+    -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
+    -q, --quiet                  No output printed to stdout
+    --color WHEN                 Coloring: auto, always, never
+    --frozen                     Require Cargo.lock and cache are up to date
+    --locked                     Require Cargo.lock is up to date
+
+Once the dependency is no longer declared in any dependency table, the
+features of the package which refer to it are updated to no longer do so.
+";
+
+pub fn execute(options: Options, config: &Config) -> CliResult {
+    config.configure(options.flag_verbose,
+                     options.flag_quiet,
+                     &options.flag_color,
+                     options.flag_frozen,
+                     options.flag_locked)?;
+    let root = find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+    let ws = Workspace::new(&root, config)?;
+
+    let kind = if options.flag_dev {
+        Kind::Development
+    } else if options.flag_build {
+        Kind::Build
+    } else {
+        Kind::Normal
+    };
+    let opts = ops::RemoveOptions {
+        spec: options.flag_package.as_ref().map(|s| &s[..]),
+        krate: &options.arg_crate,
+        kind: kind,
+        target: options.flag_target.as_ref().map(|s| &s[..]),
+    };
+    ops::remove(&ws, &opts)?;
+    Ok(())
+}
@@ -81,15 +81,17 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
     let packages = Vec::from_iter(options.flag_package.iter().cloned());
     let spec = Packages::Packages(&packages);
 
+    let target = options.flag_target.map_or_else(Vec::new, |t| vec![t]);
     let compile_opts = ops::CompileOptions {
         config: config,
         jobs: options.flag_jobs,
-        target: options.flag_target.as_ref().map(|t| &t[..]),
+        target: &target,
         features: &options.flag_features,
         all_features: options.flag_all_features,
         no_default_features: options.flag_no_default_features,
         spec: spec,
         release: options.flag_release,
+        profile: None,
         mode: ops::CompileMode::Build,
         filter: if examples.is_empty() && bins.is_empty() {
             ops::CompileFilter::Everything { required_features_filterable: false, }
@@ -101,6 +103,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                                     &[], false)
         },
         message_format: options.flag_message_format,
+        timings: false,
         target_rustdoc_args: None,
         target_rustc_args: None,
     };
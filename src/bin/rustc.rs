@@ -106,22 +106,25 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
 
     let spec = options.flag_package.map_or_else(Vec::new, |s| vec![s]);
 
+    let target = options.flag_target.map_or_else(Vec::new, |t| vec![t]);
     let opts = CompileOptions {
         config: config,
         jobs: options.flag_jobs,
-        target: options.flag_target.as_ref().map(|t| &t[..]),
+        target: &target,
         features: &options.flag_features,
         all_features: options.flag_all_features,
         no_default_features: options.flag_no_default_features,
         spec: Packages::Packages(&spec),
         mode: mode,
         release: options.flag_release,
+        profile: None,
         filter: ops::CompileFilter::new(options.flag_lib,
                                         &options.flag_bin, options.flag_bins,
                                         &options.flag_test, options.flag_tests,
                                         &options.flag_example, options.flag_examples,
                                         &options.flag_bench, options.flag_benches,),
         message_format: options.flag_message_format,
+        timings: false,
         target_rustdoc_args: None,
         target_rustc_args: options.arg_opts.as_ref().map(|a| &a[..]),
     };
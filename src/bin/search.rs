@@ -14,6 +14,7 @@ pub struct Options {
     flag_frozen: bool,
     flag_locked: bool,
     arg_query: Vec<String>,
+    flag_registry: Option<String>,
 }
 
 pub const USAGE: &'static str = "
@@ -31,6 +32,7 @@ Options:
     -q, --quiet              No output printed to stdout
     --color WHEN             Coloring: auto, always, never
     --limit LIMIT            Limit the number of results (default: 10, max: 100)
+    --registry REGISTRY      Registry to search in
     --frozen                 Require Cargo.lock and cache are up to date
     --locked                 Require Cargo.lock is up to date
 ";
@@ -46,6 +48,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         flag_host: host,    // TODO: Depricated, remove
         flag_limit: limit,
         arg_query: query,
+        flag_registry: registry,
         ..
     } = options;
 
@@ -73,6 +76,7 @@ about this warning.";
         host
     };
 
-    ops::search(&query.join("+"), config, index, cmp::min(100, limit.unwrap_or(10)) as u8)?;
+    ops::search(&query.join("+"), config, index,
+                cmp::min(100, limit.unwrap_or(10)) as u8, registry)?;
     Ok(())
 }
@@ -28,7 +28,9 @@ pub struct Options {
     flag_quiet: Option<bool>,
     flag_color: Option<String>,
     flag_message_format: MessageFormat,
+    flag_timings: bool,
     flag_release: bool,
+    flag_profile: Option<String>,
     flag_no_fail_fast: bool,
     flag_frozen: bool,
     flag_locked: bool,
@@ -60,6 +62,7 @@ Options:
     --exclude SPEC ...           Exclude packages from the test
     -j N, --jobs N               Number of parallel builds, see below for details
     --release                    Build artifacts in release mode, with optimizations
+    --profile NAME               Build artifacts with the specified custom profile
     --features FEATURES          Space-separated list of features to also build
     --all-features               Build all available features
     --no-default-features        Do not build the `default` feature
@@ -69,6 +72,7 @@ Options:
     -q, --quiet                  No output printed to stdout
     --color WHEN                 Coloring: auto, always, never
     --message-format FMT         Error format: human, json [default: human]
+    --timings                    Write a report of the time spent on each unit
     --no-fail-fast               Run all tests regardless of failure
     --frozen                     Require Cargo.lock and cache are up to date
     --locked                     Require Cargo.lock is up to date
@@ -136,6 +140,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
                                     &options.flag_exclude,
                                     &options.flag_package)?;
 
+    let target = options.flag_target.map_or_else(Vec::new, |t| vec![t]);
     let ops = ops::TestOptions {
         no_run: options.flag_no_run,
         no_fail_fast: options.flag_no_fail_fast,
@@ -143,15 +148,17 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         compile_opts: ops::CompileOptions {
             config: config,
             jobs: options.flag_jobs,
-            target: options.flag_target.as_ref().map(|s| &s[..]),
+            target: &target,
             features: &options.flag_features,
             all_features: options.flag_all_features,
             no_default_features: options.flag_no_default_features,
             spec: spec,
             release: options.flag_release,
+            profile: options.flag_profile.as_ref().map(|s| &s[..]),
             mode: mode,
             filter: filter,
             message_format: options.flag_message_format,
+            timings: options.flag_timings,
             target_rustdoc_args: None,
             target_rustc_args: None,
         },
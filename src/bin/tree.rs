@@ -0,0 +1,99 @@
+use cargo::core::Workspace;
+use cargo::ops::{self, Packages};
+use cargo::util::important_paths::find_root_manifest_for_wd;
+use cargo::util::{CliResult, Config};
+
+#[derive(Deserialize)]
+pub struct Options {
+    flag_package: Vec<String>,
+    flag_all: bool,
+    flag_exclude: Vec<String>,
+    flag_features: Vec<String>,
+    flag_all_features: bool,
+    flag_no_default_features: bool,
+    flag_target: Option<String>,
+    flag_edges: Vec<String>,
+    flag_invert: Option<String>,
+    flag_duplicates: bool,
+    flag_depth: Option<usize>,
+    flag_prune: Vec<String>,
+    flag_format: String,
+    flag_manifest_path: Option<String>,
+    flag_verbose: u32,
+    flag_quiet: Option<bool>,
+    flag_color: Option<String>,
+    flag_frozen: bool,
+    flag_locked: bool,
+}
+
+pub const USAGE: &'static str = "
+Display a tree visualization of a dependency graph
+
+Usage:
+    cargo tree [options]
+
+Options:
+    -h, --help                   Print this message
+    -p SPEC, --package SPEC ...  Package to be used as the root of the tree
+    --all                        Display the tree for all packages in the workspace
+    --exclude SPEC ...           Exclude packages from the display
+    --features FEATURES          Space-separated list of features to activate
+    --all-features               Activate all available features
+    --no-default-features        Do not activate the `default` feature
+    --target TRIPLE              Filter dependencies matching the given target
+                                 triple, or `all` to include all targets
+    -e KINDS, --edges KINDS ...  The kinds of dependencies to display: normal,
+                                 build, dev, features or all
+    -i SPEC, --invert SPEC       Invert the tree direction and focus on the
+                                 given package
+    -d, --duplicates             Show only dependencies which come in multiple
+                                 versions (implies --invert)
+    --depth DEPTH                Maximum display depth of the dependency tree
+    --prune SPEC ...             Prune the given package from the display
+    -f FORMAT, --format FORMAT   Format string used for printing dependencies
+                                 [default: {p}]
+    --manifest-path PATH         Path to the manifest of the package
+    -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
+    -q, --quiet                  No output printed to stdout
+    --color WHEN                 Coloring: auto, always, never
+    --frozen                     Require Cargo.lock and cache are up to date
+    --locked                     Require Cargo.lock is up to date
+
+By default the dependencies used on the host platform are displayed. Packages
+whose dependencies were already displayed are marked with `(*)`.
+
+The format string may contain `{p}` for the package name and version, `{l}`
+for its license, `{r}` for its repository URL and `{f}` for its enabled
+features. Several kinds of edges are given with a comma-separated list, and
+the `features` kind also displays which features of each package are enabled,
+and by whom.
+";
+
+pub fn execute(options: Options, config: &Config) -> CliResult {
+    config.configure(options.flag_verbose,
+                     options.flag_quiet,
+                     &options.flag_color,
+                     options.flag_frozen,
+                     options.flag_locked)?;
+    let root = find_root_manifest_for_wd(options.flag_manifest_path.clone(), config.cwd())?;
+    let ws = Workspace::new(&root, config)?;
+
+    let packages = Packages::from_flags(options.flag_all,
+                                        &options.flag_exclude,
+                                        &options.flag_package)?;
+    let opts = ops::TreeOptions {
+        features: options.flag_features.clone(),
+        all_features: options.flag_all_features,
+        no_default_features: options.flag_no_default_features,
+        packages: packages,
+        target: options.flag_target.clone(),
+        edges: options.flag_edges.clone(),
+        invert: options.flag_invert.clone(),
+        duplicates: options.flag_duplicates,
+        depth: options.flag_depth,
+        prune: options.flag_prune.clone(),
+        format: options.flag_format.clone(),
+    };
+    ops::tree(&ws, &opts)?;
+    Ok(())
+}
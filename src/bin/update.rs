@@ -10,6 +10,7 @@ pub struct Options {
     flag_package: Vec<String>,
     flag_aggressive: bool,
     flag_precise: Option<String>,
+    flag_rust_version_compatible: bool,
     flag_manifest_path: Option<String>,
     flag_verbose: u32,
     flag_quiet: Option<bool>,
@@ -29,6 +30,7 @@ Options:
     -p SPEC, --package SPEC ...  Package to update
     --aggressive                 Force updating all dependencies of <name> as well
     --precise PRECISE            Update a single dependency to exactly PRECISE
+    --rust-version-compatible    Prefer versions compatible with the workspace's rust-version
     --manifest-path PATH         Path to the crate's manifest
     -v, --verbose ...            Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet                  No output printed to stdout
@@ -54,6 +56,12 @@ be updated to.
 If SPEC is not given, then all dependencies will be re-resolved and
 updated.
 
+With --rust-version-compatible, versions of dependencies whose `rust-version`
+is no newer than the oldest `rust-version` of the workspace members (or the
+current rustc, if none of them specify one) are preferred over newer versions.
+Incompatible versions are still used if nothing else satisfies the
+requirements.
+
 For more information about package id specifications, see `cargo help pkgid`.
 ";
 
@@ -70,6 +78,7 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
         aggressive: options.flag_aggressive,
         precise: options.flag_precise.as_ref().map(|s| &s[..]),
         to_update: &options.flag_package,
+        rust_version_compatible: options.flag_rust_version_compatible,
         config: config,
     };
 
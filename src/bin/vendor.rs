@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use cargo::core::Workspace;
+use cargo::ops;
+use cargo::util::{CliResult, Config};
+use cargo::util::important_paths::find_root_manifest_for_wd;
+
+#[derive(Deserialize)]
+pub struct Options {
+    arg_path: Option<String>,
+    flag_sync: Vec<String>,
+    flag_versioned_dirs: bool,
+    flag_no_delete: bool,
+    flag_respect_source_config: bool,
+    flag_manifest_path: Option<String>,
+    flag_verbose: u32,
+    flag_quiet: Option<bool>,
+    flag_color: Option<String>,
+    flag_frozen: bool,
+    flag_locked: bool,
+}
+
+pub const USAGE: &'static str = "
+Vendor all dependencies of a project into a local directory
+
+Usage:
+    cargo vendor [options] [<path>]
+
+Options:
+    -h, --help               Print this message
+    -s, --sync TOML ...      Additional `Cargo.toml` to sync and vendor
+    --versioned-dirs         Always include version in subdir name
+    --no-delete              Don't delete older crates in the vendor directory
+    --respect-source-config  Respect `[source]` config in `.cargo/config`
+    --manifest-path PATH     Path to the manifest to vendor dependencies for
+    -v, --verbose ...        Use verbose output (-vv very verbose/build.rs output)
+    -q, --quiet              No output printed to stdout
+    --color WHEN             Coloring: auto, always, never
+    --frozen                 Require Cargo.lock and cache are up to date
+    --locked                 Require Cargo.lock is up to date
+
+This cargo subcommand will vendor all crates.io and git dependencies for a
+project into the specified directory at `<path>`, which defaults to `vendor`.
+After this command completes the vendor directory will contain all remote
+sources from the dependencies of the project, and the configuration needed
+to use them instead of the network is printed to stdout.
+
+Each package is placed in a directory named after it, with its version
+appended for all but the newest version of a package that's used several
+times, or for all packages with `--versioned-dirs`.
+
+The `[source]` configuration is ignored by default, so that the original
+sources are vendored even when they're already replaced with vendored ones.
+";
+
+pub fn execute(options: Options, config: &Config) -> CliResult {
+    config.configure(options.flag_verbose,
+                     options.flag_quiet,
+                     &options.flag_color,
+                     options.flag_frozen,
+                     options.flag_locked)?;
+    let root = find_root_manifest_for_wd(options.flag_manifest_path, config.cwd())?;
+    let ws = Workspace::new(&root, config)?;
+
+    let path = options.arg_path.unwrap_or_else(|| "vendor".to_string());
+    ops::vendor(&ws, &ops::VendorOptions {
+        destination: Path::new(&path),
+        extra: options.flag_sync.iter().map(PathBuf::from).collect(),
+        versioned_dirs: options.flag_versioned_dirs,
+        no_delete: options.flag_no_delete,
+        respect_source_config: options.flag_respect_source_config,
+    })?;
+    Ok(())
+}
@@ -13,6 +13,7 @@ pub struct Options {
     flag_undo: bool,
     flag_frozen: bool,
     flag_locked: bool,
+    flag_registry: Option<String>,
 }
 
 pub static USAGE: &'static str = "
@@ -27,6 +28,7 @@ Options:
     --undo              Undo a yank, putting a version back into the index
     --index INDEX       Registry index to yank from
     --token TOKEN       API token to use when authenticating
+    --registry REG      Registry to use
     -v, --verbose ...   Use verbose output (-vv very verbose/build.rs output)
     -q, --quiet         No output printed to stdout
     --color WHEN        Coloring: auto, always, never
@@ -53,7 +55,8 @@ pub fn execute(options: Options, config: &Config) -> CliResult {
               options.flag_vers,
               options.flag_token,
               options.flag_index,
-              options.flag_undo)?;
+              options.flag_undo,
+              options.flag_registry)?;
     Ok(())
 }
 
@@ -27,6 +27,10 @@ struct Inner {
     kind: Kind,
     only_match_name: bool,
 
+    // The name this dependency was given in the manifest, if it differs
+    // from the name of the package being depended on (via `package = ...`).
+    explicit_name_in_toml: Option<String>,
+
     optional: bool,
     default_features: bool,
     features: Vec<String>,
@@ -34,6 +38,20 @@ struct Inner {
     // This dependency should be used only for this platform.
     // `None` means *all platforms*.
     platform: Option<Platform>,
+
+    // For artifact dependencies, the kind of artifact which is built instead
+    // of linking the library, and the platform it is built for (`None` is
+    // the platform of the dependent).
+    artifact: Option<ArtifactKind>,
+    artifact_target: Option<String>,
+}
+
+/// The kind of compiled artifact requested with `artifact = "..."`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ArtifactKind {
+    Bin,
+    Cdylib,
+    Staticlib,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -53,6 +71,9 @@ struct SerializedDependency<'a> {
     uses_default_features: bool,
     features: &'a [String],
     target: Option<&'a Platform>,
+    rename: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artifact: Option<&'a str>,
 }
 
 impl ser::Serialize for Dependency {
@@ -68,6 +89,8 @@ impl ser::Serialize for Dependency {
             uses_default_features: self.uses_default_features(),
             features: self.features(),
             target: self.platform(),
+            rename: self.explicit_name_in_toml(),
+            artifact: self.artifact().map(|a| a.crate_type()),
         }.serialize(s)
     }
 }
@@ -175,11 +198,14 @@ impl Dependency {
                 req: VersionReq::any(),
                 kind: Kind::Normal,
                 only_match_name: true,
+                explicit_name_in_toml: None,
                 optional: false,
                 features: Vec::new(),
                 default_features: true,
                 specified_req: false,
                 platform: None,
+                artifact: None,
+                artifact_target: None,
             }),
         }
     }
@@ -192,6 +218,21 @@ impl Dependency {
         &self.inner.name
     }
 
+    /// Returns the name this dependency was declared under in the manifest.
+    ///
+    /// This is the name used for features and for the `--extern` passed to
+    /// rustc, and is the same as `name` unless the dependency was renamed
+    /// with the `package` key.
+    pub fn name_in_toml(&self) -> &str {
+        self.explicit_name_in_toml().unwrap_or(&self.inner.name)
+    }
+
+    /// Returns the local name given to this dependency through the `package`
+    /// key, if any.
+    pub fn explicit_name_in_toml(&self) -> Option<&str> {
+        self.inner.explicit_name_in_toml.as_ref().map(|s| &s[..])
+    }
+
     pub fn source_id(&self) -> &SourceId {
         &self.inner.source_id
     }
@@ -204,6 +245,25 @@ impl Dependency {
         self.inner.specified_req
     }
 
+    /// Returns whether this dependency has been locked to a precise version,
+    /// that is whether its requirement is of the form `=a.b.c`.
+    pub fn is_locked(&self) -> bool {
+        // Kind of a hack to figure this out, but it works!
+        self.inner.req.to_string().starts_with("=")
+    }
+
+    /// Returns the kind of artifact requested, if this is an artifact
+    /// dependency rather than a dependency on the package's library.
+    pub fn artifact(&self) -> Option<ArtifactKind> {
+        self.inner.artifact
+    }
+
+    /// Returns the platform an artifact dependency is built for, either a
+    /// target triple or `"target"` for the platform being compiled for.
+    pub fn artifact_target(&self) -> Option<&str> {
+        self.inner.artifact_target.as_ref().map(|s| &s[..])
+    }
+
     /// If none, this dependencies must be built for all platforms.
     /// If some, it must only be built for the specified platform.
     pub fn platform(&self) -> Option<&Platform> {
@@ -250,6 +310,26 @@ impl Dependency {
         self
     }
 
+    /// Makes this an artifact dependency on the given kind of artifact,
+    /// built for `target` (or the platform of the dependent if `None`).
+    pub fn set_artifact(&mut self,
+                        artifact: Option<ArtifactKind>,
+                        target: Option<String>) -> &mut Dependency {
+        {
+            let inner = Rc::make_mut(&mut self.inner);
+            inner.artifact = artifact;
+            inner.artifact_target = target;
+        }
+        self
+    }
+
+    /// Set the name this dependency is known by locally, when it differs
+    /// from the name of the package.
+    pub fn set_explicit_name_in_toml(&mut self, name: &str) -> &mut Dependency {
+        Rc::make_mut(&mut self.inner).explicit_name_in_toml = Some(name.to_string());
+        self
+    }
+
     /// Lock this dependency to depending on the specified package id
     pub fn lock_to(&mut self, id: &PackageId) -> &mut Dependency {
         assert_eq!(self.inner.source_id, *id.source_id());
@@ -292,6 +372,13 @@ impl Dependency {
         self.matches_id(sum.package_id())
     }
 
+    /// Returns true if the package (`sum`) can fulfill this dependency request
+    /// by name and version, regardless of the source it comes from.
+    pub fn matches_ignoring_source(&self, sum: &Summary) -> bool {
+        self.name() == sum.package_id().name() &&
+            self.version_req().matches(sum.package_id().version())
+    }
+
     /// Returns true if the package (`id`) can fulfill this dependency request.
     pub fn matches_id(&self, id: &PackageId) -> bool {
         self.inner.name == id.name() &&
@@ -332,6 +419,31 @@ impl ser::Serialize for Platform {
     }
 }
 
+impl ArtifactKind {
+    /// Returns the crate type producing this artifact.
+    pub fn crate_type(&self) -> &'static str {
+        match *self {
+            ArtifactKind::Bin => "bin",
+            ArtifactKind::Cdylib => "cdylib",
+            ArtifactKind::Staticlib => "staticlib",
+        }
+    }
+}
+
+impl FromStr for ArtifactKind {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> CargoResult<ArtifactKind> {
+        match s {
+            "bin" => Ok(ArtifactKind::Bin),
+            "cdylib" => Ok(ArtifactKind::Cdylib),
+            "staticlib" => Ok(ArtifactKind::Staticlib),
+            _ => bail!("`artifact = \"{}\"` is not valid, valid options are \
+                        \"bin\", \"cdylib\" or \"staticlib\"", s),
+        }
+    }
+}
+
 impl FromStr for Platform {
     type Err = CargoError;
 
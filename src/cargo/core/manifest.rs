@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::{PathBuf, Path};
 use std::rc::Rc;
+use std::str::FromStr;
 
 use semver::Version;
 use serde::ser;
+use url::Url;
 
 use core::{Dependency, PackageId, Summary, SourceId, PackageIdSpec};
 use core::WorkspaceConfig;
+use core::resolver::ResolveBehavior;
+use util::errors::CargoError;
 use util::toml::TomlManifest;
 
 pub enum EitherManifest {
@@ -26,17 +30,23 @@ pub struct Manifest {
     include: Vec<String>,
     metadata: ManifestMetadata,
     profiles: Profiles,
-    publish: bool,
+    publish: Option<Vec<String>>,
     replace: Vec<(PackageIdSpec, Dependency)>,
+    patch: HashMap<Url, Vec<Dependency>>,
     workspace: WorkspaceConfig,
     original: Rc<TomlManifest>,
+    resolve_behavior: Option<ResolveBehavior>,
+    edition: Edition,
+    lints: Lints,
 }
 
 #[derive(Clone, Debug)]
 pub struct VirtualManifest {
     replace: Vec<(PackageIdSpec, Dependency)>,
+    patch: HashMap<Url, Vec<Dependency>>,
     workspace: WorkspaceConfig,
     profiles: Profiles,
+    resolve_behavior: Option<ResolveBehavior>,
 }
 
 /// General metadata about a package which is just blindly uploaded to the
@@ -62,6 +72,53 @@ pub struct ManifestMetadata {
     pub badges: HashMap<String, HashMap<String, String>>,
 }
 
+/// The lint levels set by the `[lints]` table of a package, as the arguments
+/// passed to the compiler.
+#[derive(PartialEq, Clone, Debug, Default, Hash)]
+pub struct Lints {
+    /// Arguments for rustc, from `[lints.rust]`.
+    pub rustc: Vec<String>,
+    /// Arguments for rustdoc, from both `[lints.rust]` and `[lints.rustdoc]`.
+    pub rustdoc: Vec<String>,
+}
+
+/// The edition of the Rust language a package or target is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Edition {
+    Edition2015,
+    Edition2018,
+}
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Edition::Edition2015 => "2015".fmt(f),
+            Edition::Edition2018 => "2018".fmt(f),
+        }
+    }
+}
+
+impl FromStr for Edition {
+    type Err = CargoError;
+
+    fn from_str(s: &str) -> Result<Edition, CargoError> {
+        match s {
+            "2015" => Ok(Edition::Edition2015),
+            "2018" => Ok(Edition::Edition2018),
+            s => bail!("supported edition values are `2015` or `2018`, but \
+                        `{}` is unknown", s),
+        }
+    }
+}
+
+impl ser::Serialize for Edition {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer,
+    {
+        self.to_string().serialize(s)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum LibKind {
     Lib,
@@ -174,6 +231,46 @@ pub struct Profiles {
     pub custom_build: Profile,
     pub check: Profile,
     pub doctest: Profile,
+    /// Profiles for packages matched by `[profile.*.package.<spec>]` tables.
+    pub package_overrides: Vec<ProfileOverride>,
+    /// Profiles for build scripts from `[profile.*.build-override]` tables,
+    /// as `(dev, release)`.
+    pub build_override: (Option<Profile>, Option<Profile>),
+    /// User-defined profiles, selected with `--profile NAME`.
+    pub custom: BTreeMap<String, CustomProfile>,
+}
+
+/// A user-defined `[profile.NAME]`, which is used in place of either `dev` or
+/// `release` depending on which profile it inherits from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomProfile {
+    /// Whether this profile ultimately inherits from `release`.
+    pub release: bool,
+    pub profiles: Profiles,
+}
+
+/// The profiles to use in place of the defaults for all packages matched by
+/// `spec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileOverride {
+    pub spec: ProfilePackageSpec,
+    pub release: Profile,
+    pub dev: Profile,
+    pub test_deps: Profile,
+    pub bench_deps: Profile,
+    pub check: Profile,
+    /// Profiles for the build scripts of the matched packages, with this
+    /// override layered on top of `build-override`, as `(dev, release)`.
+    pub build_override: (Option<Profile>, Option<Profile>),
+}
+
+/// The key of a `[profile.*.package.<spec>]` table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProfilePackageSpec {
+    /// Matches the packages described by the spec.
+    Spec(PackageIdSpec),
+    /// `"*"`, which matches all packages which aren't workspace members.
+    All,
 }
 
 /// Information about a binary, a library, an example, etc. that is part of the
@@ -190,6 +287,7 @@ pub struct Target {
     doctest: bool,
     harness: bool, // whether to use the test harness (--test)
     for_host: bool,
+    edition: Edition,
 }
 
 #[derive(Serialize)]
@@ -202,6 +300,7 @@ struct SerializedTarget<'a> {
     crate_types: Vec<&'a str>,
     name: &'a str,
     src_path: &'a PathBuf,
+    edition: &'a Edition,
 }
 
 impl ser::Serialize for Target {
@@ -211,6 +310,7 @@ impl ser::Serialize for Target {
             crate_types: self.rustc_crate_types(),
             name: &self.name,
             src_path: &self.src_path,
+            edition: &self.edition,
         }.serialize(s)
     }
 }
@@ -223,10 +323,14 @@ impl Manifest {
                links: Option<String>,
                metadata: ManifestMetadata,
                profiles: Profiles,
-               publish: bool,
+               publish: Option<Vec<String>>,
                replace: Vec<(PackageIdSpec, Dependency)>,
+               patch: HashMap<Url, Vec<Dependency>>,
                workspace: WorkspaceConfig,
-               original: Rc<TomlManifest>) -> Manifest {
+               original: Rc<TomlManifest>,
+               resolve_behavior: Option<ResolveBehavior>,
+               edition: Edition,
+               lints: Lints) -> Manifest {
         Manifest {
             summary: summary,
             targets: targets,
@@ -238,8 +342,12 @@ impl Manifest {
             profiles: profiles,
             publish: publish,
             replace: replace,
+            patch: patch,
             workspace: workspace,
             original: original,
+            resolve_behavior: resolve_behavior,
+            edition: edition,
+            lints: lints,
         }
     }
 
@@ -254,8 +362,9 @@ impl Manifest {
     pub fn version(&self) -> &Version { self.package_id().version() }
     pub fn warnings(&self) -> &[String] { &self.warnings }
     pub fn profiles(&self) -> &Profiles { &self.profiles }
-    pub fn publish(&self) -> bool { self.publish }
+    pub fn publish(&self) -> &Option<Vec<String>> { &self.publish }
     pub fn replace(&self) -> &[(PackageIdSpec, Dependency)] { &self.replace }
+    pub fn patch(&self) -> &HashMap<Url, Vec<Dependency>> { &self.patch }
     pub fn original(&self) -> &TomlManifest { &self.original }
     pub fn links(&self) -> Option<&str> {
         self.links.as_ref().map(|s| &s[..])
@@ -265,6 +374,19 @@ impl Manifest {
         &self.workspace
     }
 
+    /// The feature resolver selected with the `resolver` key, if any.
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
+
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    pub fn lints(&self) -> &Lints {
+        &self.lints
+    }
+
     pub fn add_warning(&mut self, s: String) {
         self.warnings.push(s)
     }
@@ -284,12 +406,16 @@ impl Manifest {
 
 impl VirtualManifest {
     pub fn new(replace: Vec<(PackageIdSpec, Dependency)>,
+               patch: HashMap<Url, Vec<Dependency>>,
                workspace: WorkspaceConfig,
-               profiles: Profiles) -> VirtualManifest {
+               profiles: Profiles,
+               resolve_behavior: Option<ResolveBehavior>) -> VirtualManifest {
         VirtualManifest {
             replace: replace,
+            patch: patch,
             workspace: workspace,
             profiles: profiles,
+            resolve_behavior: resolve_behavior,
         }
     }
 
@@ -297,6 +423,10 @@ impl VirtualManifest {
         &self.replace
     }
 
+    pub fn patch(&self) -> &HashMap<Url, Vec<Dependency>> {
+        &self.patch
+    }
+
     pub fn workspace_config(&self) -> &WorkspaceConfig {
         &self.workspace
     }
@@ -304,6 +434,10 @@ impl VirtualManifest {
     pub fn profiles(&self) -> &Profiles {
         &self.profiles
     }
+
+    pub fn resolve_behavior(&self) -> Option<ResolveBehavior> {
+        self.resolve_behavior
+    }
 }
 
 impl Target {
@@ -320,6 +454,7 @@ impl Target {
             for_host: false,
             tested: true,
             benched: true,
+            edition: Edition::Edition2015,
         }
     }
 
@@ -408,6 +543,7 @@ impl Target {
     pub fn harness(&self) -> bool { self.harness }
     pub fn documented(&self) -> bool { self.doc }
     pub fn for_host(&self) -> bool { self.for_host }
+    pub fn edition(&self) -> Edition { self.edition }
     pub fn benched(&self) -> bool { self.benched }
 
     pub fn doctested(&self) -> bool {
@@ -527,6 +663,10 @@ impl Target {
         self.for_host = for_host;
         self
     }
+    pub fn set_edition(&mut self, edition: Edition) -> &mut Target {
+        self.edition = edition;
+        self
+    }
     pub fn set_harness(&mut self, harness: bool) -> &mut Target {
         self.harness = harness;
         self
@@ -551,6 +691,41 @@ impl fmt::Display for Target {
     }
 }
 
+impl Profiles {
+    /// Returns the override which applies to `pkg`, if any.
+    ///
+    /// Overrides naming a package take precedence over `"*"`, which only
+    /// applies to packages outside of the workspace.
+    pub fn package_override(&self, pkg: &PackageId, is_member: bool)
+                            -> Option<&ProfileOverride> {
+        self.package_overrides.iter().find(|o| o.spec.matches(pkg)).or_else(|| {
+            if is_member {
+                return None
+            }
+            self.package_overrides.iter().find(|o| o.spec == ProfilePackageSpec::All)
+        })
+    }
+}
+
+impl ProfilePackageSpec {
+    /// Returns whether this spec names `pkg`. `"*"` never names a package.
+    pub fn matches(&self, pkg: &PackageId) -> bool {
+        match *self {
+            ProfilePackageSpec::Spec(ref spec) => spec.matches(pkg),
+            ProfilePackageSpec::All => false,
+        }
+    }
+}
+
+impl fmt::Display for ProfilePackageSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProfilePackageSpec::Spec(ref spec) => spec.fmt(f),
+            ProfilePackageSpec::All => "*".fmt(f),
+        }
+    }
+}
+
 impl Profile {
     pub fn default_dev() -> Profile {
         Profile {
@@ -1,6 +1,7 @@
 pub use self::dependency::Dependency;
 pub use self::manifest::{Manifest, Target, TargetKind, Profile, LibKind, Profiles};
-pub use self::manifest::{EitherManifest, VirtualManifest};
+pub use self::manifest::{CustomProfile, ProfileOverride, ProfilePackageSpec};
+pub use self::manifest::{EitherManifest, VirtualManifest, Edition};
 pub use self::package::{Package, PackageSet};
 pub use self::package_id::PackageId;
 pub use self::package_id_spec::PackageIdSpec;
@@ -9,7 +10,7 @@ pub use self::resolver::Resolve;
 pub use self::shell::{Shell, Verbosity};
 pub use self::source::{Source, SourceId, SourceMap, GitReference};
 pub use self::summary::Summary;
-pub use self::workspace::{Workspace, WorkspaceConfig};
+pub use self::workspace::{Workspace, WorkspaceConfig, find_workspace_root};
 
 pub mod source;
 pub mod package;
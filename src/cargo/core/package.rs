@@ -1,9 +1,13 @@
 use std::cell::{Ref, RefCell};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt;
 use std::hash;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use curl::easy::{Easy, HttpVersion};
+use curl::multi::{EasyHandle, Multi};
 use semver::Version;
 use serde::ser;
 use toml;
@@ -11,8 +15,9 @@ use toml;
 use core::{Dependency, Manifest, PackageId, SourceId, Target};
 use core::{Summary, SourceMap};
 use ops;
-use util::{Config, LazyCell, internal, lev_distance};
-use util::errors::{CargoResult, CargoResultExt};
+use util::{Config, LazyCell, Progress, internal, lev_distance};
+use util::errors::{CargoError, CargoErrorKind, CargoResult, CargoResultExt};
+use util::network;
 
 /// Information about a package that is available somewhere in the file system.
 ///
@@ -95,7 +100,7 @@ impl Package {
     pub fn targets(&self) -> &[Target] { self.manifest.targets() }
     pub fn version(&self) -> &Version { self.package_id().version() }
     pub fn authors(&self) -> &Vec<String> { &self.manifest.metadata().authors }
-    pub fn publish(&self) -> bool { self.manifest.publish() }
+    pub fn publish(&self) -> &Option<Vec<String>> { self.manifest.publish() }
 
     pub fn has_custom_build(&self) -> bool {
         self.targets().iter().any(|t| t.is_custom_build())
@@ -120,10 +125,10 @@ impl Package {
         }
     }
 
-    pub fn to_registry_toml(&self) -> String {
-        let manifest = self.manifest().original().prepare_for_publish();
-        let toml = toml::to_string(&manifest).unwrap();
-        format!("\
+    pub fn to_registry_toml(&self, config: &Config) -> CargoResult<String> {
+        let manifest = self.manifest().original().prepare_for_publish(config)?;
+        let toml = toml::to_string(&manifest)?;
+        Ok(format!("\
             # THIS FILE IS AUTOMATICALLY GENERATED BY CARGO\n\
             #\n\
             # When uploading crates to the registry Cargo will automatically\n\
@@ -164,16 +169,29 @@ impl hash::Hash for Package {
 pub struct PackageSet<'cfg> {
     packages: Vec<(PackageId, LazyCell<Package>)>,
     sources: RefCell<SourceMap<'cfg>>,
+    config: &'cfg Config,
+}
+
+/// A crate being downloaded by `PackageSet::get_many`.
+struct Download {
+    handle: EasyHandle,
+    id: PackageId,
+    url: String,
+    data: Arc<Mutex<Vec<u8>>>,
+    /// Number of times the download may still be retried.
+    retries: i64,
 }
 
 impl<'cfg> PackageSet<'cfg> {
     pub fn new(package_ids: &[PackageId],
-               sources: SourceMap<'cfg>) -> PackageSet<'cfg> {
+               sources: SourceMap<'cfg>,
+               config: &'cfg Config) -> PackageSet<'cfg> {
         PackageSet {
             packages: package_ids.iter().map(|id| {
                 (id.clone(), LazyCell::new())
             }).collect(),
             sources: RefCell::new(sources),
+            config: config,
         }
     }
 
@@ -181,11 +199,15 @@ impl<'cfg> PackageSet<'cfg> {
         Box::new(self.packages.iter().map(|&(ref p, _)| p))
     }
 
-    pub fn get(&self, id: &PackageId) -> CargoResult<&Package> {
+    fn slot(&self, id: &PackageId) -> CargoResult<&LazyCell<Package>> {
         let slot = self.packages.iter().find(|p| p.0 == *id).ok_or_else(|| {
             internal(format!("couldn't find `{}` in package set", id))
         })?;
-        let slot = &slot.1;
+        Ok(&slot.1)
+    }
+
+    pub fn get(&self, id: &PackageId) -> CargoResult<&Package> {
+        let slot = self.slot(id)?;
         if let Some(pkg) = slot.borrow() {
             return Ok(pkg)
         }
@@ -200,7 +222,178 @@ impl<'cfg> PackageSet<'cfg> {
         Ok(slot.borrow().unwrap())
     }
 
+    /// Gets all of the packages `ids`, downloading those which sources serve
+    /// over HTTP in parallel instead of one after the other.
+    pub fn get_many(&self, ids: &[&PackageId]) -> CargoResult<Vec<&Package>> {
+        let mut pending = Vec::new();
+        {
+            let mut sources = self.sources.borrow_mut();
+            for &id in ids {
+                let slot = self.slot(id)?;
+                if slot.borrow().is_some() ||
+                   pending.iter().any(|&(ref p, _)| p == id) {
+                    continue
+                }
+                let source = sources.get_mut(id.source_id()).ok_or_else(|| {
+                    internal(format!("couldn't find source for `{}`", id))
+                })?;
+                let url = source.download_url(id).chain_err(|| {
+                    "unable to get packages from source"
+                })?;
+                match url {
+                    Some(url) => pending.push((id.clone(), url)),
+                    None => {
+                        let pkg = source.download(id).chain_err(|| {
+                            "unable to get packages from source"
+                        })?;
+                        assert!(slot.fill(pkg).is_ok());
+                    }
+                }
+            }
+        }
+        if !pending.is_empty() {
+            self.download_all(pending).chain_err(|| {
+                "unable to get packages from source"
+            })?;
+        }
+        ids.iter().map(|id| self.get(id)).collect()
+    }
+
+    /// Downloads the packages in `pending` from their URLs, several at a time
+    /// on a curl multi handle.
+    ///
+    /// Each download is retried on its own according to `net.retry`, just
+    /// like `network::with_retry`, and is verified by its source when it's
+    /// done.
+    fn download_all(&self, pending: Vec<(PackageId, String)>) -> CargoResult<()> {
+        let config = self.config;
+        let multiplexing = ops::http_multiplexing(config)?;
+        let max_concurrency = ops::http_max_concurrency(config)?;
+        let retries = config.net_retry()?;
+
+        let mut multi = Multi::new();
+        multi.pipelining(false, multiplexing)?;
+        if multiplexing {
+            // Share a few connections to each host between all downloads
+            // rather than opening one for each of them.
+            multi.set_max_host_connections(2)?;
+        }
+
+        let total = pending.len();
+        let mut pending = pending.into_iter().map(|(id, url)| {
+            (id, url, retries)
+        }).collect::<VecDeque<_>>();
+        let mut active = BTreeMap::new();
+        let mut next_token = 0;
+        let mut finished = 0;
+        let mut progress = Progress::new("Downloading", config)?;
+        let mut sources = self.sources.borrow_mut();
+
+        loop {
+            while active.len() < max_concurrency {
+                let (id, url, retries) = match pending.pop_front() {
+                    Some(download) => download,
+                    None => break,
+                };
+                config.shell().status("Downloading", &id)?;
+                let data = Arc::new(Mutex::new(Vec::new()));
+                let mut handle = ops::http_handle(config)?;
+                handle.get(true)?;
+                handle.url(&url)?;
+                handle.follow_location(true)?;
+                if multiplexing {
+                    // libcurl may have been built without HTTP/2 support, in
+                    // which case downloads just use HTTP/1.1.
+                    if let Err(e) = handle.http_version(HttpVersion::V2) {
+                        debug!("HTTP/2 isn't available: {}", e);
+                    }
+                    handle.pipewait(true)?;
+                }
+                let data2 = data.clone();
+                handle.write_function(move |buf| {
+                    data2.lock().unwrap().extend_from_slice(buf);
+                    Ok(buf.len())
+                })?;
+                let mut handle = multi.add(handle)?;
+                handle.set_token(next_token)?;
+                active.insert(next_token, Download {
+                    handle: handle,
+                    id: id,
+                    url: url,
+                    data: data,
+                    retries: retries,
+                });
+                next_token += 1;
+            }
+            if active.is_empty() {
+                break
+            }
+
+            let msg = active.values().map(|d| d.id.name())
+                            .collect::<Vec<_>>().join(", ");
+            progress.tick(finished, total, &msg)?;
+
+            multi.perform()?;
+            let mut done = Vec::new();
+            multi.messages(|msg| {
+                if let (Ok(token), Some(result)) = (msg.token(), msg.result()) {
+                    done.push((token, result));
+                }
+            });
+            if done.is_empty() {
+                multi.wait(&mut [], Duration::from_secs(1))?;
+                continue
+            }
+
+            for (token, result) in done {
+                let download = active.remove(&token).unwrap();
+                let mut handle = multi.remove(download.handle)?;
+                let result = result.map_err(CargoError::from).and_then(|()| {
+                    check_response(&mut handle, &download.url)
+                });
+                match result {
+                    Ok(()) => {
+                        let data = download.data.lock().unwrap().split_off(0);
+                        let id = &download.id;
+                        let source = sources.get_mut(id.source_id());
+                        let source = source.ok_or_else(|| {
+                            internal(format!("couldn't find source for `{}`", id))
+                        })?;
+                        let pkg = source.finish_download(id, &data)?;
+                        assert!(self.slot(id)?.fill(pkg).is_ok());
+                        finished += 1;
+                    }
+                    Err(ref e) if download.retries > 0 &&
+                                  network::maybe_spurious(e) => {
+                        let msg = format!("spurious network error ({} tries \
+                                           remaining): {}", download.retries, e);
+                        config.shell().warn(msg)?;
+                        pending.push_back((download.id, download.url,
+                                           download.retries - 1));
+                    }
+                    Err(e) => {
+                        return Err(e).chain_err(|| {
+                            format!("failed to download `{}`", download.id)
+                        })
+                    }
+                }
+            }
+        }
+        progress.clear();
+        Ok(())
+    }
+
     pub fn sources(&self) -> Ref<SourceMap<'cfg>> {
         self.sources.borrow()
     }
 }
+
+/// Turns an unsuccessful HTTP response to a finished transfer into an error.
+fn check_response(handle: &mut Easy, url: &str) -> CargoResult<()> {
+    let code = handle.response_code()?;
+    if code != 200 && code != 0 {
+        let url = handle.effective_url()?.unwrap_or(url);
+        bail!(CargoErrorKind::HttpNot200(code, url.to_string()))
+    }
+    Ok(())
+}
@@ -5,7 +5,7 @@ use std::str::FromStr;
 use serde::ser;
 use serde::de;
 
-use core::{Package, PackageId, SourceId, Workspace};
+use core::{Dependency, Package, PackageId, SourceId, Workspace};
 use util::{Graph, Config, internal};
 use util::errors::{CargoResult, CargoResultExt, CargoError};
 
@@ -17,6 +17,14 @@ pub struct EncodableResolve {
     /// `root` is optional to allow forward compatibility.
     root: Option<EncodableDependency>,
     metadata: Option<Metadata>,
+
+    #[serde(default, skip_serializing_if = "Patch::is_empty")]
+    patch: Patch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Patch {
+    unused: Vec<EncodableDependency>,
 }
 
 pub type Metadata = BTreeMap<String, String>;
@@ -153,13 +161,25 @@ impl EncodableResolve {
             metadata.remove(&k);
         }
 
+        let mut unused_patches = Vec::new();
+        for pkg in self.patch.unused {
+            let id = match pkg.source.as_ref().or(path_deps.get(&pkg.name)) {
+                Some(src) => PackageId::new(&pkg.name, &pkg.version, src)?,
+                None => continue,
+            };
+            unused_patches.push(id);
+        }
+
         Ok(Resolve {
             graph: g,
             empty_features: HashSet::new(),
             features: HashMap::new(),
+            optional_deps: HashMap::new(),
             replacements: replacements,
             checksums: checksums,
             metadata: metadata,
+            unused_patches: unused_patches,
+            patched: HashMap::new(),
         })
     }
 }
@@ -183,6 +203,11 @@ fn build_path_deps(ws: &Workspace) -> HashMap<String, SourceId> {
     for member in members.iter() {
         build(member, ws.config(), &mut ret, &mut visited);
     }
+    for deps in ws.root_patch().values() {
+        for dep in deps {
+            build_dep(dep, ws.config(), &mut ret, &mut visited);
+        }
+    }
 
     return ret;
 
@@ -191,9 +216,11 @@ fn build_path_deps(ws: &Workspace) -> HashMap<String, SourceId> {
              ret: &mut HashMap<String, SourceId>,
              visited: &mut HashSet<SourceId>) {
         let replace = pkg.manifest().replace();
+        let patch = pkg.manifest().patch();
         let deps = pkg.dependencies()
                       .iter()
                       .chain(replace.iter().map(|p| &p.1))
+                      .chain(patch.values().flat_map(|v| v))
                       .map(|d| d.source_id())
                       .filter(|id| !visited.contains(id) && id.is_path())
                       .filter_map(|id| id.url().to_file_path().ok())
@@ -207,6 +234,34 @@ fn build_path_deps(ws: &Workspace) -> HashMap<String, SourceId> {
             build(&pkg, config, ret, visited);
         }
     }
+
+    fn build_dep(dep: &Dependency,
+                 config: &Config,
+                 ret: &mut HashMap<String, SourceId>,
+                 visited: &mut HashSet<SourceId>) {
+        let id = dep.source_id();
+        if visited.contains(id) || !id.is_path() {
+            return
+        }
+        let path = match id.url().to_file_path() {
+            Ok(p) => p.join("Cargo.toml"),
+            Err(_) => return,
+        };
+        let pkg = match Package::for_path(&path, config) {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        ret.insert(pkg.name().to_string(),
+                   pkg.package_id().source_id().clone());
+        visited.insert(pkg.package_id().source_id().clone());
+        build(&pkg, config, ret, visited);
+    }
+}
+
+impl Patch {
+    fn is_empty(&self) -> bool {
+        self.unused.is_empty()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialOrd, Ord, PartialEq, Eq)]
@@ -325,10 +380,23 @@ impl<'a, 'cfg> ser::Serialize for WorkspaceResolve<'a, 'cfg> {
             Some(root) if self.use_root_key => Some(encodable_resolve_node(&root, self.resolve)),
             _ => None,
         };
+
+        let patch = Patch {
+            unused: self.resolve.unused_patches().iter().map(|id| {
+                EncodableDependency {
+                    name: id.name().to_string(),
+                    version: id.version().to_string(),
+                    source: encode_source(id.source_id()),
+                    dependencies: None,
+                    replace: None,
+                }
+            }).collect(),
+        };
         EncodableResolve {
             package: Some(encodable),
             root: root,
             metadata: metadata,
+            patch: patch,
         }.serialize(s)
     }
 }
@@ -349,21 +417,23 @@ fn encodable_resolve_node(id: &PackageId, resolve: &Resolve)
         }
     };
 
-    let source = if id.source_id().is_path() {
-        None
-    } else {
-        Some(id.source_id().clone())
-    };
-
     EncodableDependency {
         name: id.name().to_string(),
         version: id.version().to_string(),
-        source: source,
+        source: encode_source(id.source_id()),
         dependencies: deps,
         replace: replace,
     }
 }
 
+fn encode_source(id: &SourceId) -> Option<SourceId> {
+    if id.is_path() {
+        None
+    } else {
+        Some(id.clone())
+    }
+}
+
 fn encodable_package_id(id: &PackageId) -> EncodablePackageId {
     let source = if id.source_id().is_path() {
         None
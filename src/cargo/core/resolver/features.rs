@@ -0,0 +1,346 @@
+//! Resolution of the features enabled for each package when compiling.
+//!
+//! The dependency resolver in the parent module computes a single set of
+//! features for each package in the graph, unifying every way in which the
+//! package is used. That means a feature enabled by a build dependency, a
+//! dev-dependency or a dependency only used on some other platform also ends
+//! up enabled for the normal build of the package.
+//!
+//! When the workspace opts into `resolver = "2"` the features are instead
+//! computed here, after the dependency graph is known, separately for
+//! packages built for the target and for packages built for the host (build
+//! scripts, their dependencies and procedural macros). Dev-dependencies only
+//! contribute features when something that uses them is being built, and
+//! dependencies for platforms other than the one being compiled for are
+//! skipped entirely.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use core::{Dependency, PackageId, PackageSet, Resolve};
+use core::dependency::Kind as DepKind;
+use core::summary;
+use util::errors::CargoResult;
+
+/// Which feature resolver a workspace uses, as set by the `resolver` key in
+/// the root manifest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolveBehavior {
+    /// Features are unified across all uses of a package.
+    V1,
+    /// Features are decoupled between host and target, dev-dependencies and
+    /// platform-specific dependencies.
+    V2,
+}
+
+impl ResolveBehavior {
+    pub fn from_manifest(resolver: &str) -> CargoResult<ResolveBehavior> {
+        match resolver {
+            "1" => Ok(ResolveBehavior::V1),
+            "2" => Ok(ResolveBehavior::V2),
+            s => bail!("`resolver` setting `{}` is not valid, valid options \
+                        are \"1\" or \"2\"", s),
+        }
+    }
+}
+
+/// The features requested on the command line for the packages being built.
+#[derive(Clone, Debug, Default)]
+pub struct RequestedFeatures {
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+}
+
+impl RequestedFeatures {
+    pub fn new(features: &[String],
+               all_features: bool,
+               no_default_features: bool) -> RequestedFeatures {
+        RequestedFeatures {
+            features: features.iter()
+                .flat_map(|s| s.split_whitespace())
+                .flat_map(|s| s.split(','))
+                .filter(|s| s.len() > 0)
+                .map(|s| s.to_string())
+                .collect(),
+            all_features: all_features,
+            no_default_features: no_default_features,
+        }
+    }
+}
+
+/// The features enabled for each package, keyed by whether the package is
+/// being built for the host.
+#[derive(Debug, Default)]
+pub struct ResolvedFeatures {
+    features: HashMap<(PackageId, bool), BTreeSet<String>>,
+    optional_deps: HashMap<(PackageId, bool), HashSet<String>>,
+}
+
+impl ResolvedFeatures {
+    /// Computes the features for everything reachable from `roots`.
+    ///
+    /// `has_dev_units` indicates whether any of the roots are being built in
+    /// a way which needs their dev-dependencies, and `platform_activated`
+    /// is used to decide whether a platform-specific dependency is used when
+    /// building for the host (`true`) or for the target (`false`).
+    pub fn resolve(resolve: &Resolve,
+                   packages: &PackageSet,
+                   roots: &[&PackageId],
+                   requested: &RequestedFeatures,
+                   has_dev_units: bool,
+                   platform_activated: &Fn(&Dependency, bool) -> bool)
+                   -> CargoResult<ResolvedFeatures> {
+        let mut r = FeatureResolver {
+            resolve: resolve,
+            packages: packages,
+            roots: roots,
+            has_dev_units: has_dev_units,
+            platform_activated: platform_activated,
+            activated: HashSet::new(),
+            features: HashMap::new(),
+            optional_deps: HashMap::new(),
+            pending_weak: HashMap::new(),
+        };
+        for &root in roots {
+            let pkg = packages.get(root)?;
+            let mut for_host = vec![false];
+            if pkg.targets().iter().any(|t| t.is_lib() && t.for_host()) {
+                for_host.push(true);
+            }
+            for for_host in for_host {
+                if requested.all_features {
+                    let namespaced = summary::namespaced_deps(pkg.summary().features());
+                    let mut fvs = pkg.summary().features().keys().cloned()
+                                     .collect::<Vec<_>>();
+                    fvs.extend(pkg.dependencies().iter().filter(|d| {
+                        d.is_optional()
+                    }).map(|d| {
+                        if namespaced.contains(d.name_in_toml()) {
+                            format!("dep:{}", d.name_in_toml())
+                        } else {
+                            d.name_in_toml().to_string()
+                        }
+                    }));
+                    r.activate_pkg(root, for_host, &fvs, true)?;
+                } else {
+                    r.activate_pkg(root, for_host, &requested.features,
+                                   !requested.no_default_features)?;
+                }
+            }
+        }
+        Ok(ResolvedFeatures {
+            features: r.features,
+            optional_deps: r.optional_deps,
+        })
+    }
+
+    /// Returns the sorted list of features enabled for `pkg`.
+    pub fn features(&self, pkg: &PackageId, for_host: bool) -> Vec<&str> {
+        self.features.get(&(pkg.clone(), for_host)).map(|features| {
+            features.iter().map(|s| &s[..]).collect()
+        }).unwrap_or(Vec::new())
+    }
+
+    /// Returns whether the optional dependency named `name` in the manifest
+    /// of `pkg` was activated.
+    pub fn optional_dep_activated(&self,
+                                  pkg: &PackageId,
+                                  for_host: bool,
+                                  name: &str) -> bool {
+        self.optional_deps.get(&(pkg.clone(), for_host)).map(|deps| {
+            deps.contains(name)
+        }).unwrap_or(false)
+    }
+}
+
+struct FeatureResolver<'a, 'cfg: 'a> {
+    resolve: &'a Resolve,
+    packages: &'a PackageSet<'cfg>,
+    roots: &'a [&'a PackageId],
+    has_dev_units: bool,
+    platform_activated: &'a Fn(&Dependency, bool) -> bool,
+    activated: HashSet<(PackageId, bool)>,
+    features: HashMap<(PackageId, bool), BTreeSet<String>>,
+    optional_deps: HashMap<(PackageId, bool), HashSet<String>>,
+    // Features of the form `foo?/bar` waiting for the optional dependency
+    // `foo` to be activated.
+    pending_weak: HashMap<(PackageId, bool, String), Vec<String>>,
+}
+
+impl<'a, 'cfg> FeatureResolver<'a, 'cfg> {
+    fn activate_pkg(&mut self,
+                    pkg_id: &PackageId,
+                    for_host: bool,
+                    fvs: &[String],
+                    uses_default_features: bool) -> CargoResult<()> {
+        let key = (pkg_id.clone(), for_host);
+        self.features.entry(key.clone()).or_insert(BTreeSet::new());
+        if self.activated.insert(key) {
+            let packages = self.packages;
+            let pkg = packages.get(pkg_id)?;
+            for dep in pkg.dependencies() {
+                if !dep.is_optional() && self.dep_enabled(pkg_id, for_host, dep) {
+                    self.activate_dep(pkg_id, for_host, dep, &[])?;
+                }
+            }
+        }
+        if uses_default_features {
+            let has_default = self.packages.get(pkg_id)?.summary().features()
+                                  .contains_key("default");
+            if has_default {
+                self.activate_fv(pkg_id, for_host, "default")?;
+            }
+        }
+        for fv in fvs {
+            self.activate_fv(pkg_id, for_host, fv)?;
+        }
+        Ok(())
+    }
+
+    fn activate_fv(&mut self,
+                   pkg_id: &PackageId,
+                   for_host: bool,
+                   fv: &str) -> CargoResult<()> {
+        if fv.is_empty() {
+            return Ok(())
+        }
+        if fv.starts_with("dep:") {
+            return self.activate_optional_dep(pkg_id, for_host, &fv[4..])
+        }
+
+        let packages = self.packages;
+        let pkg = packages.get(pkg_id)?;
+        let mut parts = fv.splitn(2, '/');
+        let name = parts.next().unwrap();
+        if let Some(feature) = parts.next() {
+            let weak = name.ends_with('?');
+            let name = name.trim_right_matches('?');
+            let optional = pkg.dependencies().iter().any(|d| {
+                d.name_in_toml() == name && d.is_optional()
+            });
+            if optional {
+                if weak && !self.optional_dep_activated(pkg_id, for_host, name) {
+                    self.pending_weak
+                        .entry((pkg_id.clone(), for_host, name.to_string()))
+                        .or_insert(Vec::new())
+                        .push(feature.to_string());
+                    return Ok(())
+                }
+                let namespaced = summary::namespaced_deps(pkg.summary().features());
+                if !weak && !namespaced.contains(name) {
+                    self.insert_feature(pkg_id, for_host, name);
+                }
+                self.activate_optional_dep(pkg_id, for_host, name)?;
+            }
+            let features = vec![feature.to_string()];
+            for dep in pkg.dependencies() {
+                if dep.name_in_toml() == name &&
+                   self.dep_enabled(pkg_id, for_host, dep) {
+                    self.activate_dep(pkg_id, for_host, dep, &features)?;
+                }
+            }
+            return Ok(())
+        }
+
+        match pkg.summary().features().get(name) {
+            Some(list) => {
+                if self.insert_feature(pkg_id, for_host, name) {
+                    for fv in list {
+                        self.activate_fv(pkg_id, for_host, fv)?;
+                    }
+                }
+            }
+            None => {
+                let is_dep = pkg.dependencies().iter().any(|d| {
+                    d.name_in_toml() == name && d.is_optional()
+                });
+                if is_dep {
+                    self.insert_feature(pkg_id, for_host, name);
+                    self.activate_optional_dep(pkg_id, for_host, name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn activate_optional_dep(&mut self,
+                             pkg_id: &PackageId,
+                             for_host: bool,
+                             name: &str) -> CargoResult<()> {
+        let newly_activated = self.optional_deps
+            .entry((pkg_id.clone(), for_host))
+            .or_insert(HashSet::new())
+            .insert(name.to_string());
+        if !newly_activated {
+            return Ok(())
+        }
+        let packages = self.packages;
+        let pkg = packages.get(pkg_id)?;
+        for dep in pkg.dependencies() {
+            if dep.name_in_toml() == name && dep.is_optional() &&
+               self.dep_enabled(pkg_id, for_host, dep) {
+                self.activate_dep(pkg_id, for_host, dep, &[])?;
+            }
+        }
+        let key = (pkg_id.clone(), for_host, name.to_string());
+        if let Some(features) = self.pending_weak.remove(&key) {
+            for dep in pkg.dependencies() {
+                if dep.name_in_toml() == name &&
+                   self.dep_enabled(pkg_id, for_host, dep) {
+                    self.activate_dep(pkg_id, for_host, dep, &features)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Activates the package(s) that `dep`, a dependency of `pkg_id`, was
+    /// resolved to, along with the features it requests.
+    fn activate_dep(&mut self,
+                    pkg_id: &PackageId,
+                    for_host: bool,
+                    dep: &Dependency,
+                    extra_features: &[String]) -> CargoResult<()> {
+        let resolve = self.resolve;
+        let packages = self.packages;
+        // The source is compared too, so that packages of the same name from
+        // other sources aren't activated.
+        let dep_ids = resolve.deps_not_replaced(pkg_id).filter(|id| {
+            resolve.dep_matches_id(dep, id)
+        }).map(|id| {
+            resolve.replacement(id).unwrap_or(id)
+        }).collect::<Vec<_>>();
+        let mut features = dep.features().to_vec();
+        features.extend(extra_features.iter().cloned());
+        for dep_id in dep_ids {
+            let dep_pkg = packages.get(dep_id)?;
+            let dep_for_host = for_host || dep.is_build() ||
+                dep_pkg.targets().iter().any(|t| t.is_lib() && t.for_host());
+            self.activate_pkg(dep_id, dep_for_host, &features,
+                              dep.uses_default_features())?;
+        }
+        Ok(())
+    }
+
+    fn dep_enabled(&self, pkg_id: &PackageId, for_host: bool, dep: &Dependency) -> bool {
+        match dep.kind() {
+            DepKind::Development => {
+                self.has_dev_units && self.roots.contains(&pkg_id)
+            }
+            DepKind::Build => (self.platform_activated)(dep, true),
+            DepKind::Normal => (self.platform_activated)(dep, for_host),
+        }
+    }
+
+    fn optional_dep_activated(&self, pkg_id: &PackageId, for_host: bool, name: &str) -> bool {
+        self.optional_deps.get(&(pkg_id.clone(), for_host)).map(|deps| {
+            deps.contains(name)
+        }).unwrap_or(false)
+    }
+
+    fn insert_feature(&mut self, pkg_id: &PackageId, for_host: bool, name: &str) -> bool {
+        self.features.entry((pkg_id.clone(), for_host))
+            .or_insert(BTreeSet::new())
+            .insert(name.to_string())
+    }
+}
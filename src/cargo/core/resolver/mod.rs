@@ -53,18 +53,22 @@ use std::ops::Range;
 use std::rc::Rc;
 
 use semver;
+use url::Url;
 
 use core::{PackageId, Registry, SourceId, Summary, Dependency};
 use core::PackageIdSpec;
+use core::summary;
 use util::Graph;
 use util::errors::{CargoResult, CargoError};
-use util::profile;
+use util::{profile, rust_version_compatible};
 use util::graph::{Nodes, Edges};
 
 pub use self::encode::{EncodableResolve, EncodableDependency, EncodablePackageId};
 pub use self::encode::{Metadata, WorkspaceResolve};
+pub use self::features::{ResolveBehavior, RequestedFeatures, ResolvedFeatures};
 
 mod encode;
+mod features;
 
 /// Represents a fully resolved package dependency graph. Each node in the graph
 /// is a package and edges represent dependencies between packages.
@@ -76,8 +80,13 @@ pub struct Resolve {
     replacements: HashMap<PackageId, PackageId>,
     empty_features: HashSet<String>,
     features: HashMap<PackageId, HashSet<String>>,
+    optional_deps: HashMap<PackageId, HashSet<String>>,
     checksums: HashMap<PackageId, Option<String>>,
     metadata: Metadata,
+    unused_patches: Vec<PackageId>,
+    /// The `[patch]` packages in the graph, with the URL of the source each
+    /// of them patches.
+    patched: HashMap<PackageId, Url>,
 }
 
 pub struct Deps<'a> {
@@ -111,6 +120,40 @@ struct Candidate {
 }
 
 impl Resolve {
+    /// Records which of the `[patch]` summaries registered with the registry
+    /// ended up in the resolved graph, and which did not.
+    pub fn register_used_patches(&mut self,
+                                 patches: &HashMap<Url, Vec<Summary>>) {
+        for (url, summaries) in patches {
+            for summary in summaries {
+                let id = summary.package_id();
+                if self.iter().any(|i| i == id) {
+                    self.patched.insert(id.clone(), url.clone());
+                } else {
+                    self.unused_patches.push(id.clone());
+                }
+            }
+        }
+    }
+
+    /// Returns whether `dep` resolved to `id`, which either comes from the
+    /// source of `dep` or is a `[patch]` of that source.
+    ///
+    /// Replacements aren't taken into account, so `id` should come from
+    /// `deps_not_replaced` rather than `deps`.
+    pub fn dep_matches_id(&self, dep: &Dependency, id: &PackageId) -> bool {
+        if dep.matches_id(id) {
+            return true
+        }
+        match self.patched.get(id) {
+            Some(url) => {
+                url == dep.source_id().url() && dep.name() == id.name() &&
+                    dep.version_req().matches(id.version())
+            }
+            None => false,
+        }
+    }
+
     pub fn merge_from(&mut self, previous: &Resolve) -> CargoResult<()> {
         // Given a previous instance of resolve, it should be forbidden to ever
         // have a checksums which *differ*. If the same package id has differing
@@ -215,6 +258,12 @@ unable to verify that `{0}` is the same as when the lockfile was generated
         self.features.get(pkg).unwrap_or(&self.empty_features)
     }
 
+    /// Returns whether the optional dependency named `name` in the manifest
+    /// of `pkg` was activated.
+    pub fn optional_dep_activated(&self, pkg: &PackageId, name: &str) -> bool {
+        self.optional_deps.get(pkg).map(|deps| deps.contains(name)).unwrap_or(false)
+    }
+
     pub fn features_sorted(&self, pkg: &PackageId) -> Vec<&str> {
         let mut v = Vec::from_iter(self.features(pkg).iter().map(|s| s.as_ref()));
         v.sort();
@@ -224,6 +273,10 @@ unable to verify that `{0}` is the same as when the lockfile was generated
     pub fn query(&self, spec: &str) -> CargoResult<&PackageId> {
         PackageIdSpec::query_str(spec, self.iter())
     }
+
+    pub fn unused_patches(&self) -> &[PackageId] {
+        &self.unused_patches
+    }
 }
 
 impl fmt::Debug for Resolve {
@@ -314,20 +367,30 @@ struct Context<'a> {
     resolve_replacements: RcList<(PackageId, PackageId)>,
 
     replacements: &'a [(PackageIdSpec, Dependency)],
+
+    // When set, candidates whose `rust-version` is newer than this are only
+    // tried after all of the compatible candidates.
+    rust_version: Option<&'a semver::Version>,
 }
 
 type Activations = HashMap<String, HashMap<SourceId, Vec<Summary>>>;
 
 /// Builds the list of all packages required to build the first argument.
+///
+/// If `rust_version` is given, versions of dependencies which declare a
+/// `rust-version` no newer than it are preferred over newer versions which
+/// don't.
 pub fn resolve(summaries: &[(Summary, Method)],
                replacements: &[(PackageIdSpec, Dependency)],
-               registry: &mut Registry) -> CargoResult<Resolve> {
+               registry: &mut Registry,
+               rust_version: Option<&semver::Version>) -> CargoResult<Resolve> {
     let cx = Context {
         resolve_graph: RcList::new(),
         resolve_features: HashMap::new(),
         resolve_replacements: RcList::new(),
         activations: HashMap::new(),
         replacements: replacements,
+        rust_version: rust_version,
     };
     let _p = profile::start(format!("resolving"));
     let cx = activate_deps_loop(cx, registry, summaries)?;
@@ -337,12 +400,26 @@ pub fn resolve(summaries: &[(Summary, Method)],
         empty_features: HashSet::new(),
         checksums: HashMap::new(),
         metadata: BTreeMap::new(),
+        unused_patches: Vec::new(),
+        patched: HashMap::new(),
         replacements: cx.resolve_replacements(),
-        features: cx.resolve_features.iter().map(|(k, v)| {
-            (k.clone(), v.clone())
-        }).collect(),
+        features: HashMap::new(),
+        optional_deps: HashMap::new(),
     };
 
+    // The resolver tracks activated optional dependencies alongside the
+    // features as `dep:NAME`, split those back out here.
+    for (id, features) in cx.resolve_features.iter() {
+        let (deps, features): (HashSet<_>, HashSet<_>) = features.iter().cloned()
+            .partition(|f| f.starts_with("dep:"));
+        if !features.is_empty() {
+            resolve.features.insert(id.clone(), features);
+        }
+        resolve.optional_deps.insert(id.clone(), deps.into_iter().map(|d| {
+            d[4..].to_string()
+        }).collect());
+    }
+
     for summary in cx.activations.values()
                                  .flat_map(|v| v.values())
                                  .flat_map(|v| v.iter()) {
@@ -818,79 +895,135 @@ fn compatible(a: &semver::Version, b: &semver::Version) -> bool {
 fn build_features<'a>(s: &'a Summary, method: &'a Method)
                       -> CargoResult<(HashMap<&'a str, Vec<String>>, HashSet<&'a str>)> {
     let mut deps = HashMap::new();
+    let mut weak = HashMap::new();
     let mut used = HashSet::new();
     let mut visited = HashSet::new();
-    match *method {
-        Method::Everything => {
-            for key in s.features().keys() {
-                add_feature(s, key, &mut deps, &mut used, &mut visited)?;
+    let namespaced = summary::namespaced_deps(s.features());
+    {
+        let mut state = FeatureState {
+            summary: s,
+            namespaced: &namespaced,
+            deps: &mut deps,
+            weak: &mut weak,
+            used: &mut used,
+            visited: &mut visited,
+        };
+        match *method {
+            Method::Everything => {
+                for key in s.features().keys() {
+                    state.add_feature(key)?;
+                }
+                for dep in s.dependencies().iter().filter(|d| d.is_optional()) {
+                    if namespaced.contains(dep.name_in_toml()) {
+                        state.deps.entry(dep.name_in_toml()).or_insert(Vec::new());
+                    } else {
+                        state.add_feature(dep.name_in_toml())?;
+                    }
+                }
             }
-            for dep in s.dependencies().iter().filter(|d| d.is_optional()) {
-                add_feature(s, dep.name(), &mut deps, &mut used,
-                            &mut visited)?;
+            Method::Required { features: requested_features, .. } =>  {
+                for feat in requested_features.iter() {
+                    state.add_feature(feat)?;
+                }
             }
         }
-        Method::Required { features: requested_features, .. } =>  {
-            for feat in requested_features.iter() {
-                add_feature(s, feat, &mut deps, &mut used, &mut visited)?;
+        match *method {
+            Method::Everything |
+            Method::Required { uses_default_features: true, .. } => {
+                if s.features().get("default").is_some() {
+                    state.add_feature("default")?;
+                }
             }
+            Method::Required { uses_default_features: false, .. } => {}
         }
     }
-    match *method {
-        Method::Everything |
-        Method::Required { uses_default_features: true, .. } => {
-            if s.features().get("default").is_some() {
-                add_feature(s, "default", &mut deps, &mut used,
-                            &mut visited)?;
-            }
+
+    // Features of the form `foo?/bar` only enable `bar` if `foo` ended up
+    // being enabled by something else, or if it isn't optional at all.
+    for (package, features) in weak {
+        let required = s.dependencies().iter().any(|d| {
+            d.name_in_toml() == package && !d.is_optional()
+        });
+        if required {
+            deps.entry(package).or_insert(Vec::new()).extend(features);
+        } else if let Some(enabled) = deps.get_mut(package) {
+            enabled.extend(features);
         }
-        Method::Required { uses_default_features: false, .. } => {}
     }
     return Ok((deps, used));
 
-    fn add_feature<'a>(s: &'a Summary,
-                       feat: &'a str,
-                       deps: &mut HashMap<&'a str, Vec<String>>,
-                       used: &mut HashSet<&'a str>,
-                       visited: &mut HashSet<&'a str>) -> CargoResult<()> {
-        if feat.is_empty() { return Ok(()) }
-
-        // If this feature is of the form `foo/bar`, then we just lookup package
-        // `foo` and enable its feature `bar`. Otherwise this feature is of the
-        // form `foo` and we need to recurse to enable the feature `foo` for our
-        // own package, which may end up enabling more features or just enabling
-        // a dependency.
-        let mut parts = feat.splitn(2, '/');
-        let feat_or_package = parts.next().unwrap();
-        match parts.next() {
-            Some(feat) => {
-                let package = feat_or_package;
-                used.insert(package);
-                deps.entry(package)
-                    .or_insert(Vec::new())
-                    .push(feat.to_string());
+    struct FeatureState<'a: 'b, 'b> {
+        summary: &'a Summary,
+        namespaced: &'b HashSet<String>,
+        deps: &'b mut HashMap<&'a str, Vec<String>>,
+        weak: &'b mut HashMap<&'a str, Vec<String>>,
+        used: &'b mut HashSet<&'a str>,
+        visited: &'b mut HashSet<&'a str>,
+    }
+
+    impl<'a, 'b> FeatureState<'a, 'b> {
+        fn add_feature(&mut self, feat: &'a str) -> CargoResult<()> {
+            if feat.is_empty() { return Ok(()) }
+
+            // `dep:foo` enables the optional dependency `foo` without
+            // enabling a feature of the same name.
+            if feat.starts_with("dep:") {
+                self.deps.entry(&feat[4..]).or_insert(Vec::new());
+                return Ok(())
             }
-            None => {
-                let feat = feat_or_package;
-                if !visited.insert(feat) {
-                    bail!("Cyclic feature dependency: feature `{}` depends \
-                           on itself", feat)
+
+            // If this feature is of the form `foo/bar`, then we just lookup
+            // package `foo` and enable its feature `bar`. Otherwise this
+            // feature is of the form `foo` and we need to recurse to enable
+            // the feature `foo` for our own package, which may end up enabling
+            // more features or just enabling a dependency.
+            let mut parts = feat.splitn(2, '/');
+            let feat_or_package = parts.next().unwrap();
+            match parts.next() {
+                Some(feat) if feat_or_package.ends_with('?') => {
+                    let package = &feat_or_package[..feat_or_package.len() - 1];
+                    self.weak.entry(package)
+                        .or_insert(Vec::new())
+                        .push(feat.to_string());
                 }
-                used.insert(feat);
-                match s.features().get(feat) {
-                    Some(recursive) => {
-                        for f in recursive {
-                            add_feature(s, f, deps, used, visited)?;
-                        }
+                Some(feat) => {
+                    let package = feat_or_package;
+                    if !self.namespaced.contains(package) {
+                        self.used.insert(package);
                     }
-                    None => {
-                        deps.entry(feat).or_insert(Vec::new());
+                    self.deps.entry(package)
+                        .or_insert(Vec::new())
+                        .push(feat.to_string());
+                }
+                None => {
+                    let feat = feat_or_package;
+                    if !self.visited.insert(feat) {
+                        bail!("Cyclic feature dependency: feature `{}` depends \
+                               on itself", feat)
+                    }
+                    match self.summary.features().get(feat) {
+                        Some(recursive) => {
+                            self.used.insert(feat);
+                            for f in recursive {
+                                self.add_feature(f)?;
+                            }
+                        }
+                        None if self.namespaced.contains(feat) => {
+                            bail!("Package `{}` does not have feature `{}`. It \
+                                   has an optional dependency with that name, \
+                                   but it is only enabled with `dep:{}`",
+                                  self.summary.package_id(), feat, feat)
+                        }
+                        None => {
+                            self.used.insert(feat);
+                            self.deps.entry(feat).or_insert(Vec::new());
+                        }
                     }
+                    self.visited.remove(feat);
                 }
-                visited.remove(feat);
             }
+            Ok(())
         }
-        Ok(())
     }
 }
 
@@ -945,9 +1078,21 @@ impl<'a> Context<'a> {
         let mut deps = deps.into_iter().map(|(dep, features)| {
             let mut candidates = self.query(registry, &dep)?;
             // When we attempt versions for a package, we'll want to start at
-            // the maximum version and work our way down.
+            // the maximum version and work our way down, optionally trying
+            // everything compatible with the requested rust version first.
+            let rust_version = self.rust_version;
+            let compatible = |s: &Summary| {
+                match (rust_version, s.rust_version()) {
+                    (Some(rustc), Some(required)) => {
+                        rust_version_compatible(required, rustc)
+                    }
+                    _ => true,
+                }
+            };
             candidates.sort_by(|a, b| {
-                b.summary.version().cmp(a.summary.version())
+                compatible(&b.summary).cmp(&compatible(&a.summary)).then_with(|| {
+                    b.summary.version().cmp(a.summary.version())
+                })
             });
             Ok((dep, Rc::new(candidates), Rc::new(features)))
         }).collect::<CargoResult<Vec<DepInfo>>>()?;
@@ -1060,11 +1205,13 @@ impl<'a> Context<'a> {
         // Next, sanitize all requested features by whitelisting all the
         // requested features that correspond to optional dependencies
         for dep in deps {
-            // weed out optional dependencies, but not those required
-            if dep.is_optional() && !feature_deps.contains_key(dep.name()) {
+            // weed out optional dependencies, but not those required. Features
+            // refer to dependencies by the name they were given in the
+            // manifest, which may differ from the package name if renamed.
+            if dep.is_optional() && !feature_deps.contains_key(dep.name_in_toml()) {
                 continue
             }
-            let mut base = feature_deps.remove(dep.name()).unwrap_or(vec![]);
+            let mut base = feature_deps.remove(dep.name_in_toml()).unwrap_or(vec![]);
             base.extend(dep.features().iter().cloned());
             for feature in base.iter() {
                 if feature.contains("/") {
@@ -1088,8 +1235,16 @@ impl<'a> Context<'a> {
             }
         }
 
+        // Record which optional dependencies were activated, as those
+        // enabled with `dep:` have no feature of the same name to tell.
+        let optional_deps = ret.iter().filter(|&&(ref dep, _)| {
+            dep.is_optional()
+        }).map(|&(ref dep, _)| {
+            format!("dep:{}", dep.name_in_toml())
+        }).collect::<Vec<_>>();
+
         // Record what list of features is active for this package.
-        if !used_features.is_empty() {
+        if !used_features.is_empty() || !optional_deps.is_empty() {
             let pkgid = candidate.package_id();
 
             let mut set = self.resolve_features.entry(pkgid.clone())
@@ -1099,6 +1254,7 @@ impl<'a> Context<'a> {
                     set.insert(feature.to_string());
                 }
             }
+            set.extend(optional_deps);
         }
 
         Ok(ret)
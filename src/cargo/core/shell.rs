@@ -2,7 +2,7 @@ use std::fmt;
 use std::io::prelude::*;
 
 use atty;
-use termcolor::Color::{Green, Red, Yellow};
+use termcolor::Color::{Cyan, Green, Red, Yellow};
 use termcolor::{self, StandardStream, Color, ColorSpec, WriteColor};
 
 use util::errors::CargoResult;
@@ -17,6 +17,9 @@ pub enum Verbosity {
 pub struct Shell {
     err: ShellOut,
     verbosity: Verbosity,
+    /// The width of the progress bar currently drawn on the last line of
+    /// stderr, if any, which has to be erased before anything else is printed.
+    needs_clear: Option<usize>,
 }
 
 enum ShellOut {
@@ -39,6 +42,7 @@ impl Shell {
                 ColorChoice::CargoAuto,
             ),
             verbosity: Verbosity::Verbose,
+            needs_clear: None,
         }
     }
 
@@ -46,6 +50,7 @@ impl Shell {
         Shell {
             err: ShellOut::Write(out),
             verbosity: Verbosity::Verbose,
+            needs_clear: None,
         }
     }
 
@@ -57,15 +62,71 @@ impl Shell {
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
             _ => {
+                if self.needs_clear.is_some() {
+                    self.err_erase_line();
+                }
                 self.err.print(status, message, color, justified)
             }
         }
     }
 
+    /// Notes that a progress bar `width` columns wide is displayed, which the
+    /// next output erases first.
+    ///
+    /// The width is remembered rather than queried from the terminal as the
+    /// bar may be drawn with `term.progress.when = "always"` even when stderr
+    /// isn't one.
+    pub fn set_needs_clear(&mut self, width: usize) {
+        self.needs_clear = Some(width);
+    }
+
+    /// Returns the width of the terminal in columns, if stderr is a terminal
+    /// and its width is known.
+    pub fn err_width(&self) -> Option<usize> {
+        match self.err {
+            ShellOut::Stream(..) if self.is_err_tty() => imp::stderr_width(),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if stderr is a terminal rather than a pipe or a file.
+    pub fn is_err_tty(&self) -> bool {
+        match self.err {
+            ShellOut::Stream(..) => atty::is(atty::Stream::Stderr),
+            ShellOut::Write(_) => false,
+        }
+    }
+
+    /// Gets a reference to the underlying writer, erasing a progress bar
+    /// first if one is displayed.
     pub fn err(&mut self) -> &mut Write {
+        if self.needs_clear.is_some() {
+            self.err_erase_line();
+        }
         self.err.as_write()
     }
 
+    /// Erases the current line of stderr.
+    pub fn err_erase_line(&mut self) {
+        if let Some(width) = self.needs_clear.take().or_else(|| self.err_width()) {
+            let _ = write!(self.err.as_write(), "\r{:1$}\r", "", width);
+        }
+    }
+
+    /// Prints a bold cyan status header without a trailing newline, used as
+    /// the prefix of the progress bar.
+    pub fn status_header<T: fmt::Display>(&mut self, status: T) -> CargoResult<()> {
+        match self.verbosity {
+            Verbosity::Quiet => Ok(()),
+            _ => {
+                if self.needs_clear.is_some() {
+                    self.err_erase_line();
+                }
+                self.err.print_header(&status, Cyan)
+            }
+        }
+    }
+
     pub fn status<T, U>(&mut self, status: T, message: U) -> CargoResult<()>
         where T: fmt::Display, U: fmt::Display
     {
@@ -136,6 +197,15 @@ impl Shell {
         Ok(())
     }
 
+    /// Returns `true` if output to stderr is colored.
+    pub fn supports_color(&self) -> bool {
+        match self.color_choice() {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::CargoAuto => self.is_err_tty(),
+        }
+    }
+
     pub fn color_choice(&self) -> ColorChoice {
         match self.err {
             ShellOut::Stream(_, cc) => cc,
@@ -176,6 +246,24 @@ impl ShellOut {
         Ok(())
     }
 
+    fn print_header(&mut self, status: &fmt::Display, color: Color) -> CargoResult<()> {
+        match *self {
+            ShellOut::Stream(ref mut err, _) => {
+                err.reset()?;
+                err.set_color(ColorSpec::new()
+                                    .set_bold(true)
+                                    .set_fg(Some(color)))?;
+                write!(err, "{:>12}", status)?;
+                err.reset()?;
+                write!(err, " ")?;
+            }
+            ShellOut::Write(ref mut w) => {
+                write!(w, "{:>12} ", status)?;
+            }
+        }
+        Ok(())
+    }
+
     fn as_write(&mut self) -> &mut Write {
         match *self {
             ShellOut::Stream(ref mut err, _) => err,
@@ -199,3 +287,43 @@ impl ColorChoice {
         }
     }
 }
+
+#[cfg(unix)]
+mod imp {
+    use std::mem;
+
+    use libc;
+
+    pub fn stderr_width() -> Option<usize> {
+        unsafe {
+            let mut winsize: libc::winsize = mem::zeroed();
+            if libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ.into(), &mut winsize) < 0 {
+                return None
+            }
+            if winsize.ws_col > 0 {
+                Some(winsize.ws_col as usize)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    extern crate kernel32;
+    extern crate winapi;
+
+    use std::mem;
+
+    pub fn stderr_width() -> Option<usize> {
+        unsafe {
+            let stderr = kernel32::GetStdHandle(winapi::STD_ERROR_HANDLE);
+            let mut csbi: winapi::CONSOLE_SCREEN_BUFFER_INFO = mem::zeroed();
+            if kernel32::GetConsoleScreenBufferInfo(stderr, &mut csbi) == 0 {
+                return None
+            }
+            Some((csbi.srWindow.Right - csbi.srWindow.Left) as usize)
+        }
+    }
+}
@@ -16,7 +16,7 @@ use ops;
 use sources::git;
 use sources::{PathSource, GitSource, RegistrySource, CRATES_IO};
 use sources::DirectorySource;
-use util::{Config, CargoResult, ToUrl};
+use util::{Config, CargoResult, ToUrl, internal};
 
 /// A Source finds and downloads remote packages based on names and
 /// versions.
@@ -32,6 +32,23 @@ pub trait Source: Registry {
     /// version specified.
     fn download(&mut self, package: &PackageId) -> CargoResult<Package>;
 
+    /// Returns the URL that the contents of the package can be fetched from
+    /// over HTTP, so that several packages can be downloaded at once.
+    ///
+    /// `None` is returned if the package is already available locally or if
+    /// the source doesn't download packages over HTTP, in which case
+    /// `download` is used instead.
+    fn download_url(&mut self, _package: &PackageId) -> CargoResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Finishes a download started with the URL returned by `download_url`,
+    /// given the contents which were fetched from it.
+    fn finish_download(&mut self, package: &PackageId, _contents: &[u8])
+                       -> CargoResult<Package> {
+        Err(internal(format!("source can't finish downloading `{}`", package)))
+    }
+
     /// Generates a unique string which represents the fingerprint of the
     /// current state of the source.
     ///
@@ -68,6 +85,15 @@ impl<'a, T: Source + ?Sized + 'a> Source for Box<T> {
         (**self).download(id)
     }
 
+    fn download_url(&mut self, id: &PackageId) -> CargoResult<Option<String>> {
+        (**self).download_url(id)
+    }
+
+    fn finish_download(&mut self, id: &PackageId, contents: &[u8])
+                       -> CargoResult<Package> {
+        (**self).finish_download(id, contents)
+    }
+
     fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
         (**self).fingerprint(pkg)
     }
@@ -169,6 +195,10 @@ impl SourceId {
                 let url = url.to_url()?;
                 Ok(SourceId::new(Kind::Path, url))
             }
+            "sparse" => {
+                let url = string.to_url()?;
+                Ok(SourceId::new(Kind::Registry, url))
+            }
             kind => Err(format!("unsupported source protocol: {}", kind).into())
         }
     }
@@ -206,7 +236,7 @@ impl SourceId {
     /// This is the main cargo registry by default, but it can be overridden in
     /// a `.cargo/config`.
     pub fn crates_io(config: &Config) -> CargoResult<SourceId> {
-        let cfg = ops::registry_configuration(config)?;
+        let cfg = ops::registry_configuration(config, None)?;
         let url = if let Some(ref index) = cfg.index {
             static WARNED: AtomicBool = ATOMIC_BOOL_INIT;
             if !WARNED.swap(true, SeqCst) {
@@ -223,6 +253,13 @@ impl SourceId {
         Ok(SourceId::for_registry(&url))
     }
 
+    /// Returns the `SourceId` of the alternative registry named `key`, as
+    /// configured in the `[registries]` table of a `.cargo/config`.
+    pub fn alt_registry(config: &Config, key: &str) -> CargoResult<SourceId> {
+        let url = config.get_registry_index(key)?;
+        Ok(SourceId::for_registry(&url))
+    }
+
     pub fn url(&self) -> &Url {
         &self.inner.url
     }
@@ -240,6 +277,13 @@ impl SourceId {
         }
     }
 
+    /// Returns `true` for registries whose index is fetched over HTTP one
+    /// file at a time, given by a `sparse+https://...` URL.
+    pub fn is_sparse(&self) -> bool {
+        self.inner.kind == Kind::Registry &&
+            self.inner.url.scheme().starts_with("sparse+")
+    }
+
     /// Creates an implementation of `Source` corresponding to this ID.
     pub fn load<'a>(&self, config: &'a Config) -> Box<Source + 'a> {
         trace!("loading SourceId; {}", self);
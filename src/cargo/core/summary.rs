@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem;
 use std::rc::Rc;
 
@@ -22,31 +22,66 @@ struct Inner {
     dependencies: Vec<Dependency>,
     features: HashMap<String, Vec<String>>,
     checksum: Option<String>,
+    rust_version: Option<Version>,
 }
 
 impl Summary {
     pub fn new(pkg_id: PackageId,
                dependencies: Vec<Dependency>,
                features: HashMap<String, Vec<String>>) -> CargoResult<Summary> {
+        // Optional dependencies referenced with `dep:` don't get an implicit
+        // feature of the same name.
+        let namespaced = namespaced_deps(&features);
         for dep in dependencies.iter() {
-            if features.get(dep.name()).is_some() {
+            if features.get(dep.name_in_toml()).is_some() &&
+               !(dep.is_optional() && namespaced.contains(dep.name_in_toml())) {
                 bail!("Features and dependencies cannot have the \
-                       same name: `{}`", dep.name())
+                       same name: `{}`", dep.name_in_toml())
             }
             if dep.is_optional() && !dep.is_transitive() {
                 bail!("Dev-dependencies are not allowed to be optional: `{}`",
-                      dep.name())
+                      dep.name_in_toml())
             }
         }
         for (feature, list) in features.iter() {
             for dep in list.iter() {
+                if dep.starts_with("dep:") {
+                    let dep = &dep[4..];
+                    match dependencies.iter().find(|d| d.name_in_toml() == dep) {
+                        Some(d) if d.is_optional() => continue,
+                        Some(_) => {
+                            bail!("Feature `{}` includes `dep:{}`, but `{}` is \
+                                   not an optional dependency", feature, dep, dep)
+                        }
+                        None => {
+                            bail!("Feature `{}` includes `dep:{}`, but `{}` is \
+                                   not a dependency", feature, dep, dep)
+                        }
+                    }
+                }
                 let mut parts = dep.splitn(2, '/');
                 let dep = parts.next().unwrap();
                 let is_reexport = parts.next().is_some();
+                if is_reexport && dep.ends_with('?') {
+                    let dep = &dep[..dep.len() - 1];
+                    if dependencies.iter().any(|d| d.name_in_toml() == dep) {
+                        continue
+                    }
+                    bail!("Feature `{}` includes `{}?/..`, but `{}` is not a \
+                           dependency", feature, dep, dep)
+                }
                 if !is_reexport && features.get(dep).is_some() { continue }
-                match dependencies.iter().find(|d| d.name() == dep) {
+                match dependencies.iter().find(|d| d.name_in_toml() == dep) {
                     Some(d) => {
-                        if d.is_optional() || is_reexport { continue }
+                        if is_reexport { continue }
+                        if d.is_optional() && !namespaced.contains(dep) {
+                            continue
+                        }
+                        if d.is_optional() {
+                            bail!("Feature `{}` includes `{}`, but `{}` is an \
+                                   optional dependency which is only enabled \
+                                   with `dep:{}`", feature, dep, dep, dep)
+                        }
                         bail!("Feature `{}` depends on `{}` which is not an \
                                optional dependency.\nConsider adding \
                                `optional = true` to the dependency",
@@ -69,6 +104,7 @@ impl Summary {
                 dependencies: dependencies,
                 features: features,
                 checksum: None,
+                rust_version: None,
             }),
         })
     }
@@ -82,6 +118,9 @@ impl Summary {
     pub fn checksum(&self) -> Option<&str> {
         self.inner.checksum.as_ref().map(|s| &s[..])
     }
+    pub fn rust_version(&self) -> Option<&Version> {
+        self.inner.rust_version.as_ref()
+    }
 
     pub fn override_id(mut self, id: PackageId) -> Summary {
         Rc::make_mut(&mut self.inner).package_id = id;
@@ -93,6 +132,11 @@ impl Summary {
         self
     }
 
+    pub fn set_rust_version(mut self, rust_version: Option<Version>) -> Summary {
+        Rc::make_mut(&mut self.inner).rust_version = rust_version;
+        self
+    }
+
     pub fn map_dependencies<F>(mut self, f: F) -> Summary
                                where F: FnMut(Dependency) -> Dependency {
         {
@@ -117,6 +161,14 @@ impl Summary {
     }
 }
 
+/// Returns the names of the optional dependencies which are referenced with
+/// `dep:` in `features`, and so have no implicit feature of their own.
+pub fn namespaced_deps(features: &HashMap<String, Vec<String>>) -> HashSet<String> {
+    features.values().flat_map(|list| list).filter(|f| {
+        f.starts_with("dep:")
+    }).map(|f| f[4..].to_string()).collect()
+}
+
 impl PartialEq for Summary {
     fn eq(&self, other: &Summary) -> bool {
         self.inner.package_id == other.inner.package_id
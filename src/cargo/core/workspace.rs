@@ -4,9 +4,11 @@ use std::path::{Path, PathBuf};
 use std::slice;
 
 use glob::glob;
+use url::Url;
 
 use core::{Package, VirtualManifest, EitherManifest, SourceId};
 use core::{PackageIdSpec, Dependency, Profile, Profiles};
+use core::resolver::ResolveBehavior;
 use util::{Config, Filesystem};
 use util::errors::{CargoResult, CargoResultExt};
 use util::paths;
@@ -191,6 +193,17 @@ impl<'cfg> Workspace<'cfg> {
         }
     }
 
+    /// Returns the feature resolver selected by the root manifest of this
+    /// workspace.
+    pub fn resolve_behavior(&self) -> ResolveBehavior {
+        let root = self.root_manifest.as_ref().unwrap_or(&self.current_manifest);
+        let behavior = match *self.packages.get(root) {
+            MaybePackage::Package(ref p) => p.manifest().resolve_behavior(),
+            MaybePackage::Virtual(ref m) => m.resolve_behavior(),
+        };
+        behavior.unwrap_or(ResolveBehavior::V1)
+    }
+
     /// Returns the root path of this workspace.
     ///
     /// That is, this returns the path of the directory containing the
@@ -222,6 +235,20 @@ impl<'cfg> Workspace<'cfg> {
         }
     }
 
+    /// Returns the root [patch] section of this workspace.
+    ///
+    /// This may be from a virtual crate or an actual crate.
+    pub fn root_patch(&self) -> &HashMap<Url, Vec<Dependency>> {
+        let path = match self.root_manifest {
+            Some(ref p) => p,
+            None => &self.current_manifest,
+        };
+        match *self.packages.get(path) {
+            MaybePackage::Package(ref p) => p.manifest().patch(),
+            MaybePackage::Virtual(ref v) => v.patch(),
+        }
+    }
+
     /// Returns an iterator over all packages in this workspace
     pub fn members<'a>(&'a self) -> Members<'a, 'cfg> {
         Members {
@@ -249,50 +276,10 @@ impl<'cfg> Workspace<'cfg> {
     /// if some other transient error happens.
     fn find_root(&mut self, manifest_path: &Path)
                  -> CargoResult<Option<PathBuf>> {
-        fn read_root_pointer(member_manifest: &Path, root_link: &str) -> CargoResult<PathBuf> {
-            let path = member_manifest.parent().unwrap()
-                .join(root_link)
-                .join("Cargo.toml");
-            debug!("find_root - pointer {}", path.display());
-            return Ok(paths::normalize_path(&path))
-        };
-
-        {
-            let current = self.packages.load(&manifest_path)?;
-            match *current.workspace_config() {
-                WorkspaceConfig::Root { .. } => {
-                    debug!("find_root - is root {}", manifest_path.display());
-                    return Ok(Some(manifest_path.to_path_buf()))
-                }
-                WorkspaceConfig::Member { root: Some(ref path_to_root) } => {
-                    return Ok(Some(read_root_pointer(manifest_path, path_to_root)?))
-                }
-                WorkspaceConfig::Member { root: None } => {}
-            }
-        }
-
-        for path in paths::ancestors(manifest_path).skip(2) {
-            let manifest = path.join("Cargo.toml");
-            debug!("find_root - trying {}", manifest.display());
-            if manifest.exists() {
-                match *self.packages.load(&manifest)?.workspace_config() {
-                    WorkspaceConfig::Root { ref exclude, ref members } => {
-                        debug!("find_root - found a root checking exclusion");
-                        if !is_excluded(members, exclude, path, manifest_path) {
-                            debug!("find_root - found!");
-                            return Ok(Some(manifest))
-                        }
-                    }
-                    WorkspaceConfig::Member { root: Some(ref path_to_root) } => {
-                        debug!("find_root - found pointer");
-                        return Ok(Some(read_root_pointer(&manifest, path_to_root)?))
-                    }
-                    WorkspaceConfig::Member { .. } => {}
-                }
-            }
-        }
-
-        Ok(None)
+        let packages = &mut self.packages;
+        find_workspace_root(manifest_path, |path| {
+            Ok(packages.load(path)?.workspace_config().clone())
+        })
     }
 
     /// After the root of a workspace has been located, probes for all members
@@ -562,6 +549,60 @@ fn expand_member_path(path: &Path) -> CargoResult<Vec<PathBuf>> {
     }).collect()
 }
 
+/// Finds the path of the root manifest of the workspace which
+/// `manifest_path` is a member of, if any.
+///
+/// The `workspace_config` callback is used to learn how each manifest
+/// encountered along the way is configured. This is separate from
+/// `Workspace::find_root` so manifests can be resolved against their
+/// workspace root before they are fully loaded.
+pub fn find_workspace_root<F>(manifest_path: &Path, mut workspace_config: F)
+                              -> CargoResult<Option<PathBuf>>
+    where F: FnMut(&Path) -> CargoResult<WorkspaceConfig>
+{
+    fn read_root_pointer(member_manifest: &Path, root_link: &str) -> CargoResult<PathBuf> {
+        let path = member_manifest.parent().unwrap()
+            .join(root_link)
+            .join("Cargo.toml");
+        debug!("find_root - pointer {}", path.display());
+        return Ok(paths::normalize_path(&path))
+    };
+
+    match workspace_config(manifest_path)? {
+        WorkspaceConfig::Root { .. } => {
+            debug!("find_root - is root {}", manifest_path.display());
+            return Ok(Some(manifest_path.to_path_buf()))
+        }
+        WorkspaceConfig::Member { root: Some(ref path_to_root) } => {
+            return Ok(Some(read_root_pointer(manifest_path, path_to_root)?))
+        }
+        WorkspaceConfig::Member { root: None } => {}
+    }
+
+    for path in paths::ancestors(manifest_path).skip(2) {
+        let manifest = path.join("Cargo.toml");
+        debug!("find_root - trying {}", manifest.display());
+        if manifest.exists() {
+            match workspace_config(&manifest)? {
+                WorkspaceConfig::Root { ref exclude, ref members } => {
+                    debug!("find_root - found a root checking exclusion");
+                    if !is_excluded(members, exclude, path, manifest_path) {
+                        debug!("find_root - found!");
+                        return Ok(Some(manifest))
+                    }
+                }
+                WorkspaceConfig::Member { root: Some(ref path_to_root) } => {
+                    debug!("find_root - found pointer");
+                    return Ok(Some(read_root_pointer(&manifest, path_to_root)?))
+                }
+                WorkspaceConfig::Member { .. } => {}
+            }
+        }
+    }
+
+    Ok(None)
+}
+
 fn is_excluded(members: &Option<Vec<String>>,
                exclude: &[String],
                root_path: &Path,
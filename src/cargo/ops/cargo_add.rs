@@ -0,0 +1,275 @@
+use semver::VersionReq;
+use toml;
+
+use core::{Dependency, Package, PackageIdSpec, Registry, SourceId, Summary};
+use core::Workspace;
+use core::dependency::Kind;
+use core::summary::namespaced_deps;
+use sources::SourceConfigMap;
+use util::errors::CargoResult;
+use util::paths;
+use util::toml::edit::{self, Document};
+
+pub struct AddOptions<'a> {
+    /// The workspace member to add the dependency to, or the current package.
+    pub spec: Option<&'a str>,
+    /// The crate to add, optionally followed by `@` and a version requirement.
+    pub krate: &'a str,
+    pub features: &'a [String],
+    pub kind: Kind,
+    pub optional: bool,
+    pub target: Option<&'a str>,
+}
+
+pub struct RemoveOptions<'a> {
+    pub spec: Option<&'a str>,
+    pub krate: &'a str,
+    pub kind: Kind,
+    pub target: Option<&'a str>,
+}
+
+/// Adds a dependency on a crate from crates.io to a manifest, picking the
+/// latest version matching the requirement given, if any.
+pub fn add(ws: &Workspace, opts: &AddOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let pkg = select_package(ws, opts.spec)?;
+
+    let mut parts = opts.krate.splitn(2, '@');
+    let name = parts.next().unwrap();
+    let req = parts.next();
+    if let Some(req) = req {
+        if let Err(e) = req.parse::<VersionReq>() {
+            bail!("the version requirement `{}` for `{}` is invalid: {}", req, name, e)
+        }
+    }
+
+    let summary = latest_summary(ws, name, req)?;
+    let available = available_features(&summary);
+    if let Some(feature) = opts.features.iter().find(|f| !available.contains(f)) {
+        bail!("package `{}` does not have a feature `{}`", summary.package_id(), feature)
+    }
+
+    let contents = paths::read(pkg.manifest_path())?;
+    let mut doc = Document::parse(&contents)?;
+    let table = dependency_table(&doc, opts.kind, opts.target);
+    let version = match req {
+        Some(req) => req.to_string(),
+        None => summary.version().to_string(),
+    };
+
+    let sub = join(&table, name);
+    if doc.has_table(&strs(&sub)) {
+        // `[dependencies.foo]` is edited key by key to keep its layout.
+        doc.set(&strs(&sub), "version", &edit::string_to_string(&version));
+        if !opts.features.is_empty() {
+            let existing = doc.get(&strs(&sub), "features");
+            let features = merge_features(existing.as_ref(), opts.features);
+            doc.set(&strs(&sub), "features", &edit::value_to_string(&features));
+        }
+        if opts.optional {
+            doc.set(&strs(&sub), "optional", "true");
+        }
+    } else {
+        let existing = match doc.get(&strs(&table), name) {
+            Some(toml::Value::Table(table)) => table,
+            _ => toml::value::Table::new(),
+        };
+        let mut entries = vec![("version".to_string(),
+                                toml::Value::String(version.clone()))];
+        for (key, value) in existing.iter() {
+            match key.as_str() {
+                "version" | "features" | "optional" => {}
+                _ => entries.push((key.clone(), value.clone())),
+            }
+        }
+        let features = merge_features(existing.get("features"), opts.features);
+        if features.as_array().map_or(false, |a| !a.is_empty()) {
+            entries.push(("features".to_string(), features));
+        }
+        let optional = opts.optional ||
+            existing.get("optional").and_then(|v| v.as_bool()).unwrap_or(false);
+        if optional {
+            entries.push(("optional".to_string(), toml::Value::Boolean(true)));
+        }
+        let value = if entries.len() == 1 {
+            edit::string_to_string(&version)
+        } else {
+            edit::inline_table(&entries)
+        };
+        doc.set(&strs(&table), name, &value);
+    }
+
+    config.shell().status("Adding", format!("{} v{} to {}", name,
+                                              summary.version(),
+                                              table_name(&table)))?;
+    if !opts.features.is_empty() {
+        config.shell().status("Features", opts.features.join(", "))?;
+    }
+    paths::write(pkg.manifest_path(), doc.to_string().as_bytes())?;
+    Ok(())
+}
+
+/// Removes a dependency from a manifest, along with the references to it in
+/// the `[features]` table once no dependency table declares it anymore.
+pub fn remove(ws: &Workspace, opts: &RemoveOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let pkg = select_package(ws, opts.spec)?;
+    let name = opts.krate;
+
+    let contents = paths::read(pkg.manifest_path())?;
+    let mut doc = Document::parse(&contents)?;
+    let table = dependency_table(&doc, opts.kind, opts.target);
+    let removed = doc.remove(&strs(&table), name) ||
+                  doc.remove_table(&strs(&join(&table, name)));
+    if !removed {
+        bail!("the dependency `{}` could not be found in `{}`",
+              name, table_name(&table))
+    }
+    config.shell().status("Removing", format!("{} from {}", name, table_name(&table)))?;
+
+    if !is_declared(&doc, name) {
+        let dep_prefix = format!("{}/", name);
+        let weak_prefix = format!("{}?/", name);
+        let namespaced = format!("dep:{}", name);
+        for feature in doc.keys(&["features"]) {
+            let list = match doc.get(&["features"], &feature) {
+                Some(toml::Value::Array(list)) => list,
+                _ => continue,
+            };
+            let kept = list.iter().filter(|v| {
+                match v.as_str() {
+                    Some(s) => s != name && s != namespaced &&
+                               !s.starts_with(&dep_prefix) &&
+                               !s.starts_with(&weak_prefix),
+                    None => true,
+                }
+            }).cloned().collect::<Vec<_>>();
+            if kept.len() != list.len() {
+                let kept = toml::Value::Array(kept);
+                doc.set(&["features"], &feature, &edit::value_to_string(&kept));
+            }
+        }
+    }
+
+    paths::write(pkg.manifest_path(), doc.to_string().as_bytes())?;
+    Ok(())
+}
+
+fn select_package<'a>(ws: &'a Workspace, spec: Option<&str>) -> CargoResult<&'a Package> {
+    let spec = match spec {
+        Some(spec) => PackageIdSpec::parse(spec)?,
+        None => return ws.current(),
+    };
+    if !ws.members().any(|p| spec.matches(p.package_id())) {
+        bail!("package `{}` is not a member of the workspace", spec)
+    }
+    let id = spec.query(ws.members().map(|p| p.package_id()))?;
+    Ok(ws.members().find(|p| p.package_id() == id).unwrap())
+}
+
+fn latest_summary(ws: &Workspace, name: &str, req: Option<&str>) -> CargoResult<Summary> {
+    let config = ws.config();
+    let source_id = SourceId::crates_io(config)?;
+    let map = SourceConfigMap::new(config)?;
+    let mut source = map.load(&source_id)?;
+    source.update()?;
+    let dep = Dependency::parse_no_deprecated(name, req, source.source_id())?;
+    let summaries = source.query_vec(&dep)?;
+
+    // Without a requirement the latest stable release is preferred, as it
+    // would be by a `*` requirement.
+    let stable = summaries.iter().filter(|s| s.version().pre.is_empty()).max_by_key(|s| {
+        s.version().clone()
+    });
+    let latest = match stable {
+        Some(s) if req.is_none() => Some(s),
+        _ => summaries.iter().max_by_key(|s| s.version().clone()),
+    };
+    match latest {
+        Some(summary) => Ok(summary.clone()),
+        None => {
+            let vers_info = req.map(|v| format!(" with version `{}`", v))
+                               .unwrap_or(String::new());
+            bail!("could not find `{}` in `{}`{}", name, source_id, vers_info)
+        }
+    }
+}
+
+/// Returns the features which can be enabled on a package, including the
+/// implicit features of its optional dependencies.
+fn available_features(summary: &Summary) -> Vec<String> {
+    let namespaced = namespaced_deps(summary.features());
+    let mut features = summary.features().keys().cloned().collect::<Vec<_>>();
+    features.extend(summary.dependencies().iter().filter(|d| {
+        d.is_optional() && !namespaced.contains(d.name())
+    }).map(|d| d.name().to_string()));
+    features
+}
+
+fn merge_features(existing: Option<&toml::Value>, new: &[String]) -> toml::Value {
+    let mut features = existing.and_then(|v| v.as_array()).cloned().unwrap_or(Vec::new());
+    for feature in new {
+        if !features.iter().any(|f| f.as_str() == Some(feature)) {
+            features.push(toml::Value::String(feature.clone()));
+        }
+    }
+    toml::Value::Array(features)
+}
+
+/// Returns the path of the table holding dependencies of `kind`, preferring
+/// the spelling with underscores only when the manifest already uses it.
+fn dependency_table(doc: &Document, kind: Kind, target: Option<&str>) -> Vec<String> {
+    let mut prefix = Vec::new();
+    if let Some(target) = target {
+        prefix.push("target".to_string());
+        prefix.push(target.to_string());
+    }
+    let (name, alt) = match kind {
+        Kind::Normal => ("dependencies", None),
+        Kind::Development => ("dev-dependencies", Some("dev_dependencies")),
+        Kind::Build => ("build-dependencies", Some("build_dependencies")),
+    };
+    let name = match alt {
+        Some(alt) if !doc.has_table(&strs(&join(&prefix, name))) &&
+                     doc.has_table(&strs(&join(&prefix, alt))) => alt,
+        _ => name,
+    };
+    join(&prefix, name)
+}
+
+/// Returns whether any dependency table of the document still declares
+/// `name`, either as an entry or as its own `[dependencies.name]` table.
+fn is_declared(doc: &Document, name: &str) -> bool {
+    let is_dep_table = |path: &[String]| {
+        let last = match path.last() {
+            Some(last) => last,
+            None => return false,
+        };
+        let is_deps = match last.as_str() {
+            "dependencies" | "dev-dependencies" | "dev_dependencies" |
+            "build-dependencies" | "build_dependencies" => true,
+            _ => false,
+        };
+        is_deps && (path.len() == 1 || (path.len() == 3 && path[0] == "target"))
+    };
+    doc.table_paths().iter().any(|path| {
+        if is_dep_table(&path[..]) && doc.keys(&strs(path)).iter().any(|k| k == name) {
+            return true
+        }
+        path.last().map_or(false, |l| l == name) && is_dep_table(&path[..path.len() - 1])
+    })
+}
+
+fn join(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+fn strs(path: &[String]) -> Vec<&str> {
+    path.iter().map(|s| s.as_str()).collect()
+}
+
+fn table_name(path: &[String]) -> String {
+    path.iter().map(|p| edit::key_to_string(p)).collect::<Vec<_>>().join(".")
+}
@@ -2,10 +2,10 @@ use std::default::Default;
 use std::fs;
 use std::path::Path;
 
-use core::{Profiles, Workspace};
+use core::{PackageSet, Profiles, Resolve, Workspace};
 use util::Config;
 use util::errors::{CargoResult, CargoResultExt};
-use ops::{self, Context, BuildConfig, Kind, Unit};
+use ops::{self, Context, BuildConfig, Kind, TargetConfig, Unit};
 
 pub struct CleanOptions<'a> {
     pub spec: &'a [String],
@@ -31,17 +31,49 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
     let (packages, resolve) = ops::resolve_ws(ws)?;
 
     let profiles = ws.profiles();
+    clean_profiles(ws, &resolve, &packages, opts, profiles, opts.release, None)?;
+
+    // Custom profiles are built into their own directories, so their units
+    // are removed with a context of their own.
+    for (name, custom) in profiles.custom.iter() {
+        if !target_dir.join(name).into_path_unlocked().is_dir() {
+            continue
+        }
+        clean_profiles(ws, &resolve, &packages, opts, &custom.profiles,
+                       custom.release, Some(name.clone()))?;
+    }
+
+    Ok(())
+}
+
+/// Removes the artifacts of the packages in `opts.spec` built with any of
+/// `profiles` from the directory of the profile named `profile_name`.
+fn clean_profiles<'a, 'cfg>(ws: &'a Workspace<'cfg>,
+                            resolve: &'a Resolve,
+                            packages: &'a PackageSet<'cfg>,
+                            opts: &CleanOptions<'cfg>,
+                            profiles: &'a Profiles,
+                            release: bool,
+                            profile_name: Option<String>) -> CargoResult<()> {
     let host_triple = opts.config.rustc()?.host.clone();
-    let mut cx = Context::new(ws, &resolve, &packages, opts.config,
+    let requested_targets = opts.target.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let targets = vec![TargetConfig::default(); requested_targets.len()];
+    let mut cx = Context::new(ws, resolve, packages, opts.config,
                                    BuildConfig {
                                        host_triple: host_triple,
-                                       requested_target: opts.target.map(|s| s.to_owned()),
-                                       release: opts.release,
+                                       requested_targets: requested_targets,
+                                       targets: targets,
+                                       release: release,
+                                       profile_name: profile_name,
                                        jobs: 1,
                                        ..BuildConfig::default()
                                    },
                                    profiles)?;
     let mut units = Vec::new();
+    let mut kinds = cx.build_config.default_kinds();
+    if !kinds.contains(&Kind::Host) {
+        kinds.push(Kind::Host);
+    }
 
     for spec in opts.spec {
         // Translate the spec to a Package
@@ -50,21 +82,34 @@ pub fn clean(ws: &Workspace, opts: &CleanOptions) -> CargoResult<()> {
 
         // Generate all relevant `Unit` targets for this package
         for target in pkg.targets() {
-            for kind in [Kind::Host, Kind::Target].iter() {
+            for kind in kinds.iter() {
                 let Profiles {
                     ref release, ref dev, ref test, ref bench, ref doc,
                     ref custom_build, ref test_deps, ref bench_deps, ref check,
-                    ref doctest,
+                    ref doctest, ref package_overrides, ref build_override,
+                    custom: _,
                 } = *profiles;
-                let profiles = [release, dev, test, bench, doc, custom_build,
-                                test_deps, bench_deps, check, doctest];
+                let mut profiles = vec![release, dev, test, bench, doc,
+                                        custom_build, test_deps, bench_deps,
+                                        check, doctest];
+                for o in package_overrides.iter() {
+                    profiles.extend(&[&o.release, &o.dev, &o.test_deps,
+                                      &o.bench_deps, &o.check]);
+                    profiles.extend(o.build_override.0.iter());
+                    profiles.extend(o.build_override.1.iter());
+                }
+                profiles.extend(build_override.0.iter());
+                profiles.extend(build_override.1.iter());
                 for profile in profiles.iter() {
-                    units.push(Unit {
-                        pkg: &pkg,
-                        target: target,
-                        profile: profile,
-                        kind: *kind,
-                    });
+                    for &for_host in [false, true].iter() {
+                        units.push(Unit {
+                            pkg: &pkg,
+                            target: target,
+                            profile: profile,
+                            kind: *kind,
+                            for_host: for_host,
+                        });
+                    }
                 }
             }
         }
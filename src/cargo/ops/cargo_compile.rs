@@ -29,7 +29,7 @@ use std::sync::Arc;
 
 use core::{Source, Package, Target};
 use core::{Profile, TargetKind, Profiles, Workspace, PackageId, PackageIdSpec};
-use core::resolver::Resolve;
+use core::resolver::{Resolve, RequestedFeatures};
 use ops::{self, BuildOutput, Executor, DefaultExecutor};
 use util::config::Config;
 use util::{CargoResult, profile};
@@ -39,8 +39,9 @@ pub struct CompileOptions<'a> {
     pub config: &'a Config,
     /// Number of concurrent jobs to use.
     pub jobs: Option<u32>,
-    /// The target platform to compile for (example: `i686-unknown-linux-gnu`).
-    pub target: Option<&'a str>,
+    /// The target platforms to compile for (example: `i686-unknown-linux-gnu`),
+    /// `build.target` or the host if empty.
+    pub target: &'a [String],
     /// Extra features to build for the root package
     pub features: &'a [String],
     /// Flag whether all available features should be built for the root package
@@ -54,10 +55,14 @@ pub struct CompileOptions<'a> {
     pub filter: CompileFilter<'a>,
     /// Whether this is a release build or not
     pub release: bool,
+    /// The profile selected with `--profile`, if any
+    pub profile: Option<&'a str>,
     /// Mode for this compile.
     pub mode: CompileMode,
     /// `--error_format` flag for the compiler.
     pub message_format: MessageFormat,
+    /// Whether to write a report of the time spent building each unit.
+    pub timings: bool,
     /// Extra arguments to be passed to rustdoc (for main crate and dependencies)
     pub target_rustdoc_args: Option<&'a [String]>,
     /// The specified target will be compiled with all the available arguments,
@@ -71,15 +76,17 @@ impl<'a> CompileOptions<'a> {
         CompileOptions {
             config: config,
             jobs: None,
-            target: None,
+            target: &[],
             features: &[],
             all_features: false,
             no_default_features: false,
             spec: ops::Packages::Packages(&[]),
             mode: mode,
             release: false,
+            profile: None,
             filter: CompileFilter::Everything { required_features_filterable: false },
             message_format: MessageFormat::Human,
+            timings: false,
             target_rustdoc_args: None,
             target_rustc_args: None,
         }
@@ -191,18 +198,34 @@ pub fn compile_ws<'a>(ws: &Workspace<'a>,
                       -> CargoResult<ops::Compilation<'a>> {
     let CompileOptions { config, jobs, target, spec, features,
                          all_features, no_default_features,
-                         release, mode, message_format,
+                         release, profile, mode, message_format, timings,
                          ref filter,
                          ref target_rustdoc_args,
                          ref target_rustc_args } = *options;
 
-    let target = target.map(|s| s.to_string());
-
     if jobs == Some(0) {
         bail!("jobs must be at least 1")
     }
 
-    let profiles = ws.profiles();
+    let (release, profiles, profile_name) = match profile {
+        None => (release, ws.profiles(), None),
+        Some(_) if release => {
+            bail!("conflicting usage of --profile and --release")
+        }
+        Some("dev") => (false, ws.profiles(), None),
+        Some("release") => (true, ws.profiles(), None),
+        Some(name @ "test") | Some(name @ "bench") | Some(name @ "doc") => {
+            bail!("the `{}` profile cannot be selected with --profile", name)
+        }
+        Some(name) => {
+            match ws.profiles().custom.get(name) {
+                Some(custom) => {
+                    (custom.release, &custom.profiles, Some(name.to_string()))
+                }
+                None => bail!("profile `{}` is not defined", name),
+            }
+        }
+    };
 
     let specs = spec.into_package_id_specs(ws)?;
     let resolve = ops::resolve_ws_precisely(ws,
@@ -290,9 +313,25 @@ pub fn compile_ws<'a>(ws: &Workspace<'a>,
     let mut ret = {
         let _p = profile::start("compiling");
         let mut build_config = scrape_build_config(config, jobs, target)?;
+        if build_config.requested_targets.len() > 1 {
+            match mode {
+                CompileMode::Test | CompileMode::Bench | CompileMode::Doctest => {
+                    bail!("tests and benchmarks can only be built for a single \
+                           target, but {} were requested: {}",
+                          build_config.requested_targets.len(),
+                          build_config.requested_targets.join(", "))
+                }
+                _ => {}
+            }
+        }
         build_config.release = release;
+        build_config.profile_name = profile_name;
         build_config.test = mode == CompileMode::Test || mode == CompileMode::Bench;
         build_config.json_messages = message_format == MessageFormat::Json;
+        build_config.timings = timings;
+        build_config.features = RequestedFeatures::new(features,
+                                                       all_features,
+                                                       no_default_features);
         if let CompileMode::Doc { deps } = mode {
             build_config.doc_all = deps;
         }
@@ -637,7 +676,7 @@ fn generate_targets<'a>(pkg: &'a Package,
 /// * target.$target.libfoo.metadata
 fn scrape_build_config(config: &Config,
                        jobs: Option<u32>,
-                       target: Option<String>)
+                       target: &[String])
                        -> CargoResult<ops::BuildConfig> {
     if jobs.is_some() && config.jobserver_from_env().is_some() {
         config.shell().warn("a `-j` argument was passed to Cargo but Cargo is \
@@ -659,22 +698,42 @@ fn scrape_build_config(config: &Config,
         None => None,
     };
     let jobs = jobs.or(cfg_jobs).unwrap_or(::num_cpus::get() as u32);
-    let cfg_target = config.get_string("build.target")?.map(|s| s.val);
-    let target = target.or(cfg_target);
+    let targets = requested_targets(config, target)?;
     let mut base = ops::BuildConfig {
         host_triple: config.rustc()?.host.clone(),
-        requested_target: target.clone(),
         jobs: jobs,
         ..Default::default()
     };
     base.host = scrape_target_config(config, &base.host_triple)?;
-    base.target = match target.as_ref() {
-        Some(triple) => scrape_target_config(config, &triple)?,
-        None => base.host.clone(),
-    };
+    for triple in targets.iter() {
+        base.targets.push(scrape_target_config(config, triple)?);
+    }
+    base.requested_targets = targets;
     Ok(base)
 }
 
+/// Returns the targets to compile for: the ones passed with `--target`,
+/// otherwise those of `build.target`, which is either a single target or a
+/// list of them. Duplicates are ignored, and an empty list means the host.
+pub fn requested_targets(config: &Config, target: &[String])
+                         -> CargoResult<Vec<String>> {
+    let targets = if target.is_empty() {
+        match config.get_list_or_string("build.target")? {
+            Some(targets) => targets.val,
+            None => Vec::new(),
+        }
+    } else {
+        target.to_vec()
+    };
+    let mut ret = Vec::new();
+    for triple in targets {
+        if !triple.is_empty() && !ret.contains(&triple) {
+            ret.push(triple);
+        }
+    }
+    Ok(ret)
+}
+
 fn scrape_target_config(config: &Config, triple: &str)
                         -> CargoResult<ops::TargetConfig> {
 
@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{self, Value};
+
+use util::config::{Config, ConfigValue as CV, Definition};
+use util::errors::CargoResult;
+use util::toml::edit;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+pub struct GetConfigOptions<'a> {
+    pub key: Option<&'a str>,
+    pub format: ConfigFormat,
+    pub show_origin: bool,
+}
+
+/// Prints the merged configuration, or only the part of it below
+/// `opts.key`, optionally along with where each value was defined.
+pub fn get_config(config: &Config, opts: &GetConfigOptions) -> CargoResult<()> {
+    let mut value = match opts.key {
+        Some(key) => {
+            if is_credential(key) {
+                bail!("config value `{}` is a credential and isn't printed", key)
+            }
+            match config.get(key)? {
+                Some(value) => value,
+                None => bail!("config value `{}` is not set", key),
+            }
+        }
+        None => CV::Table(config.values()?.clone(), PathBuf::from(".")),
+    };
+    remove_credentials(opts.key.unwrap_or(""), &mut value);
+
+    match opts.format {
+        ConfigFormat::Toml => {
+            print_toml(opts.key.unwrap_or(""), &value, opts.show_origin);
+        }
+        ConfigFormat::Json => {
+            let mut json = to_json(&value, opts.show_origin);
+            if let Some(key) = opts.key {
+                for part in key.split('.').rev() {
+                    let mut map = serde_json::Map::new();
+                    map.insert(part.to_string(), json);
+                    json = Value::Object(map);
+                }
+            }
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+    }
+    Ok(())
+}
+
+/// Whether the value at `key` is a registry token, which may come from the
+/// credentials file and shouldn't end up in logs.
+fn is_credential(key: &str) -> bool {
+    let parts = key.split('.').collect::<Vec<_>>();
+    match parts.len() {
+        2 => parts[0] == "registry" && parts[1] == "token",
+        3 => parts[0] == "registries" && parts[2] == "token",
+        _ => false,
+    }
+}
+
+/// Removes all credentials from the table `value` found at `key`.
+fn remove_credentials(key: &str, value: &mut CV) {
+    if let CV::Table(ref mut table, _) = *value {
+        table.retain(|k, _| !is_credential(&join_key(key, k)));
+        for (k, v) in table.iter_mut() {
+            remove_credentials(&join_key(key, k), v);
+        }
+    }
+}
+
+fn join_key(key: &str, child: &str) -> String {
+    if key.is_empty() {
+        child.to_string()
+    } else {
+        format!("{}.{}", key, child)
+    }
+}
+
+fn print_toml(key: &str, value: &CV, show_origin: bool) {
+    let origin = |path: &Path| {
+        if show_origin {
+            format!(" # {}", Definition::from_path(path.to_path_buf()))
+        } else {
+            String::new()
+        }
+    };
+    match *value {
+        CV::Table(ref table, _) => {
+            let mut keys = table.keys().collect::<Vec<_>>();
+            keys.sort();
+            for k in keys {
+                let full_key = if key.is_empty() {
+                    edit::key_to_string(k)
+                } else {
+                    format!("{}.{}", key, edit::key_to_string(k))
+                };
+                print_toml(&full_key, &table[k], show_origin);
+            }
+        }
+        CV::String(ref s, ref path) => {
+            println!("{} = {}{}", key, edit::string_to_string(s), origin(path));
+        }
+        CV::Integer(i, ref path) => println!("{} = {}{}", key, i, origin(path)),
+        CV::Boolean(b, ref path) => println!("{} = {}{}", key, b, origin(path)),
+        CV::List(ref list, _) if show_origin => {
+            println!("{} = [", key);
+            for &(ref s, ref path) in list {
+                println!("    {},{}", edit::string_to_string(s), origin(path));
+            }
+            println!("]");
+        }
+        CV::List(ref list, _) => {
+            let list = list.iter().map(|&(ref s, _)| edit::string_to_string(s))
+                           .collect::<Vec<_>>();
+            println!("{} = [{}]", key, list.join(", "));
+        }
+    }
+}
+
+fn to_json(value: &CV, show_origin: bool) -> Value {
+    let (json, path) = match *value {
+        CV::Table(ref table, _) => {
+            return Value::Object(table.iter().map(|(k, v)| {
+                (k.clone(), to_json(v, show_origin))
+            }).collect())
+        }
+        CV::String(ref s, ref path) => (json!(s), path),
+        CV::Integer(i, ref path) => (json!(i), path),
+        CV::Boolean(b, ref path) => (json!(b), path),
+        CV::List(ref list, ref path) => {
+            (Value::Array(list.iter().map(|&(ref s, _)| json!(s)).collect()), path)
+        }
+    };
+    if show_origin {
+        json!({
+            "value": json,
+            "origin": Definition::from_path(path.clone()).to_string(),
+        })
+    } else {
+        json
+    }
+}
@@ -5,8 +5,6 @@ use util::CargoResult;
 /// Executes `cargo fetch`.
 pub fn fetch<'a>(ws: &Workspace<'a>) -> CargoResult<(Resolve, PackageSet<'a>)> {
     let (packages, resolve) = ops::resolve_ws(ws)?;
-    for id in resolve.iter() {
-        packages.get(id)?;
-    }
+    packages.get_many(&resolve.iter().collect::<Vec<_>>())?;
     Ok((resolve, packages))
 }
@@ -0,0 +1,296 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use util::Config;
+use util::cache_usage::{self, CacheUsage};
+use util::config::PACKAGE_CACHE_LOCK;
+use util::errors::{CargoResult, CargoResultExt};
+
+/// Entries not used for this long are removed unless configured otherwise.
+const DEFAULT_MAX_AGE: u64 = 90 * 24 * 60 * 60;
+
+/// The cache is cleaned automatically at most this often.
+const AUTO_GC_INTERVAL: u64 = 24 * 60 * 60;
+
+pub struct GcOptions {
+    /// Entries which haven't been used for this many seconds are removed.
+    pub max_age: Option<u64>,
+    /// The least recently used entries are removed until the cache takes up
+    /// at most this many bytes.
+    pub max_size: Option<u64>,
+}
+
+impl GcOptions {
+    /// Reads the `gc.max-age` and `gc.max-size` configuration, falling back
+    /// to removing entries which weren't used for three months.
+    pub fn from_config(config: &Config) -> CargoResult<GcOptions> {
+        let max_age = match config.get_string("gc.max-age")? {
+            Some(v) => Some(parse_age(&v.val).chain_err(|| {
+                format!("invalid `gc.max-age` in {}", v.definition)
+            })?),
+            None => None,
+        };
+        let max_size = match config.get_string("gc.max-size")? {
+            Some(v) => Some(parse_size(&v.val).chain_err(|| {
+                format!("invalid `gc.max-size` in {}", v.definition)
+            })?),
+            None => None,
+        };
+        Ok(GcOptions {
+            max_age: max_age.or(if max_size.is_none() {
+                Some(DEFAULT_MAX_AGE)
+            } else {
+                None
+            }),
+            max_size: max_size,
+        })
+    }
+}
+
+/// An entry of the global package cache, which is removed as a whole.
+struct Entry {
+    path: PathBuf,
+    key: String,
+    size: u64,
+    last_use: u64,
+}
+
+/// Executes `cargo clean gc`, removing entries of the global package cache
+/// in `CARGO_HOME` which are too old or don't fit into the maximum size.
+pub fn gc(config: &Config, opts: &GcOptions) -> CargoResult<()> {
+    let _lock = config.home().open_rw(PACKAGE_CACHE_LOCK, config,
+                                      "the package cache")?;
+    let mut file = CacheUsage::lock(config)?;
+    let mut usage = CacheUsage::load(&mut file)?;
+    clean(config, opts, &mut usage)?;
+    // The cache was just cleaned, so there's no need to do it again
+    // automatically for a while.
+    usage.last_auto_gc = cache_usage::now();
+    usage.save(&mut file)
+}
+
+/// Cleans the package cache after a command finished if `gc.auto` is
+/// enabled and it wasn't cleaned within the last day.
+///
+/// Only commands which used the package cache clean it, so commands like
+/// `cargo --version` or builds of workspaces without any dependencies from
+/// registries or git repositories never spend time on it. Neither do commands
+/// run with `--frozen`, which aren't supposed to change anything besides the
+/// target directory.
+///
+/// Nothing is done if another Cargo is using the cache at the moment, and
+/// errors are only reported as warnings as the command itself succeeded.
+pub fn auto_gc(config: &Config) {
+    if let Err(e) = try_auto_gc(config) {
+        let msg = format!("failed to clean the package cache: {}", e);
+        let _ = config.shell().warn(msg);
+    }
+}
+
+fn try_auto_gc(config: &Config) -> CargoResult<()> {
+    let auto = config.get_bool("gc.auto")?.map(|v| v.val).unwrap_or(false);
+    if !auto || !config.network_allowed() ||
+       !config.holds_package_cache_lock() {
+        return Ok(())
+    }
+
+    // Our own lock has to go before we can take the exclusive one.
+    config.release_package_cache_lock();
+    let _lock = match config.home().try_open_rw(PACKAGE_CACHE_LOCK, config,
+                                                  "the package cache")? {
+        Some(lock) => lock,
+        None => return Ok(()),
+    };
+    let mut file = CacheUsage::lock(config)?;
+    let mut usage = CacheUsage::load(&mut file)?;
+    let now = cache_usage::now();
+    if usage.last_auto_gc.saturating_add(AUTO_GC_INTERVAL) > now {
+        return Ok(())
+    }
+    let opts = GcOptions::from_config(config)?;
+    clean(config, &opts, &mut usage)?;
+    usage.last_auto_gc = now;
+    usage.save(&mut file)
+}
+
+fn clean(config: &Config,
+         opts: &GcOptions,
+         usage: &mut CacheUsage) -> CargoResult<()> {
+    let now = cache_usage::now();
+    let mut entries = Vec::new();
+    for path in entry_paths(config)? {
+        let key = match cache_usage::entry_key(config, &path) {
+            Some(key) => key,
+            None => continue,
+        };
+        // Entries from before usage was tracked are treated as if they were
+        // just used, so they're only removed once they're unused for long.
+        let last_use = *usage.entries.entry(key.clone()).or_insert(now);
+        entries.push(Entry {
+            size: size(&path)?,
+            path: path,
+            key: key,
+            last_use: last_use,
+        });
+    }
+
+    // Forget about entries which were removed by other means.
+    usage.entries = entries.iter().map(|e| (e.key.clone(), e.last_use))
+                           .collect();
+
+    // Oldest first, so that the least recently used entries are removed
+    // first to get below the maximum size.
+    entries.sort_by_key(|e| e.last_use);
+    let mut total = entries.iter().map(|e| e.size).sum::<u64>();
+    let mut removed = 0;
+    let mut removed_size = 0;
+    for entry in entries {
+        let too_old = opts.max_age.map(|age| {
+            entry.last_use.saturating_add(age) < now
+        }).unwrap_or(false);
+        let too_big = opts.max_size.map(|max| total > max).unwrap_or(false);
+        if !too_old && !too_big {
+            continue
+        }
+        config.shell().verbose(|s| {
+            s.status("Removing", entry.path.display())
+        })?;
+        remove(&entry.path).chain_err(|| {
+            format!("failed to remove `{}`", entry.path.display())
+        })?;
+        usage.entries.remove(&entry.key);
+        total -= entry.size;
+        removed += 1;
+        removed_size += entry.size;
+    }
+
+    // Don't leave the directories of removed git checkouts behind.
+    let checkouts = config.git_path().into_path_unlocked().join("checkouts");
+    for dir in children(&checkouts)? {
+        let _ = fs::remove_dir(dir);
+    }
+
+    config.shell().status("Removed",
+                          format!("{} package cache entries, {} total",
+                                  removed, human_size(removed_size)))?;
+    Ok(())
+}
+
+/// Lists the entries of the package cache: the downloaded `.crate` files and
+/// their unpacked sources, and the git databases and checkouts.
+fn entry_paths(config: &Config) -> CargoResult<Vec<PathBuf>> {
+    let git = config.git_path().into_path_unlocked();
+    let dirs = [
+        config.registry_cache_path().into_path_unlocked(),
+        config.registry_source_path().into_path_unlocked(),
+        git.join("checkouts"),
+    ];
+    let mut paths = Vec::new();
+    for dir in dirs.iter() {
+        for registry in children(dir)? {
+            paths.extend(children(&registry)?);
+        }
+    }
+    paths.extend(children(&git.join("db"))?);
+    Ok(paths)
+}
+
+/// Lists the entries of `dir`, skipping hidden ones such as lock files.
+fn children(dir: &Path) -> CargoResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new())
+    }
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_str().map(|s| s.starts_with('.'))
+                          .unwrap_or(false);
+        if !hidden {
+            paths.push(entry.path());
+        }
+    }
+    Ok(paths)
+}
+
+fn size(path: &Path) -> CargoResult<u64> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len())
+    }
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        total += size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+fn remove(path: &Path) -> CargoResult<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Parses an age like `30d` or `12 hours` into seconds.
+pub fn parse_age(s: &str) -> CargoResult<u64> {
+    let (n, unit) = split_number(s)?;
+    let secs = match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => 1,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60,
+        "h" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        _ => bail!("invalid age `{}`, expected a number followed by one of \
+                    `s`, `m`, `h`, `d` or `w`", s),
+    };
+    match n.checked_mul(secs) {
+        Some(secs) => Ok(secs),
+        None => bail!("age `{}` is too large", s),
+    }
+}
+
+/// Parses a size like `5GB` or `500 MiB` into bytes.
+pub fn parse_size(s: &str) -> CargoResult<u64> {
+    let (n, unit) = split_number(s)?;
+    let bytes = match &unit.to_lowercase()[..] {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000 * 1000,
+        "gb" => 1000 * 1000 * 1000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        _ => bail!("invalid size `{}`, expected a number of bytes, \
+                    optionally followed by a unit such as `MB` or `GiB`", s),
+    };
+    match n.checked_mul(bytes) {
+        Some(bytes) => Ok(bytes),
+        None => bail!("size `{}` is too large", s),
+    }
+}
+
+fn split_number(s: &str) -> CargoResult<(u64, &str)> {
+    let s = s.trim();
+    let end = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+    let n = s[..end].parse().chain_err(|| {
+        format!("`{}` doesn't start with a number", s)
+    })?;
+    Ok((n, s[end..].trim()))
+}
+
+fn human_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
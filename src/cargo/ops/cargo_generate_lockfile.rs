@@ -1,5 +1,7 @@
 use std::collections::{BTreeMap, HashSet};
 
+use semver::Version;
+
 use core::PackageId;
 use core::registry::PackageRegistry;
 use core::{Resolve, SourceId, Workspace};
@@ -13,17 +15,39 @@ pub struct UpdateOptions<'a> {
     pub to_update: &'a [String],
     pub precise: Option<&'a str>,
     pub aggressive: bool,
+    /// Prefer versions of dependencies whose `rust-version` is compatible
+    /// with the workspace (or the current rustc if no member declares one).
+    pub rust_version_compatible: bool,
 }
 
 pub fn generate_lockfile(ws: &Workspace) -> CargoResult<()> {
+    generate_lockfile_for(ws, None)
+}
+
+fn generate_lockfile_for(ws: &Workspace, rust_version: Option<&Version>)
+                         -> CargoResult<()> {
     let mut registry = PackageRegistry::new(ws.config())?;
     let resolve = ops::resolve_with_previous(&mut registry, ws,
                                              Method::Everything,
-                                             None, None, &[])?;
+                                             None, None, &[],
+                                             rust_version)?;
     ops::write_pkg_lockfile(ws, &resolve)?;
     Ok(())
 }
 
+/// Returns the rust version dependencies should be compatible with: the
+/// oldest `rust-version` declared by a workspace member, or the version of
+/// the active rustc if there is none.
+fn workspace_rust_version(ws: &Workspace) -> CargoResult<Version> {
+    let declared = ws.members().filter_map(|pkg| {
+        pkg.summary().rust_version().cloned()
+    }).min();
+    match declared {
+        Some(v) => Ok(v),
+        None => ws.config().rustc()?.version(),
+    }
+}
+
 pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions)
                        -> CargoResult<()> {
 
@@ -35,9 +59,15 @@ pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions)
         bail!("you can't generate a lockfile for an empty workspace.")
     }
 
+    let rust_version = if opts.rust_version_compatible {
+        Some(workspace_rust_version(ws)?)
+    } else {
+        None
+    };
+
     let previous_resolve = match ops::load_pkg_lockfile(ws)? {
         Some(resolve) => resolve,
-        None => return generate_lockfile(ws),
+        None => return generate_lockfile_for(ws, rust_version.as_ref()),
     };
     let mut registry = PackageRegistry::new(opts.config)?;
     let mut to_avoid = HashSet::new();
@@ -79,7 +109,8 @@ pub fn update_lockfile(ws: &Workspace, opts: &UpdateOptions)
                                                   Method::Everything,
                                                   Some(&previous_resolve),
                                                   Some(&to_avoid),
-                                                  &[])?;
+                                                  &[],
+                                                  rust_version.as_ref())?;
 
     // Summarize what is changing for the user.
     let print_change = |status: &str, msg: String| {
@@ -15,6 +15,7 @@ use toml;
 use core::{SourceId, Source, Package, Dependency, PackageIdSpec};
 use core::{PackageId, Workspace};
 use ops::{self, CompileFilter, DefaultExecutor};
+use ops::cargo_compile::requested_targets;
 use sources::{GitSource, PathSource, SourceConfigMap};
 use util::{Config, internal};
 use util::{Filesystem, FileLock};
@@ -103,6 +104,13 @@ pub fn install(root: Option<&str>,
     };
     let pkg = ws.current()?;
 
+    let targets = requested_targets(config, opts.target)?;
+    if targets.len() > 1 {
+        bail!("`cargo install` can only install executables built for a \
+               single target, but {} were requested: {}",
+              targets.len(), targets.join(", "))
+    }
+
     config.shell().status("Installing", pkg)?;
 
     // Preflight checks to check up front whether we'll overwrite something.
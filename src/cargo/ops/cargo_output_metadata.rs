@@ -1,6 +1,4 @@
-use serde::ser::{self, Serialize};
-
-use core::resolver::Resolve;
+use core::resolver::{RequestedFeatures, ResolveBehavior, ResolvedFeatures};
 use core::{Package, PackageId, Workspace};
 use ops::{self, Packages};
 use util::CargoResult;
@@ -53,6 +51,33 @@ fn metadata_full(ws: &Workspace,
                                          &specs)?;
     let (packages, resolve) = deps;
 
+    // With the new feature resolver the features of each package may differ
+    // between the host and the target, so report both. Metadata isn't
+    // specific to any platform, so dependencies for every platform count.
+    let resolved_features = if ws.resolve_behavior() == ResolveBehavior::V2 {
+        let roots = ws.members().map(|pkg| pkg.package_id()).collect::<Vec<_>>();
+        let requested = RequestedFeatures::new(&opt.features,
+                                               opt.all_features,
+                                               opt.no_default_features);
+        Some(ResolvedFeatures::resolve(&resolve, &packages, &roots, &requested,
+                                       true, &|_, _| true)?)
+    } else {
+        None
+    };
+
+    let nodes = resolve.iter().map(|id| {
+        MetadataNode {
+            id: id.clone(),
+            dependencies: resolve.deps(id).cloned().collect(),
+            features: resolved_features.as_ref().map(|r| {
+                to_owned(r.features(id, false))
+            }),
+            host_features: resolved_features.as_ref().map(|r| {
+                to_owned(r.features(id, true))
+            }),
+        }
+    }).collect();
+
     let packages = packages.package_ids()
                            .map(|i| packages.get(i).map(|p| p.clone()))
                            .collect::<CargoResult<Vec<_>>>()?;
@@ -61,7 +86,7 @@ fn metadata_full(ws: &Workspace,
         packages: packages,
         workspace_members: ws.members().map(|pkg| pkg.package_id().clone()).collect(),
         resolve: Some(MetadataResolve{
-            resolve: resolve,
+            nodes: nodes,
             root: ws.current_opt().map(|pkg| pkg.package_id().clone()),
         }),
         target_directory: ws.target_dir().display().to_string(),
@@ -78,29 +103,28 @@ pub struct ExportInfo {
     version: u32,
 }
 
-/// Newtype wrapper to provide a custom `Serialize` implementation.
-/// The one from lockfile does not fit because it uses a non-standard
-/// format for `PackageId`s
+/// The resolved dependency graph. The one from lockfile does not fit because
+/// it uses a non-standard format for `PackageId`s
 #[derive(Serialize)]
 struct MetadataResolve {
-    #[serde(rename = "nodes", serialize_with = "serialize_resolve")]
-    resolve: Resolve,
+    nodes: Vec<MetadataNode>,
     root: Option<PackageId>,
 }
 
-fn serialize_resolve<S>(resolve: &Resolve, s: S) -> Result<S::Ok, S::Error>
-    where S: ser::Serializer,
-{
-    #[derive(Serialize)]
-    struct Node<'a> {
-        id: &'a PackageId,
-        dependencies: Vec<&'a PackageId>,
-    }
+#[derive(Serialize)]
+struct MetadataNode {
+    id: PackageId,
+    dependencies: Vec<PackageId>,
+    /// With `resolver = "2"`, the features used when the package is built
+    /// for the target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    features: Option<Vec<String>>,
+    /// With `resolver = "2"`, the features used when the package is built
+    /// for the host as a build dependency or procedural macro.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host_features: Option<Vec<String>>,
+}
 
-    resolve.iter().map(|id| {
-        Node {
-            id: id,
-            dependencies: resolve.deps(id).collect(),
-        }
-    }).collect::<Vec<_>>().serialize(s)
+fn to_owned(features: Vec<&str>) -> Vec<String> {
+    features.into_iter().map(|s| s.to_string()).collect()
 }
@@ -10,8 +10,8 @@ use git2;
 use tar::{Archive, Builder, Header, EntryType};
 
 use core::{Package, Workspace, Source, SourceId};
-use sources::PathSource;
-use util::{self, internal, Config, FileLock};
+use sources::{PathSource, CRATES_IO};
+use util::{self, internal, Config, FileLock, ToUrl};
 use util::errors::{CargoResult, CargoResultExt};
 use ops::{self, DefaultExecutor};
 
@@ -37,7 +37,7 @@ pub fn package(ws: &Workspace,
         check_metadata(pkg, config)?;
     }
 
-    verify_dependencies(&pkg)?;
+    verify_dependencies(&pkg, config)?;
 
     if opts.list {
         let root = pkg.root();
@@ -124,7 +124,7 @@ fn check_metadata(pkg: &Package, config: &Config) -> CargoResult<()> {
 }
 
 // check that the package dependencies are safe to deploy.
-fn verify_dependencies(pkg: &Package) -> CargoResult<()> {
+fn verify_dependencies(pkg: &Package, config: &Config) -> CargoResult<()> {
     for dep in pkg.dependencies() {
         if dep.source_id().is_path() {
             if !dep.specified_req() {
@@ -134,9 +134,55 @@ fn verify_dependencies(pkg: &Package) -> CargoResult<()> {
             }
         }
     }
+
+    // With a `publish` list, dependencies may only come from the registries
+    // in it, as the package can't be used from them otherwise.
+    let allowed = match *pkg.publish() {
+        Some(ref allowed) if !allowed.is_empty() => allowed,
+        _ => return Ok(()),
+    };
+    for dep in pkg.dependencies() {
+        if !dep.source_id().is_registry() {
+            continue
+        }
+        let name = registry_name(config, dep.source_id())?;
+        if !name.as_ref().map(|n| allowed.contains(n)).unwrap_or(false) {
+            let registry = name.unwrap_or(dep.source_id().url().to_string());
+            bail!("`{}` may only be published to {}, but its dependency `{}` \
+                   comes from registry `{}`",
+                  pkg.name(),
+                  allowed.iter().map(|r| format!("`{}`", r))
+                         .collect::<Vec<_>>().join(", "),
+                  dep.name(),
+                  registry)
+        }
+    }
     Ok(())
 }
 
+/// Returns the name `id` is configured under in `[registries]`, or
+/// `crates-io` for the default registry.
+fn registry_name(config: &Config, id: &SourceId) -> CargoResult<Option<String>> {
+    let default = match ops::registry_configuration(config, None)?.index {
+        Some(index) => SourceId::for_registry(&index.to_url()?),
+        None => SourceId::for_registry(&CRATES_IO.to_url()?),
+    };
+    if *id == default {
+        return Ok(Some("crates-io".to_string()))
+    }
+    let registries = match config.get_table("registries")? {
+        Some(registries) => registries.val,
+        None => return Ok(None),
+    };
+    for name in registries.keys() {
+        let url = config.get_registry_index(name)?;
+        if *id == SourceId::for_registry(&url) {
+            return Ok(Some(name.clone()))
+        }
+    }
+    Ok(None)
+}
+
 fn check_not_dirty(p: &Package, src: &PathSource) -> CargoResult<()> {
     if let Ok(repo) = git2::Repository::discover(p.root()) {
         if let Some(workdir) = repo.workdir() {
@@ -248,7 +294,7 @@ fn tar(ws: &Workspace,
             })?;
 
             let mut header = Header::new_ustar();
-            let toml = pkg.to_registry_toml();
+            let toml = pkg.to_registry_toml(config)?;
             header.set_path(&path)?;
             header.set_entry_type(EntryType::file());
             header.set_mode(0o644);
@@ -294,14 +340,16 @@ fn run_verify(ws: &Workspace, tar: &File, opts: &PackageOpts) -> CargoResult<()>
     ops::compile_ws(&ws, None, &ops::CompileOptions {
         config: config,
         jobs: opts.jobs,
-        target: None,
+        target: &[],
         features: &[],
         no_default_features: false,
         all_features: false,
         spec: ops::Packages::Packages(&[]),
         filter: ops::CompileFilter::Everything { required_features_filterable: true },
         release: false,
+        profile: None,
         message_format: ops::MessageFormat::Human,
+        timings: false,
         mode: ops::CompileMode::Build,
         target_rustdoc_args: None,
         target_rustc_args: None,
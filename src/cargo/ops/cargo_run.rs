@@ -1,7 +1,8 @@
 use std::path::Path;
 
-use ops::{self, Packages};
-use util::{self, CargoResult, CargoError, ProcessError};
+use ops::{self, MessageFormat, Packages};
+use ops::cargo_compile::requested_targets;
+use util::{self, machine_message, CargoResult, CargoError, ProcessError};
 use util::errors::CargoErrorKind;
 use core::Workspace;
 
@@ -50,6 +51,13 @@ pub fn run(ws: &Workspace,
         }
     }
 
+    let targets = requested_targets(config, options.target)?;
+    if targets.len() > 1 {
+        bail!("`cargo run` can only run an executable built for a single \
+               target, but {} were requested: {}",
+              targets.len(), targets.join(", "))
+    }
+
     let compile = ops::compile(ws, options)?;
     assert_eq!(compile.binaries.len(), 1);
     let exe = &compile.binaries[0];
@@ -63,6 +71,10 @@ pub fn run(ws: &Workspace,
     process.args(args).cwd(config.cwd());
 
     config.shell().status("Running", process.to_string())?;
+    if options.message_format == MessageFormat::Json {
+        machine_message::emit(machine_message::RunTarget::new(pkg.package_id(),
+                                                              &process));
+    }
 
     let result = process.exec_replace();
 
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use semver::Version;
 
 use core::{PackageId, Package, Target, TargetKind};
-use util::{self, CargoResult, Config, LazyCell, ProcessBuilder, process, join_paths};
+use util::{self, CargoResult, Config, ProcessBuilder, process, join_paths};
 
 /// A structure returning the result of a compilation.
 pub struct Compilation<'cfg> {
@@ -50,11 +50,17 @@ pub struct Compilation<'cfg> {
     /// Features per package enabled during this compilation.
     pub cfgs: HashMap<PackageId, HashSet<String>>,
 
+    /// Lint levels per package from its `[lints]` table, as the arguments
+    /// rustdoc is run with for doctests.
+    pub lint_args: HashMap<PackageId, Vec<String>>,
+
     pub target: String,
 
-    config: &'cfg Config,
+    /// The program (and its arguments) which executables built for the
+    /// target are run with, from the `runner` key of the `[target]` config.
+    pub target_runner: Option<(PathBuf, Vec<String>)>,
 
-    target_runner: LazyCell<Option<(PathBuf, Vec<String>)>>,
+    config: &'cfg Config,
 }
 
 impl<'cfg> Compilation<'cfg> {
@@ -72,9 +78,10 @@ impl<'cfg> Compilation<'cfg> {
             extra_env: HashMap::new(),
             to_doc_test: Vec::new(),
             cfgs: HashMap::new(),
+            lint_args: HashMap::new(),
             config: config,
             target: String::new(),
-            target_runner: LazyCell::new(),
+            target_runner: None,
         }
     }
 
@@ -94,17 +101,10 @@ impl<'cfg> Compilation<'cfg> {
         self.fill_env(process(cmd), pkg, true)
     }
 
-    fn target_runner(&self) -> CargoResult<&Option<(PathBuf, Vec<String>)>> {
-        self.target_runner.get_or_try_init(|| {
-            let key = format!("target.{}.runner", self.target);
-            Ok(self.config.get_path_and_args(&key)?.map(|v| v.val))
-        })
-    }
-
     /// See `process`.
     pub fn target_process<T: AsRef<OsStr>>(&self, cmd: T, pkg: &Package)
                                            -> CargoResult<ProcessBuilder> {
-        let builder = if let &Some((ref runner, ref args)) = self.target_runner()? {
+        let builder = if let Some((ref runner, ref args)) = self.target_runner {
             let mut builder = process(runner);
             builder.args(args);
             builder.arg(cmd);
@@ -141,6 +141,9 @@ impl<'cfg> Compilation<'cfg> {
         let search_path = join_paths(&search_path, util::dylib_path_envvar())?;
 
         cmd.env(util::dylib_path_envvar(), &search_path);
+        for &(ref k, ref v) in self.config.env_config()? {
+            cmd.env(k, v);
+        }
         if let Some(env) = self.extra_env.get(pkg.package_id()) {
             for &(ref k, ref v) in env {
                 cmd.env(k, v);
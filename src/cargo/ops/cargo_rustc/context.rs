@@ -12,8 +12,10 @@ use jobserver::Client;
 
 use core::{Package, PackageId, PackageSet, Resolve, Target, Profile};
 use core::{TargetKind, Profiles, Dependency, Workspace};
-use core::dependency::Kind as DepKind;
-use util::{self, internal, Config, profile, Cfg, CfgExpr};
+use core::{ProfileOverride, ProfilePackageSpec};
+use core::dependency::{ArtifactKind, Kind as DepKind};
+use core::resolver::{ResolveBehavior, ResolvedFeatures};
+use util::{self, internal, Config, profile, Cfg, CfgExpr, Progress};
 use util::errors::{CargoResult, CargoResultExt};
 
 use super::TargetConfig;
@@ -29,6 +31,10 @@ pub struct Unit<'a> {
     pub target: &'a Target,
     pub profile: &'a Profile,
     pub kind: Kind,
+    /// Whether this unit is used at build time, by a build script or as a
+    /// procedural macro. Only ever set with the `resolver = "2"` feature
+    /// resolver, where such units get their own set of features.
+    pub for_host: bool,
 }
 
 pub struct Context<'a, 'cfg: 'a> {
@@ -46,12 +52,18 @@ pub struct Context<'a, 'cfg: 'a> {
     pub links: Links<'a>,
     pub used_in_plugin: HashSet<Unit<'a>>,
     pub jobserver: Client,
+    /// Whether the output of the compiler is captured so that the main thread
+    /// can erase the progress bar before printing it, which is only needed
+    /// when the bar is drawn at all.
+    pub capture_diagnostics: bool,
 
     host: Layout,
-    target: Option<Layout>,
-    target_info: TargetInfo,
+    /// The layout of each of the requested targets, in order.
+    targets: Vec<Layout>,
+    target_info: Vec<TargetInfo>,
     host_info: TargetInfo,
     profiles: &'a Profiles,
+    resolved_features: Option<ResolvedFeatures>,
     incremental_enabled: bool,
     target_filenames: HashMap<Unit<'a>, Arc<Vec<(PathBuf, Option<PathBuf>, bool)>>>,
 }
@@ -73,12 +85,18 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                build_config: BuildConfig,
                profiles: &'a Profiles) -> CargoResult<Context<'a, 'cfg>> {
 
-        let dest = if build_config.release { "release" } else { "debug" };
-        let host_layout = Layout::new(ws, None, &dest)?;
-        let target_layout = match build_config.requested_target.as_ref() {
-            Some(target) => Some(Layout::new(ws, Some(&target), dest)?),
-            None => None,
+        // Custom profiles get their own output directory so they don't
+        // clobber the artifacts of `dev` or `release` builds.
+        let dest = match build_config.profile_name {
+            Some(ref name) => &name[..],
+            None if build_config.release => "release",
+            None => "debug",
         };
+        let host_layout = Layout::new(ws, None, &dest)?;
+        let target_layouts = build_config.requested_targets.iter().map(|target| {
+            Layout::new(ws, Some(target), dest)
+        }).collect::<CargoResult<Vec<_>>>()?;
+        let target_info = vec![TargetInfo::default(); target_layouts.len()];
 
         // Enable incremental builds if the user opts in. For now,
         // this is an environment variable until things stabilize a
@@ -113,20 +131,23 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             })?,
         };
 
+        let capture_diagnostics = Progress::new("Building", config)?.is_enabled();
+
         Ok(Context {
             ws: ws,
             host: host_layout,
-            target: target_layout,
+            targets: target_layouts,
             resolve: resolve,
             packages: packages,
             config: config,
-            target_info: TargetInfo::default(),
+            target_info: target_info,
             host_info: TargetInfo::default(),
             compilation: Compilation::new(config),
             build_state: Arc::new(BuildState::new(&build_config)),
             build_config: build_config,
             fingerprints: HashMap::new(),
             profiles: profiles,
+            resolved_features: None,
             compiled: HashSet::new(),
             build_scripts: HashMap::new(),
             build_explicit_deps: HashMap::new(),
@@ -134,6 +155,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             used_in_plugin: HashSet::new(),
             incremental_enabled: incremental_enabled,
             jobserver: jobserver,
+            capture_diagnostics: capture_diagnostics,
             target_filenames: HashMap::new(),
         })
     }
@@ -146,20 +168,20 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         self.host.prepare().chain_err(|| {
             internal(format!("couldn't prepare build directories"))
         })?;
-        match self.target {
-            Some(ref mut target) => {
-                target.prepare().chain_err(|| {
-                    internal(format!("couldn't prepare build directories"))
-                })?;
-            }
-            None => {}
+        for target in self.targets.iter_mut() {
+            target.prepare().chain_err(|| {
+                internal(format!("couldn't prepare build directories"))
+            })?;
         }
 
         self.compilation.plugins_dylib_path = self.host.deps().to_path_buf();
 
-        let layout = self.target.as_ref().unwrap_or(&self.host);
-        self.compilation.root_output = layout.dest().to_path_buf();
-        self.compilation.deps_output = layout.deps().to_path_buf();
+        let (root_output, deps_output) = {
+            let layout = self.layout(self.build_config.default_kinds()[0]);
+            (layout.dest().to_path_buf(), layout.deps().to_path_buf())
+        };
+        self.compilation.root_output = root_output;
+        self.compilation.deps_output = deps_output;
         Ok(())
     }
 
@@ -176,15 +198,127 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             self.visit_crate_type(unit, &mut crate_types)?;
         }
         debug!("probe_target_info: crate_types={:?}", crate_types);
-        self.probe_target_info_kind(&crate_types, Kind::Target)?;
-        if self.requested_target().is_none() {
-            self.host_info = self.target_info.clone();
-        } else {
-            self.probe_target_info_kind(&crate_types, Kind::Host)?;
+        self.probe_target_info_kind(&crate_types, Kind::Host)?;
+        for kind in self.build_config.default_kinds() {
+            if kind != Kind::Host {
+                self.probe_target_info_kind(&crate_types, kind)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads the packages needed to compile `units`, a level of the
+    /// dependency graph at a time so the downloads of each level run in
+    /// parallel.
+    ///
+    /// Dependencies for other platforms are skipped, as are dev-dependencies
+    /// unless one of `units` is a test or an example of the package declaring
+    /// them. Optional dependencies which weren't activated aren't in the
+    /// resolve to begin with.
+    pub fn download_deps(&self, units: &[Unit<'a>]) -> CargoResult<()> {
+        let resolve = self.resolve;
+        let dev_roots = units.iter().filter(|u| {
+            u.target.is_test() || u.target.is_example() || u.profile.test
+        }).map(|u| u.pkg.package_id()).collect::<HashSet<_>>();
+
+        let mut visited = HashSet::new();
+        let mut todo = units.iter().map(|u| {
+            (u.pkg.package_id(), u.kind)
+        }).collect::<Vec<_>>();
+        while !todo.is_empty() {
+            todo.retain(|&(id, kind)| visited.insert((id, kind)));
+            let ids = todo.iter().map(|&(id, _)| id).collect::<Vec<_>>();
+            let pkgs = self.packages.get_many(&ids)?;
+
+            let mut next = Vec::new();
+            for (&(id, kind), pkg) in todo.iter().zip(pkgs) {
+                for dep_id in resolve.deps_not_replaced(id) {
+                    let replaced = resolve.replacement(dep_id).unwrap_or(dep_id);
+                    for d in pkg.dependencies() {
+                        if !resolve.dep_matches_id(d, dep_id) ||
+                           (!d.is_transitive() && !dev_roots.contains(id)) {
+                            continue
+                        }
+                        let kind = if d.is_build() { Kind::Host } else { kind };
+                        if self.dep_platform_activated(d, kind) {
+                            next.push((replaced, kind));
+                        }
+                    }
+                }
+            }
+            todo = next;
         }
         Ok(())
     }
 
+    /// With the `resolver = "2"` feature resolver, computes the features of
+    /// every package needed to compile `units`. Until this is called (and
+    /// always with the default resolver) the features recorded in the
+    /// `Resolve` are used.
+    pub fn resolve_features(&mut self, units: &[Unit<'a>]) -> CargoResult<()> {
+        if self.ws.resolve_behavior() != ResolveBehavior::V2 {
+            return Ok(())
+        }
+        let mut roots = Vec::new();
+        for unit in units {
+            if !roots.contains(&unit.pkg.package_id()) {
+                roots.push(unit.pkg.package_id());
+            }
+        }
+        let has_dev_units = units.iter().any(|unit| {
+            unit.profile.test || unit.target.is_test() ||
+                unit.target.is_example() || unit.target.is_bench()
+        });
+        let features = {
+            let kinds = self.build_config.default_kinds();
+            let platform_activated = |dep: &Dependency, for_host: bool| {
+                if for_host {
+                    self.dep_platform_activated(dep, Kind::Host)
+                } else {
+                    kinds.iter().any(|&kind| self.dep_platform_activated(dep, kind))
+                }
+            };
+            ResolvedFeatures::resolve(self.resolve,
+                                      self.packages,
+                                      &roots,
+                                      &self.build_config.features,
+                                      has_dev_units,
+                                      &platform_activated)?
+        };
+        self.resolved_features = Some(features);
+        Ok(())
+    }
+
+    /// Returns the sorted list of features enabled when compiling `unit`.
+    pub fn unit_features(&self, unit: &Unit) -> Vec<&str> {
+        match self.resolved_features {
+            Some(ref features) => {
+                features.features(unit.pkg.package_id(), unit.for_host)
+            }
+            None => self.resolve.features_sorted(unit.pkg.package_id()),
+        }
+    }
+
+    /// Returns whether the optional dependency `name` of the package being
+    /// compiled by `unit` was activated.
+    fn optional_dep_activated(&self, unit: &Unit, name: &str) -> bool {
+        let id = unit.pkg.package_id();
+        match self.resolved_features {
+            Some(ref features) => {
+                features.optional_dep_activated(id, unit.for_host, name)
+            }
+            None => self.resolve.optional_dep_activated(id, name),
+        }
+    }
+
+    /// Returns whether the dependency `target` of `parent` is used at build
+    /// time and should get the features resolved for the host.
+    fn dep_for_host(&self, parent: &Unit<'a>, target: &Target) -> bool {
+        self.ws.resolve_behavior() == ResolveBehavior::V2 &&
+            (parent.for_host || parent.target.is_custom_build() ||
+             target.for_host())
+    }
+
     fn visit_crate_type(&self,
                         unit: &Unit<'a>,
                         crate_types: &mut BTreeSet<String>)
@@ -223,8 +357,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         for crate_type in crate_types {
             process.arg("--crate-type").arg(crate_type);
         }
-        if kind == Kind::Target {
-            process.arg("--target").arg(&self.target_triple());
+        if let Some(target) = self.build_config.requested_target(kind) {
+            process.arg("--target").arg(target);
         }
 
         let mut with_cfg = process.clone();
@@ -277,17 +411,19 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                                target-specific information from rustc"),
             };
             let mut rustlib = PathBuf::from(line);
-            if kind == Kind::Host {
+            // The compilation only runs what's built for the first target.
+            let targets = &self.build_config.requested_targets;
+            if kind == Kind::Host && !targets.is_empty() {
                 if cfg!(windows) {
                     rustlib.push("bin");
                 } else {
                     rustlib.push("lib");
                 }
                 self.compilation.host_dylib_path = Some(rustlib);
-            } else {
+            } else if kind == self.build_config.default_kinds()[0] {
                 rustlib.push("lib");
                 rustlib.push("rustlib");
-                rustlib.push(self.target_triple());
+                rustlib.push(self.build_config.triple(kind));
                 rustlib.push("lib");
                 self.compilation.target_dylib_path = Some(rustlib);
             }
@@ -300,7 +436,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         };
 
         let info = match kind {
-            Kind::Target => &mut self.target_info,
+            Kind::Target(i) => &mut self.target_info[i],
             Kind::Host => &mut self.host_info,
         };
         info.crate_types = map;
@@ -347,7 +483,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     fn layout(&self, kind: Kind) -> &Layout {
         match kind {
             Kind::Host => &self.host,
-            Kind::Target => self.target.as_ref().unwrap_or(&self.host)
+            Kind::Target(i) => &self.targets[i],
         }
     }
 
@@ -416,14 +552,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         &self.build_config.host_triple
     }
 
-    /// Return the target triple which this context is targeting.
-    pub fn target_triple(&self) -> &str {
-        self.requested_target().unwrap_or(self.host_triple())
-    }
-
-    /// Requested (not actual) target for the build
-    pub fn requested_target(&self) -> Option<&str> {
-        self.build_config.requested_target.as_ref().map(|s| &s[..])
+    /// Return the target triple which units of `kind` are compiled for.
+    pub fn target_triple(&self, kind: Kind) -> &str {
+        self.build_config.triple(kind)
     }
 
     /// Get the metadata for a target in a specific profile
@@ -471,7 +602,13 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
 
         // Also mix in enabled features to our metadata. This'll ensure that
         // when changing feature sets each lib is separately cached.
-        self.resolve.features_sorted(unit.pkg.package_id()).hash(&mut hasher);
+        self.unit_features(unit).hash(&mut hasher);
+
+        // With decoupled features a package may be compiled both for use at
+        // build time and for the target, keep those apart as well.
+        if unit.for_host {
+            unit.for_host.hash(&mut hasher);
+        }
 
         // Throw in the profile we're compiling with. This helps caching
         // panic=abort and panic=unwind artifacts, additionally with various
@@ -571,10 +708,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         let out_dir = self.out_dir(unit);
         let stem = self.file_stem(unit);
         let link_stem = self.link_stem(unit);
-        let info = if unit.target.for_host() {
-            &self.host_info
-        } else {
-            &self.target_info
+        let info = match unit.kind {
+            Kind::Host => &self.host_info,
+            Kind::Target(i) => &self.target_info[i],
         };
 
         let mut ret = Vec::new();
@@ -636,11 +772,11 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             if unsupported.len() > 0 {
                 bail!("cannot produce {} for `{}` as the target `{}` \
                        does not support these crate types",
-                      unsupported.join(", "), unit.pkg, self.target_triple())
+                      unsupported.join(", "), unit.pkg, self.target_triple(unit.kind))
             }
             bail!("cannot compile `{}` as the target `{}` does not \
                    support any of the output crate types",
-                  unit.pkg, self.target_triple());
+                  unit.pkg, self.target_triple(unit.kind));
         }
         info!("Target filenames: {:?}", ret);
 
@@ -659,57 +795,35 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         }
 
         let id = unit.pkg.package_id();
-        let deps = self.resolve.deps(id);
-        let mut ret = deps.filter(|dep| {
-            unit.pkg.dependencies().iter().filter(|d| {
-                d.name() == dep.name() && d.version_req().matches(dep.version())
-            }).any(|d| {
-                // If this target is a build command, then we only want build
-                // dependencies, otherwise we want everything *other than* build
-                // dependencies.
-                if unit.target.is_custom_build() != d.is_build() {
-                    return false
-                }
-
-                // If this dependency is *not* a transitive dependency, then it
-                // only applies to test/example targets
-                if !d.is_transitive() && !unit.target.is_test() &&
-                   !unit.target.is_example() && !unit.profile.test {
-                    return false
-                }
+        let used = self.resolve.deps(id).map(|dep_id| {
+            (dep_id, self.used_deps(unit, dep_id))
+        }).filter(|&(_, ref deps)| !deps.is_empty()).collect::<Vec<_>>();
 
-                // If this dependency is only available for certain platforms,
-                // make sure we're only enabling it for that platform.
-                if !self.dep_platform_activated(d, unit.kind) {
-                    return false
-                }
-
-                // If the dependency is optional, then we're only activating it
-                // if the corresponding feature was activated
-                if d.is_optional() && !self.resolve.features(id).contains(d.name()) {
-                    return false;
-                }
+        // Most packages were downloaded up front by `download_deps`, this only
+        // fetches those it couldn't foresee, like the dependencies of
+        // proc-macros built for the host.
+        let ids = used.iter().map(|&(dep_id, _)| dep_id).collect::<Vec<_>>();
+        let pkgs = self.packages.get_many(&ids)?;
 
-                // If we've gotten past all that, then this dependency is
-                // actually used!
-                true
-            })
-        }).filter_map(|id| {
-            match self.get_package(id) {
-                Ok(pkg) => {
-                    pkg.targets().iter().find(|t| t.is_lib()).map(|t| {
-                        let unit = Unit {
-                            pkg: pkg,
-                            target: t,
-                            profile: self.lib_or_check_profile(unit, t),
-                            kind: unit.kind.for_target(t),
-                        };
-                        Ok(unit)
-                    })
+        let mut ret = Vec::new();
+        for (&(_, ref deps), pkg) in used.iter().zip(pkgs) {
+            // Artifact dependencies don't depend on the library of the
+            // package, unless it's also used as a regular dependency.
+            if deps.iter().any(|d| d.artifact().is_none()) {
+                if let Some(t) = pkg.targets().iter().find(|t| t.is_lib()) {
+                    ret.push(Unit {
+                        pkg: pkg,
+                        target: t,
+                        profile: self.lib_or_check_profile(unit, t),
+                        kind: unit.kind.for_target(t),
+                        for_host: self.dep_for_host(unit, t),
+                    });
                 }
-                Err(e) => Some(Err(e))
             }
-        }).collect::<CargoResult<Vec<_>>>()?;
+            for dep in deps.iter().filter(|d| d.artifact().is_some()) {
+                ret.extend(self.artifact_units(unit, dep, pkg)?);
+            }
+        }
 
         // If this target is a build script, then what we've collected so far is
         // all we need. If this isn't a build script, then it depends on the
@@ -737,20 +851,180 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 t.is_bin() &&
                 // Skip binaries with required features that have not been selected.
                 t.required_features().unwrap_or(&no_required_features).iter().all(|f| {
-                    self.resolve.features(id).contains(f)
+                    self.unit_features(unit).contains(&&f[..])
                 })
             }).map(|t| {
                 Unit {
                     pkg: unit.pkg,
                     target: t,
-                    profile: self.lib_profile(),
+                    profile: self.lib_profile(id),
                     kind: unit.kind.for_target(t),
+                    for_host: unit.for_host,
                 }
             }));
         }
         Ok(ret)
     }
 
+    /// Returns the dependencies in the manifest of `unit`'s package which were
+    /// resolved to `dep_id` and are actually used when compiling `unit`.
+    fn used_deps(&self, unit: &Unit<'a>, dep_id: &PackageId) -> Vec<&'a Dependency> {
+        let resolve = self.resolve;
+        let id = unit.pkg.package_id();
+        unit.pkg.dependencies().iter().filter(|d| {
+            // Dependencies are matched, source included, against the packages
+            // they resolved to before any `[replace]` is applied.
+            resolve.deps_not_replaced(id).any(|orig| {
+                resolve.replacement(orig).unwrap_or(orig) == dep_id &&
+                    resolve.dep_matches_id(d, orig)
+            })
+        }).filter(|d| {
+            // If this target is a build command, then we only want build
+            // dependencies, otherwise we want everything *other than* build
+            // dependencies.
+            if unit.target.is_custom_build() != d.is_build() {
+                return false
+            }
+
+            // If this dependency is *not* a transitive dependency, then it
+            // only applies to test/example targets
+            if !d.is_transitive() && !unit.target.is_test() &&
+               !unit.target.is_example() && !unit.profile.test {
+                return false
+            }
+
+            // If this dependency is only available for certain platforms,
+            // make sure we're only enabling it for that platform.
+            if !self.dep_platform_activated(d, unit.kind) {
+                return false
+            }
+
+            // If the dependency is optional, then we're only activating it
+            // if the corresponding feature was activated
+            if d.is_optional() &&
+               !self.optional_dep_activated(unit, d.name_in_toml()) {
+                return false;
+            }
+
+            // If we've gotten past all that, then this dependency is
+            // actually used!
+            true
+        }).collect()
+    }
+
+    /// Returns the units to build for the artifact dependency `dep` of
+    /// `unit`, which was resolved to `pkg`.
+    fn artifact_units(&self, unit: &Unit<'a>, dep: &Dependency, pkg: &'a Package)
+                      -> CargoResult<Vec<Unit<'a>>> {
+        let requested = &self.build_config.requested_targets;
+        let kind = match dep.artifact_target() {
+            None => unit.kind,
+            // Units compiled for one of the requested targets keep using it,
+            // those compiled for the host use the first one.
+            Some("target") => {
+                match unit.kind {
+                    Kind::Target(i) => Kind::Target(i),
+                    Kind::Host => self.build_config.default_kinds()[0],
+                }
+            }
+            Some(t) if requested.iter().any(|r| r == t) => {
+                Kind::Target(requested.iter().position(|r| r == t).unwrap())
+            }
+            Some(t) if t == self.host_triple() => Kind::Host,
+            Some(t) => {
+                bail!("artifact dependency `{}` of package `{}` requests the \
+                       target `{}`, but only the host (`{}`) or the targets \
+                       passed with `--target` can be used",
+                      dep.name_in_toml(), unit.pkg, t, self.host_triple())
+            }
+        };
+        let artifact = dep.artifact().unwrap();
+        let targets = match artifact {
+            ArtifactKind::Bin => {
+                pkg.targets().iter().filter(|t| t.is_bin()).collect::<Vec<_>>()
+            }
+            ArtifactKind::Cdylib | ArtifactKind::Staticlib => {
+                pkg.targets().iter().filter(|t| {
+                    t.is_lib() &&
+                        t.rustc_crate_types().contains(&artifact.crate_type())
+                }).collect()
+            }
+        };
+        if targets.is_empty() {
+            bail!("dependency `{}` of package `{}` is an artifact dependency \
+                   on `{}`, but package `{}` has no {} target",
+                  dep.name_in_toml(), unit.pkg, artifact.crate_type(),
+                  pkg, artifact.crate_type())
+        }
+        Ok(targets.into_iter().map(|t| {
+            Unit {
+                pkg: pkg,
+                target: t,
+                profile: self.lib_profile(pkg.package_id()),
+                kind: kind,
+                for_host: self.dep_for_host(unit, t),
+            }
+        }).collect())
+    }
+
+    /// Returns whether `dep`, a dependency of `unit`, is only used as an
+    /// artifact dependency, in which case it isn't linked into `unit`.
+    pub fn is_artifact_dep(&self, unit: &Unit<'a>, dep: &Unit<'a>) -> bool {
+        if unit.pkg.package_id() == dep.pkg.package_id() {
+            return false
+        }
+        let deps = self.used_deps(unit, dep.pkg.package_id());
+        !deps.is_empty() && deps.iter().all(|d| d.artifact().is_some())
+    }
+
+    /// Returns the environment variables pointing at the files built for the
+    /// artifact dependencies of `unit`.
+    ///
+    /// Binaries are available as `CARGO_BIN_FILE_<DEP>_<name>`, C libraries
+    /// as `CARGO_CDYLIB_FILE_<DEP>` and `CARGO_STATICLIB_FILE_<DEP>`.
+    pub fn artifact_env(&mut self, unit: &Unit<'a>)
+                        -> CargoResult<Vec<(String, PathBuf)>> {
+        let mut ret = Vec::new();
+        for dep_unit in self.dep_targets(unit)? {
+            let deps = self.used_deps(unit, dep_unit.pkg.package_id());
+            for dep in deps {
+                let artifact = match dep.artifact() {
+                    Some(artifact) => artifact,
+                    None => continue,
+                };
+                let name = dep.name_in_toml().to_uppercase().replace("-", "_");
+                let key = match artifact {
+                    ArtifactKind::Bin if dep_unit.target.is_bin() => {
+                        format!("CARGO_BIN_FILE_{}_{}", name, dep_unit.target.name())
+                    }
+                    ArtifactKind::Cdylib if dep_unit.target.is_lib() => {
+                        format!("CARGO_CDYLIB_FILE_{}", name)
+                    }
+                    ArtifactKind::Staticlib if dep_unit.target.is_lib() => {
+                        format!("CARGO_STATICLIB_FILE_{}", name)
+                    }
+                    _ => continue,
+                };
+                let crate_type = artifact.crate_type();
+                let (prefix, suffix) = match self.info(&dep_unit.kind)
+                                                 .crate_types.get(crate_type) {
+                    Some(&Some((ref prefix, ref suffix))) => {
+                        (prefix.clone(), suffix.clone())
+                    }
+                    _ => {
+                        bail!("cannot produce {} for `{}` as the target `{}` \
+                               does not support this crate type",
+                              crate_type, dep_unit.pkg,
+                              self.target_triple(dep_unit.kind))
+                    }
+                };
+                let file = format!("{}{}{}", prefix, self.file_stem(&dep_unit), suffix);
+                ret.push((key, self.out_dir(&dep_unit).join(file)));
+            }
+        }
+        Ok(ret)
+    }
+
     /// Returns the dependencies needed to run a build script.
     ///
     /// The `unit` provided must represent an execution of a build script, and
@@ -820,8 +1094,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             ret.push(Unit {
                 pkg: dep,
                 target: lib,
-                profile: self.lib_profile(),
+                profile: self.lib_profile(dep.package_id()),
                 kind: unit.kind.for_target(lib),
+                for_host: self.dep_for_host(unit, lib),
             });
             if self.build_config.doc_all {
                 ret.push(Unit {
@@ -829,6 +1104,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                     target: lib,
                     profile: &self.profiles.doc,
                     kind: unit.kind.for_target(lib),
+                    for_host: self.dep_for_host(unit, lib),
                 });
             }
         }
@@ -857,6 +1133,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 target: t,
                 profile: &self.profiles.custom_build,
                 kind: unit.kind,
+                for_host: unit.for_host,
             }
         })
     }
@@ -868,6 +1145,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 target: t,
                 profile: self.lib_or_check_profile(unit, t),
                 kind: unit.kind.for_target(t),
+                for_host: unit.for_host,
             }
         })
     }
@@ -879,11 +1157,9 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
             Some(p) => p,
             None => return true,
         };
-        let (name, info) = match kind {
-            Kind::Host => (self.host_triple(), &self.host_info),
-            Kind::Target => (self.target_triple(), &self.target_info),
-        };
-        platform.matches(name, info.cfg.as_ref().map(|cfg| &cfg[..]))
+        let info = self.info(&kind);
+        platform.matches(self.target_triple(kind),
+                         info.cfg.as_ref().map(|cfg| &cfg[..]))
     }
 
     /// Gets a package for the given package id.
@@ -903,25 +1179,20 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
 
     /// Get the list of cfg printed out from the compiler for the specified kind
     pub fn cfg(&self, kind: Kind) -> &[Cfg] {
-        let info = match kind {
-            Kind::Host => &self.host_info,
-            Kind::Target => &self.target_info,
-        };
-        info.cfg.as_ref().map(|s| &s[..]).unwrap_or(&[])
+        self.info(&kind).cfg.as_ref().map(|s| &s[..]).unwrap_or(&[])
     }
 
     /// Get the target configuration for a particular host or target
     fn target_config(&self, kind: Kind) -> &TargetConfig {
-        match kind {
-            Kind::Host => &self.build_config.host,
-            Kind::Target => &self.build_config.target,
-        }
+        self.build_config.target_config(kind)
     }
 
     /// Number of jobs specified for this build
     pub fn jobs(&self) -> u32 { self.build_config.jobs }
 
-    pub fn lib_profile(&self) -> &'a Profile {
+    /// Returns the profile libraries are built with, ignoring any
+    /// per-package overrides.
+    pub fn base_lib_profile(&self) -> &'a Profile {
         let (normal, test) = if self.build_config.release {
             (&self.profiles.release, &self.profiles.bench_deps)
         } else {
@@ -934,18 +1205,75 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         }
     }
 
+    /// Returns the profile the library of `pkg` is built with, taking
+    /// `[profile.*.package.<spec>]` overrides into account.
+    ///
+    /// Note that the fingerprint and metadata of a unit hash its profile, so
+    /// changing an override will rebuild the affected packages.
+    pub fn lib_profile(&self, pkg: &PackageId) -> &'a Profile {
+        let o = match self.profile_override(pkg) {
+            Some(o) => o,
+            None => return self.base_lib_profile(),
+        };
+        let (normal, test) = if self.build_config.release {
+            (&o.release, &o.bench_deps)
+        } else {
+            (&o.dev, &o.test_deps)
+        };
+        if self.build_config.test {
+            test
+        } else {
+            normal
+        }
+    }
+
     pub fn lib_or_check_profile(&self, unit: &Unit, target: &Target) -> &'a Profile {
         if unit.profile.check && !target.is_custom_build() && !target.for_host() {
-            &self.profiles.check
+            match self.profile_override(unit.pkg.package_id()) {
+                Some(o) => &o.check,
+                None => &self.profiles.check,
+            }
+        } else {
+            self.lib_profile(unit.pkg.package_id())
+        }
+    }
+
+    /// Returns the profile the build script of `pkg` is built with, which is
+    /// `build-override` with any per-package override applied on top.
+    pub fn build_script_profile(&self, pkg: &PackageId) -> &'a Profile {
+        let build_override = match self.profile_override(pkg) {
+            Some(o) => &o.build_override,
+            None => &self.profiles.build_override,
+        };
+        let build_override = if self.build_config.release {
+            &build_override.1
         } else {
-            self.lib_profile()
+            &build_override.0
+        };
+        match *build_override {
+            Some(ref profile) => profile,
+            None => self.lib_profile(pkg),
         }
     }
 
-    pub fn build_script_profile(&self, _pkg: &PackageId) -> &'a Profile {
-        // TODO: should build scripts always be built with the same library
-        //       profile? How is this controlled at the CLI layer?
-        self.lib_profile()
+    fn profile_override(&self, pkg: &PackageId) -> Option<&'a ProfileOverride> {
+        let is_member = self.ws.members().any(|p| p.package_id() == pkg);
+        self.profiles.package_override(pkg, is_member)
+    }
+
+    /// Warns about `[profile.*.package.<spec>]` tables which don't match any
+    /// package in the dependency graph.
+    pub fn warn_unused_profile_overrides(&self) -> CargoResult<()> {
+        for o in self.profiles.package_overrides.iter() {
+            if let ProfilePackageSpec::Spec(ref spec) = o.spec {
+                if !self.resolve.iter().any(|id| spec.matches(id)) {
+                    self.config.shell().warn(format!(
+                        "profile package spec `{}` did not match any packages",
+                        spec))?;
+                }
+            }
+        }
+        Ok(())
     }
 
     pub fn incremental_args(&self, unit: &Unit) -> CargoResult<Vec<String>> {
@@ -979,6 +1307,71 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
         env_args(self.config, &self.build_config, self.info(&unit.kind), unit.kind, "RUSTDOCFLAGS")
     }
 
+    /// Returns the flags for the lint levels in the `[lints]` table of the
+    /// unit's package.
+    ///
+    /// Only workspace members are affected, the lints of other packages are
+    /// left alone (and capped by `--cap-lints`).
+    pub fn lint_args(&self, unit: &Unit) -> Vec<String> {
+        self.pkg_lint_args(unit.pkg, unit.profile.doc)
+    }
+
+    /// Returns the flags for the lint levels of `pkg`, for rustdoc if
+    /// `rustdoc` is set and for rustc otherwise.
+    pub fn pkg_lint_args(&self, pkg: &Package, rustdoc: bool) -> Vec<String> {
+        if !self.ws.members().any(|p| p == pkg) {
+            return Vec::new()
+        }
+        let lints = pkg.manifest().lints();
+        if rustdoc {
+            lints.rustdoc.clone()
+        } else {
+            lints.rustc.clone()
+        }
+    }
+
+    /// Returns the program that executables built for the target are run
+    /// with, from `target.<triple>.runner` or otherwise from the matching
+    /// `target.'cfg(...)'.runner`.
+    ///
+    /// Only executables built for the first requested target are run.
+    pub fn target_runner(&self) -> CargoResult<Option<(PathBuf, Vec<String>)>> {
+        let kind = self.build_config.default_kinds()[0];
+        let key = format!("target.{}.runner", self.target_triple(kind));
+        if let Some(runner) = self.config.get_path_and_args(&key)? {
+            return Ok(Some(runner.val))
+        }
+
+        let target_cfg = match self.info(&kind).cfg {
+            Some(ref cfg) => cfg,
+            None => return Ok(None),
+        };
+        let table = match self.config.get_table("target")? {
+            Some(table) => table.val,
+            None => return Ok(None),
+        };
+        let mut cfgs = table.keys().filter(|t| {
+            t.starts_with("cfg(") && t.ends_with(")") &&
+                CfgExpr::from_str(&t[4..t.len() - 1]).map(|c| {
+                    c.matches(target_cfg)
+                }).unwrap_or(false)
+        }).collect::<Vec<_>>();
+        cfgs.sort();
+
+        let mut ret = None;
+        for cfg in cfgs {
+            let key = format!("target.{}.runner", cfg);
+            if let Some(runner) = self.config.get_path_and_args(&key)? {
+                if ret.is_some() {
+                    bail!("several matching instances of `target.'cfg(..)'.runner` \
+                           in configurations")
+                }
+                ret = Some(runner.val);
+            }
+        }
+        Ok(ret)
+    }
+
     pub fn show_warnings(&self, pkg: &PackageId) -> bool {
         pkg.source_id().is_path() || self.config.extra_verbose()
     }
@@ -986,7 +1379,7 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
     fn info(&self, kind: &Kind) -> &TargetInfo {
         match *kind {
             Kind::Host => &self.host_info,
-            Kind::Target => &self.target_info,
+            Kind::Target(i) => &self.target_info[i],
         }
     }
 }
@@ -1017,8 +1410,8 @@ fn env_args(config: &Config,
     // This means that, e.g. even if the specified --target is the
     // same as the host, build scripts in plugins won't get
     // RUSTFLAGS.
-    let compiling_with_target = build_config.requested_target.is_some();
-    let is_target_kind = kind == Kind::Target;
+    let compiling_with_target = !build_config.requested_targets.is_empty();
+    let is_target_kind = kind != Kind::Host;
 
     if compiling_with_target && !is_target_kind {
         // This is probably a build script or plugin and we're
@@ -1040,7 +1433,7 @@ fn env_args(config: &Config,
 
     let name = name.chars().flat_map(|c| c.to_lowercase()).collect::<String>();
     // Then the target.*.rustflags value...
-    let target = build_config.requested_target.as_ref().unwrap_or(&build_config.host_triple);
+    let target = build_config.triple(kind);
     let key = format!("target.{}.{}", target, name);
     if let Some(args) = config.get_list_or_split_string(&key)? {
         let args = args.val.into_iter();
@@ -108,16 +108,13 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
     // environment variables. Note that the profile-related environment
     // variables are not set with this the build script's profile but rather the
     // package's library profile.
-    let profile = cx.lib_profile();
+    let profile = cx.lib_profile(unit.pkg.package_id());
     let to_exec = to_exec.into_os_string();
     let mut cmd = cx.compilation.host_process(to_exec, unit.pkg)?;
     cmd.env("OUT_DIR", &build_output)
        .env("CARGO_MANIFEST_DIR", unit.pkg.root())
        .env("NUM_JOBS", &cx.jobs().to_string())
-       .env("TARGET", &match unit.kind {
-           Kind::Host => cx.host_triple(),
-           Kind::Target => cx.target_triple(),
-       })
+       .env("TARGET", cx.target_triple(unit.kind))
        .env("DEBUG", &profile.debuginfo.is_some().to_string())
        .env("OPT_LEVEL", &profile.opt_level)
        .env("PROFILE", if cx.build_config.release { "release" } else { "debug" })
@@ -132,7 +129,7 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
 
     // Be sure to pass along all enabled features for this package, this is the
     // last piece of statically known information that we have.
-    for feat in cx.resolve.features(unit.pkg.package_id()).iter() {
+    for feat in cx.unit_features(unit) {
         cmd.env(&format!("CARGO_FEATURE_{}", super::envify(feat)), "1");
     }
 
@@ -156,6 +153,12 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
         }
     }
 
+    // Artifact build-dependencies are made available to the build script
+    // when it runs, not only when it's compiled.
+    for (key, path) in cx.artifact_env(build_script_unit)? {
+        cmd.env(&key, &path);
+    }
+
     // Gather the set of native dependencies that this package has along with
     // some other variables to close over.
     //
@@ -300,10 +303,14 @@ fn build_work<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
 impl BuildState {
     pub fn new(config: &super::BuildConfig) -> BuildState {
         let mut overrides = HashMap::new();
-        let i1 = config.host.overrides.iter().map(|p| (p, Kind::Host));
-        let i2 = config.target.overrides.iter().map(|p| (p, Kind::Target));
-        for ((name, output), kind) in i1.chain(i2) {
-            overrides.insert((name.clone(), kind), output.clone());
+        let mut kinds = config.default_kinds();
+        if !kinds.contains(&Kind::Host) {
+            kinds.push(Kind::Host);
+        }
+        for kind in kinds {
+            for (name, output) in config.target_config(kind).overrides.iter() {
+                overrides.insert((name.clone(), kind), output.clone());
+            }
         }
         BuildState {
             outputs: Mutex::new(HashMap::new()),
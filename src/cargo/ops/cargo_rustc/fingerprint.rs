@@ -142,6 +142,8 @@ pub struct Fingerprint {
     #[serde(skip_serializing, skip_deserializing)]
     memoized_hash: Mutex<Option<u64>>,
     rustflags: Vec<String>,
+    lints: Vec<String>,
+    env_config: Vec<(String, String)>,
 }
 
 fn serialize_deps<S>(deps: &Vec<(String, Arc<Fingerprint>)>, ser: S)
@@ -167,6 +169,8 @@ fn deserialize_deps<'de, D>(d: D) -> Result<Vec<(String, Arc<Fingerprint>)>, D::
             deps: Vec::new(),
             memoized_hash: Mutex::new(Some(hash)),
             rustflags: Vec::new(),
+            lints: Vec::new(),
+            env_config: Vec::new(),
         }))
     }).collect())
 }
@@ -230,6 +234,13 @@ impl Fingerprint {
         if self.rustflags != old.rustflags {
             return Err(internal("RUSTFLAGS has changed"))
         }
+        if self.lints != old.lints {
+            bail!("lints have changed: {:?} != {:?}", self.lints, old.lints)
+        }
+        if self.env_config != old.env_config {
+            bail!("[env] config has changed: {:?} != {:?}",
+                  self.env_config, old.env_config)
+        }
         if self.local.len() != old.local.len() {
             bail!("local lens changed");
         }
@@ -296,8 +307,11 @@ impl hash::Hash for Fingerprint {
             ref local,
             memoized_hash: _,
             ref rustflags,
+            ref lints,
+            ref env_config,
         } = *self;
-        (rustc, features, target, profile, deps, local, rustflags).hash(h)
+        (rustc, features, target, profile, deps, local, rustflags, lints,
+         env_config).hash(h)
     }
 }
 
@@ -382,11 +396,13 @@ fn calculate<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
         rustc: util::hash_u64(&cx.config.rustc()?.verbose_version),
         target: util::hash_u64(&unit.target),
         profile: util::hash_u64(&unit.profile),
-        features: format!("{:?}", cx.resolve.features_sorted(unit.pkg.package_id())),
+        features: format!("{:?}", cx.unit_features(unit)),
         deps: deps,
         local: vec![local],
         memoized_hash: Mutex::new(None),
         rustflags: extra_flags,
+        lints: cx.lint_args(unit),
+        env_config: cx.config.env_config()?.to_vec(),
     });
     cx.fingerprints.insert(*unit, fingerprint.clone());
     Ok(fingerprint)
@@ -438,6 +454,8 @@ pub fn prepare_build_cmd<'a, 'cfg>(cx: &mut Context<'a, 'cfg>, unit: &Unit<'a>)
         local: local,
         memoized_hash: Mutex::new(None),
         rustflags: Vec::new(),
+        lints: Vec::new(),
+        env_config: cx.config.env_config()?.to_vec(),
     };
     let compare = compare_old_fingerprint(&loc, &fingerprint);
     log_compare(unit, &compare);
@@ -3,18 +3,20 @@ use std::collections::hash_map::HashMap;
 use std::fmt;
 use std::io;
 use std::mem;
-use std::sync::mpsc::{channel, Sender, Receiver};
+use std::sync::mpsc::{channel, Sender, Receiver, RecvTimeoutError};
+use std::time::Duration;
 
 use crossbeam::{self, Scope};
 use jobserver::{Acquired, HelperThread};
 
 use core::{PackageId, Target, Profile};
-use util::{Config, DependencyQueue, Fresh, Dirty, Freshness};
+use util::{Config, DependencyQueue, Fresh, Dirty, Freshness, Progress};
 use util::{CargoResult, ProcessBuilder, profile, internal, CargoResultExt};
 use {handle_error};
 
 use super::{Context, Kind, Unit};
 use super::job::Job;
+use super::timings::Timings;
 
 /// A management structure of the entire dependency graph to compile.
 ///
@@ -31,6 +33,7 @@ pub struct JobQueue<'a> {
     documented: HashSet<&'a PackageId>,
     counts: HashMap<&'a PackageId, usize>,
     is_release: bool,
+    timings: Timings<'a>,
 }
 
 /// A helper structure for metadata about the state of a building package.
@@ -43,22 +46,26 @@ struct PendingBuild {
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
-struct Key<'a> {
-    pkg: &'a PackageId,
-    target: &'a Target,
-    profile: &'a Profile,
-    kind: Kind,
+pub struct Key<'a> {
+    pub pkg: &'a PackageId,
+    pub target: &'a Target,
+    pub profile: &'a Profile,
+    pub kind: Kind,
+    pub for_host: bool,
 }
 
 pub struct JobState<'a> {
     tx: Sender<Message<'a>>,
+    key: Key<'a>,
 }
 
 enum Message<'a> {
     Run(String),
     Stdout(String),
     Stderr(String),
+    Diagnostic(String),
     Token(io::Result<Acquired>),
+    Rmeta(Key<'a>),
     Finish(Key<'a>, CargoResult<()>),
 }
 
@@ -74,10 +81,23 @@ impl<'a> JobState<'a> {
     pub fn stderr(&self, err: &str) {
         let _ = self.tx.send(Message::Stderr(err.to_string()));
     }
+
+    /// Prints a whole diagnostic the compiler wrote to stderr, which is done
+    /// by the main thread so that it doesn't get mixed up with the progress
+    /// bar or with the diagnostics of other crates.
+    pub fn stderr_diagnostic(&self, diagnostic: &str) {
+        let _ = self.tx.send(Message::Diagnostic(diagnostic.to_string()));
+    }
+
+    /// Notes that the metadata of the crate being compiled was written, which
+    /// is only recorded for `--timings`.
+    pub fn rmeta_produced(&self) {
+        let _ = self.tx.send(Message::Rmeta(self.key));
+    }
 }
 
 impl<'a> JobQueue<'a> {
-    pub fn new<'cfg>(cx: &Context<'a, 'cfg>) -> JobQueue<'a> {
+    pub fn new<'cfg>(cx: &Context<'a, 'cfg>, units: &[Unit<'a>]) -> JobQueue<'a> {
         let (tx, rx) = channel();
         JobQueue {
             queue: DependencyQueue::new(),
@@ -89,6 +109,7 @@ impl<'a> JobQueue<'a> {
             documented: HashSet::new(),
             counts: HashMap::new(),
             is_release: cx.build_config.release,
+            timings: Timings::new(cx, units),
         }
     }
 
@@ -99,6 +120,7 @@ impl<'a> JobQueue<'a> {
                          fresh: Freshness) -> CargoResult<()> {
         let key = Key::new(unit);
         let deps = key.dependencies(cx)?;
+        self.timings.unit_enqueued(key, &deps);
         self.queue.queue(Fresh, key, Vec::new(), &deps).push((job, fresh));
         *self.counts.entry(key.pkg).or_insert(0) += 1;
         Ok(())
@@ -149,6 +171,9 @@ impl<'a> JobQueue<'a> {
 
         let mut tokens = Vec::new();
         let mut queue = Vec::new();
+        let mut running = Vec::new();
+        let mut progress = Progress::new("Building", cx.config)?;
+        let total = self.queue.len();
         trace!("queue: {:#?}", self.queue);
 
         // Iteratively execute the entire dependency graph. Each turn of the
@@ -189,6 +214,9 @@ impl<'a> JobQueue<'a> {
             // we're able to perform some parallel work.
             while error.is_none() && self.active < tokens.len() + 1 && !queue.is_empty() {
                 let (key, job, fresh) = queue.remove(0);
+                if fresh == Dirty {
+                    running.push(key);
+                }
                 self.run(key, fresh, job, cx.config, scope)?;
             }
 
@@ -205,12 +233,38 @@ impl<'a> JobQueue<'a> {
             // to the jobserver itself.
             tokens.truncate(self.active - 1);
 
-            match self.rx.recv().unwrap() {
+            let (active, waiting, inactive) = self.concurrency(&queue);
+            self.timings.mark_concurrency(active, waiting, inactive);
+            self.timings.record_cpu();
+
+            let names = running.iter().map(|key| {
+                if key.target.is_custom_build() {
+                    format!("{}(build)", key.pkg.name())
+                } else {
+                    key.pkg.name().to_string()
+                }
+            }).collect::<Vec<_>>();
+            progress.tick(total - self.queue.len(), total, &names.join(", "))?;
+
+            // With `--timings` wake up regularly to sample the CPU usage.
+            let message = if self.timings.enabled() {
+                match self.rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(internal("job queue channel disconnected"))
+                    }
+                }
+            } else {
+                self.rx.recv().unwrap()
+            };
+            match message {
                 Message::Run(cmd) => {
                     cx.config.shell().verbose(|c| c.status("Running", &cmd))?;
                 }
                 Message::Stdout(out) => {
                     if cx.config.extra_verbose() {
+                        progress.clear();
                         println!("{}", out);
                     }
                 }
@@ -219,8 +273,17 @@ impl<'a> JobQueue<'a> {
                         writeln!(cx.config.shell().err(), "{}", err)?;
                     }
                 }
+                Message::Diagnostic(diagnostic) => {
+                    writeln!(cx.config.shell().err(), "{}", diagnostic)?;
+                }
+                Message::Rmeta(key) => {
+                    self.timings.unit_rmeta_finished(key);
+                }
                 Message::Finish(key, result) => {
                     info!("end: {:?}", key);
+                    if let Some(i) = running.iter().position(|k| *k == key) {
+                        running.remove(i);
+                    }
                     self.active -= 1;
                     if self.active > 0 {
                         assert!(tokens.len() > 0);
@@ -252,9 +315,14 @@ impl<'a> JobQueue<'a> {
                 }
             }
         }
+        progress.clear();
 
-        let build_type = if self.is_release { "release" } else { "dev" };
-        let profile = cx.lib_profile();
+        let build_type = match cx.build_config.profile_name {
+            Some(ref name) => &name[..],
+            None if self.is_release => "release",
+            None => "dev",
+        };
+        let profile = cx.base_lib_profile();
         let mut opt_type = String::from(if profile.opt_level == "0" { "unoptimized" }
                                         else { "optimized" });
         if profile.debuginfo.is_some() {
@@ -270,6 +338,7 @@ impl<'a> JobQueue<'a> {
                                   opt_type,
                                   time_elapsed);
             cx.config.shell().status("Finished", message)?;
+            self.timings.finished(cx)?;
             Ok(())
         } else if let Some(e) = error {
             Err(e)
@@ -279,6 +348,23 @@ impl<'a> JobQueue<'a> {
         }
     }
 
+    /// Returns how many units are running, how many are ready to run but
+    /// waiting for a jobserver token in `queue`, and how many are waiting on
+    /// their dependencies.
+    fn concurrency(&self, queue: &[(Key<'a>, Job, Freshness)]) -> (usize, usize, usize) {
+        let mut queued = HashMap::new();
+        for &(key, _, _) in queue {
+            *queued.entry(key).or_insert(0) += 1;
+        }
+        let started = self.pending.values().filter(|p| p.amt > 0).count();
+        // A unit is only waiting if none of its jobs started yet.
+        let waiting = queued.iter().filter(|&(key, &n)| {
+            self.pending[key].amt == n
+        }).count();
+        let inactive = self.queue.len() - started;
+        (started - waiting, waiting, inactive)
+    }
+
     /// Executes a job in the `scope` given, pushing the spawned thread's
     /// handled onto `threads`.
     fn run(&mut self,
@@ -289,6 +375,10 @@ impl<'a> JobQueue<'a> {
            scope: &Scope<'a>) -> CargoResult<()> {
         info!("start: {:?}", key);
 
+        self.timings.unit_start(key, match fresh {
+            Fresh => true,
+            Dirty => false,
+        });
         self.active += 1;
         *self.counts.get_mut(key.pkg).unwrap() -= 1;
 
@@ -296,6 +386,7 @@ impl<'a> JobQueue<'a> {
         let doit = move || {
             let res = job.run(fresh, &JobState {
                 tx: my_tx.clone(),
+                key: key,
             });
             my_tx.send(Message::Finish(key, res)).unwrap();
         };
@@ -341,6 +432,7 @@ impl<'a> JobQueue<'a> {
         state.amt -= 1;
         if state.amt == 0 {
             self.queue.finish(&key, state.fresh);
+            self.timings.unit_finished(key);
         }
         Ok(())
     }
@@ -394,6 +486,7 @@ impl<'a> Key<'a> {
             target: unit.target,
             profile: unit.profile,
             kind: unit.kind,
+            for_host: unit.for_host,
         }
     }
 
@@ -404,6 +497,7 @@ impl<'a> Key<'a> {
             target: self.target,
             profile: self.profile,
             kind: self.kind,
+            for_host: self.for_host,
         };
         let targets = cx.dep_targets(&unit)?;
         Ok(targets.iter().filter_map(|unit| {
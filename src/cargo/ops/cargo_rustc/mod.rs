@@ -8,8 +8,9 @@ use std::sync::Arc;
 
 use serde_json;
 
-use core::{Package, PackageId, PackageSet, Target, Resolve};
+use core::{Edition, Package, PackageId, PackageSet, Target, Resolve};
 use core::{Profile, Profiles, Workspace};
+use core::resolver::{RequestedFeatures, ResolveBehavior};
 use core::shell::ColorChoice;
 use util::{self, ProcessBuilder, machine_message};
 use util::{Config, internal, profile, join_paths, short_hash};
@@ -35,21 +36,62 @@ mod job_queue;
 mod layout;
 mod links;
 mod output_depinfo;
+mod timings;
 
+/// The platform a unit is compiled for. `Target` holds the index of one of
+/// the triples passed with `--target` in `BuildConfig::requested_targets`.
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
-pub enum Kind { Host, Target }
+pub enum Kind { Host, Target(usize) }
 
 #[derive(Default, Clone)]
 pub struct BuildConfig {
     pub host_triple: String,
     pub host: TargetConfig,
-    pub requested_target: Option<String>,
-    pub target: TargetConfig,
+    pub requested_targets: Vec<String>,
+    /// The configuration of each of the `requested_targets`, in order.
+    pub targets: Vec<TargetConfig>,
     pub jobs: u32,
     pub release: bool,
+    pub profile_name: Option<String>,
     pub test: bool,
     pub doc_all: bool,
     pub json_messages: bool,
+    pub features: RequestedFeatures,
+    /// Whether to record the timing of each unit, see `timings.rs`.
+    pub timings: bool,
+}
+
+impl BuildConfig {
+    /// Returns the kinds that units are compiled for unless they're needed
+    /// on the host: one per requested target, or the host if there are none.
+    pub fn default_kinds(&self) -> Vec<Kind> {
+        if self.requested_targets.is_empty() {
+            vec![Kind::Host]
+        } else {
+            (0..self.requested_targets.len()).map(Kind::Target).collect()
+        }
+    }
+
+    /// Returns the triple passed with `--target` for `kind`, if any.
+    pub fn requested_target(&self, kind: Kind) -> Option<&str> {
+        match kind {
+            Kind::Host => None,
+            Kind::Target(i) => Some(&self.requested_targets[i]),
+        }
+    }
+
+    /// Returns the triple that units of `kind` are compiled for.
+    pub fn triple(&self, kind: Kind) -> &str {
+        self.requested_target(kind).unwrap_or(&self.host_triple)
+    }
+
+    /// Returns the `[target.<triple>]` configuration for `kind`.
+    pub fn target_config(&self, kind: Kind) -> &TargetConfig {
+        match kind {
+            Kind::Host => &self.host,
+            Kind::Target(i) => &self.targets[i],
+        }
+    }
 }
 
 #[derive(Clone, Default)]
@@ -109,29 +151,38 @@ pub fn compile_targets<'a, 'cfg: 'a>(ws: &Workspace<'cfg>,
                                      profiles: &'a Profiles,
                                      exec: Arc<Executor>)
                                      -> CargoResult<Compilation<'cfg>> {
-    let units = pkg_targets.iter().flat_map(|&(pkg, ref targets)| {
-        let default_kind = if build_config.requested_target.is_some() {
-            Kind::Target
-        } else {
-            Kind::Host
-        };
-        targets.iter().map(move |&(target, profile)| {
-            Unit {
-                pkg: pkg,
-                target: target,
-                profile: profile,
-                kind: if target.for_host() {Kind::Host} else {default_kind},
+    let decouple_host = ws.resolve_behavior() == ResolveBehavior::V2;
+    // Every target is compiled once for each requested platform. Targets
+    // which run on the host, like proc-macros, are only compiled once.
+    let mut units = Vec::new();
+    let mut seen = HashSet::new();
+    for kind in build_config.default_kinds() {
+        for &(pkg, ref targets) in pkg_targets {
+            for &(target, profile) in targets.iter() {
+                let unit = Unit {
+                    pkg: pkg,
+                    target: target,
+                    profile: profile,
+                    kind: kind.for_target(target),
+                    for_host: decouple_host && target.for_host(),
+                };
+                if seen.insert(unit) {
+                    units.push(unit);
+                }
             }
-        })
-    }).collect::<Vec<_>>();
+        }
+    }
 
     let mut cx = Context::new(ws, resolve, packages, config,
                                    build_config, profiles)?;
+    cx.warn_unused_profile_overrides()?;
 
-    let mut queue = JobQueue::new(&cx);
+    let mut queue = JobQueue::new(&cx, &units);
 
     cx.prepare()?;
     cx.probe_target_info(&units)?;
+    cx.download_deps(&units)?;
+    cx.resolve_features(&units)?;
     cx.build_used_in_plugin_map(&units)?;
     custom_build::build_map(&mut cx, &units)?;
 
@@ -190,10 +241,15 @@ pub fn compile_targets<'a, 'cfg: 'a>(ws: &Workspace<'cfg>,
                 }));
         }
 
-        let feats = cx.resolve.features(&unit.pkg.package_id());
+        let feats = cx.unit_features(unit).iter().map(|feat| {
+            format!("feature=\"{}\"", feat)
+        }).collect::<Vec<_>>();
         cx.compilation.cfgs.entry(unit.pkg.package_id().clone())
             .or_insert_with(HashSet::new)
-            .extend(feats.iter().map(|feat| format!("feature=\"{}\"", feat)));
+            .extend(feats);
+
+        let lint_args = cx.pkg_lint_args(unit.pkg, true);
+        cx.compilation.lint_args.insert(unit.pkg.package_id().clone(), lint_args);
 
         output_depinfo(&mut cx, unit)?;
     }
@@ -211,7 +267,9 @@ pub fn compile_targets<'a, 'cfg: 'a>(ws: &Workspace<'cfg>,
             cx.compilation.native_dirs.insert(dir.clone());
         }
     }
-    cx.compilation.target = cx.target_triple().to_string();
+    let kind = cx.build_config.default_kinds()[0];
+    cx.compilation.target = cx.build_config.triple(kind).to_string();
+    cx.compilation.target_runner = cx.target_runner()?;
     Ok(cx.compilation)
 }
 
@@ -223,6 +281,17 @@ fn compile<'a, 'cfg: 'a>(cx: &mut Context<'a, 'cfg>,
         return Ok(())
     }
 
+    if let Some(rust_version) = unit.pkg.summary().rust_version() {
+        let rustc = cx.config.rustc()?;
+        if !rustc.supports(rust_version)? {
+            bail!("package `{} v{}` cannot be built because it requires \
+                   rustc {} or newer, while the currently active rustc \
+                   version is {}",
+                  unit.pkg.name(), unit.pkg.version(), rust_version,
+                  rustc.version()?)
+        }
+    }
+
     // Build up the work to be done to compile this unit, enqueuing it once
     // we've got everything constructed.
     let p = profile::start(format!("preparing: {}/{}", unit.pkg,
@@ -309,8 +378,11 @@ fn rustc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
     let cwd = cx.config.cwd().to_path_buf();
 
     rustc.args(&cx.incremental_args(unit)?);
+    rustc.args(&cx.lint_args(unit));
     rustc.args(&cx.rustflags_args(unit)?);
     let json_messages = cx.build_config.json_messages;
+    let timings = cx.build_config.timings;
+    let capture_diagnostics = cx.capture_diagnostics;
     let package_id = unit.pkg.package_id().clone();
     let target = unit.target.clone();
 
@@ -356,7 +428,7 @@ fn rustc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
         }
 
         state.running(&rustc);
-        if json_messages {
+        if json_messages || timings || capture_diagnostics {
             exec.exec_json(rustc, &package_id,
                 &mut |line| if !line.is_empty() {
                     Err(internal(&format!("compiler stdout is not empty: `{}`", line)))
@@ -367,18 +439,36 @@ fn rustc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
                     // stderr from rustc can have a mix of JSON and non-JSON output
                     if line.starts_with('{') {
                         // Handle JSON lines
-                        let compiler_message = serde_json::from_str(line).map_err(|_| {
-                            internal(&format!("compiler produced invalid json: `{}`", line))
-                        })?;
-
-                        machine_message::emit(machine_message::FromCompiler {
-                            package_id: &package_id,
-                            target: &target,
-                            message: compiler_message,
-                        });
+                        let compiler_message: serde_json::Value =
+                            serde_json::from_str(line).map_err(|_| {
+                                internal(&format!("compiler produced invalid json: `{}`", line))
+                            })?;
+
+                        // Artifact notifications, requested for `--timings`,
+                        // tell when the metadata of the crate is written.
+                        if compiler_message.get("artifact").is_some() {
+                            if compiler_message["emit"].as_str() == Some("metadata") {
+                                state.rmeta_produced();
+                            }
+                            return Ok(())
+                        }
+
+                        if json_messages {
+                            machine_message::emit(machine_message::FromCompiler {
+                                package_id: &package_id,
+                                target: &target,
+                                message: compiler_message,
+                            });
+                        } else if let Some(rendered) = compiler_message["rendered"].as_str() {
+                            // rustc only emits JSON for `--timings` or so
+                            // that each diagnostic is printed in one go
+                            // with the progress bar erased, so print them
+                            // the way rustc would have.
+                            state.stderr_diagnostic(rendered.trim_right_matches('\n'));
+                        }
                     } else {
                         // Forward non-JSON to stderr
-                        writeln!(io::stderr(), "{}", line)?;
+                        state.stderr_diagnostic(line);
                     }
                     Ok(())
                 }
@@ -469,9 +559,10 @@ fn link_targets<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
     let package_id = unit.pkg.package_id().clone();
     let target = unit.target.clone();
     let profile = unit.profile.clone();
-    let features = cx.resolve.features_sorted(&package_id).into_iter()
+    let features = cx.unit_features(unit).into_iter()
         .map(|s| s.to_owned())
         .collect();
+    let triple = cx.build_config.triple(unit.kind).to_string();
     let json_messages = cx.build_config.json_messages;
 
     Ok(Work::new(move |_| {
@@ -516,6 +607,7 @@ fn link_targets<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
             machine_message::emit(machine_message::Artifact {
                 package_id: &package_id,
                 target: &target,
+                target_triple: &triple,
                 profile: &profile,
                 features: features,
                 filenames: destinations,
@@ -605,11 +697,10 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
     rustdoc.arg("--crate-name").arg(&unit.target.crate_name())
            .cwd(cx.config.cwd())
            .arg(&root_path(cx, unit));
+    add_edition_args(&mut rustdoc, unit.target.edition());
 
-    if unit.kind != Kind::Host {
-        if let Some(target) = cx.requested_target() {
-            rustdoc.arg("--target").arg(target);
-        }
+    if let Some(target) = cx.build_config.requested_target(unit.kind) {
+        rustdoc.arg("--target").arg(target);
     }
 
     let doc_dir = cx.out_dir(unit);
@@ -621,7 +712,7 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
 
     rustdoc.arg("-o").arg(doc_dir);
 
-    for feat in cx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in cx.unit_features(unit) {
         rustdoc.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
@@ -631,11 +722,13 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
 
     build_deps_args(&mut rustdoc, cx, unit)?;
 
+    rustdoc.args(&cx.lint_args(unit));
     rustdoc.args(&cx.rustdocflags_args(unit)?);
 
     let name = unit.pkg.name().to_string();
     let build_state = cx.build_state.clone();
     let key = (unit.pkg.package_id().clone(), unit.kind);
+    let capture_diagnostics = cx.capture_diagnostics;
 
     Ok(Work::new(move |state| {
         if let Some(output) = build_state.outputs.lock().unwrap().get(&key) {
@@ -647,7 +740,29 @@ fn rustdoc<'a, 'cfg>(cx: &mut Context<'a, 'cfg>,
             }
         }
         state.running(&rustdoc);
-        rustdoc.exec().chain_err(|| format!("Could not document `{}`.", name))
+        if !capture_diagnostics {
+            return rustdoc.exec().chain_err(|| format!("Could not document `{}`.", name))
+        }
+
+        // Print everything rustdoc wrote to stderr at once when it's done,
+        // so that it isn't mixed up with the progress bar or other output.
+        let mut stderr = String::new();
+        let result = rustdoc.exec_with_streaming(
+            &mut |line| {
+                writeln!(io::stdout(), "{}", line)?;
+                Ok(())
+            },
+            &mut |line| {
+                stderr.push_str(line);
+                stderr.push('\n');
+                Ok(())
+            },
+            false,
+        );
+        if !stderr.is_empty() {
+            state.stderr_diagnostic(stderr.trim_right_matches('\n'));
+        }
+        result.map(drop).chain_err(|| format!("Could not document `{}`.", name))
     }))
 }
 
@@ -672,6 +787,16 @@ fn root_path(cx: &Context, unit: &Unit) -> PathBuf {
     }
 }
 
+/// Passes the edition a target is written in to rustc or rustdoc.
+///
+/// Compilers which predate editions only understand 2015 code, so nothing is
+/// passed for the default edition.
+pub fn add_edition_args(cmd: &mut ProcessBuilder, edition: Edition) {
+    if edition != Edition::Edition2015 {
+        cmd.arg(format!("--edition={}", edition));
+    }
+}
+
 fn build_base_args(cx: &mut Context,
                    cmd: &mut ProcessBuilder,
                    unit: &Unit,
@@ -688,6 +813,8 @@ fn build_base_args(cx: &mut Context,
 
     cmd.arg("--crate-name").arg(&unit.target.crate_name());
 
+    add_edition_args(cmd, unit.target.edition());
+
     cmd.arg(&root_path(cx, unit));
 
     match cx.config.shell().color_choice() {
@@ -696,10 +823,28 @@ fn build_base_args(cx: &mut Context,
         ColorChoice::CargoAuto => {}
     }
 
-    if cx.build_config.json_messages {
+    if cx.build_config.json_messages || cx.build_config.timings ||
+       cx.capture_diagnostics {
         cmd.arg("--error-format").arg("json");
     }
 
+    // `--timings` needs to know when the metadata of each crate is written,
+    // which rustc reports with artifact notifications. Unless JSON messages
+    // were requested, diagnostics are still printed in the form rustc renders
+    // them in, colored if the shell supports it.
+    let mut json = Vec::new();
+    if cx.build_config.timings {
+        json.push("artifacts");
+    }
+    if !cx.build_config.json_messages &&
+       (cx.build_config.timings || cx.capture_diagnostics) &&
+       cx.config.shell().supports_color() {
+        json.push("diagnostic-rendered-ansi");
+    }
+    if !json.is_empty() {
+        cmd.arg("--json").arg(json.join(","));
+    }
+
     if !test {
         for crate_type in crate_types.iter() {
             cmd.arg("--crate-type").arg(crate_type);
@@ -792,7 +937,7 @@ fn build_base_args(cx: &mut Context,
     // We ideally want deterministic invocations of rustc to ensure that
     // rustc-caching strategies like sccache are able to cache more, so sort the
     // feature list here.
-    for feat in cx.resolve.features_sorted(unit.pkg.package_id()) {
+    for feat in cx.unit_features(unit) {
         cmd.arg("--cfg").arg(&format!("feature=\"{}\"", feat));
     }
 
@@ -821,9 +966,8 @@ fn build_base_args(cx: &mut Context,
         }
     }
 
-    if unit.kind == Kind::Target {
-        opt(cmd, "--target", "", cx.requested_target().map(|s| s.as_ref()));
-    }
+    opt(cmd, "--target", "",
+        cx.build_config.requested_target(unit.kind).map(|s| s.as_ref()));
 
     opt(cmd, "-C", "ar=", cx.ar(unit.kind).map(|s| s.as_ref()));
     opt(cmd, "-C", "linker=", cx.linker(unit.kind).map(|s| s.as_ref()));
@@ -841,7 +985,7 @@ fn build_deps_args<'a, 'cfg>(cmd: &mut ProcessBuilder,
 
     // Be sure that the host path is also listed. This'll ensure that proc-macro
     // dependencies are correctly found (for reexported macros).
-    if let Kind::Target = unit.kind {
+    if let Kind::Target(..) = unit.kind {
         cmd.arg("-L").arg(&{
             let mut deps = OsString::from("dependency=");
             deps.push(cx.host_deps());
@@ -849,31 +993,69 @@ fn build_deps_args<'a, 'cfg>(cmd: &mut ProcessBuilder,
         });
     }
 
-    for unit in cx.dep_targets(unit)?.iter() {
-        if unit.profile.run_custom_build {
-            cmd.env("OUT_DIR", &cx.build_script_out_dir(unit));
+    for dep in cx.dep_targets(unit)?.iter() {
+        if dep.profile.run_custom_build {
+            cmd.env("OUT_DIR", &cx.build_script_out_dir(dep));
         }
-        if unit.target.linkable() && !unit.profile.doc {
-            link_to(cmd, cx, unit)?;
+        if dep.target.linkable() && !dep.profile.doc &&
+           !cx.is_artifact_dep(unit, dep) {
+            link_to(cmd, cx, unit, dep)?;
         }
     }
 
+    for (key, path) in cx.artifact_env(unit)? {
+        cmd.env(&key, &path);
+    }
+
     return Ok(());
 
     fn link_to<'a, 'cfg>(cmd: &mut ProcessBuilder,
                          cx: &mut Context<'a, 'cfg>,
-                         unit: &Unit<'a>) -> CargoResult<()> {
-        for &(ref dst, _, ref linkable) in cx.target_filenames(unit)?.iter() {
+                         current: &Unit<'a>,
+                         dep: &Unit<'a>) -> CargoResult<()> {
+        // If the dependency was renamed in the manifest with the `package`
+        // key then it's made available to the crate under its new name, once
+        // for each name it was given.
+        let mut names = Vec::new();
+        let current_id = current.pkg.package_id();
+        let dep_id = dep.pkg.package_id();
+        if current_id != dep_id {
+            let resolve = cx.resolve;
+            for d in current.pkg.dependencies() {
+                // Dependencies are matched before any `[replace]`, as they
+                // refer to the original package.
+                let matched = resolve.deps_not_replaced(current_id).any(|id| {
+                    resolve.dep_matches_id(d, id) &&
+                        resolve.replacement(id).unwrap_or(id) == dep_id
+                });
+                if !matched {
+                    continue
+                }
+                let name = match d.explicit_name_in_toml() {
+                    Some(renamed) => renamed.replace("-", "_"),
+                    None => dep.target.crate_name(),
+                };
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        if names.is_empty() {
+            names.push(dep.target.crate_name());
+        }
+        for &(ref dst, _, ref linkable) in cx.target_filenames(dep)?.iter() {
             if !*linkable {
                 continue
             }
-            let mut v = OsString::new();
-            v.push(&unit.target.crate_name());
-            v.push("=");
-            v.push(cx.out_dir(unit));
-            v.push(&path::MAIN_SEPARATOR.to_string());
-            v.push(&dst.file_name().unwrap());
-            cmd.arg("--extern").arg(&v);
+            for name in names.iter() {
+                let mut v = OsString::new();
+                v.push(name);
+                v.push("=");
+                v.push(cx.out_dir(dep));
+                v.push(&path::MAIN_SEPARATOR.to_string());
+                v.push(&dst.file_name().unwrap());
+                cmd.arg("--extern").arg(&v);
+            }
         }
         Ok(())
     }
@@ -893,8 +1075,8 @@ impl Kind {
         // that needs to be on the host we lift ourselves up to `Host`
         match *self {
             Kind::Host => Kind::Host,
-            Kind::Target if target.for_host() => Kind::Host,
-            Kind::Target => Kind::Target,
+            Kind::Target(..) if target.for_host() => Kind::Host,
+            Kind::Target(i) => Kind::Target(i),
         }
     }
 }
@@ -0,0 +1,652 @@
+//! Timing information about the units of work of a build, collected when
+//! `--timings` is passed.
+//!
+//! The job queue reports when each unit starts, produces its metadata and
+//! finishes, along with how many units are running, waiting for a jobserver
+//! token or waiting on their dependencies. Once the build is done this is
+//! turned into a self-contained HTML report in `target/cargo-timings` and,
+//! with `--message-format json`, a `timing-info` message.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use core::TargetKind;
+use util::{CargoResult, CargoResultExt, machine_message, paths};
+
+use super::{Context, Unit};
+use super::job_queue::Key;
+
+pub struct Timings<'a> {
+    enabled: bool,
+    start: Instant,
+    /// When the build started, in seconds since the Unix epoch.
+    start_time: u64,
+    /// Description of the packages and targets requested.
+    root_targets: Vec<String>,
+    profile: String,
+    jobs: u32,
+    units: Vec<UnitTime<'a>>,
+    /// Index in `units` of the units which are currently running.
+    active: HashMap<Key<'a>, usize>,
+    deps: HashMap<Key<'a>, Vec<Key<'a>>>,
+    concurrency: Vec<Concurrency>,
+    cpu_state: Option<CpuState>,
+    last_cpu_sample: f64,
+    /// Fraction of the CPU used, sampled over time.
+    cpu_usage: Vec<(f64, f64)>,
+}
+
+struct UnitTime<'a> {
+    key: Key<'a>,
+    name: String,
+    fresh: bool,
+    start: f64,
+    duration: f64,
+    /// When the metadata of the unit was available, relative to its start.
+    rmeta_time: Option<f64>,
+}
+
+struct Concurrency {
+    t: f64,
+    /// Number of units being built.
+    active: usize,
+    /// Number of units ready to be built, waiting for a jobserver token.
+    waiting: usize,
+    /// Number of units waiting on their dependencies.
+    inactive: usize,
+}
+
+impl<'a> Timings<'a> {
+    pub fn new<'cfg>(cx: &Context<'a, 'cfg>, root_units: &[Unit<'a>]) -> Timings<'a> {
+        let mut root_map: Vec<(String, Vec<String>)> = Vec::new();
+        for unit in root_units {
+            let pkg = format!("{} v{}", unit.pkg.name(), unit.pkg.version());
+            let target = target_desc(unit.target.kind(), unit.target.name());
+            match root_map.iter().position(|&(ref p, _)| *p == pkg) {
+                Some(i) => {
+                    if !root_map[i].1.contains(&target) {
+                        root_map[i].1.push(target);
+                    }
+                }
+                None => root_map.push((pkg, vec![target])),
+            }
+        }
+        let root_targets = root_map.into_iter().map(|(pkg, targets)| {
+            escape(&format!("{} ({})", pkg, targets.join(", ")))
+        }).collect();
+        let profile = match cx.build_config.profile_name {
+            Some(ref name) => name.clone(),
+            None if cx.build_config.release => "release".to_string(),
+            None => "dev".to_string(),
+        };
+        let start_time = SystemTime::now().duration_since(UNIX_EPOCH)
+                                          .map(|d| d.as_secs())
+                                          .unwrap_or(0);
+        Timings {
+            enabled: cx.build_config.timings,
+            start: Instant::now(),
+            start_time: start_time,
+            root_targets: root_targets,
+            profile: profile,
+            jobs: cx.build_config.jobs,
+            units: Vec::new(),
+            active: HashMap::new(),
+            deps: HashMap::new(),
+            concurrency: Vec::new(),
+            cpu_state: CpuState::current(),
+            last_cpu_sample: 0.0,
+            cpu_usage: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records the dependencies of `key`, used to find the critical path.
+    pub fn unit_enqueued(&mut self, key: Key<'a>, deps: &[Key<'a>]) {
+        if !self.enabled {
+            return
+        }
+        self.deps.insert(key, deps.to_vec());
+    }
+
+    pub fn unit_start(&mut self, key: Key<'a>, fresh: bool) {
+        if !self.enabled || self.active.contains_key(&key) {
+            return
+        }
+        let start = secs(self.start.elapsed());
+        self.active.insert(key, self.units.len());
+        self.units.push(UnitTime {
+            key: key,
+            name: unit_name(&key),
+            fresh: fresh,
+            start: start,
+            duration: 0.0,
+            rmeta_time: None,
+        });
+    }
+
+    pub fn unit_rmeta_finished(&mut self, key: Key<'a>) {
+        if !self.enabled {
+            return
+        }
+        let now = secs(self.start.elapsed());
+        if let Some(&i) = self.active.get(&key) {
+            let unit = &mut self.units[i];
+            unit.rmeta_time = Some(now - unit.start);
+        }
+    }
+
+    pub fn unit_finished(&mut self, key: Key<'a>) {
+        if !self.enabled {
+            return
+        }
+        let now = secs(self.start.elapsed());
+        if let Some(i) = self.active.remove(&key) {
+            let unit = &mut self.units[i];
+            unit.duration = now - unit.start;
+            // Checking a crate only produces its metadata.
+            if unit.key.profile.check && unit.rmeta_time.is_none() {
+                unit.rmeta_time = Some(unit.duration);
+            }
+        }
+    }
+
+    pub fn mark_concurrency(&mut self, active: usize, waiting: usize, inactive: usize) {
+        if !self.enabled {
+            return
+        }
+        if let Some(last) = self.concurrency.last() {
+            if (last.active, last.waiting, last.inactive) == (active, waiting, inactive) {
+                return
+            }
+        }
+        let t = secs(self.start.elapsed());
+        self.concurrency.push(Concurrency {
+            t: t,
+            active: active,
+            waiting: waiting,
+            inactive: inactive,
+        });
+    }
+
+    /// Samples the CPU usage, at most every 100ms.
+    pub fn record_cpu(&mut self) {
+        if !self.enabled {
+            return
+        }
+        let now = secs(self.start.elapsed());
+        if now - self.last_cpu_sample < 0.1 {
+            return
+        }
+        self.last_cpu_sample = now;
+        let prev = match self.cpu_state.take() {
+            Some(prev) => prev,
+            None => return,
+        };
+        let current = match CpuState::current() {
+            Some(current) => current,
+            None => return,
+        };
+        let total = current.total.saturating_sub(prev.total);
+        let idle = current.idle.saturating_sub(prev.idle);
+        if total > 0 {
+            let usage = 1.0 - idle as f64 / total as f64;
+            self.cpu_usage.push((now, usage));
+        }
+        self.cpu_state = Some(current);
+    }
+
+    /// Writes the report once the build is done, and emits the `timing-info`
+    /// message if JSON messages were requested.
+    pub fn finished(&mut self, cx: &Context) -> CargoResult<()> {
+        if !self.enabled {
+            return Ok(())
+        }
+        let duration = secs(self.start.elapsed());
+        self.mark_concurrency(0, 0, 0);
+        let critical_path = self.critical_path();
+        let (average, idle) = self.parallelism(duration);
+
+        let dir = cx.ws.target_dir().join("cargo-timings").into_path_unlocked();
+        fs::create_dir_all(&dir).chain_err(|| {
+            format!("failed to create directory `{}`", dir.display())
+        })?;
+        let html = self.report_html(cx, duration, &critical_path, average, idle);
+        let path = dir.join(format!("cargo-timing-{}.html",
+                                    format_time(self.start_time, true)));
+        paths::write(&path, html.as_bytes())?;
+        paths::write(&dir.join("cargo-timing.html"), html.as_bytes())?;
+
+        if cx.build_config.json_messages {
+            machine_message::emit(machine_message::TimingInfo {
+                duration: duration,
+                jobs: self.jobs,
+                max_concurrency: self.max_concurrency(),
+                average_concurrency: average,
+                idle_parallelism: idle,
+                units: self.units.iter().map(|unit| {
+                    machine_message::UnitTiming {
+                        package_id: unit.key.pkg,
+                        target: unit.key.target,
+                        fresh: unit.fresh,
+                        start: unit.start,
+                        duration: unit.duration,
+                        rmeta_time: unit.rmeta_time,
+                    }
+                }).collect(),
+                critical_path: critical_path,
+            });
+        }
+        cx.config.shell().status("Timing", format!("report saved to {}",
+                                                    path.display()))?;
+        Ok(())
+    }
+
+    /// Returns the indices in `units` of the chain of units which determined
+    /// the length of the build: starting from the unit finishing last, each
+    /// unit is preceded by the dependency which finished last.
+    fn critical_path(&self) -> Vec<usize> {
+        let index = self.units.iter().enumerate().map(|(i, unit)| {
+            (unit.key, i)
+        }).collect::<HashMap<_, _>>();
+        let end = |i: usize| self.units[i].start + self.units[i].duration;
+
+        let mut path = Vec::new();
+        let mut current = (0..self.units.len()).fold(None, |max, i| {
+            match max {
+                Some(m) if end(m) >= end(i) => Some(m),
+                _ => Some(i),
+            }
+        });
+        while let Some(i) = current {
+            path.push(i);
+            let deps = match self.deps.get(&self.units[i].key) {
+                Some(deps) => deps,
+                None => break,
+            };
+            current = deps.iter().filter_map(|d| index.get(d).cloned()).fold(None, |max, i| {
+                match max {
+                    Some(m) if end(m) >= end(i) => Some(m),
+                    _ => Some(i),
+                }
+            });
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the average number of units being built at once, and the
+    /// fraction of the jobs which were left idle over the build.
+    fn parallelism(&self, duration: f64) -> (f64, f64) {
+        if duration <= 0.0 {
+            return (0.0, 0.0)
+        }
+        let mut busy = 0.0;
+        for pair in self.concurrency.windows(2) {
+            busy += pair[0].active as f64 * (pair[1].t - pair[0].t);
+        }
+        let average = busy / duration;
+        let idle = 1.0 - average / self.jobs as f64;
+        (average, if idle < 0.0 { 0.0 } else { idle })
+    }
+
+    fn max_concurrency(&self) -> usize {
+        self.concurrency.iter().map(|c| c.active).max().unwrap_or(0)
+    }
+
+    fn report_html(&self,
+                   cx: &Context,
+                   duration: f64,
+                   critical_path: &[usize],
+                   average: f64,
+                   idle: f64) -> String {
+        let fresh = self.units.iter().filter(|u| u.fresh).count();
+        let rustc = cx.config.rustc().map(|r| r.verbose_version.lines().next()
+                                                               .unwrap_or("")
+                                                               .to_string())
+                                    .unwrap_or(String::new());
+        let cpu = if self.cpu_usage.is_empty() {
+            "n/a".to_string()
+        } else {
+            let sum = self.cpu_usage.iter().map(|&(_, u)| u).fold(0.0, |a, b| a + b);
+            format!("{:.0}%", sum / self.cpu_usage.len() as f64 * 100.0)
+        };
+
+        let mut out = String::new();
+        out.push_str(HTML_HEADER);
+        out.push_str("<h1>Cargo Build Timings</h1>\n<table class=\"summary\">\n");
+        let rows = [
+            ("Targets:", self.root_targets.join("<br>")),
+            ("Profile:", escape(&self.profile)),
+            ("Fresh units:", fresh.to_string()),
+            ("Dirty units:", (self.units.len() - fresh).to_string()),
+            ("Total units:", self.units.len().to_string()),
+            ("Max concurrency:", format!("{} (jobs={})", self.max_concurrency(),
+                                         self.jobs)),
+            ("Average concurrency:", format!("{:.1}", average)),
+            ("Idle parallelism:", format!("{:.0}%", idle * 100.0)),
+            ("CPU usage:", cpu),
+            ("Build start:", format_time(self.start_time, false)),
+            ("Total time:", format!("{:.1}s", duration)),
+            ("rustc:", escape(&rustc)),
+        ];
+        for &(ref name, ref value) in rows.iter() {
+            let _ = writeln!(out, "<tr><td>{}</td><td>{}</td></tr>", name, value);
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h2>Concurrency</h2>\n");
+        self.write_concurrency_graph(&mut out, duration);
+        out.push_str("<h2>Units</h2>\n");
+        self.write_unit_graph(&mut out, duration, critical_path);
+        self.write_unit_table(&mut out, critical_path);
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn write_concurrency_graph(&self, out: &mut String, duration: f64) {
+        let max = self.concurrency.iter().map(|c| {
+            c.active + c.waiting + c.inactive
+        }).max().unwrap_or(0);
+        let max = if max < self.jobs as usize { self.jobs as usize } else { max };
+        let width = GRAPH_WIDTH;
+        let height = 300.0;
+        let x = |t: f64| if duration > 0.0 { t / duration * width } else { 0.0 };
+        let y = |n: f64| height - n / (if max > 0 { max as f64 } else { 1.0 }) * height;
+
+        let _ = writeln!(out, "<svg width=\"{}\" height=\"{}\" class=\"graph\">",
+                         width + 2.0 * MARGIN, height + 2.0 * MARGIN);
+        let _ = writeln!(out, "<g transform=\"translate({m},{m})\">", m = MARGIN);
+        let _ = writeln!(out, "<rect width=\"{}\" height=\"{}\" class=\"frame\"/>",
+                         width, height);
+        let lines: [(&str, &Fn(&Concurrency) -> usize); 3] = [
+            ("inactive", &|c: &Concurrency| c.inactive),
+            ("waiting", &|c: &Concurrency| c.waiting),
+            ("active", &|c: &Concurrency| c.active),
+        ];
+        for &(class, value) in lines.iter() {
+            let mut points = String::new();
+            let mut prev: Option<usize> = None;
+            for c in self.concurrency.iter() {
+                // Draw steps rather than slopes between samples.
+                if let Some(p) = prev {
+                    let _ = write!(points, "{:.1},{:.1} ", x(c.t), y(p as f64));
+                }
+                let _ = write!(points, "{:.1},{:.1} ", x(c.t), y(value(c) as f64));
+                prev = Some(value(c));
+            }
+            let _ = writeln!(out, "<polyline class=\"{}\" points=\"{}\"/>", class, points);
+        }
+        if !self.cpu_usage.is_empty() {
+            let points = self.cpu_usage.iter().map(|&(t, u)| {
+                format!("{:.1},{:.1}", x(t), height - u * height)
+            }).collect::<Vec<_>>().join(" ");
+            let _ = writeln!(out, "<polyline class=\"cpu\" points=\"{}\"/>", points);
+        }
+        self.write_time_axis(out, duration, height);
+        let _ = writeln!(out, "<text x=\"-5\" y=\"10\" class=\"axis\" \
+                                text-anchor=\"end\">{}</text>", max);
+        out.push_str("</g>\n</svg>\n");
+        out.push_str("<p class=\"legend\"><span class=\"active\">active</span> \
+                      <span class=\"waiting\">waiting for a job token</span> \
+                      <span class=\"inactive\">waiting on dependencies</span> \
+                      <span class=\"cpu\">CPU usage</span></p>\n");
+    }
+
+    fn write_unit_graph(&self, out: &mut String, duration: f64, critical_path: &[usize]) {
+        let mut dirty = (0..self.units.len()).filter(|&i| {
+            !self.units[i].fresh
+        }).collect::<Vec<_>>();
+        dirty.sort_by(|&a, &b| {
+            total_cmp(self.units[a].start, self.units[b].start)
+        });
+        let width = GRAPH_WIDTH;
+        let row = 20.0;
+        let height = row * dirty.len() as f64;
+        let x = |t: f64| if duration > 0.0 { t / duration * width } else { 0.0 };
+
+        let _ = writeln!(out, "<svg width=\"{}\" height=\"{}\" class=\"graph\">",
+                         width + 2.0 * MARGIN, height + 2.0 * MARGIN);
+        let _ = writeln!(out, "<g transform=\"translate({m},{m})\">", m = MARGIN);
+        for (n, &i) in dirty.iter().enumerate() {
+            let unit = &self.units[i];
+            let class = if critical_path.contains(&i) { "unit critical" } else { "unit" };
+            let top = n as f64 * row;
+            let _ = writeln!(out, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" \
+                                   height=\"{:.1}\" class=\"{}\"/>",
+                             x(unit.start), top + 1.0, x(unit.duration).max(1.0),
+                             row - 2.0, class);
+            if let Some(rmeta) = unit.rmeta_time {
+                let _ = writeln!(out, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" \
+                                       height=\"{:.1}\" class=\"rmeta\"/>",
+                                 x(unit.start), top + 1.0, x(rmeta).max(1.0), row - 2.0);
+            }
+            let _ = writeln!(out, "<text x=\"{:.1}\" y=\"{:.1}\" class=\"label\">{} \
+                                   {:.1}s</text>",
+                             x(unit.start) + 4.0, top + row - 6.0,
+                             escape(&unit.name), unit.duration);
+        }
+        self.write_time_axis(out, duration, height);
+        out.push_str("</g>\n</svg>\n");
+        out.push_str("<p class=\"legend\"><span class=\"rmeta\">metadata</span> \
+                      <span class=\"unit\">codegen</span> \
+                      <span class=\"critical\">critical path</span></p>\n");
+    }
+
+    fn write_time_axis(&self, out: &mut String, duration: f64, height: f64) {
+        let step = tick_step(duration);
+        let mut t = 0.0;
+        while t <= duration {
+            let x = if duration > 0.0 { t / duration * GRAPH_WIDTH } else { 0.0 };
+            let _ = writeln!(out, "<line x1=\"{x:.1}\" x2=\"{x:.1}\" y1=\"0\" \
+                                   y2=\"{h:.1}\" class=\"tick\"/>\
+                                   <text x=\"{x:.1}\" y=\"{y:.1}\" class=\"axis\" \
+                                   text-anchor=\"middle\">{t}s</text>",
+                             x = x, h = height, y = height + 15.0, t = t);
+            t += step;
+        }
+    }
+
+    fn write_unit_table(&self, out: &mut String, critical_path: &[usize]) {
+        let mut dirty = (0..self.units.len()).filter(|&i| {
+            !self.units[i].fresh
+        }).collect::<Vec<_>>();
+        dirty.sort_by(|&a, &b| {
+            total_cmp(self.units[b].duration, self.units[a].duration)
+        });
+        out.push_str("<table class=\"units\">\n<thead><tr><th></th><th>Unit</th>\
+                      <th>Total</th><th>Metadata</th><th>Codegen</th></tr></thead>\n\
+                      <tbody>\n");
+        for (n, &i) in dirty.iter().enumerate() {
+            let unit = &self.units[i];
+            let (rmeta, codegen) = match unit.rmeta_time {
+                Some(rmeta) => (format!("{:.1}s", rmeta),
+                                format!("{:.1}s", unit.duration - rmeta)),
+                None => (String::new(), String::new()),
+            };
+            let class = if critical_path.contains(&i) { " class=\"critical\"" } else { "" };
+            let _ = writeln!(out, "<tr{}><td>{}.</td><td>{}</td><td>{:.1}s</td>\
+                                   <td>{}</td><td>{}</td></tr>",
+                             class, n + 1, escape(&unit.name), unit.duration,
+                             rmeta, codegen);
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+}
+
+/// The `/proc/stat` counters of the time spent by all CPUs, in ticks.
+struct CpuState {
+    total: u64,
+    idle: u64,
+}
+
+impl CpuState {
+    #[cfg(target_os = "linux")]
+    fn current() -> Option<CpuState> {
+        let stat = match paths::read(Path::new("/proc/stat")) {
+            Ok(stat) => stat,
+            Err(..) => return None,
+        };
+        let line = match stat.lines().next() {
+            Some(line) if line.starts_with("cpu ") => line,
+            _ => return None,
+        };
+        let values = line.split_whitespace().skip(1).map(|v| {
+            v.parse::<u64>().unwrap_or(0)
+        }).collect::<Vec<_>>();
+        if values.len() < 4 {
+            return None
+        }
+        // The fourth and fifth fields are `idle` and `iowait`.
+        let idle = values[3] + values.get(4).cloned().unwrap_or(0);
+        Some(CpuState {
+            total: values.iter().fold(0, |a, b| a + b),
+            idle: idle,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn current() -> Option<CpuState> {
+        None
+    }
+}
+
+const GRAPH_WIDTH: f64 = 1000.0;
+const MARGIN: f64 = 30.0;
+
+/// Compares two floats by the IEEE 754 total order, so that sorting never
+/// panics, even if a NaN sneaks in.
+fn total_cmp(a: f64, b: f64) -> Ordering {
+    let key = |f: f64| {
+        let bits = f.to_bits() as i64;
+        bits ^ (((bits >> 63) as u64) >> 1) as i64
+    };
+    key(a).cmp(&key(b))
+}
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + d.subsec_nanos() as f64 / 1_000_000_000.0
+}
+
+/// Picks the interval between the ticks of the time axis so there are
+/// about ten of them.
+fn tick_step(duration: f64) -> f64 {
+    for &step in [1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0].iter() {
+        if duration / step <= 10.0 {
+            return step
+        }
+    }
+    1200.0
+}
+
+fn target_desc(kind: &TargetKind, name: &str) -> String {
+    match *kind {
+        TargetKind::Lib(..) => "lib".to_string(),
+        TargetKind::Bin => format!("bin \"{}\"", name),
+        TargetKind::Test => format!("test \"{}\"", name),
+        TargetKind::Bench => format!("bench \"{}\"", name),
+        TargetKind::ExampleLib(..) |
+        TargetKind::ExampleBin => format!("example \"{}\"", name),
+        TargetKind::CustomBuild => "build script".to_string(),
+    }
+}
+
+fn unit_name(key: &Key) -> String {
+    let mut name = format!("{} v{}", key.pkg.name(), key.pkg.version());
+    if key.profile.run_custom_build {
+        name.push_str(" run build script");
+        return name
+    }
+    match *key.target.kind() {
+        TargetKind::Lib(..) => {}
+        ref kind => {
+            name.push(' ');
+            name.push_str(&target_desc(kind, key.target.name()));
+        }
+    }
+    if key.profile.doc {
+        name.push_str(" (doc)");
+    } else if key.profile.test && key.target.is_lib() {
+        name.push_str(" (test)");
+    } else if key.profile.check {
+        name.push_str(" (check)");
+    }
+    name
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+     .replace('<', "&lt;")
+     .replace('>', "&gt;")
+     .replace('"', "&quot;")
+}
+
+/// Formats seconds since the Unix epoch as a UTC date, either for a file
+/// name (`20170102T030405Z`) or for display (`2017-01-02 03:04:05 UTC`).
+fn format_time(secs: u64, compact: bool) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    let (hour, min, sec) = (rem / 3600, rem % 3600 / 60, rem % 60);
+
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    if compact {
+        format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, hour, min, sec)
+    } else {
+        format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+                year, month, day, hour, min, sec)
+    }
+}
+
+static HTML_HEADER: &'static str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Cargo Build Timings</title>
+<style>
+body { font-family: sans-serif; margin: 20px; }
+h1 { border-bottom: 1px solid #ccc; }
+table { border-collapse: collapse; }
+td, th { padding: 2px 8px; text-align: left; }
+table.units { margin-top: 20px; }
+table.units tbody tr:nth-child(odd) { background: #f4f4f4; }
+tr.critical td { color: #b00; }
+.graph text { font-size: 11px; }
+.frame { fill: none; stroke: #ccc; }
+.tick { stroke: #eee; }
+.axis { fill: #555; }
+polyline { fill: none; stroke-width: 2; }
+polyline.active { stroke: #2a7ab0; }
+polyline.waiting { stroke: #e08a1e; }
+polyline.inactive { stroke: #aaa; }
+polyline.cpu { stroke: #4caf50; stroke-width: 1; }
+rect.unit { fill: #7fb3d5; }
+rect.critical { stroke: #b00; stroke-width: 2; }
+rect.rmeta { fill: #b39ddb; }
+.legend span { padding: 2px 6px; margin-right: 8px; border-left: 12px solid; }
+.legend .active, .legend .unit { border-color: #2a7ab0; }
+.legend .waiting { border-color: #e08a1e; }
+.legend .inactive { border-color: #aaa; }
+.legend .cpu { border-color: #4caf50; }
+.legend .rmeta { border-color: #b39ddb; }
+.legend .critical { border-color: #b00; }
+</style>
+</head>
+<body>
+"#;
@@ -1,7 +1,7 @@
 use std::ffi::{OsString, OsStr};
 
-use ops::{self, Compilation};
-use util::{self, CargoTestError, Test, ProcessError};
+use ops::{self, Compilation, MessageFormat};
+use util::{self, machine_message, CargoTestError, Test, ProcessError};
 use util::errors::{CargoResult, CargoErrorKind, CargoError};
 use core::Workspace;
 
@@ -100,6 +100,10 @@ fn run_unit_tests(options: &TestOptions,
         config.shell().verbose(|shell| {
             shell.status("Running", cmd.to_string())
         })?;
+        if options.compile_opts.message_format == MessageFormat::Json {
+            machine_message::emit(machine_message::RunTarget::new(pkg.package_id(),
+                                                                  &cmd));
+        }
 
         let result = cmd.exec();
 
@@ -134,15 +138,17 @@ fn run_doc_tests(options: &TestOptions,
 
     let libs = compilation.to_doc_test.iter().map(|package| {
         (package, package.targets().iter().filter(|t| t.doctested())
-                         .map(|t| (t.src_path(), t.name(), t.crate_name())))
+                         .map(|t| (t.src_path(), t.name(), t.crate_name(),
+                                   t.edition())))
     });
 
     for (package, tests) in libs {
-        for (lib, name, crate_name) in tests {
+        for (lib, name, crate_name, edition) in tests {
             config.shell().status("Doc-tests", name)?;
             let mut p = compilation.rustdoc_process(package)?;
             p.arg("--test").arg(lib)
              .arg("--crate-name").arg(&crate_name);
+            ops::add_edition_args(&mut p, edition);
 
             for &rust_dep in &[&compilation.deps_output] {
                 let mut arg = OsString::from("dependency=");
@@ -153,6 +159,10 @@ fn run_doc_tests(options: &TestOptions,
                 p.arg("-L").arg(native_dep);
             }
 
+            if let Some(args) = compilation.lint_args.get(package.package_id()) {
+                p.args(args);
+            }
+
             for arg in test_args {
                 p.arg("--test-args").arg(arg);
             }
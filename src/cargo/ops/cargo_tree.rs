@@ -0,0 +1,641 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::{self, FromStr};
+
+use core::{Dependency, Package, PackageId, PackageIdSpec, PackageSet, Resolve, Workspace};
+use core::dependency::Kind as DepKind;
+use core::resolver::{RequestedFeatures, ResolveBehavior, ResolvedFeatures};
+use ops::{self, Packages};
+use ops::cargo_compile::requested_targets;
+use util::{Cfg, Config};
+use util::errors::{CargoResult, CargoResultExt};
+
+pub struct TreeOptions<'a> {
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+    pub packages: Packages<'a>,
+    /// The platform whose dependencies are displayed, `all` for every
+    /// platform, or the host by default.
+    pub target: Option<String>,
+    /// The kinds of edges to display, as given to `--edges`.
+    pub edges: Vec<String>,
+    /// Displays the dependents of the given package instead.
+    pub invert: Option<String>,
+    /// Displays only the packages present in several versions, inverted.
+    pub duplicates: bool,
+    pub depth: Option<usize>,
+    /// Packages to leave out of the tree, along with their dependencies.
+    pub prune: Vec<String>,
+    /// The format of each package line, see `Pattern`.
+    pub format: String,
+}
+
+/// Prints the resolved dependency graph of the workspace as a tree.
+pub fn tree(ws: &Workspace, opts: &TreeOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let kinds = EdgeKinds::parse(&opts.edges)?;
+    let pattern = Pattern::parse(&opts.format)?;
+    if opts.duplicates && opts.invert.is_some() {
+        bail!("the `--duplicates` flag cannot be used with `--invert`")
+    }
+
+    let mut specs = opts.packages.into_package_id_specs(ws)?;
+    if specs.is_empty() {
+        specs = match ws.current_opt() {
+            Some(pkg) => vec![PackageIdSpec::from_package_id(pkg.package_id())],
+            None => Packages::All.into_package_id_specs(ws)?,
+        };
+    }
+    let (packages, resolve) = ops::resolve_ws_precisely(ws,
+                                                        None,
+                                                        &opts.features,
+                                                        opts.all_features,
+                                                        opts.no_default_features,
+                                                        &specs)?;
+    let mut roots = Vec::new();
+    for spec in specs.iter() {
+        roots.push(spec.query(resolve.iter())?.clone());
+    }
+
+    let platforms = Platforms::new(config, opts.target.as_ref().map(|s| &s[..]))?;
+
+    // With the new feature resolver the features may differ between the host
+    // and the target, and both sets are displayed together.
+    let resolved_features = if ws.resolve_behavior() == ResolveBehavior::V2 {
+        let root_refs = roots.iter().collect::<Vec<_>>();
+        let requested = RequestedFeatures::new(&opts.features,
+                                               opts.all_features,
+                                               opts.no_default_features);
+        Some(ResolvedFeatures::resolve(&resolve, &packages, &root_refs, &requested,
+                                       kinds.deps.contains(&DepKindRank::Development),
+                                       &|dep, for_host| platforms.matches(dep, for_host))?)
+    } else {
+        None
+    };
+
+    let mut builder = Builder {
+        resolve: &resolve,
+        packages: &packages,
+        resolved_features: resolved_features.as_ref(),
+        platforms: &platforms,
+        kinds: &kinds,
+        members: ws.members().map(|p| p.package_id().clone()).collect(),
+        graph: Graph::default(),
+    };
+    for root in roots.iter() {
+        builder.add_package(root)?;
+    }
+    let graph = builder.graph;
+
+    let mut pruned = HashSet::new();
+    for spec in opts.prune.iter() {
+        let spec = PackageIdSpec::parse(spec).chain_err(|| {
+            format!("invalid package id specification for `--prune`: `{}`", spec)
+        })?;
+        let mut matched = false;
+        for id in resolve.iter().filter(|id| spec.matches(id)) {
+            pruned.insert(id.clone());
+            matched = true;
+        }
+        if !matched {
+            bail!("package ID specification `{}` for `--prune` did not match \
+                   any packages", spec)
+        }
+    }
+
+    let (roots, edges) = if opts.duplicates {
+        (graph.duplicates(), graph.reversed())
+    } else if let Some(ref invert) = opts.invert {
+        let id = resolve.query(invert)?;
+        match graph.index.get(&Node::Package(id.clone())) {
+            Some(&idx) => (vec![idx], graph.reversed()),
+            None => bail!("package `{}` is not part of the displayed dependency tree", id),
+        }
+    } else {
+        let roots = roots.iter().map(|id| graph.index[&Node::Package(id.clone())]);
+        (roots.collect(), graph.edges.clone())
+    };
+
+    let mut printer = Printer {
+        graph: &graph,
+        edges: &edges,
+        packages: &packages,
+        resolve: &resolve,
+        resolved_features: resolved_features.as_ref(),
+        pattern: &pattern,
+        depth: opts.depth,
+        pruned: &pruned,
+        visited: HashSet::new(),
+        levels: Vec::new(),
+    };
+    for (i, &root) in roots.iter().enumerate() {
+        if i > 0 {
+            println!("");
+        }
+        printer.print_node(root)?;
+    }
+    Ok(())
+}
+
+/// The kinds of dependencies, ordered as they're displayed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum DepKindRank {
+    Normal,
+    Build,
+    Development,
+}
+
+impl DepKindRank {
+    fn new(kind: DepKind) -> DepKindRank {
+        match kind {
+            DepKind::Normal => DepKindRank::Normal,
+            DepKind::Build => DepKindRank::Build,
+            DepKind::Development => DepKindRank::Development,
+        }
+    }
+}
+
+struct EdgeKinds {
+    deps: Vec<DepKindRank>,
+    features: bool,
+}
+
+impl EdgeKinds {
+    fn parse(edges: &[String]) -> CargoResult<EdgeKinds> {
+        let mut kinds = EdgeKinds { deps: Vec::new(), features: false };
+        let all = [DepKindRank::Normal, DepKindRank::Build, DepKindRank::Development];
+        for kind in edges.iter().flat_map(|s| s.split(',')).map(|s| s.trim()) {
+            match kind {
+                "" => {}
+                "normal" => kinds.deps.push(DepKindRank::Normal),
+                "build" => kinds.deps.push(DepKindRank::Build),
+                "dev" => kinds.deps.push(DepKindRank::Development),
+                "features" => kinds.features = true,
+                "all" => {
+                    kinds.deps.extend(all.iter().cloned());
+                    kinds.features = true;
+                }
+                _ => bail!("unknown edge kind `{}`, valid kinds are \
+                            `normal`, `build`, `dev`, `features` and `all`", kind),
+            }
+        }
+        if kinds.deps.is_empty() {
+            kinds.deps.extend(all.iter().cloned());
+        }
+        Ok(kinds)
+    }
+}
+
+/// The platforms dependencies are filtered with, or `None` to display the
+/// dependencies of every platform.
+struct Platforms {
+    targets: Vec<(String, Vec<Cfg>)>,
+    host: Option<(String, Vec<Cfg>)>,
+}
+
+impl Platforms {
+    fn new(config: &Config, target: Option<&str>) -> CargoResult<Platforms> {
+        let targets = match target {
+            Some(target) => vec![target.to_string()],
+            None => requested_targets(config, &[])?,
+        };
+        if targets.iter().any(|t| t == "all") {
+            return Ok(Platforms { targets: Vec::new(), host: None })
+        }
+        let host = config.rustc()?.host.clone();
+        let host_cfg = target_cfg(config, &host)?;
+        let mut platforms = Vec::new();
+        for target in targets {
+            let cfg = target_cfg(config, &target)?;
+            platforms.push((target, cfg));
+        }
+        if platforms.is_empty() {
+            platforms.push((host.clone(), host_cfg.clone()));
+        }
+        Ok(Platforms { targets: platforms, host: Some((host, host_cfg)) })
+    }
+
+    /// Returns whether `dep` is used on the platform of the host (for build
+    /// dependencies) or of one of the targets (for everything else).
+    fn matches(&self, dep: &Dependency, for_host: bool) -> bool {
+        let platform = match dep.platform() {
+            Some(p) => p,
+            None => return true,
+        };
+        if for_host || dep.kind() == DepKind::Build {
+            match self.host {
+                Some((ref name, ref cfg)) => platform.matches(name, Some(cfg)),
+                None => true,
+            }
+        } else {
+            self.targets.is_empty() || self.targets.iter().any(|&(ref name, ref cfg)| {
+                platform.matches(name, Some(cfg))
+            })
+        }
+    }
+}
+
+fn target_cfg(config: &Config, triple: &str) -> CargoResult<Vec<Cfg>> {
+    let mut process = config.rustc()?.process();
+    process.arg("-")
+           .arg("--crate-name").arg("___")
+           .arg("--print=cfg")
+           .arg("--target").arg(triple)
+           .env_remove("RUST_LOG");
+    let output = process.exec_with_output().chain_err(|| {
+        format!("failed to run `rustc` to learn about the `{}` target", triple)
+    })?;
+    let output = str::from_utf8(&output.stdout).unwrap();
+    output.lines().map(Cfg::from_str).collect()
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum Node {
+    Package(PackageId),
+    Feature(PackageId, String),
+}
+
+impl Node {
+    fn package_id(&self) -> &PackageId {
+        match *self {
+            Node::Package(ref id) | Node::Feature(ref id, _) => id,
+        }
+    }
+}
+
+/// How a node leads to another one: through a dependency, or by enabling a
+/// feature.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Edge {
+    Dep(DepKindRank),
+    Feature,
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    index: HashMap<Node, usize>,
+    edges: Vec<Vec<(Edge, usize)>>,
+}
+
+impl Graph {
+    fn add(&mut self, node: Node) -> usize {
+        let idx = self.nodes.len();
+        self.index.insert(node.clone(), idx);
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        idx
+    }
+
+    fn set_edges(&mut self, idx: usize, mut edges: Vec<(Edge, usize)>) {
+        {
+            let nodes = &self.nodes;
+            edges.sort_by(|a, b| (a.0, &nodes[a.1]).cmp(&(b.0, &nodes[b.1])));
+        }
+        edges.dedup();
+        self.edges[idx] = edges;
+    }
+
+    fn reversed(&self) -> Vec<Vec<(Edge, usize)>> {
+        let mut edges = vec![Vec::new(); self.nodes.len()];
+        for (from, list) in self.edges.iter().enumerate() {
+            for &(edge, to) in list {
+                edges[to].push((edge, from));
+            }
+        }
+        for list in edges.iter_mut() {
+            list.sort_by(|a, b| (a.0, &self.nodes[a.1]).cmp(&(b.0, &self.nodes[b.1])));
+            list.dedup();
+        }
+        edges
+    }
+
+    /// Returns the packages of which several versions are in the graph.
+    fn duplicates(&self) -> Vec<usize> {
+        let mut by_name = BTreeMap::new();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if let Node::Package(ref id) = *node {
+                by_name.entry(id.name()).or_insert(Vec::new()).push(idx);
+            }
+        }
+        let mut ret = Vec::new();
+        for (_, mut list) in by_name {
+            let versions = list.iter().map(|&idx| {
+                self.nodes[idx].package_id().version()
+            }).collect::<HashSet<_>>();
+            if versions.len() > 1 {
+                list.sort_by(|&a, &b| self.nodes[a].cmp(&self.nodes[b]));
+                ret.extend(list);
+            }
+        }
+        ret
+    }
+}
+
+struct Builder<'a, 'cfg: 'a> {
+    resolve: &'a Resolve,
+    packages: &'a PackageSet<'cfg>,
+    resolved_features: Option<&'a ResolvedFeatures>,
+    platforms: &'a Platforms,
+    kinds: &'a EdgeKinds,
+    members: HashSet<PackageId>,
+    graph: Graph,
+}
+
+impl<'a, 'cfg> Builder<'a, 'cfg> {
+    fn add_package(&mut self, id: &PackageId) -> CargoResult<usize> {
+        let node = Node::Package(id.clone());
+        if let Some(&idx) = self.graph.index.get(&node) {
+            return Ok(idx)
+        }
+        let idx = self.graph.add(node);
+        let mut edges = Vec::new();
+        for (dep, dep_id) in self.used_deps(id, None)? {
+            let edge = Edge::Dep(DepKindRank::new(dep.kind()));
+            let mut features = Vec::new();
+            if self.kinds.features {
+                let enabled = features_of(self.resolve, self.resolved_features, &dep_id);
+                let default = "default".to_string();
+                let requested = Some(&default).into_iter().filter(|_| {
+                    dep.uses_default_features()
+                }).chain(dep.features());
+                for feature in requested {
+                    if enabled.contains(feature) && !features.contains(feature) {
+                        features.push(feature.clone());
+                    }
+                }
+            }
+            if features.is_empty() {
+                edges.push((edge, self.add_package(&dep_id)?));
+            }
+            for feature in features {
+                edges.push((edge, self.add_feature(&dep_id, &feature)?));
+            }
+        }
+        self.graph.set_edges(idx, edges);
+        Ok(idx)
+    }
+
+    /// Adds the node of the feature `name` of a package, which leads to the
+    /// package and to what the feature enables.
+    fn add_feature(&mut self, id: &PackageId, name: &str) -> CargoResult<usize> {
+        let node = Node::Feature(id.clone(), name.to_string());
+        if let Some(&idx) = self.graph.index.get(&node) {
+            return Ok(idx)
+        }
+        let idx = self.graph.add(node);
+        let mut edges = vec![(Edge::Feature, self.add_package(id)?)];
+
+        let packages = self.packages;
+        let pkg = packages.get(id)?;
+        let fvs = match pkg.summary().features().get(name) {
+            Some(list) => list.clone(),
+            // The implicit feature of an optional dependency.
+            None => vec![format!("dep:{}", name)],
+        };
+        let enabled = features_of(self.resolve, self.resolved_features, id);
+        for fv in fvs {
+            if fv.starts_with("dep:") {
+                for (_, dep_id) in self.used_deps(id, Some(&fv[4..]))? {
+                    edges.push((Edge::Feature, self.add_package(&dep_id)?));
+                }
+            } else if let Some(slash) = fv.find('/') {
+                let dep_name = fv[..slash].trim_right_matches('?');
+                let dep_feature = &fv[slash + 1..];
+                for (_, dep_id) in self.used_deps(id, Some(dep_name))? {
+                    let dep_enabled = features_of(self.resolve,
+                                                  self.resolved_features,
+                                                  &dep_id);
+                    if dep_enabled.iter().any(|f| f == dep_feature) {
+                        edges.push((Edge::Feature, self.add_feature(&dep_id, dep_feature)?));
+                    }
+                }
+            } else if pkg.summary().features().contains_key(&fv) {
+                if enabled.contains(&fv) {
+                    edges.push((Edge::Feature, self.add_feature(id, &fv)?));
+                }
+            } else {
+                for (_, dep_id) in self.used_deps(id, Some(&fv))? {
+                    edges.push((Edge::Feature, self.add_package(&dep_id)?));
+                }
+            }
+        }
+        self.graph.set_edges(idx, edges);
+        Ok(idx)
+    }
+
+    /// Returns the dependencies of a package which are displayed, optionally
+    /// only the ones named `name`, along with the packages they resolved to.
+    fn used_deps(&self, id: &PackageId, name: Option<&str>)
+                 -> CargoResult<Vec<(Dependency, PackageId)>> {
+        let pkg = self.packages.get(id)?;
+        let mut ret = Vec::new();
+        for dep in pkg.dependencies() {
+            if name.map_or(false, |name| dep.name_in_toml() != name) {
+                continue
+            }
+            let kind = DepKindRank::new(dep.kind());
+            if !self.kinds.deps.contains(&kind) {
+                continue
+            }
+            // Dev-dependencies are only used by the workspace members.
+            if kind == DepKindRank::Development && !self.members.contains(id) {
+                continue
+            }
+            if dep.is_optional() && !self.optional_dep_activated(pkg, dep) {
+                continue
+            }
+            if !self.platforms.matches(dep, false) {
+                continue
+            }
+            for dep_id in self.resolve.deps_not_replaced(id) {
+                if self.resolve.dep_matches_id(dep, dep_id) {
+                    let dep_id = self.resolve.replacement(dep_id).unwrap_or(dep_id);
+                    ret.push((dep.clone(), dep_id.clone()));
+                }
+            }
+        }
+        Ok(ret)
+    }
+
+    fn optional_dep_activated(&self, pkg: &Package, dep: &Dependency) -> bool {
+        let id = pkg.package_id();
+        match self.resolved_features {
+            Some(features) => {
+                features.optional_dep_activated(id, false, dep.name_in_toml()) ||
+                    features.optional_dep_activated(id, true, dep.name_in_toml())
+            }
+            None => self.resolve.optional_dep_activated(id, dep.name_in_toml()),
+        }
+    }
+}
+
+/// Returns the features enabled for a package, for the host and the target.
+fn features_of(resolve: &Resolve,
+               resolved_features: Option<&ResolvedFeatures>,
+               id: &PackageId) -> Vec<String> {
+    let mut features = match resolved_features {
+        Some(features) => {
+            let mut list = features.features(id, false);
+            list.extend(features.features(id, true));
+            list
+        }
+        None => resolve.features_sorted(id),
+    }.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    features.sort();
+    features.dedup();
+    features
+}
+
+/// The format of the line printed for each package, in which `{p}` is
+/// replaced by the package id, `{l}` by its license, `{r}` by its repository
+/// and `{f}` by its enabled features.
+struct Pattern(Vec<Chunk>);
+
+enum Chunk {
+    Raw(String),
+    Package,
+    License,
+    Repository,
+    Features,
+}
+
+impl Pattern {
+    fn parse(format: &str) -> CargoResult<Pattern> {
+        let mut chunks = Vec::new();
+        let mut rest = format;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                chunks.push(Chunk::Raw(rest[..start].to_string()));
+            }
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => bail!("unterminated `{{` in format `{}`", format),
+            };
+            chunks.push(match &rest[start + 1..end] {
+                "p" => Chunk::Package,
+                "l" => Chunk::License,
+                "r" => Chunk::Repository,
+                "f" => Chunk::Features,
+                s => bail!("unsupported pattern `{{{}}}` in format `{}`", s, format),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            chunks.push(Chunk::Raw(rest.to_string()));
+        }
+        Ok(Pattern(chunks))
+    }
+}
+
+struct Printer<'a, 'cfg: 'a> {
+    graph: &'a Graph,
+    edges: &'a [Vec<(Edge, usize)>],
+    packages: &'a PackageSet<'cfg>,
+    resolve: &'a Resolve,
+    resolved_features: Option<&'a ResolvedFeatures>,
+    pattern: &'a Pattern,
+    depth: Option<usize>,
+    pruned: &'a HashSet<PackageId>,
+    visited: HashSet<usize>,
+    /// For each level of the node being printed, whether more siblings
+    /// follow it on that level.
+    levels: Vec<bool>,
+}
+
+impl<'a, 'cfg> Printer<'a, 'cfg> {
+    fn print_node(&mut self, idx: usize) -> CargoResult<()> {
+        let children = self.edges[idx].iter().filter(|&&(_, child)| {
+            !self.pruned.contains(self.graph.nodes[child].package_id())
+        }).cloned().collect::<Vec<_>>();
+
+        // Subtrees are only printed the first time a node is reached, which
+        // also keeps cycles through dev-dependencies from looping.
+        let seen = !self.visited.insert(idx);
+        let line = self.format(&self.graph.nodes[idx])?;
+        let mark = if seen && !children.is_empty() { " (*)" } else { "" };
+        println!("{}{}{}", self.prefix(), line, mark);
+        if seen || self.depth.map_or(false, |d| self.levels.len() >= d) {
+            return Ok(())
+        }
+
+        for &group in [DepKindRank::Normal, DepKindRank::Build, DepKindRank::Development].iter() {
+            let group_children = children.iter().filter(|&&(edge, _)| {
+                match edge {
+                    Edge::Dep(kind) => kind == group,
+                    Edge::Feature => group == DepKindRank::Normal,
+                }
+            }).map(|&(_, child)| child).collect::<Vec<_>>();
+            if group_children.is_empty() {
+                continue
+            }
+            match group {
+                DepKindRank::Normal => {}
+                DepKindRank::Build => println!("{}[build-dependencies]", self.indent()),
+                DepKindRank::Development => println!("{}[dev-dependencies]", self.indent()),
+            }
+            for (i, &child) in group_children.iter().enumerate() {
+                self.levels.push(i + 1 < group_children.len());
+                let res = self.print_node(child);
+                self.levels.pop();
+                res?;
+            }
+        }
+        Ok(())
+    }
+
+    fn format(&self, node: &Node) -> CargoResult<String> {
+        let (id, feature) = match *node {
+            Node::Package(ref id) => (id, None),
+            Node::Feature(ref id, ref feature) => (id, Some(feature)),
+        };
+        if let Some(feature) = feature {
+            return Ok(format!("{} feature \"{}\"", id.name(), feature))
+        }
+        let pkg = self.packages.get(id)?;
+        let metadata = pkg.manifest().metadata();
+        let mut line = String::new();
+        for chunk in self.pattern.0.iter() {
+            match *chunk {
+                Chunk::Raw(ref s) => line.push_str(s),
+                Chunk::Package => {
+                    line.push_str(&format!("{} v{}", id.name(), id.version()));
+                    let source = id.source_id();
+                    if source.is_path() {
+                        if let Ok(path) = source.url().to_file_path() {
+                            line.push_str(&format!(" ({})", path.display()));
+                        }
+                    } else if !source.is_default_registry() {
+                        line.push_str(&format!(" ({})", source));
+                    }
+                }
+                Chunk::License => {
+                    line.push_str(metadata.license.as_ref().map(|s| &s[..]).unwrap_or(""));
+                }
+                Chunk::Repository => {
+                    line.push_str(metadata.repository.as_ref().map(|s| &s[..]).unwrap_or(""));
+                }
+                Chunk::Features => {
+                    let features = features_of(self.resolve, self.resolved_features, id);
+                    line.push_str(&features.join(","));
+                }
+            }
+        }
+        Ok(line)
+    }
+
+    fn prefix(&self) -> String {
+        let mut prefix = String::new();
+        if let Some((&last, rest)) = self.levels.split_last() {
+            for &more in rest {
+                prefix.push_str(if more { "│   " } else { "    " });
+            }
+            prefix.push_str(if last { "├── " } else { "└── " });
+        }
+        prefix
+    }
+
+    fn indent(&self) -> String {
+        self.levels.iter().map(|&more| if more { "│   " } else { "    " }).collect()
+    }
+}
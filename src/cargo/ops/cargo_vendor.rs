@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use hex::ToHex;
+
+use core::{GitReference, Package, SourceId, Workspace};
+use core::registry::PackageRegistry;
+use ops;
+use sources::PathSource;
+use sources::config::SourceConfigMap;
+use util::{self, Config, Sha256};
+use util::errors::{CargoResult, CargoResultExt};
+use util::paths;
+
+/// Name of the directory source that the vendored sources are replaced with
+/// in the printed configuration.
+const VENDORED_SOURCES: &'static str = "vendored-sources";
+
+pub struct VendorOptions<'a> {
+    /// Directory that the packages are vendored into.
+    pub destination: &'a Path,
+    /// Manifests of additional workspaces to vendor alongside the current one.
+    pub extra: Vec<PathBuf>,
+    /// Whether to always include the version in the directory names.
+    pub versioned_dirs: bool,
+    /// Whether to keep vendored packages which are no longer used.
+    pub no_delete: bool,
+    /// Whether to apply the `[source]` replacements of the configuration
+    /// when resolving the packages to vendor.
+    pub respect_source_config: bool,
+}
+
+/// Executes `cargo vendor`, copying all registry and git dependencies of the
+/// workspaces into `opts.destination` and printing the configuration needed
+/// to use it as a directory source.
+pub fn vendor(ws: &Workspace, opts: &VendorOptions) -> CargoResult<()> {
+    let config = ws.config();
+    let mut extra_workspaces = Vec::new();
+    for extra in opts.extra.iter() {
+        let manifest = config.cwd().join(extra);
+        extra_workspaces.push(Workspace::new(&manifest, config)?);
+    }
+    let workspaces = extra_workspaces.iter().chain(Some(ws)).collect::<Vec<_>>();
+    let sources = sync(config, &workspaces, opts).chain_err(|| {
+        "failed to sync"
+    })?;
+
+    if sources.is_empty() {
+        config.shell().status("Vendoring",
+                              "there are no dependencies to vendor")?;
+        return Ok(())
+    }
+
+    config.shell().status("Vendored",
+                          "add the following to `.cargo/config` to use the \
+                           vendored sources:")?;
+    print!("{}", source_config(&sources, opts.destination));
+    Ok(())
+}
+
+fn sync(config: &Config,
+        workspaces: &[&Workspace],
+        opts: &VendorOptions) -> CargoResult<BTreeSet<SourceId>> {
+    // Vendored directories which are already present. Unless they're reused
+    // below they're deleted at the end, as the packages aren't used anymore.
+    let mut to_remove = HashSet::new();
+    if !opts.no_delete && opts.destination.is_dir() {
+        for entry in fs::read_dir(opts.destination)? {
+            let path = entry?.path();
+            if path.join(".cargo-checksum.json").exists() {
+                to_remove.insert(path);
+            }
+        }
+    }
+
+    let mut packages = BTreeMap::new();
+    for ws in workspaces {
+        // The sources are resolved without any replacement by default, as
+        // the vendored sources may already replace them.
+        let source_config = if opts.respect_source_config {
+            SourceConfigMap::new(ws.config())?
+        } else {
+            SourceConfigMap::empty(ws.config())?
+        };
+        let registry = PackageRegistry::new_with_source_config(source_config)?;
+        let (set, resolve) = ops::resolve_ws_with_registry(ws, registry).chain_err(|| {
+            format!("failed to resolve dependencies of `{}`",
+                    ws.root().display())
+        })?;
+        let ids = resolve.iter().filter(|id| {
+            !id.source_id().is_path()
+        }).collect::<Vec<_>>();
+        for pkg in set.get_many(&ids)? {
+            packages.insert(pkg.package_id().clone(), pkg.clone());
+        }
+    }
+
+    // A directory source can only hold one copy of each version of a
+    // package, so the same version coming from two sources can't be vendored.
+    let mut versions = HashMap::new();
+    for id in packages.keys() {
+        let map = versions.entry(id.name()).or_insert_with(BTreeMap::new);
+        if let Some(prev) = map.get(id.version()) {
+            bail!("found duplicate version of package `{} v{}` vendored from \
+                   two sources:\n\n\tsource 1: {}\n\tsource 2: {}",
+                  id.name(), id.version(), prev, id.source_id());
+        }
+        map.insert(id.version(), id.source_id());
+    }
+
+    let mut sources = BTreeSet::new();
+    for (id, pkg) in packages.iter() {
+        sources.insert(id.source_id().clone());
+
+        // The newest version of each package gets the plain name so paths
+        // stay stable across updates, older ones are suffixed with their
+        // version.
+        let max_version = *versions[id.name()].keys().next_back().unwrap();
+        let versioned = opts.versioned_dirs || id.version() != max_version;
+        let dst_name = if versioned {
+            format!("{}-{}", id.name(), id.version())
+        } else {
+            id.name().to_string()
+        };
+        let dst = opts.destination.join(&dst_name);
+        to_remove.remove(&dst);
+
+        // Versioned directories always hold the same contents, so they don't
+        // need to be copied again.
+        let cksum = dst.join(".cargo-checksum.json");
+        if versioned && cksum.exists() {
+            continue
+        }
+
+        config.shell().status("Vendoring",
+                              format!("{} to {}", id, dst.display()))?;
+
+        // The sources are copied next to the destination first, so that a
+        // failure doesn't leave a partially vendored package behind.
+        let tmp = opts.destination.join(format!(".{}.tmp", dst_name));
+        if tmp.exists() {
+            fs::remove_dir_all(&tmp).chain_err(|| {
+                format!("failed to remove `{}`", tmp.display())
+            })?;
+        }
+        let files = cp_sources(config, pkg, &tmp).chain_err(|| {
+            format!("failed to copy over vendored sources for: {}", id)
+        })?;
+        let json = json!({
+            "package": pkg.summary().checksum(),
+            "files": files,
+        });
+        paths::write(&tmp.join(".cargo-checksum.json"),
+                     json.to_string().as_bytes())?;
+        if dst.exists() {
+            fs::remove_dir_all(&dst).chain_err(|| {
+                format!("failed to remove `{}`", dst.display())
+            })?;
+        }
+        fs::rename(&tmp, &dst).chain_err(|| {
+            format!("failed to move vendored sources to `{}`", dst.display())
+        })?;
+    }
+
+    for path in to_remove {
+        fs::remove_dir_all(&path).chain_err(|| {
+            format!("failed to remove `{}`", path.display())
+        })?;
+    }
+
+    Ok(sources)
+}
+
+/// Copies the files of `pkg` which would be packaged into `dst`, returning
+/// the checksum of each copied file keyed by its relative path.
+fn cp_sources(config: &Config,
+              pkg: &Package,
+              dst: &Path) -> CargoResult<BTreeMap<String, String>> {
+    let src = pkg.root();
+    let source = PathSource::new(src, pkg.package_id().source_id(), config);
+    let mut cksums = BTreeMap::new();
+    for path in source.list_files(pkg)? {
+        let relative = util::without_prefix(&path, src).unwrap();
+
+        // Files managed by Cargo itself, and VCS files which could make a
+        // checked in vendor directory ignore some of its own contents.
+        let skip = relative.iter().any(|c| {
+            c == ".git" || c == ".gitignore" || c == ".gitattributes"
+        }) || relative == Path::new(".cargo-ok") ||
+             relative == Path::new(".cargo-checksum.json");
+        if skip {
+            continue
+        }
+
+        let dst = dst.join(relative);
+        fs::create_dir_all(dst.parent().unwrap())?;
+        let contents = paths::read_bytes(&path)?;
+        paths::write(&dst, &contents)?;
+
+        let mut h = Sha256::new();
+        h.update(&contents);
+        let key = relative.to_str().ok_or_else(|| {
+            format!("path `{}` is not valid UTF-8", relative.display())
+        })?.replace("\\", "/");
+        cksums.insert(key, h.finish().to_hex());
+    }
+    Ok(cksums)
+}
+
+/// Renders the `[source]` tables replacing each vendored source with the
+/// vendor directory.
+fn source_config(sources: &BTreeSet<SourceId>, destination: &Path) -> String {
+    let mut out = String::new();
+    for id in sources {
+        if id.is_default_registry() {
+            out.push_str("[source.crates-io]\n");
+        } else {
+            out.push_str(&format!("[source.\"{}\"]\n", id.url()));
+            if id.is_git() {
+                out.push_str(&format!("git = \"{}\"\n", id.url()));
+                match id.git_reference() {
+                    Some(&GitReference::Branch(ref b)) if b == "master" => {}
+                    Some(&GitReference::Branch(ref b)) => {
+                        out.push_str(&format!("branch = \"{}\"\n", b));
+                    }
+                    Some(&GitReference::Tag(ref t)) => {
+                        out.push_str(&format!("tag = \"{}\"\n", t));
+                    }
+                    Some(&GitReference::Rev(ref r)) => {
+                        out.push_str(&format!("rev = \"{}\"\n", r));
+                    }
+                    None => {}
+                }
+            } else {
+                out.push_str(&format!("registry = \"{}\"\n", id.url()));
+            }
+        }
+        out.push_str(&format!("replace-with = \"{}\"\n\n", VENDORED_SOURCES));
+    }
+    out.push_str(&format!("[source.{}]\ndirectory = \"{}\"\n",
+                          VENDORED_SOURCES,
+                          destination.display().to_string().replace("\\", "/")));
+    out
+}
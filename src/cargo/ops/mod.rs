@@ -1,9 +1,12 @@
+pub use self::cargo_add::{add, remove, AddOptions, RemoveOptions};
 pub use self::cargo_clean::{clean, CleanOptions};
+pub use self::cargo_gc::{gc, auto_gc, parse_age, parse_size, GcOptions};
+pub use self::cargo_config::{get_config, ConfigFormat, GetConfigOptions};
 pub use self::cargo_compile::{compile, compile_with_exec, compile_ws, CompileOptions};
 pub use self::cargo_compile::{CompileFilter, CompileMode, MessageFormat, Packages};
 pub use self::cargo_read_manifest::{read_package, read_packages};
 pub use self::cargo_rustc::{compile_targets, Compilation, Kind, Unit};
-pub use self::cargo_rustc::{Context, is_bad_artifact_name};
+pub use self::cargo_rustc::{Context, is_bad_artifact_name, add_edition_args};
 pub use self::cargo_rustc::{BuildOutput, BuildConfig, TargetConfig};
 pub use self::cargo_rustc::{Executor, DefaultExecutor};
 pub use self::cargo_run::run;
@@ -18,16 +21,23 @@ pub use self::cargo_test::{run_tests, run_benches, TestOptions};
 pub use self::cargo_package::{package, PackageOpts};
 pub use self::registry::{publish, registry_configuration, RegistryConfig};
 pub use self::registry::{registry_login, search, http_proxy_exists, http_handle};
+pub use self::registry::{http_multiplexing, http_max_concurrency};
 pub use self::registry::{modify_owners, yank, OwnersOptions, PublishOpts};
 pub use self::cargo_fetch::fetch;
+pub use self::cargo_tree::{tree, TreeOptions};
+pub use self::cargo_vendor::{vendor, VendorOptions};
 pub use self::cargo_pkgid::pkgid;
 pub use self::resolve::{resolve_ws, resolve_ws_precisely, resolve_with_previous};
+pub use self::resolve::resolve_ws_with_registry;
 pub use self::cargo_output_metadata::{output_metadata, OutputMetadataOptions, ExportInfo};
 
+mod cargo_add;
 mod cargo_clean;
+mod cargo_config;
 mod cargo_compile;
 mod cargo_doc;
 mod cargo_fetch;
+mod cargo_gc;
 mod cargo_generate_lockfile;
 mod cargo_install;
 mod cargo_new;
@@ -38,6 +48,8 @@ mod cargo_read_manifest;
 mod cargo_run;
 mod cargo_rustc;
 mod cargo_test;
+mod cargo_tree;
+mod cargo_vendor;
 mod lockfile;
 mod registry;
 mod resolve;
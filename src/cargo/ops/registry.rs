@@ -35,20 +35,25 @@ pub struct PublishOpts<'cfg> {
     pub allow_dirty: bool,
     pub jobs: Option<u32>,
     pub dry_run: bool,
+    pub registry: Option<String>,
 }
 
 pub fn publish(ws: &Workspace, opts: &PublishOpts) -> CargoResult<()> {
     let pkg = ws.current()?;
 
-    if !pkg.publish() {
-        bail!("some crates cannot be published.\n\
-               `{}` is marked as unpublishable", pkg.name());
+    if let Some(ref allowed_registries) = *pkg.publish() {
+        let registry = opts.registry.as_ref().map(|s| &s[..]).unwrap_or("crates-io");
+        if !allowed_registries.iter().any(|r| r == registry) {
+            bail!("some crates cannot be published.\n\
+                   `{}` is marked as unpublishable", pkg.name());
+        }
     }
 
     let (mut registry, reg_id) = registry(opts.config,
-                                               opts.token.clone(),
-                                               opts.index.clone())?;
-    verify_dependencies(pkg, &reg_id)?;
+                                          opts.token.clone(),
+                                          opts.index.clone(),
+                                          opts.registry.clone())?;
+    verify_dependencies(pkg, &reg_id, opts.registry.is_some())?;
 
     // Prepare a tarball, with a non-surpressable warning if metadata
     // is missing since this is being put online.
@@ -63,20 +68,35 @@ pub fn publish(ws: &Workspace, opts: &PublishOpts) -> CargoResult<()> {
 
     // Upload said tarball to the specified destination
     opts.config.shell().status("Uploading", pkg.package_id().to_string())?;
-    transmit(opts.config, pkg, tarball.file(), &mut registry, opts.dry_run)?;
+    transmit(opts.config, pkg, tarball.file(), &mut registry, &reg_id,
+             opts.dry_run)?;
 
     Ok(())
 }
 
-fn verify_dependencies(pkg: &Package, registry_src: &SourceId)
-                       -> CargoResult<()> {
+fn verify_dependencies(pkg: &Package,
+                       registry_src: &SourceId,
+                       alt_registry: bool) -> CargoResult<()> {
     for dep in pkg.dependencies().iter() {
+        if dep.artifact().is_some() {
+            bail!("artifact dependencies cannot be published yet\n\
+                   dependency `{}` is an artifact dependency", dep.name_in_toml())
+        }
         if dep.source_id().is_path() {
             if !dep.specified_req() {
                 bail!("all path dependencies must have a version specified \
                        when publishing.\ndependency `{}` does not specify \
                        a version", dep.name())
             }
+        } else if dep.source_id() != registry_src && dep.source_id().is_registry() {
+            // Only alternative registries may depend on crates from other
+            // registries; crates.io must be self-contained.
+            if !alt_registry {
+                bail!("crates cannot be published to crates.io with dependencies sourced from \
+                       other registries\neither publish `{}` on crates.io or pull it into \
+                       this repository and specify it with a path and version\n(crate `{}` \
+                       is pulled from `{}`)", dep.name(), dep.name(), dep.source_id());
+            }
         } else if dep.source_id() != registry_src {
             bail!("crates cannot be published to crates.io with dependencies sourced from \
                    a repository\neither publish `{}` as its own crate on crates.io and \
@@ -92,8 +112,17 @@ fn transmit(config: &Config,
             pkg: &Package,
             tarball: &File,
             registry: &mut Registry,
+            registry_id: &SourceId,
             dry_run: bool) -> CargoResult<()> {
     let deps = pkg.dependencies().iter().map(|dep| {
+        // Dependencies from another registry name it, everything else is
+        // looked up in the registry the crate is published to.
+        let dep_registry = if dep.source_id().is_registry() &&
+                              dep.source_id() != registry_id {
+            Some(dep.source_id().url().to_string())
+        } else {
+            None
+        };
         NewCrateDependency {
             optional: dep.is_optional(),
             default_features: dep.uses_default_features(),
@@ -106,6 +135,8 @@ fn transmit(config: &Config,
                 Kind::Build => "build",
                 Kind::Development => "dev",
             }.to_string(),
+            explicit_name_in_toml: dep.explicit_name_in_toml().map(|s| s.to_string()),
+            registry: dep_registry,
         }
     }).collect::<Vec<NewCrateDependency>>();
     let manifest = pkg.manifest();
@@ -146,6 +177,7 @@ fn transmit(config: &Config,
         license: license.clone(),
         license_file: license_file.clone(),
         badges: badges.clone(),
+        rust_version: pkg.summary().rust_version().map(|v| v.to_string()),
     }, tarball);
 
     match publish {
@@ -176,24 +208,42 @@ fn transmit(config: &Config,
     }
 }
 
-pub fn registry_configuration(config: &Config) -> CargoResult<RegistryConfig> {
-    let index = config.get_string("registry.index")?.map(|p| p.val);
-    let token = config.get_string("registry.token")?.map(|p| p.val);
+/// Returns the configured index and token of `registry`, or of the default
+/// registry if `None`.
+pub fn registry_configuration(config: &Config,
+                              registry: Option<String>) -> CargoResult<RegistryConfig> {
+    let (index, token) = match registry {
+        Some(registry) => {
+            let index = config.get_registry_index(&registry)?.to_string();
+            let key = format!("registries.{}.token", registry);
+            (Some(index), config.get_string(&key)?.map(|p| p.val))
+        }
+        None => {
+            (config.get_string("registry.index")?.map(|p| p.val),
+             config.get_string("registry.token")?.map(|p| p.val))
+        }
+    };
     Ok(RegistryConfig { index: index, token: token })
 }
 
 pub fn registry(config: &Config,
                 token: Option<String>,
-                index: Option<String>) -> CargoResult<(Registry, SourceId)> {
+                index: Option<String>,
+                registry: Option<String>) -> CargoResult<(Registry, SourceId)> {
+    if index.is_some() && registry.is_some() {
+        bail!("both `--index` and `--registry` should not be set at the same time")
+    }
+
     // Parse all configuration options
     let RegistryConfig {
         token: token_config,
         index: _index_config,
-    } = registry_configuration(config)?;
+    } = registry_configuration(config, registry.clone())?;
     let token = token.or(token_config);
-    let sid = match index {
-        Some(index) => SourceId::for_registry(&index.to_url()?),
-        None => SourceId::crates_io(config)?,
+    let sid = match (index, registry) {
+        (Some(index), _) => SourceId::for_registry(&index.to_url()?),
+        (None, Some(registry)) => SourceId::alt_registry(config, &registry)?,
+        (None, None) => SourceId::crates_io(config)?,
     };
     let api_host = {
         let mut src = RegistrySource::remote(&sid, config);
@@ -280,15 +330,39 @@ pub fn http_timeout(config: &Config) -> CargoResult<Option<i64>> {
     Ok(env::var("HTTP_TIMEOUT").ok().and_then(|s| s.parse().ok()))
 }
 
-pub fn registry_login(config: &Config, token: String) -> CargoResult<()> {
-    let RegistryConfig { index: _, token: old_token } = registry_configuration(config)?;
+/// Whether crates are downloaded over multiplexed HTTP/2 connections.
+pub fn http_multiplexing(config: &Config) -> CargoResult<bool> {
+    Ok(config.get_bool("http.multiplexing")?.map(|v| v.val).unwrap_or(true))
+}
+
+/// The maximum number of crates downloaded at the same time.
+pub fn http_max_concurrency(config: &Config) -> CargoResult<usize> {
+    match config.get_i64("http.max-concurrency")? {
+        Some(v) => {
+            if v.val < 1 {
+                bail!("http.max-concurrency must be at least 1, but found {} \
+                       in {}", v.val, v.definition)
+            }
+            Ok(v.val as usize)
+        }
+        None => Ok(16),
+    }
+}
+
+pub fn registry_login(config: &Config,
+                      token: String,
+                      registry: Option<String>) -> CargoResult<()> {
+    let RegistryConfig {
+        index: _,
+        token: old_token,
+    } = registry_configuration(config, registry.clone())?;
     if let Some(old_token) = old_token {
         if old_token == token {
             return Ok(());
         }
     }
 
-    config::save_credentials(config, token)
+    config::save_credentials(config, token, registry)
 }
 
 pub struct OwnersOptions {
@@ -298,6 +372,7 @@ pub struct OwnersOptions {
     pub to_add: Option<Vec<String>>,
     pub to_remove: Option<Vec<String>>,
     pub list: bool,
+    pub registry: Option<String>,
 }
 
 pub fn modify_owners(config: &Config, opts: &OwnersOptions) -> CargoResult<()> {
@@ -311,7 +386,7 @@ pub fn modify_owners(config: &Config, opts: &OwnersOptions) -> CargoResult<()> {
     };
 
     let (mut registry, _) = registry(config, opts.token.clone(),
-                                          opts.index.clone())?;
+                                     opts.index.clone(), opts.registry.clone())?;
 
     if let Some(ref v) = opts.to_add {
         let v = v.iter().map(|s| &s[..]).collect::<Vec<_>>();
@@ -354,7 +429,8 @@ pub fn yank(config: &Config,
             version: Option<String>,
             token: Option<String>,
             index: Option<String>,
-            undo: bool) -> CargoResult<()> {
+            undo: bool,
+            reg: Option<String>) -> CargoResult<()> {
     let name = match krate {
         Some(name) => name,
         None => {
@@ -368,7 +444,7 @@ pub fn yank(config: &Config,
         None => bail!("a version must be specified to yank")
     };
 
-    let (mut registry, _) = registry(config, token, index)?;
+    let (mut registry, _) = registry(config, token, index, reg)?;
 
     if undo {
         config.shell().status("Unyank", format!("{}:{}", name, version))?;
@@ -388,7 +464,8 @@ pub fn yank(config: &Config,
 pub fn search(query: &str,
               config: &Config,
               index: Option<String>,
-              limit: u8) -> CargoResult<()> {
+              limit: u8,
+              reg: Option<String>) -> CargoResult<()> {
     fn truncate_with_ellipsis(s: &str, max_length: usize) -> String {
         if s.len() < max_length {
             s.to_string()
@@ -397,7 +474,7 @@ pub fn search(query: &str,
         }
     }
 
-    let (mut registry, _) = registry(config, None, index)?;
+    let (mut registry, _) = registry(config, None, index, reg)?;
     let (crates, total_crates) = registry.search(query, limit).map_err(|e| {
         CargoError::from(format!("failed to retrieve search results from the registry: {}", e))
     })?;
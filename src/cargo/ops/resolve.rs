@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use semver::Version;
+
 use core::{PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
 use core::registry::PackageRegistry;
 use core::resolver::{self, Resolve, Method};
@@ -14,7 +16,15 @@ use util::errors::{CargoResult, CargoResultExt};
 /// This function will also write the result of resolution as a new
 /// lockfile.
 pub fn resolve_ws<'a>(ws: &Workspace<'a>) -> CargoResult<(PackageSet<'a>, Resolve)> {
-    let mut registry = PackageRegistry::new(ws.config())?;
+    let registry = PackageRegistry::new(ws.config())?;
+    resolve_ws_with_registry(ws, registry)
+}
+
+/// Resolves dependencies of the workspace like `resolve_ws`, loading the
+/// sources through `registry`.
+pub fn resolve_ws_with_registry<'a>(ws: &Workspace<'a>,
+                                    mut registry: PackageRegistry<'a>)
+                                    -> CargoResult<(PackageSet<'a>, Resolve)> {
     let resolve = resolve_with_registry(ws, &mut registry)?;
     let packages = get_resolved_packages(&resolve, registry);
     Ok((packages, resolve))
@@ -61,6 +71,15 @@ pub fn resolve_ws_precisely<'a>(ws: &Workspace<'a>,
             }
         }
 
+        for id in resolve.unused_patches() {
+            ws.config().shell().warn(
+                format!("patch `{}` was not used in the crate graph\n\
+                         check that the patched package version and available \
+                         features are compatible with the dependency \
+                         requirements", id)
+            )?
+        }
+
         Some(resolve)
     } else {
         None
@@ -79,7 +98,7 @@ pub fn resolve_ws_precisely<'a>(ws: &Workspace<'a>,
     let resolved_with_overrides =
     ops::resolve_with_previous(&mut registry, ws,
                                method, resolve.as_ref(), None,
-                               specs)?;
+                               specs, None)?;
 
     let packages = get_resolved_packages(&resolved_with_overrides, registry);
 
@@ -91,7 +110,7 @@ fn resolve_with_registry(ws: &Workspace, registry: &mut PackageRegistry)
     let prev = ops::load_pkg_lockfile(ws)?;
     let resolve = resolve_with_previous(registry, ws,
                                         Method::Everything,
-                                        prev.as_ref(), None, &[])?;
+                                        prev.as_ref(), None, &[], None)?;
 
     if !ws.is_ephemeral() {
         ops::write_pkg_lockfile(ws, &resolve)?;
@@ -107,6 +126,9 @@ fn resolve_with_registry(ws: &Workspace, registry: &mut PackageRegistry)
 /// ids that should be avoided when consulting the previous instance of resolve
 /// (often used in pairings with updates).
 ///
+/// If `rust_version` is given, dependency versions compatible with that
+/// version of rustc are preferred when picking new versions.
+///
 /// The previous resolve normally comes from a lockfile. This function does not
 /// read or write lockfiles from the filesystem.
 pub fn resolve_with_previous<'a>(registry: &mut PackageRegistry,
@@ -114,7 +136,8 @@ pub fn resolve_with_previous<'a>(registry: &mut PackageRegistry,
                                  method: Method,
                                  previous: Option<&'a Resolve>,
                                  to_avoid: Option<&HashSet<&'a PackageId>>,
-                                 specs: &[PackageIdSpec])
+                                 specs: &[PackageIdSpec],
+                                 rust_version: Option<&Version>)
                                  -> CargoResult<Resolve> {
     // Here we place an artificial limitation that all non-registry sources
     // cannot be locked at more than one revision. This means that if a git
@@ -161,6 +184,34 @@ pub fn resolve_with_previous<'a>(registry: &mut PackageRegistry,
         }
     }
 
+    // Register all `[patch]` entries of the workspace root with the registry.
+    // If a previous resolve is available then each patch is locked to the
+    // version it was previously resolved to (whether or not it was actually
+    // used) so that patches don't needlessly drift between runs.
+    for (url, patches) in ws.root_patch() {
+        let previous = match previous {
+            Some(r) => r,
+            None => {
+                registry.patch(url, patches)?;
+                continue
+            }
+        };
+        let patches = patches.iter().map(|dep| {
+            let unused = previous.unused_patches();
+            let candidates = previous.iter().chain(unused);
+            match candidates.filter(|p| keep(p, to_avoid, &to_avoid_sources))
+                            .find(|id| dep.matches_id(id)) {
+                Some(id) => {
+                    let mut dep = dep.clone();
+                    dep.lock_to(id);
+                    dep
+                }
+                None => dep.clone(),
+            }
+        }).collect::<Vec<_>>();
+        registry.patch(url, &patches)?;
+    }
+
     let mut summaries = Vec::new();
     for member in ws.members() {
         registry.add_sources(&[member.package_id().source_id().clone()])?;
@@ -228,7 +279,9 @@ pub fn resolve_with_previous<'a>(registry: &mut PackageRegistry,
         None => root_replace.to_vec(),
     };
 
-    let mut resolved = resolver::resolve(&summaries, &replace, registry)?;
+    let mut resolved = resolver::resolve(&summaries, &replace, registry,
+                                         rust_version)?;
+    resolved.register_used_patches(registry.patches());
     if let Some(previous) = previous {
         resolved.merge_from(previous)?;
     }
@@ -9,7 +9,7 @@ use std::path::{Path, PathBuf};
 
 use url::Url;
 
-use core::{Source, SourceId};
+use core::{GitReference, Source, SourceId};
 use sources::ReplacedSource;
 use util::{Config, ToUrl};
 use util::config::ConfigValue;
@@ -27,6 +27,11 @@ pub struct SourceConfigMap<'cfg> {
 /// [source.crates-io]
 /// registry = 'https://github.com/rust-lang/crates.io-index'
 /// replace-with = 'foo'    # optional
+///
+/// [source.my-git-repo]
+/// git = 'https://github.com/example/repo'
+/// branch = 'next'         # optional, or `tag` or `rev`
+/// replace-with = 'foo'
 /// ```
 struct SourceConfig {
     // id this source corresponds to, inferred from the various defined keys in
@@ -105,18 +110,16 @@ impl<'cfg> SourceConfigMap<'cfg> {
         }
         let new_src = new_id.load(self.config);
         let old_src = id.load(self.config);
-        if new_src.supports_checksums() != old_src.supports_checksums() {
-            let (supports, no_support) = if new_src.supports_checksums() {
-                (name, orig_name)
-            } else {
-                (orig_name, name)
-            };
+        // A source with checksums may stand in for one without them (e.g. a
+        // vendored copy of a git dependency, which then simply lists no
+        // checksum), but not the other way around.
+        if old_src.supports_checksums() && !new_src.supports_checksums() {
             bail!("\
-cannot replace `{orig}` with `{name}`, the source `{supports}` supports \
-checksums, but `{no_support}` does not
+cannot replace `{orig}` with `{name}`, the source `{orig}` supports \
+checksums, but `{name}` does not
 
 a lock file compatible with `{orig}` cannot be generated in this situation
-", orig = orig_name, name = name, supports = supports, no_support = no_support);
+", orig = orig_name, name = name);
         }
         Ok(Box::new(ReplacedSource::new(id, &new_id, new_src)))
     }
@@ -151,6 +154,28 @@ a lock file compatible with `{orig}` cannot be generated in this situation
             path.push(s);
             srcs.push(SourceId::for_directory(&path)?);
         }
+        if let Some(val) = table.get("git") {
+            let url = url(val, &format!("source.{}.git", name))?;
+            let git_ref = |key: &str| -> CargoResult<Option<String>> {
+                match table.get(key) {
+                    Some(val) => {
+                        let key = format!("source.{}.{}", name, key);
+                        Ok(Some(val.string(&key)?.0.to_string()))
+                    }
+                    None => Ok(None),
+                }
+            };
+            let reference = if let Some(b) = git_ref("branch")? {
+                GitReference::Branch(b)
+            } else if let Some(t) = git_ref("tag")? {
+                GitReference::Tag(t)
+            } else if let Some(r) = git_ref("rev")? {
+                GitReference::Rev(r)
+            } else {
+                GitReference::Branch("master".to_string())
+            };
+            srcs.push(SourceId::for_git(&url, reference));
+        }
         if name == "crates-io" && srcs.is_empty() {
             srcs.push(SourceId::crates_io(self.config)?);
         }
@@ -158,7 +183,8 @@ a lock file compatible with `{orig}` cannot be generated in this situation
         let mut srcs = srcs.into_iter();
         let src = srcs.next().ok_or_else(|| {
             CargoError::from(format!("no source URL specified for `source.{}`, need \
-                                      either `registry` or `local-registry` defined",
+                                      either `registry`, `local-registry`, \
+                                      `directory` or `git` defined",
                                      name))
         })?;
         if srcs.next().is_some() {
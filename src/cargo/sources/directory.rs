@@ -23,7 +23,10 @@ pub struct DirectorySource<'cfg> {
 
 #[derive(Deserialize)]
 struct Checksum {
-    package: String,
+    /// Checksum of the package's `.crate` file, or `None` for packages (such
+    /// as git dependencies) which have no checksum of their own.
+    #[serde(default)]
+    package: Option<String>,
     files: HashMap<String, String>,
 }
 
@@ -133,8 +136,11 @@ impl<'cfg> Source for DirectorySource<'cfg> {
             })?;
 
             let mut manifest = pkg.manifest().clone();
-            let summary = manifest.summary().clone();
-            manifest.set_summary(summary.set_checksum(cksum.package.clone()));
+            let mut summary = manifest.summary().clone();
+            if let Some(ref package) = cksum.package {
+                summary = summary.set_checksum(package.clone());
+            }
+            manifest.set_summary(summary);
             let pkg = Package::new(manifest, pkg.manifest_path());
             self.packages.insert(pkg.package_id().clone(), (pkg, cksum));
         }
@@ -1,4 +1,5 @@
 pub use self::utils::{GitRemote, GitDatabase, GitCheckout, GitRevision, fetch};
+pub use self::utils::{fetch_shallow, is_shallow};
 pub use self::source::{GitSource, canonicalize_url};
 mod utils;
 mod source;
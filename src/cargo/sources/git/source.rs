@@ -5,7 +5,7 @@ use url::Url;
 use core::source::{Source, SourceId};
 use core::GitReference;
 use core::{Package, PackageId, Summary, Registry, Dependency};
-use util::Config;
+use util::{cache_usage, Config};
 use util::errors::{CargoError, CargoResult};
 use util::hex::short_hash;
 use sources::PathSource;
@@ -130,6 +130,7 @@ impl<'cfg> Source for GitSource<'cfg> {
     }
 
     fn update(&mut self) -> CargoResult<()> {
+        self.config.acquire_package_cache_lock()?;
         let lock = self.config.git_path()
             .open_rw(".cargo-lock-git", self.config, "the git checkouts")?;
 
@@ -149,7 +150,18 @@ impl<'cfg> Source for GitSource<'cfg> {
 
             trace!("updating git source `{:?}`", self.remote);
 
-            let repo = self.remote.checkout(&db_path, self.config)?;
+            // Dependencies pinned to a `rev` or a `tag` never need any other
+            // commit, so only that one is fetched if shallow fetches are
+            // enabled.
+            let shallow = match self.source_id.git_reference() {
+                Some(&GitReference::Branch(..)) | None => false,
+                Some(_) => {
+                    let shallow = self.config.get_bool("net.git-shallow-deps")?;
+                    shallow.map(|v| v.val).unwrap_or(false)
+                }
+            };
+            let reference = if shallow { Some(&self.reference) } else { None };
+            let repo = self.remote.checkout(&db_path, reference, self.config)?;
             let rev = repo.rev_for(&self.reference).map_err(CargoError::into_internal)?;
             (repo, rev)
         } else {
@@ -170,6 +182,7 @@ impl<'cfg> Source for GitSource<'cfg> {
         // Checkout is immutable, so we don't need to protect it with a lock once
         // it is created.
         repo.copy_to(actual_rev.clone(), &checkout_path, self.config)?;
+        cache_usage::mark_used(self.config, &[&db_path, &checkout_path])?;
 
         let source_id = self.source_id.with_precise(Some(actual_rev.to_string()));
         let path_source = PathSource::new_recursive(&checkout_path,
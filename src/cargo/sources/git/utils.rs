@@ -8,8 +8,8 @@ use serde::ser::{self, Serialize};
 use url::Url;
 
 use core::GitReference;
-use util::{ToUrl, internal, Config, network};
-use util::errors::{CargoResult, CargoResultExt, CargoError};
+use util::{ToUrl, internal, process, Config, network};
+use util::errors::{CargoResult, CargoResultExt, CargoError, CargoErrorKind};
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct GitRevision(git2::Oid);
@@ -88,16 +88,24 @@ impl GitRemote {
         db.rev_for(reference)
     }
 
-    pub fn checkout(&self, into: &Path, cargo_config: &Config) -> CargoResult<GitDatabase> {
+    /// Fetches the remote into the database at `into`.
+    ///
+    /// If `shallow` is given, only the commit it points to is fetched, without
+    /// its history, unless the database already holds the full history.
+    pub fn checkout(&self,
+                    into: &Path,
+                    shallow: Option<&GitReference>,
+                    cargo_config: &Config) -> CargoResult<GitDatabase> {
+        let shallow = shallow.and_then(shallow_refspec);
         let repo = match git2::Repository::open(into) {
             Ok(repo) => {
-                self.fetch_into(&repo, cargo_config).chain_err(|| {
+                self.fetch_into(&repo, shallow, cargo_config).chain_err(|| {
                     format!("failed to fetch into {}", into.display())
                 })?;
                 repo
             }
             Err(..) => {
-                self.clone_into(into, cargo_config).chain_err(|| {
+                self.clone_into(into, shallow, cargo_config).chain_err(|| {
                     format!("failed to clone into: {}", into.display())
                 })?
             }
@@ -119,25 +127,64 @@ impl GitRemote {
         })
     }
 
-    fn fetch_into(&self, dst: &git2::Repository, cargo_config: &Config) -> CargoResult<()> {
+    fn fetch_into(&self,
+                  dst: &git2::Repository,
+                  shallow: Option<String>,
+                  cargo_config: &Config) -> CargoResult<()> {
         // Create a local anonymous remote in the repository to fetch the url
         let url = self.url.to_string();
-        let refspec = "refs/heads/*:refs/heads/*";
-        fetch(dst, &url, refspec, cargo_config)
+        match shallow {
+            // Fetching with a depth into a full database would throw away
+            // history that's already there, so it's only done if the
+            // database is shallow anyway.
+            Some(ref refspec) if is_shallow(dst) => {
+                fetch_shallow(dst, &url, refspec, cargo_config)
+            }
+            _ => fetch(dst, &url, "refs/heads/*:refs/heads/*", cargo_config),
+        }
     }
 
-    fn clone_into(&self, dst: &Path, cargo_config: &Config) -> CargoResult<git2::Repository> {
+    fn clone_into(&self,
+                  dst: &Path,
+                  shallow: Option<String>,
+                  cargo_config: &Config) -> CargoResult<git2::Repository> {
         let url = self.url.to_string();
         if fs::metadata(&dst).is_ok() {
             fs::remove_dir_all(dst)?;
         }
         fs::create_dir_all(dst)?;
         let repo = git2::Repository::init_bare(dst)?;
-        fetch(&repo, &url, "refs/heads/*:refs/heads/*", cargo_config)?;
+        match shallow {
+            Some(refspec) => fetch_shallow(&repo, &url, &refspec, cargo_config)?,
+            None => fetch(&repo, &url, "refs/heads/*:refs/heads/*", cargo_config)?,
+        }
         Ok(repo)
     }
 }
 
+/// Returns the refspec fetching only the commit that `reference` pins, if it
+/// pins one which can be fetched on its own.
+fn shallow_refspec(reference: &GitReference) -> Option<String> {
+    match *reference {
+        GitReference::Tag(ref t) => Some(format!("refs/tags/{0}:refs/tags/{0}", t)),
+        // Servers only hand out commits which aren't the tip of a ref when
+        // asked for them by their full hash.
+        GitReference::Rev(ref r) if r.len() == 40 &&
+                                    r.chars().all(|c| c.is_digit(16)) => {
+            Some(format!("{0}:refs/commit/{0}", r))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `repo` only holds part of the history of its remote.
+///
+/// Git marks the boundaries of shallow repositories in their `shallow` file,
+/// which it removes again once they're unshallowed.
+pub fn is_shallow(repo: &git2::Repository) -> bool {
+    repo.path().join("shallow").exists()
+}
+
 impl GitDatabase {
     fn path(&self) -> &Path {
         &self.path
@@ -155,7 +202,7 @@ impl GitDatabase {
                 }
                 checkout
             }
-            Err(..) => GitCheckout::clone_into(dest, self, rev)?,
+            Err(..) => GitCheckout::clone_into(dest, self, rev, cargo_config)?,
         };
         checkout.update_submodules(cargo_config)?;
         Ok(checkout)
@@ -220,10 +267,14 @@ impl<'a> GitCheckout<'a> {
     }
 
     fn clone_into(into: &Path, database: &'a GitDatabase,
-                  revision: GitRevision)
+                  revision: GitRevision, cargo_config: &Config)
                   -> CargoResult<GitCheckout<'a>>
     {
-        let repo = GitCheckout::clone_repo(database.path(), into)?;
+        let repo = if is_shallow(&database.repo) {
+            GitCheckout::clone_shallow_repo(database.path(), into, cargo_config)?
+        } else {
+            GitCheckout::clone_repo(database.path(), into)?
+        };
         let checkout = GitCheckout::new(into, database, revision, repo);
         checkout.reset()?;
         Ok(checkout)
@@ -252,6 +303,28 @@ impl<'a> GitCheckout<'a> {
         Ok(repo)
     }
 
+    /// Clones a shallow database, which libgit2 can't do, by fetching all of
+    /// its refs with git.
+    fn clone_shallow_repo(source: &Path, into: &Path, cargo_config: &Config)
+                          -> CargoResult<git2::Repository> {
+        if fs::metadata(&into).is_ok() {
+            fs::remove_dir_all(into).chain_err(|| {
+                format!("Couldn't rmdir {}", into.display())
+            })?;
+        }
+        fs::create_dir_all(into).chain_err(|| {
+            format!("Couldn't mkdir {}", into.display())
+        })?;
+        let repo = git2::Repository::init(into)?;
+        let url = source.to_url()?.to_string();
+        fetch_with_cli(&repo, &url, "refs/*:refs/*", &["--update-shallow"],
+                       cargo_config).chain_err(|| {
+            internal(format!("failed to clone {} into {}", source.display(),
+                             into.display()))
+        })?;
+        Ok(repo)
+    }
+
     fn is_fresh(&self) -> bool {
         match self.repo.revparse_single("HEAD") {
             Ok(ref head) if head.id() == self.revision.0 => {
@@ -266,8 +339,13 @@ impl<'a> GitCheckout<'a> {
         info!("fetch {}", self.repo.path().display());
         let url = self.database.path.to_url()?;
         let url = url.to_string();
-        let refspec = "refs/heads/*:refs/heads/*";
-        fetch(&self.repo, &url, refspec, cargo_config)?;
+        if is_shallow(&self.database.repo) {
+            fetch_with_cli(&self.repo, &url, "refs/*:refs/*",
+                           &["--update-shallow"], cargo_config)?;
+        } else {
+            let refspec = "refs/heads/*:refs/heads/*";
+            fetch(&self.repo, &url, refspec, cargo_config)?;
+        }
         Ok(())
     }
 
@@ -566,6 +644,17 @@ pub fn fetch(repo: &git2::Repository,
                was specified")
     }
 
+    // libgit2 can't fetch into shallow repositories, so git deepens them
+    // to their full history instead.
+    if is_shallow(repo) {
+        return fetch_with_cli(repo, url, refspec, &["--tags", "--unshallow"],
+                              config)
+    }
+    let with_cli = config.get_bool("net.git-fetch-with-cli")?;
+    if with_cli.map(|v| v.val).unwrap_or(false) {
+        return fetch_with_cli(repo, url, refspec, &["--tags"], config)
+    }
+
     with_authentication(url, &repo.config()?, |f| {
         let mut cb = git2::RemoteCallbacks::new();
         cb.credentials(f);
@@ -583,3 +672,47 @@ pub fn fetch(repo: &git2::Repository,
         Ok(())
     })
 }
+
+/// Fetches only the commit that `refspec` points to, without its history.
+///
+/// libgit2 doesn't support shallow fetches, so they're always done with git.
+pub fn fetch_shallow(repo: &git2::Repository,
+                     url: &str,
+                     refspec: &str,
+                     config: &Config) -> CargoResult<()> {
+    if !config.network_allowed() {
+        bail!("attempting to update a git repository, but --frozen \
+               was specified")
+    }
+    fetch_with_cli(repo, url, refspec, &["--depth=1"], config)
+}
+
+/// Fetches `refspec` from `url` into `repo` by running `git fetch`, which
+/// picks up git's own configuration such as credential helpers and supports
+/// protocols that libgit2 doesn't.
+fn fetch_with_cli(repo: &git2::Repository,
+                  url: &str,
+                  refspec: &str,
+                  args: &[&str],
+                  config: &Config) -> CargoResult<()> {
+    let mut cmd = process("git");
+    cmd.arg("fetch")
+       .args(args)
+       .arg("--force") // overwrite local refs that were rewritten upstream
+       .arg("--update-head-ok") // the current branch may be updated too
+       .arg(url)
+       .arg(refspec)
+       // Make sure git works on `repo` rather than on a repository that it's
+       // nested in.
+       .env("GIT_DIR", repo.path())
+       .cwd(repo.path());
+    config.shell().verbose(|s| s.status("Running", &cmd.to_string()))?;
+    network::with_retry(config, || {
+        cmd.exec_with_output().map(drop).map_err(|e| match e {
+            CargoError(CargoErrorKind::ProcessErrorKind(err), state) => {
+                CargoError(CargoErrorKind::GitCli(err), state)
+            }
+            e => e,
+        })
+    })
+}
@@ -7,9 +7,9 @@ use semver::Version;
 
 use core::dependency::Dependency;
 use core::{SourceId, Summary, PackageId};
-use sources::registry::{RegistryPackage, INDEX_LOCK};
+use sources::registry::{RegistryPackage, INDEX_LOCK, INDEX_V_MAX};
 use sources::registry::RegistryData;
-use util::{CargoError, CargoResult, internal, Filesystem, Config};
+use util::{self, CargoError, CargoResult, internal, Filesystem, Config};
 
 pub struct RegistryIndex<'cfg> {
     source_id: SourceId,
@@ -98,6 +98,7 @@ impl<'cfg> RegistryIndex<'cfg> {
             3 => format!("3/{}/{}", &fs_name[..1], fs_name),
             _ => format!("{}/{}/{}", &fs_name[0..2], &fs_name[2..4], fs_name),
         };
+        load.prepare(Path::new(&path))?;
         let mut ret = Vec::new();
         let mut hit_closure = false;
         let err = load.load(&root, Path::new(&path), &mut |contents| {
@@ -139,13 +140,26 @@ impl<'cfg> RegistryIndex<'cfg> {
     fn parse_registry_package(&mut self, line: &str)
                               -> CargoResult<(Summary, bool)> {
         let RegistryPackage {
-            name, vers, cksum, deps, features, yanked
+            name, vers, cksum, deps, mut features, features2, yanked,
+            rust_version, v
         } = super::DEFAULT_ID.set(&self.source_id, || {
             serde_json::from_str::<RegistryPackage>(line)
         })?;
+        if v.unwrap_or(1) > INDEX_V_MAX {
+            bail!("index entry for `{}` uses an unsupported format version", name)
+        }
+        if let Some(features2) = features2 {
+            for (feature, list) in features2 {
+                features.entry(feature).or_insert(Vec::new()).extend(list);
+            }
+        }
         let pkgid = PackageId::new(&name, &vers, &self.source_id)?;
         let summary = Summary::new(pkgid, deps.inner, features)?;
         let summary = summary.set_checksum(cksum.clone());
+        let rust_version = rust_version.and_then(|v| {
+            util::parse_rust_version(&v).ok()
+        });
+        let summary = summary.set_rust_version(rust_version);
         if self.hashes.contains_key(&name[..]) {
             self.hashes.get_mut(&name[..]).unwrap().insert(vers, cksum);
         } else {
@@ -49,6 +49,10 @@
 //!   the end of a file (the exact format is described later). This means that
 //!   the commits for an index are quite small and easily applied/compressable.
 //!
+//! Registries may alternatively serve the files of the index over plain HTTP
+//! (a "sparse" registry, see the `sparse` module), in which case only the
+//! files of the crates which are actually needed get downloaded.
+//!
 //! ## The format of the Index
 //!
 //! The index is a store for the list of versions for all packages known, so its
@@ -173,8 +177,10 @@ use core::{Source, SourceId, PackageId, Package, Summary, Registry};
 use core::dependency::{Dependency, Kind};
 use sources::PathSource;
 use util::{CargoResult, Config, internal, FileLock, Filesystem};
+use util::cache_usage;
 use util::errors::CargoResultExt;
 use util::hex;
+use util::ToUrl;
 
 const INDEX_LOCK: &'static str = ".cargo-index-lock";
 pub static CRATES_IO: &'static str = "https://github.com/rust-lang/crates.io-index";
@@ -201,14 +207,29 @@ pub struct RegistryConfig {
     pub api: String,
 }
 
+/// The version of the index format understood by this version of Cargo.
+///
+/// Entries with a higher `v` are ignored.
+const INDEX_V_MAX: u32 = 2;
+
 #[derive(Deserialize)]
 struct RegistryPackage<'a> {
     name: Cow<'a, str>,
     vers: Version,
     deps: DependencyList,
     features: HashMap<String, Vec<String>>,
+    /// Features which use the `dep:` or `foo?/bar` syntax. These are kept
+    /// apart from `features` so that older versions of Cargo, which can't
+    /// parse them, don't reject the whole entry.
+    features2: Option<HashMap<String, Vec<String>>>,
     cksum: String,
     yanked: Option<bool>,
+    /// The minimum version of rustc this version of the crate supports, as
+    /// declared by its `rust-version` field.
+    rust_version: Option<String>,
+    /// The version of the index format this entry uses, `1` if missing.
+    /// Entries using `features2` are written with `v = 2`.
+    v: Option<u32>,
 }
 
 struct DependencyList {
@@ -224,10 +245,24 @@ struct RegistryDependency<'a> {
     default_features: bool,
     target: Option<Cow<'a, str>>,
     kind: Option<Cow<'a, str>>,
+    package: Option<Cow<'a, str>>,
+    /// The index URL of the registry the dependency comes from, if it's not
+    /// this registry.
+    registry: Option<Cow<'a, str>>,
 }
 
 pub trait RegistryData {
     fn index_path(&self) -> &Filesystem;
+
+    /// Makes sure that the index file at `path` is available locally before
+    /// it's `load`ed, for registries which fetch index files on demand.
+    ///
+    /// Unlike failing to `load` a file, which just means that the crate
+    /// doesn't exist, errors returned here are reported.
+    fn prepare(&self, _path: &Path) -> CargoResult<()> {
+        Ok(())
+    }
+
     fn load(&self,
             _root: &Path,
             path: &Path,
@@ -237,11 +272,27 @@ pub trait RegistryData {
     fn download(&mut self,
                 pkg: &PackageId,
                 checksum: &str) -> CargoResult<FileLock>;
+
+    /// Returns the URL to fetch the `.crate` file of `pkg` from, or `None` if
+    /// it's already cached or `download` has to be used to get it.
+    fn download_url(&mut self, _pkg: &PackageId) -> CargoResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Verifies and stores the contents of a `.crate` file fetched from the
+    /// URL returned by `download_url`.
+    fn finish_download(&mut self,
+                       pkg: &PackageId,
+                       _checksum: &str,
+                       _data: &[u8]) -> CargoResult<FileLock> {
+        Err(internal(format!("registry can't finish downloading `{}`", pkg)))
+    }
 }
 
 mod index;
 mod remote;
 mod local;
+mod sparse;
 
 fn short_name(id: &SourceId) -> String {
     let hash = hex::short_hash(id);
@@ -250,11 +301,20 @@ fn short_name(id: &SourceId) -> String {
 }
 
 impl<'cfg> RegistrySource<'cfg> {
+    /// Creates a source for a registry hosted on the network, either as a git
+    /// repository or, for `sparse+` URLs, as index files served over HTTP.
     pub fn remote(source_id: &SourceId,
                   config: &'cfg Config) -> RegistrySource<'cfg> {
         let name = short_name(source_id);
-        let ops = remote::RemoteRegistry::new(source_id, config, &name);
-        RegistrySource::new(source_id, config, &name, Box::new(ops), true)
+        if source_id.is_sparse() {
+            // Index files are written while they're loaded, so the sparse
+            // registry takes care of locking the index itself.
+            let ops = sparse::SparseRegistry::new(source_id, config, &name);
+            RegistrySource::new(source_id, config, &name, Box::new(ops), false)
+        } else {
+            let ops = remote::RemoteRegistry::new(source_id, config, &name);
+            RegistrySource::new(source_id, config, &name, Box::new(ops), true)
+        }
     }
 
     pub fn local(source_id: &SourceId,
@@ -318,6 +378,30 @@ impl<'cfg> RegistrySource<'cfg> {
         Ok(dst)
     }
 
+    /// Loads the package stored in the downloaded `.crate` file at `path`.
+    fn get_pkg(&mut self, package: &PackageId, tarball: FileLock)
+               -> CargoResult<Package> {
+        let path = self.unpack_package(package, &tarball).chain_err(|| {
+            internal(format!("failed to unpack package `{}`", package))
+        })?;
+        cache_usage::mark_used(self.config, &[tarball.path(), &path])?;
+        let mut src = PathSource::new(&path, &self.source_id, self.config);
+        src.update()?;
+        let pkg = src.download(package)?;
+
+        // Unfortunately the index and the actual Cargo.toml in the index can
+        // differ due to historical Cargo bugs. To paper over these we trash the
+        // *summary* loaded from the Cargo.toml we just downloaded with the one
+        // we loaded from the index.
+        let summaries = self.index.summaries(package.name(), &mut *self.ops)?;
+        let summary = summaries.iter().map(|s| &s.0).find(|s| {
+            s.package_id() == package
+        }).expect("summary not found");
+        let mut manifest = pkg.manifest().clone();
+        manifest.set_summary(summary.clone());
+        Ok(Package::new(manifest, pkg.manifest_path()))
+    }
+
     fn do_update(&mut self) -> CargoResult<()> {
         self.ops.update_index()?;
         let path = self.ops.index_path();
@@ -378,26 +462,22 @@ impl<'cfg> Source for RegistrySource<'cfg> {
     }
 
     fn download(&mut self, package: &PackageId) -> CargoResult<Package> {
+        self.config.acquire_package_cache_lock()?;
         let hash = self.index.hash(package, &mut *self.ops)?;
         let path = self.ops.download(package, &hash)?;
-        let path = self.unpack_package(package, &path).chain_err(|| {
-            internal(format!("failed to unpack package `{}`", package))
-        })?;
-        let mut src = PathSource::new(&path, &self.source_id, self.config);
-        src.update()?;
-        let pkg = src.download(package)?;
+        self.get_pkg(package, path)
+    }
 
-        // Unfortunately the index and the actual Cargo.toml in the index can
-        // differ due to historical Cargo bugs. To paper over these we trash the
-        // *summary* loaded from the Cargo.toml we just downloaded with the one
-        // we loaded from the index.
-        let summaries = self.index.summaries(package.name(), &mut *self.ops)?;
-        let summary = summaries.iter().map(|s| &s.0).find(|s| {
-            s.package_id() == package
-        }).expect("summary not found");
-        let mut manifest = pkg.manifest().clone();
-        manifest.set_summary(summary.clone());
-        Ok(Package::new(manifest, pkg.manifest_path()))
+    fn download_url(&mut self, package: &PackageId) -> CargoResult<Option<String>> {
+        self.config.acquire_package_cache_lock()?;
+        self.ops.download_url(package)
+    }
+
+    fn finish_download(&mut self, package: &PackageId, contents: &[u8])
+                       -> CargoResult<Package> {
+        let hash = self.index.hash(package, &mut *self.ops)?;
+        let path = self.ops.finish_download(package, &hash, contents)?;
+        self.get_pkg(package, path)
     }
 
     fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
@@ -455,12 +535,24 @@ impl<'de> de::Deserialize<'de> for DependencyList {
 fn parse_registry_dependency(dep: RegistryDependency)
                              -> CargoResult<Dependency> {
     let RegistryDependency {
-        name, req, features, optional, default_features, target, kind
+        name, req, features, optional, default_features, target, kind, package,
+        registry,
     } = dep;
 
-    let mut dep = DEFAULT_ID.with(|id| {
-        Dependency::parse_no_deprecated(&name, Some(&req), id)
-    })?;
+    let id = match registry {
+        Some(registry) => SourceId::for_registry(&registry.to_url()?),
+        None => DEFAULT_ID.with(|id| id.clone()),
+    };
+
+    // Renamed dependencies are listed under their local name, with the name
+    // of the package actually depended on in `package`.
+    let mut dep = match package {
+        Some(ref package) => Dependency::parse_no_deprecated(package, Some(&req), &id)?,
+        None => Dependency::parse_no_deprecated(&name, Some(&req), &id)?,
+    };
+    if package.is_some() {
+        dep.set_explicit_name_in_toml(&name);
+    }
     let kind = match kind.as_ref().map(|s| &s[..]).unwrap_or("") {
         "dev" => Kind::Development,
         "build" => Kind::Build,
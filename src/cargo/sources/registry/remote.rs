@@ -195,9 +195,20 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
             // git fetch origin master
             let url = self.source_id.url().to_string();
             let refspec = "refs/heads/master:refs/remotes/origin/master";
-            git::fetch(&repo, &url, refspec, self.config).chain_err(|| {
-                format!("failed to fetch `{}`", url)
-            })?;
+
+            // Only the latest commit of the index is ever read, so its
+            // history is only fetched if shallow fetches are disabled or the
+            // index was already cloned with its full history.
+            let shallow = self.config.get_bool("net.git-shallow-index")?;
+            let shallow = shallow.map(|v| v.val).unwrap_or(false) &&
+                          (git::is_shallow(repo) ||
+                           repo.refname_to_id("refs/remotes/origin/master").is_err());
+            let result = if shallow {
+                git::fetch_shallow(&repo, &url, refspec, self.config)
+            } else {
+                git::fetch(&repo, &url, refspec, self.config)
+            };
+            result.chain_err(|| format!("failed to fetch `{}`", url))?;
         }
 
         self.head.set(None);
@@ -207,74 +218,40 @@ impl<'cfg> RegistryData for RemoteRegistry<'cfg> {
 
     fn download(&mut self, pkg: &PackageId, checksum: &str)
                 -> CargoResult<FileLock> {
-        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
-        let path = Path::new(&filename);
-
         // Attempt to open an read-only copy first to avoid an exclusive write
         // lock and also work with read-only filesystems. Note that we check the
         // length of the file like below to handle interrupted downloads.
         //
         // If this fails then we fall through to the exclusive path where we may
         // have to redownload the file.
-        if let Ok(dst) = self.cache_path.open_ro(path, self.config, &filename) {
-            let meta = dst.file().metadata()?;
-            if meta.len() > 0 {
-                return Ok(dst)
-            }
+        if let Some(dst) = cached_crate(&self.cache_path, self.config, pkg)? {
+            return Ok(dst)
         }
+        let filename = crate_filename(pkg);
+        let path = Path::new(&filename);
         let mut dst = self.cache_path.open_rw(path, self.config, &filename)?;
         let meta = dst.file().metadata()?;
         if meta.len() > 0 {
             return Ok(dst)
         }
-        self.config.shell().status("Downloading", pkg)?;
-
         let config = self.config()?.unwrap();
-        let mut url = config.dl.to_url()?;
-        url.path_segments_mut().unwrap()
-            .push(pkg.name())
-            .push(&pkg.version().to_string())
-            .push("download");
-
-        // TODO: don't download into memory, but ensure that if we ctrl-c a
-        //       download we should resume either from the start or the middle
-        //       on the next time
-        let url = url.to_string();
         let mut handle = self.easy()?.borrow_mut();
-        handle.get(true)?;
-        handle.url(&url)?;
-        handle.follow_location(true)?;
-        let mut state = Sha256::new();
-        let mut body = Vec::new();
-        network::with_retry(self.config, || {
-            state = Sha256::new();
-            body = Vec::new();
-            {
-                let mut handle = handle.transfer();
-                handle.write_function(|buf| {
-                    state.update(buf);
-                    body.extend_from_slice(buf);
-                    Ok(buf.len())
-                })?;
-                handle.perform()?;
-            }
-            let code = handle.response_code()?;
-            if code != 200 && code != 0 {
-                let url = handle.effective_url()?.unwrap_or(&url);
-                Err(CargoErrorKind::HttpNot200(code, url.to_string()).into())
-            } else {
-                Ok(())
-            }
-        })?;
+        download_crate(self.config, &mut handle, &config.dl, pkg, checksum,
+                       &mut dst)?;
+        Ok(dst)
+    }
 
-        // Verify what we just downloaded
-        if state.finish().to_hex() != checksum {
-            bail!("failed to verify the checksum of `{}`", pkg)
+    fn download_url(&mut self, pkg: &PackageId) -> CargoResult<Option<String>> {
+        if cached_crate(&self.cache_path, self.config, pkg)?.is_some() {
+            return Ok(None)
         }
+        let config = self.config()?.unwrap();
+        Ok(Some(crate_url(&config.dl, pkg)?))
+    }
 
-        dst.write_all(&body)?;
-        dst.seek(SeekFrom::Start(0))?;
-        Ok(dst)
+    fn finish_download(&mut self, pkg: &PackageId, checksum: &str, data: &[u8])
+                       -> CargoResult<FileLock> {
+        store_crate(&self.cache_path, self.config, pkg, checksum, data)
     }
 }
 
@@ -285,6 +262,114 @@ impl<'cfg> Drop for RemoteRegistry<'cfg> {
     }
 }
 
+fn crate_filename(pkg: &PackageId) -> String {
+    format!("{}-{}.crate", pkg.name(), pkg.version())
+}
+
+/// Returns the URL of the `.crate` file of `pkg` on the `dl` endpoint of a
+/// registry.
+pub fn crate_url(dl: &str, pkg: &PackageId) -> CargoResult<String> {
+    let mut url = dl.to_url()?;
+    url.path_segments_mut().unwrap()
+        .push(pkg.name())
+        .push(&pkg.version().to_string())
+        .push("download");
+    Ok(url.to_string())
+}
+
+/// Opens the `.crate` file of `pkg` in `cache_path` if it has already been
+/// downloaded.
+pub fn cached_crate(cache_path: &Filesystem,
+                    config: &Config,
+                    pkg: &PackageId) -> CargoResult<Option<FileLock>> {
+    let filename = crate_filename(pkg);
+    let path = Path::new(&filename);
+    if let Ok(dst) = cache_path.open_ro(path, config, &filename) {
+        let meta = dst.file().metadata()?;
+        if meta.len() > 0 {
+            return Ok(Some(dst))
+        }
+    }
+    Ok(None)
+}
+
+/// Verifies that the downloaded `.crate` file of `pkg` matches `checksum`
+/// and stores it in `cache_path`.
+pub fn store_crate(cache_path: &Filesystem,
+                   config: &Config,
+                   pkg: &PackageId,
+                   checksum: &str,
+                   data: &[u8]) -> CargoResult<FileLock> {
+    verify_checksum(pkg, checksum, data)?;
+    let filename = crate_filename(pkg);
+    let path = Path::new(&filename);
+    let mut dst = cache_path.open_rw(path, config, &filename)?;
+    // Another Cargo may have finished downloading it while we were.
+    let meta = dst.file().metadata()?;
+    if meta.len() > 0 {
+        return Ok(dst)
+    }
+    dst.write_all(data)?;
+    dst.seek(SeekFrom::Start(0))?;
+    Ok(dst)
+}
+
+fn verify_checksum(pkg: &PackageId, checksum: &str, data: &[u8])
+                   -> CargoResult<()> {
+    let mut state = Sha256::new();
+    state.update(data);
+    if state.finish().to_hex() != checksum {
+        bail!("failed to verify the checksum of `{}`", pkg)
+    }
+    Ok(())
+}
+
+/// Downloads the `.crate` file of `pkg` from the `dl` endpoint of a registry
+/// into `dst`, verifying that it matches `checksum`.
+pub fn download_crate(config: &Config,
+                      handle: &mut Easy,
+                      dl: &str,
+                      pkg: &PackageId,
+                      checksum: &str,
+                      dst: &mut FileLock) -> CargoResult<()> {
+    config.shell().status("Downloading", pkg)?;
+
+    // TODO: don't download into memory, but ensure that if we ctrl-c a
+    //       download we should resume either from the start or the middle
+    //       on the next time
+    let url = crate_url(dl, pkg)?;
+    handle.get(true)?;
+    handle.url(&url)?;
+    handle.follow_location(true)?;
+    handle.http_headers(List::new())?;
+    let mut body = Vec::new();
+    network::with_retry(config, || {
+        body = Vec::new();
+        {
+            let mut handle = handle.transfer();
+            handle.write_function(|buf| {
+                body.extend_from_slice(buf);
+                Ok(buf.len())
+            })?;
+            handle.perform()?;
+        }
+        let code = handle.response_code()?;
+        if code != 200 && code != 0 {
+            let url = handle.effective_url()?.unwrap_or(&url);
+            Err(CargoErrorKind::HttpNot200(code, url.to_string()).into())
+        } else {
+            Ok(())
+        }
+    })?;
+
+    // Verify what we just downloaded
+    verify_checksum(pkg, checksum, &body)?;
+
+    dst.write_all(&body)?;
+    dst.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
 /// Updating the index is done pretty regularly so we want it to be as fast as
 /// possible. For registries hosted on github (like the crates.io index) there's
 /// a fast path available to use [1] to tell us that there's no updates to be
@@ -0,0 +1,278 @@
+//! Access to a registry index served over plain HTTP.
+//!
+//! Registries whose index URL is of the form `sparse+https://...` don't clone
+//! the index as a git repository. Instead each index file (laid out exactly
+//! like in the git repository) is fetched with an HTTP GET request the first
+//! time a crate is looked up, and then cached locally together with the
+//! `ETag` or `Last-Modified` header of the response. Once the index is
+//! updated, each cached file is revalidated with a conditional request at
+//! most once per invocation of Cargo.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str;
+
+use curl::easy::{Easy, List};
+use serde_json;
+
+use core::{PackageId, SourceId};
+use ops;
+use sources::registry::{RegistryData, RegistryConfig, INDEX_LOCK};
+use sources::registry::remote::{cached_crate, crate_url, download_crate};
+use sources::registry::remote::store_crate;
+use util::network;
+use util::paths;
+use util::{Config, FileLock, Filesystem, LazyCell};
+use util::errors::{CargoErrorKind, CargoResult, CargoResultExt};
+
+pub struct SparseRegistry<'cfg> {
+    index_path: Filesystem,
+    cache_path: Filesystem,
+    source_id: SourceId,
+    /// URL of the root of the index, without the `sparse+` prefix and with a
+    /// trailing slash.
+    url: String,
+    config: &'cfg Config,
+    handle: LazyCell<RefCell<Easy>>,
+    /// Whether cached index files need to be revalidated, which is the case
+    /// once `update_index` has been called.
+    updated: Cell<bool>,
+    /// Index files which have been fetched or revalidated since the last
+    /// update.
+    fresh: RefCell<HashSet<PathBuf>>,
+}
+
+impl<'cfg> SparseRegistry<'cfg> {
+    pub fn new(source_id: &SourceId, config: &'cfg Config, name: &str)
+               -> SparseRegistry<'cfg> {
+        let url = source_id.url().as_str();
+        let mut url = url.trim_left_matches("sparse+").to_string();
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        SparseRegistry {
+            index_path: config.registry_index_path().join(name),
+            cache_path: config.registry_cache_path().join(name),
+            source_id: source_id.clone(),
+            url: url,
+            config: config,
+            handle: LazyCell::new(),
+            updated: Cell::new(false),
+            fresh: RefCell::new(HashSet::new()),
+        }
+    }
+
+    fn easy(&self) -> CargoResult<&RefCell<Easy>> {
+        self.handle.get_or_try_init(|| {
+            ops::http_handle(self.config).map(RefCell::new)
+        })
+    }
+
+    fn cache_file(&self, path: &Path) -> PathBuf {
+        self.index_path.clone().into_path_unlocked().join(path)
+    }
+
+    /// Fetches the index file at `path` unless it was already fetched since
+    /// the last update.
+    fn fetch_once(&self, path: &Path) -> CargoResult<()> {
+        if self.fresh.borrow().contains(path) {
+            return Ok(())
+        }
+        let url = format!("{}{}", self.url, path.display())
+            .replace("\\", "/");
+        self.fetch(&url, &self.cache_file(path)).chain_err(|| {
+            format!("failed to fetch `{}`", url)
+        })?;
+        self.fresh.borrow_mut().insert(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Downloads `url` into the cache file `dst`, sending the validator of
+    /// the cached copy along so that the server can tell us it's unchanged.
+    fn fetch(&self, url: &str, dst: &Path) -> CargoResult<()> {
+        if !self.config.network_allowed() {
+            bail!("attempting to update a registry index, but --frozen \
+                   was specified")
+        }
+
+        let _lock = self.index_path.open_rw(Path::new(INDEX_LOCK),
+                                            self.config,
+                                            "the registry index")?;
+
+        let mut headers = List::new();
+        if let Ok(cached) = paths::read_bytes(dst) {
+            let validator = cached.split(|b| *b == b'\n').next().unwrap_or(&[]);
+            let validator = str::from_utf8(validator).unwrap_or("");
+            if validator.starts_with("etag: ") {
+                headers.append(&format!("If-None-Match: {}", &validator[6..]))?;
+            } else if validator.starts_with("last-modified: ") {
+                headers.append(&format!("If-Modified-Since: {}", &validator[15..]))?;
+            }
+        }
+
+        let mut handle = self.easy()?.borrow_mut();
+        handle.get(true)?;
+        handle.url(url)?;
+        handle.follow_location(true)?;
+        handle.http_headers(headers)?;
+        let mut body = Vec::new();
+        let mut validator = String::new();
+        network::with_retry(self.config, || {
+            body = Vec::new();
+            validator = String::new();
+            {
+                let mut handle = handle.transfer();
+                handle.write_function(|buf| {
+                    body.extend_from_slice(buf);
+                    Ok(buf.len())
+                })?;
+                handle.header_function(|header| {
+                    let header = match str::from_utf8(header) {
+                        Ok(header) => header.trim(),
+                        Err(_) => return true,
+                    };
+                    let mut parts = header.splitn(2, ':');
+                    let name = parts.next().unwrap().to_lowercase();
+                    let value = parts.next().unwrap_or("").trim();
+                    // An ETag is preferred as it doesn't depend on clocks.
+                    if name == "etag" {
+                        validator = format!("etag: {}", value);
+                    } else if name == "last-modified" && validator.is_empty() {
+                        validator = format!("last-modified: {}", value);
+                    }
+                    true
+                })?;
+                handle.perform()?;
+            }
+            match handle.response_code()? {
+                0 | 200 | 304 | 404 | 410 => Ok(()),
+                code => {
+                    let url = handle.effective_url()?.unwrap_or(url);
+                    Err(CargoErrorKind::HttpNot200(code, url.to_string()).into())
+                }
+            }
+        })?;
+
+        match handle.response_code()? {
+            // The cached copy is still up to date.
+            304 => Ok(()),
+            // The crate doesn't exist (anymore).
+            404 | 410 => {
+                if dst.exists() {
+                    fs::remove_file(dst)?;
+                }
+                Ok(())
+            }
+            _ => {
+                // Write the new copy next to the old one first so readers
+                // never see a partially written file.
+                let mut contents = validator.into_bytes();
+                contents.push(b'\n');
+                contents.extend_from_slice(&body);
+                fs::create_dir_all(dst.parent().unwrap())?;
+                let tmp = dst.with_extension("tmp");
+                paths::write(&tmp, &contents)?;
+                fs::rename(&tmp, dst)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'cfg> RegistryData for SparseRegistry<'cfg> {
+    fn index_path(&self) -> &Filesystem {
+        &self.index_path
+    }
+
+    fn prepare(&self, path: &Path) -> CargoResult<()> {
+        // Until the index is updated, whatever is cached is used as is, which
+        // avoids touching the network when a lock file is present.
+        if self.updated.get() {
+            self.fetch_once(path)?;
+        }
+        Ok(())
+    }
+
+    fn load(&self,
+            root: &Path,
+            path: &Path,
+            data: &mut FnMut(&[u8]) -> CargoResult<()>) -> CargoResult<()> {
+        let contents = paths::read_bytes(&root.join(path))?;
+        // Skip the line holding the validator of the cached response.
+        let start = contents.iter().position(|b| *b == b'\n')
+                            .map(|i| i + 1)
+                            .unwrap_or(contents.len());
+        data(&contents[start..])
+    }
+
+    fn config(&mut self) -> CargoResult<Option<RegistryConfig>> {
+        let path = Path::new("config.json");
+        if self.updated.get() || !self.cache_file(path).exists() {
+            self.fetch_once(path)?;
+        }
+        let mut config = None;
+        let root = self.index_path.clone().into_path_unlocked();
+        self.load(&root, path, &mut |json| {
+            config = Some(serde_json::from_slice(json)?);
+            Ok(())
+        }).chain_err(|| {
+            format!("failed to read the configuration of registry `{}`",
+                    self.source_id.url())
+        })?;
+        Ok(config)
+    }
+
+    fn update_index(&mut self) -> CargoResult<()> {
+        if !self.config.network_allowed() {
+            bail!("attempting to update a registry index, but --frozen \
+                   was specified")
+        }
+
+        // Make sure an HTTP handle can be created at all before doing
+        // anything, which reports problems with the configuration early.
+        self.easy()?;
+
+        self.config.shell().status("Updating",
+             format!("registry `{}`", self.source_id.url()))?;
+        self.updated.set(true);
+        self.fresh.borrow_mut().clear();
+
+        // Only the configuration is fetched eagerly, index files of crates
+        // are fetched as they're needed.
+        self.fetch_once(Path::new("config.json"))
+    }
+
+    fn download(&mut self, pkg: &PackageId, checksum: &str)
+                -> CargoResult<FileLock> {
+        if let Some(dst) = cached_crate(&self.cache_path, self.config, pkg)? {
+            return Ok(dst)
+        }
+        let filename = format!("{}-{}.crate", pkg.name(), pkg.version());
+        let path = Path::new(&filename);
+        let mut dst = self.cache_path.open_rw(path, self.config, &filename)?;
+        let meta = dst.file().metadata()?;
+        if meta.len() > 0 {
+            return Ok(dst)
+        }
+        let config = self.config()?.unwrap();
+        let mut handle = self.easy()?.borrow_mut();
+        download_crate(self.config, &mut handle, &config.dl, pkg, checksum,
+                       &mut dst)?;
+        Ok(dst)
+    }
+
+    fn download_url(&mut self, pkg: &PackageId) -> CargoResult<Option<String>> {
+        if cached_crate(&self.cache_path, self.config, pkg)?.is_some() {
+            return Ok(None)
+        }
+        let config = self.config()?.unwrap();
+        Ok(Some(crate_url(&config.dl, pkg)?))
+    }
+
+    fn finish_download(&mut self, pkg: &PackageId, checksum: &str, data: &[u8])
+                       -> CargoResult<FileLock> {
+        store_crate(&self.cache_path, self.config, pkg, checksum, data)
+    }
+}
@@ -56,6 +56,24 @@ impl<'cfg> Source for ReplacedSource<'cfg> {
         Ok(pkg.map_source(&self.replace_with, &self.to_replace))
     }
 
+    fn download_url(&mut self, id: &PackageId) -> CargoResult<Option<String>> {
+        let id = id.with_source_id(&self.replace_with);
+        self.inner.download_url(&id).chain_err(|| {
+            format!("failed to download replaced source `{}`",
+                    self.to_replace)
+        })
+    }
+
+    fn finish_download(&mut self, id: &PackageId, contents: &[u8])
+                       -> CargoResult<Package> {
+        let id = id.with_source_id(&self.replace_with);
+        let pkg = self.inner.finish_download(&id, contents).chain_err(|| {
+            format!("failed to download replaced source `{}`",
+                    self.to_replace)
+        })?;
+        Ok(pkg.map_source(&self.replace_with, &self.to_replace))
+    }
+
     fn fingerprint(&self, id: &Package) -> CargoResult<String> {
         self.inner.fingerprint(id)
     }
@@ -0,0 +1,108 @@
+//! Tracking of when the entries of the global package cache in `CARGO_HOME`
+//! were last used, which `cargo clean gc` uses to decide what to remove.
+//!
+//! The last-use times are stored as JSON in a file in `CARGO_HOME`, keyed by
+//! the path of each entry relative to `CARGO_HOME`.
+
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::io::prelude::*;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json;
+
+use util::{Config, FileLock};
+use util::errors::{CargoResult, CargoResultExt};
+use util::paths;
+
+/// Name of the file in `CARGO_HOME` holding the usage data.
+const USAGE_FILE: &'static str = ".package-cache-usage.json";
+
+/// Last-use times are only updated once they're older than this many
+/// seconds, so that builds don't rewrite the usage file all the time.
+const UPDATE_INTERVAL: u64 = 60 * 60;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CacheUsage {
+    /// When the cache was last cleaned automatically, in seconds since the
+    /// Unix epoch.
+    #[serde(default)]
+    pub last_auto_gc: u64,
+    /// Last-use times of the entries of the cache, in seconds since the Unix
+    /// epoch.
+    #[serde(default)]
+    pub entries: BTreeMap<String, u64>,
+}
+
+impl CacheUsage {
+    /// Locks the usage file of the cache in `config`'s `CARGO_HOME`.
+    pub fn lock(config: &Config) -> CargoResult<FileLock> {
+        config.home().open_rw(USAGE_FILE, config, "the package cache usage")
+    }
+
+    /// Reads the usage data from the locked usage file.
+    ///
+    /// A corrupt file is treated like an empty one, which just makes all
+    /// entries look like they've never been used.
+    pub fn load(file: &mut FileLock) -> CargoResult<CacheUsage> {
+        let mut contents = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut contents).chain_err(|| {
+            format!("failed to read `{}`", file.path().display())
+        })?;
+        Ok(serde_json::from_str(&contents).unwrap_or_default())
+    }
+
+    /// Replaces the contents of the locked usage file with this data.
+    pub fn save(&self, file: &mut FileLock) -> CargoResult<()> {
+        let contents = serde_json::to_string(self)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.file().set_len(0)?;
+        file.write_all(contents.as_bytes()).chain_err(|| {
+            format!("failed to write `{}`", file.path().display())
+        })?;
+        Ok(())
+    }
+}
+
+/// Returns the key of the cache entry at `path`, or `None` if it's not in
+/// `config`'s `CARGO_HOME`.
+pub fn entry_key(config: &Config, path: &Path) -> Option<String> {
+    let home = config.home().clone().into_path_unlocked();
+    paths::without_prefix(path, &home).and_then(|p| p.to_str()).map(|p| {
+        p.replace("\\", "/")
+    })
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Records that the entries of the package cache at `paths` were just used.
+///
+/// This also takes the shared lock on the package cache for the rest of the
+/// process, so that the entries aren't removed while they're in use.
+pub fn mark_used(config: &Config, paths: &[&Path]) -> CargoResult<()> {
+    config.acquire_package_cache_lock()?;
+
+    let now = now();
+    let keys = paths.iter().filter_map(|path| entry_key(config, path));
+    let mut file = CacheUsage::lock(config)?;
+    let mut usage = CacheUsage::load(&mut file)?;
+    let mut changed = false;
+    for key in keys {
+        let fresh = usage.entries.get(&key).map(|&last| {
+            last + UPDATE_INTERVAL > now
+        }).unwrap_or(false);
+        if !fresh {
+            usage.entries.insert(key, now);
+            changed = true;
+        }
+    }
+    if changed {
+        usage.save(&mut file)?;
+    }
+    Ok(())
+}
@@ -17,27 +17,39 @@ use core::shell::Verbosity;
 use jobserver;
 use serde::{Serialize, Serializer};
 use toml;
-use util::Rustc;
+use url::Url;
+use util::{Rustc, ToUrl};
 use util::errors::{CargoResult, CargoResultExt, CargoError, internal};
 use util::paths;
-use util::{Filesystem, LazyCell};
+use util::{FileLock, Filesystem, LazyCell};
 
 use util::toml as cargo_toml;
 
 use self::ConfigValue as CV;
 
+/// The stand-in path recorded for configuration values which were given on
+/// the command line with `--config KEY=VALUE`.
+const CLI_PATH: &'static str = "--config cli option";
+
+/// Name of the file in `CARGO_HOME` which is locked to protect the global
+/// package cache.
+pub const PACKAGE_CACHE_LOCK: &'static str = ".package-cache";
+
 pub struct Config {
     home_path: Filesystem,
     shell: RefCell<Shell>,
     rustc: LazyCell<Rustc>,
     values: LazyCell<HashMap<String, ConfigValue>>,
+    cli_config: LazyCell<ConfigValue>,
     cwd: PathBuf,
     cargo_exe: LazyCell<PathBuf>,
     rustdoc: LazyCell<PathBuf>,
+    env_config: LazyCell<Vec<(String, String)>>,
     extra_verbose: Cell<bool>,
     frozen: Cell<bool>,
     locked: Cell<bool>,
     jobserver: Option<jobserver::Client>,
+    package_cache_lock: RefCell<Option<FileLock>>,
 }
 
 impl Config {
@@ -61,8 +73,10 @@ impl Config {
             rustc: LazyCell::new(),
             cwd: cwd,
             values: LazyCell::new(),
+            cli_config: LazyCell::new(),
             cargo_exe: LazyCell::new(),
             rustdoc: LazyCell::new(),
+            env_config: LazyCell::new(),
             extra_verbose: Cell::new(false),
             frozen: Cell::new(false),
             locked: Cell::new(false),
@@ -73,6 +87,7 @@ impl Config {
                     Some((*GLOBAL_JOBSERVER).clone())
                 }
             },
+            package_cache_lock: RefCell::new(None),
         }
     }
 
@@ -106,6 +121,35 @@ impl Config {
         self.home_path.join("registry").join("src")
     }
 
+    /// Takes a shared lock on the global package cache (the registry caches
+    /// and sources and the git databases and checkouts) which is held for
+    /// the rest of the process, so that `cargo clean gc` doesn't remove
+    /// anything while it may still be in use.
+    pub fn acquire_package_cache_lock(&self) -> CargoResult<()> {
+        if self.package_cache_lock.borrow().is_some() {
+            return Ok(())
+        }
+        let path = Path::new(PACKAGE_CACHE_LOCK);
+        let msg = "the package cache";
+        if !self.home_path.clone().into_path_unlocked().join(path).exists() {
+            self.home_path.open_rw(path, self, msg)?;
+        }
+        let lock = self.home_path.open_ro(path, self, msg)?;
+        *self.package_cache_lock.borrow_mut() = Some(lock);
+        Ok(())
+    }
+
+    /// Whether `acquire_package_cache_lock` was called, which is the case once
+    /// anything in the package cache was used.
+    pub fn holds_package_cache_lock(&self) -> bool {
+        self.package_cache_lock.borrow().is_some()
+    }
+
+    /// Releases the lock taken by `acquire_package_cache_lock`.
+    pub fn release_package_cache_lock(&self) {
+        self.package_cache_lock.borrow_mut().take();
+    }
+
     pub fn shell(&self) -> RefMut<Shell> {
         self.shell.borrow_mut()
     }
@@ -140,6 +184,101 @@ impl Config {
         }
     }
 
+    /// Sets the configuration passed with `--config`, either as `KEY=VALUE`
+    /// TOML fragments or as paths to extra configuration files. These take
+    /// precedence over both configuration files and environment variables,
+    /// and later arguments take precedence over earlier ones.
+    ///
+    /// This must be called before any configuration is read.
+    pub fn set_cli_config(&self, args: &[String]) -> CargoResult<()> {
+        if self.values.borrow().is_some() {
+            return Err("Config values already found".into());
+        }
+        let mut cfg = CV::Table(HashMap::new(), PathBuf::from(CLI_PATH));
+        for arg in args.iter().rev() {
+            let value = if arg.contains('=') && !arg.ends_with(".toml") {
+                self.parse_cli_value(arg).chain_err(|| {
+                    format!("failed to parse `--config` argument `{}`", arg)
+                })?
+            } else {
+                let path = self.cwd.join(arg);
+                if !path.is_file() {
+                    bail!("config file `{}` passed with `--config` does not exist",
+                          path.display())
+                }
+                self.load_file(&path)?
+            };
+            cfg.merge(value).chain_err(|| {
+                format!("failed to merge `--config` argument `{}`", arg)
+            })?;
+        }
+        match self.cli_config.fill(cfg) {
+            Ok(()) => Ok(()),
+            Err(_) => Err("Could not fill values".into()),
+        }
+    }
+
+    /// Parses a `KEY=VALUE` argument to `--config`, where `KEY` is a dotted
+    /// key (whose parts may be quoted) and `VALUE` is a TOML value.
+    fn parse_cli_value(&self, arg: &str) -> CargoResult<ConfigValue> {
+        let mut quote = None;
+        let idx = arg.char_indices().find(|&(_, c)| {
+            match (quote, c) {
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (Some(q), c) if q == c => quote = None,
+                (None, '=') => return true,
+                _ => {}
+            }
+            false
+        }).map(|(i, _)| i);
+        let idx = match idx {
+            Some(idx) => idx,
+            None => bail!("expected `KEY=VALUE`"),
+        };
+        let (key, value) = (arg[..idx].trim(), arg[idx + 1..].trim());
+        let path = Path::new(CLI_PATH);
+
+        let mut parts = Vec::new();
+        let mut rest = key;
+        while !rest.is_empty() {
+            let (part, len) = if rest.starts_with('"') || rest.starts_with('\'') {
+                let quote = &rest[..1];
+                match rest[1..].find(quote) {
+                    Some(end) => (&rest[1..end + 1], end + 2),
+                    None => bail!("unterminated quote in key `{}`", key),
+                }
+            } else {
+                let end = rest.find('.').unwrap_or(rest.len());
+                (rest[..end].trim(), end)
+            };
+            if part.is_empty() {
+                bail!("invalid key `{}`", key)
+            }
+            parts.push(part.to_string());
+            rest = &rest[len..];
+            if rest.starts_with('.') {
+                rest = &rest[1..];
+            } else if !rest.is_empty() {
+                bail!("invalid key `{}`", key)
+            }
+        }
+        if parts.is_empty() {
+            bail!("expected a key like `build.jobs` before the `=`")
+        }
+
+        let toml = cargo_toml::parse(&format!("value = {}", value), path, self)?;
+        let mut toml = match toml {
+            toml::Value::Table(mut table) => table.remove("value").unwrap(),
+            _ => unreachable!(),
+        };
+        for part in parts.into_iter().rev() {
+            let mut table = toml::value::Table::new();
+            table.insert(part, toml);
+            toml = toml::Value::Table(table);
+        }
+        CV::from_toml(path, toml)
+    }
+
     pub fn cwd(&self) -> &Path { &self.cwd }
 
     pub fn target_dir(&self) -> CargoResult<Option<Filesystem>> {
@@ -153,7 +292,9 @@ impl Config {
         }
     }
 
-    fn get(&self, key: &str) -> CargoResult<Option<ConfigValue>> {
+    /// Returns the configuration value for `key`, as merged from all the
+    /// configuration files and `--config` arguments.
+    pub fn get(&self, key: &str) -> CargoResult<Option<ConfigValue>> {
         let vals = self.values()?;
         let mut parts = key.split('.').enumerate();
         let mut val = match vals.get(parts.next().unwrap().1) {
@@ -187,6 +328,10 @@ impl Config {
     fn get_env<V: FromStr>(&self, key: &str) -> CargoResult<Option<Value<V>>>
         where CargoError: From<V::Err>
     {
+        // Values passed with `--config` take precedence over the environment.
+        if self.cli_config.borrow().map_or(false, |cli| cli_contains(cli, key)) {
+            return Ok(None)
+        }
         let key = key.replace(".", "_")
                      .replace("-", "_")
                      .chars()
@@ -211,7 +356,7 @@ impl Config {
             Some(CV::String(i, path)) => {
                 Ok(Some(Value {
                     val: i,
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("string", key, val),
@@ -227,7 +372,7 @@ impl Config {
             Some(CV::Boolean(b, path)) => {
                 Ok(Some(Value {
                     val: b,
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("bool", key, val),
@@ -246,6 +391,20 @@ impl Config {
         }
     }
 
+    /// Looks up the index URL of the registry named `registry` in the
+    /// `[registries]` table.
+    pub fn get_registry_index(&self, registry: &str) -> CargoResult<Url> {
+        match self.get_string(&format!("registries.{}.index", registry))? {
+            Some(index) => {
+                index.val.to_url().chain_err(|| {
+                    format!("invalid index URL for registry `{}` defined in {}",
+                            registry, index.definition)
+                })
+            }
+            None => bail!("no index found for registry: `{}`", registry),
+        }
+    }
+
     pub fn get_path(&self, key: &str) -> CargoResult<Option<Value<PathBuf>>> {
         if let Some(val) = self.get_string(key)? {
             Ok(Some(Value {
@@ -276,7 +435,7 @@ impl Config {
             Some(CV::List(i, path)) => {
                 Ok(Some(Value {
                     val: i,
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("list", key, val),
@@ -300,13 +459,42 @@ impl Config {
             Some(CV::List(i, path)) => {
                 Ok(Some(Value {
                     val: i.into_iter().map(|(s, _)| s).collect(),
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(CV::String(i, path)) => {
                 Ok(Some(Value {
                     val: i.split(' ').map(str::to_string).collect(),
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
+                }))
+            }
+            Some(val) => self.expected("list or string", key, val),
+            None => Ok(None),
+        }
+    }
+
+    /// Like `get_list_or_split_string`, except that a string is a single
+    /// value, even if it contains spaces.
+    pub fn get_list_or_string(&self, key: &str)
+                              -> CargoResult<Option<Value<Vec<String>>>> {
+        if let Some(value) = self.get_env::<String>(key)? {
+            return Ok(Some(Value {
+                val: vec![value.val],
+                definition: value.definition,
+            }))
+        }
+
+        match self.get(key)? {
+            Some(CV::List(i, path)) => {
+                Ok(Some(Value {
+                    val: i.into_iter().map(|(s, _)| s).collect(),
+                    definition: Definition::from_path(path),
+                }))
+            }
+            Some(CV::String(i, path)) => {
+                Ok(Some(Value {
+                    val: vec![i],
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("list or string", key, val),
@@ -320,7 +508,7 @@ impl Config {
             Some(CV::Table(i, path)) => {
                 Ok(Some(Value {
                     val: i,
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("table", key, val),
@@ -328,6 +516,65 @@ impl Config {
         }
     }
 
+    /// Returns the environment variables of the `[env]` table, sorted by
+    /// name, which are set for the compiler, build scripts and the programs
+    /// run by Cargo.
+    ///
+    /// Variables already set in Cargo's own environment are left alone
+    /// unless the entry has `force = true`, and values with
+    /// `relative = true` are paths relative to the directory containing the
+    /// `.cargo` directory the config was read from.
+    pub fn env_config(&self) -> CargoResult<&[(String, String)]> {
+        self.env_config.get_or_try_init(|| -> CargoResult<Vec<(String, String)>> {
+            let table = match self.get_table("env")? {
+                Some(table) => table.val,
+                None => return Ok(Vec::new()),
+            };
+            let mut vars = table.into_iter().collect::<Vec<_>>();
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut ret = Vec::new();
+            for (name, value) in vars {
+                let key = format!("env.{}", name);
+                let (value, force, relative, path) = match value {
+                    CV::String(value, path) => (value, false, false, path),
+                    CV::Table(table, path) => {
+                        let value = match table.get("value") {
+                            Some(value) => {
+                                value.string(&format!("{}.value", key))?.0.to_string()
+                            }
+                            None => bail!("`{}` in {} is missing a `value`",
+                                          key, path.display()),
+                        };
+                        let force = match table.get("force") {
+                            Some(force) => force.boolean(&format!("{}.force", key))?.0,
+                            None => false,
+                        };
+                        let relative = match table.get("relative") {
+                            Some(relative) => {
+                                relative.boolean(&format!("{}.relative", key))?.0
+                            }
+                            None => false,
+                        };
+                        (value, force, relative, path)
+                    }
+                    val => return self.expected("string or table", &key, val),
+                };
+                if !force && env::var_os(&name).is_some() {
+                    continue
+                }
+                let value = if relative {
+                    Definition::from_path(path).root(self).join(value)
+                                          .display().to_string()
+                } else {
+                    value
+                };
+                ret.push((name, value));
+            }
+            Ok(ret)
+        }).map(|v| &v[..])
+    }
+
     pub fn get_i64(&self, key: &str) -> CargoResult<Option<Value<i64>>> {
         if let Some(v) = self.get_env(key)? {
             return Ok(Some(v))
@@ -336,7 +583,7 @@ impl Config {
             Some(CV::Integer(i, path)) => {
                 Ok(Some(Value {
                     val: i,
-                    definition: Definition::Path(path),
+                    definition: Definition::from_path(path),
                 }))
             }
             Some(val) => self.expected("integer", key, val),
@@ -426,25 +673,15 @@ impl Config {
     }
 
     pub fn load_values(&self) -> CargoResult<HashMap<String, ConfigValue>> {
-        let mut cfg = CV::Table(HashMap::new(), PathBuf::from("."));
+        // Values found first take precedence, so start out with those given
+        // on the command line.
+        let mut cfg = match self.cli_config.borrow() {
+            Some(cli) => cli.clone(),
+            None => CV::Table(HashMap::new(), PathBuf::from(".")),
+        };
 
         walk_tree(&self.cwd, |path| {
-            let mut contents = String::new();
-            let mut file = File::open(&path)?;
-            file.read_to_string(&mut contents).chain_err(|| {
-                format!("failed to read configuration file `{}`",
-                              path.display())
-            })?;
-            let toml = cargo_toml::parse(&contents,
-                                         &path,
-                                         self).chain_err(|| {
-                format!("could not parse TOML configuration in `{}`",
-                        path.display())
-            })?;
-            let value = CV::from_toml(&path, toml).chain_err(|| {
-                format!("failed to load TOML configuration from `{}`",
-                        path.display())
-            })?;
+            let value = self.load_file(path)?;
             cfg.merge(value).chain_err(|| {
                 format!("failed to merge configuration at `{}`", path.display())
             })?;
@@ -458,6 +695,25 @@ impl Config {
         }
     }
 
+    fn load_file(&self, path: &Path) -> CargoResult<ConfigValue> {
+        let mut contents = String::new();
+        let mut file = File::open(&path)?;
+        file.read_to_string(&mut contents).chain_err(|| {
+            format!("failed to read configuration file `{}`",
+                          path.display())
+        })?;
+        let toml = cargo_toml::parse(&contents,
+                                     &path,
+                                     self).chain_err(|| {
+            format!("could not parse TOML configuration in `{}`",
+                    path.display())
+        })?;
+        CV::from_toml(&path, toml).chain_err(|| {
+            format!("failed to load TOML configuration from `{}`",
+                    path.display())
+        })
+    }
+
     fn load_credentials(&self, cfg: &mut ConfigValue) -> CargoResult<()> {
         let home_path = self.home_path.clone().into_path_unlocked();
         let credentials = home_path.join("credentials");
@@ -477,28 +733,33 @@ impl Config {
             format!("could not parse TOML configuration in `{}`", credentials.display())
         })?;
 
-        let value = CV::from_toml(&credentials, toml).chain_err(|| {
+        let mut value = CV::from_toml(&credentials, toml).chain_err(|| {
             format!("failed to load TOML configuration from `{}`", credentials.display())
         })?;
 
-        let mut cfg = match *cfg {
-            CV::Table(ref mut map, _) => map,
-            _ => unreachable!(),
-        };
-
-        let registry = cfg.entry("registry".into())
-                          .or_insert(CV::Table(HashMap::new(), PathBuf::from(".")));
-
-        match (registry, value) {
-            (&mut CV::Table(ref mut old, _), CV::Table(ref mut new, _)) => {
-                let new = mem::replace(new, HashMap::new());
-                for (key, value) in new.into_iter() {
-                    old.insert(key, value);
+        // Older versions of Cargo stored a single top-level `token` in the
+        // credentials file, which is the token for the default registry.
+        {
+            let value = match value {
+                CV::Table(ref mut value, _) => value,
+                _ => unreachable!(),
+            };
+
+            if let Some(token) = value.remove("token") {
+                if let Vacant(entry) = value.entry("registry".into()) {
+                    let mut map = HashMap::new();
+                    map.insert("token".into(), token);
+                    let table = CV::Table(map, PathBuf::from("."));
+                    entry.insert(table);
                 }
             }
-            _ => unreachable!(),
         }
 
+        // Values in the credentials file take precedence over those in the
+        // rest of the configuration, so merge the configuration into them.
+        mem::swap(cfg, &mut value);
+        cfg.merge(value)?;
+
         Ok(())
     }
 
@@ -553,6 +814,7 @@ pub struct Value<T> {
 pub enum Definition {
     Path(PathBuf),
     Environment,
+    Cli,
 }
 
 impl fmt::Debug for ConfigValue {
@@ -739,10 +1001,21 @@ impl ConfigValue {
 }
 
 impl Definition {
+    /// Returns the definition of a value read from the configuration file at
+    /// `path`, or passed with `--config` on the command line.
+    pub fn from_path(path: PathBuf) -> Definition {
+        if path == Path::new(CLI_PATH) {
+            Definition::Cli
+        } else {
+            Definition::Path(path)
+        }
+    }
+
     pub fn root<'a>(&'a self, config: &'a Config) -> &'a Path {
         match *self {
             Definition::Path(ref p) => p.parent().unwrap().parent().unwrap(),
-            Definition::Environment => config.cwd(),
+            Definition::Environment |
+            Definition::Cli => config.cwd(),
         }
     }
 }
@@ -752,6 +1025,7 @@ impl fmt::Display for Definition {
         match *self {
             Definition::Path(ref p) => p.display().fmt(f),
             Definition::Environment => "the environment".fmt(f),
+            Definition::Cli => "--config cli option".fmt(f),
         }
     }
 }
@@ -806,6 +1080,23 @@ pub fn homedir(cwd: &Path) -> Option<PathBuf> {
     }
 }
 
+/// Returns whether the `--config` values in `cli` define `key`.
+fn cli_contains(cli: &ConfigValue, key: &str) -> bool {
+    let mut val = cli;
+    for part in key.split('.') {
+        val = match *val {
+            CV::Table(ref map, _) => {
+                match map.get(part) {
+                    Some(val) => val,
+                    None => return false,
+                }
+            }
+            _ => return false,
+        };
+    }
+    true
+}
+
 fn walk_tree<F>(pwd: &Path, mut walk: F) -> CargoResult<()>
     where F: FnMut(&Path) -> CargoResult<()>
 {
@@ -835,7 +1126,8 @@ fn walk_tree<F>(pwd: &Path, mut walk: F) -> CargoResult<()>
 }
 
 pub fn save_credentials(cfg: &Config,
-                       token: String) -> CargoResult<()> {
+                        token: String,
+                        registry: Option<String>) -> CargoResult<()> {
     let mut file = {
         cfg.home_path.create_dir()?;
         cfg.home_path.open_rw(Path::new("credentials"), cfg,
@@ -848,10 +1140,38 @@ pub fn save_credentials(cfg: &Config,
                       file.path().display())
     })?;
     let mut toml = cargo_toml::parse(&contents, file.path(), cfg)?;
-    toml.as_table_mut()
-        .unwrap()
-        .insert("token".to_string(),
-                ConfigValue::String(token, file.path().to_path_buf()).into_toml());
+    let token = ConfigValue::String(token, file.path().to_path_buf()).into_toml();
+    {
+        let root = toml.as_table_mut().unwrap();
+
+        // Move a token saved by older versions of Cargo to its new location.
+        if let Some(old_token) = root.remove("token") {
+            let mut table = toml::value::Table::new();
+            table.insert("token".to_string(), old_token);
+            root.entry("registry".to_string())
+                .or_insert(toml::Value::Table(table));
+        }
+
+        // The default registry's token lives in `[registry]`, and those of
+        // alternative registries in `[registries.<name>]`.
+        let table = match registry {
+            Some(registry) => {
+                credentials_table(root, "registries")?
+                    .entry(registry)
+                    .or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+            }
+            None => {
+                root.entry("registry".to_string())
+                    .or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+            }
+        };
+        match *table {
+            toml::Value::Table(ref mut table) => {
+                table.insert("token".to_string(), token);
+            }
+            _ => bail!("expected a table in the credentials file"),
+        }
+    }
 
     let contents = toml.to_string();
     file.seek(SeekFrom::Start(0))?;
@@ -861,6 +1181,16 @@ pub fn save_credentials(cfg: &Config,
 
     return Ok(());
 
+    fn credentials_table<'a>(root: &'a mut toml::value::Table, key: &str)
+                             -> CargoResult<&'a mut toml::value::Table> {
+        let value = root.entry(key.to_string())
+                        .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+        match *value {
+            toml::Value::Table(ref mut table) => Ok(table),
+            _ => bail!("expected `{}` to be a table in the credentials file", key),
+        }
+    }
+
     #[cfg(unix)]
     fn set_permissions(file: & File, mode: u32) -> CargoResult<()> {
         use std::os::unix::fs::PermissionsExt;
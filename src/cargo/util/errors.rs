@@ -36,6 +36,7 @@ error_chain! {
         Parse(string::ParseError);
         Git(git2::Error);
         Curl(curl::Error);
+        CurlMulti(curl::MultiError);
     }
 
     errors {
@@ -51,6 +52,10 @@ error_chain! {
             description(&test_err.desc)
             display("{}", &test_err.desc)
         }
+        GitCli(proc_err: ProcessError) {
+            description(&proc_err.desc)
+            display("{}", &proc_err.desc)
+        }
         HttpNot200(code: u32, url: String) {
             description("failed to get a 200 response")
             display("failed to get 200 response from `{}`, got {}", url, code)
@@ -69,8 +74,10 @@ impl CargoError {
             &CargoErrorKind::TomlSer(_) => true,
             &CargoErrorKind::TomlDe(_) => true,
             &CargoErrorKind::Curl(_) => true,
+            &CargoErrorKind::CurlMulti(_) => true,
             &CargoErrorKind::HttpNot200(..) => true,
             &CargoErrorKind::ProcessErrorKind(_) => true,
+            &CargoErrorKind::GitCli(_) => true,
             &CargoErrorKind::CrateRegistry(_) => true,
             &CargoErrorKind::ParseSemver(_) |
             &CargoErrorKind::Semver(_) |
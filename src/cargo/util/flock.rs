@@ -169,7 +169,24 @@ impl Filesystem {
                   OpenOptions::new().read(true).write(true).create(true),
                   State::Exclusive,
                   config,
-                  msg)
+                  msg,
+                  true).map(|lock| lock.unwrap())
+    }
+
+    /// Like `open_rw`, except that `None` is returned instead of blocking if
+    /// another process currently holds a lock on the file.
+    pub fn try_open_rw<P>(&self,
+                          path: P,
+                          config: &Config,
+                          msg: &str) -> CargoResult<Option<FileLock>>
+        where P: AsRef<Path>
+    {
+        self.open(path.as_ref(),
+                  OpenOptions::new().read(true).write(true).create(true),
+                  State::Exclusive,
+                  config,
+                  msg,
+                  false)
     }
 
     /// Opens shared access to a file, returning the locked version of a file.
@@ -191,15 +208,20 @@ impl Filesystem {
                   OpenOptions::new().read(true),
                   State::Shared,
                   config,
-                  msg)
+                  msg,
+                  true).map(|lock| lock.unwrap())
     }
 
+    /// Opens and locks the file at `path`. If the lock is taken, this blocks
+    /// for it after printing `msg` if `blocking` is set, and returns `None`
+    /// otherwise.
     fn open(&self,
             path: &Path,
             opts: &OpenOptions,
             state: State,
             config: &Config,
-            msg: &str) -> CargoResult<FileLock> {
+            msg: &str,
+            blocking: bool) -> CargoResult<Option<FileLock>> {
         let path = self.root.join(path);
 
         // If we want an exclusive lock then if we fail because of NotFound it's
@@ -215,21 +237,23 @@ impl Filesystem {
         }).chain_err(|| {
             format!("failed to open: {}", path.display())
         })?;
-        match state {
+        let locked = match state {
             State::Exclusive => {
-                acquire(config, msg, &path,
+                acquire(config, msg, blocking, &path,
                         &|| f.try_lock_exclusive(),
-                        &|| f.lock_exclusive())?;
+                        &|| f.lock_exclusive())?
             }
             State::Shared => {
-                acquire(config, msg, &path,
+                acquire(config, msg, blocking, &path,
                         &|| f.try_lock_shared(),
-                        &|| f.lock_shared())?;
+                        &|| f.lock_shared())?
             }
-            State::Unlocked => {}
-
+            State::Unlocked => true,
+        };
+        if !locked {
+            return Ok(None)
         }
-        Ok(FileLock { f: Some(f), path: path, state: state })
+        Ok(Some(FileLock { f: Some(f), path: path, state: state }))
     }
 }
 
@@ -244,15 +268,17 @@ impl Filesystem {
 /// to the console if we have to wait for it. It will first attempt to use `try`
 /// to acquire a lock on the crate, and in the case of contention it will emit a
 /// status message based on `msg` to `config`'s shell, and then use `block` to
-/// block waiting to acquire a lock.
+/// block waiting to acquire a lock. If `blocking` isn't set, `false` is
+/// returned instead of waiting.
 ///
 /// Returns an error if the lock could not be acquired or if any error other
 /// than a contention error happens.
 fn acquire(config: &Config,
            msg: &str,
+           blocking: bool,
            path: &Path,
            try: &Fn() -> io::Result<()>,
-           block: &Fn() -> io::Result<()>) -> CargoResult<()> {
+           block: &Fn() -> io::Result<()>) -> CargoResult<bool> {
 
     // File locking on Unix is currently implemented via `flock`, which is known
     // to be broken on NFS. We could in theory just ignore errors that happen on
@@ -265,21 +291,21 @@ fn acquire(config: &Config,
     //
     // [1]: https://github.com/rust-lang/cargo/issues/2615
     if is_on_nfs_mount(path) {
-        return Ok(())
+        return Ok(true)
     }
 
     match try() {
-        Ok(()) => return Ok(()),
+        Ok(()) => return Ok(true),
 
         // In addition to ignoring NFS which is commonly not working we also
         // just ignore locking on filesystems that look like they don't
         // implement file locking. We detect that here via the return value of
         // locking (e.g. inspecting errno).
         #[cfg(unix)]
-        Err(ref e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(()),
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(true),
 
         #[cfg(target_os = "linux")]
-        Err(ref e) if e.raw_os_error() == Some(libc::ENOSYS) => return Ok(()),
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOSYS) => return Ok(true),
 
         Err(e) => {
             if e.raw_os_error() != lock_contended_error().raw_os_error() {
@@ -289,12 +315,16 @@ fn acquire(config: &Config,
             }
         }
     }
+    if !blocking {
+        return Ok(false)
+    }
     let msg = format!("waiting for file lock on {}", msg);
     config.shell().status_with_color("Blocking", &msg, Cyan)?;
 
-    return block().chain_err(|| {
+    block().chain_err(|| {
         format!("failed to lock file: {}", path.display())
-    });
+    })?;
+    return Ok(true);
 
     #[cfg(all(target_os = "linux", not(target_env = "musl")))]
     fn is_on_nfs_mount(path: &Path) -> bool {
@@ -2,6 +2,7 @@ use serde::ser;
 use serde_json::{self, Value};
 
 use core::{PackageId, Target, Profile};
+use util::ProcessBuilder;
 
 pub trait Message: ser::Serialize {
     fn reason(&self) -> &str;
@@ -30,6 +31,7 @@ impl<'a> Message for FromCompiler<'a> {
 pub struct Artifact<'a> {
     pub package_id: &'a PackageId,
     pub target: &'a Target,
+    pub target_triple: &'a str,
     pub profile: &'a Profile,
     pub features: Vec<String>,
     pub filenames: Vec<String>,
@@ -56,3 +58,58 @@ impl<'a> Message for BuildScript<'a> {
         "build-script-executed"
     }
 }
+
+#[derive(Serialize)]
+pub struct RunTarget<'a> {
+    pub package_id: &'a PackageId,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl<'a> RunTarget<'a> {
+    /// Describes `process`, which is about to be run for `package_id`. With
+    /// a target runner configured `program` is the runner.
+    pub fn new(package_id: &'a PackageId, process: &ProcessBuilder) -> RunTarget<'a> {
+        RunTarget {
+            package_id: package_id,
+            program: process.get_program().to_string_lossy().into_owned(),
+            args: process.get_args().iter().map(|arg| {
+                arg.to_string_lossy().into_owned()
+            }).collect(),
+        }
+    }
+}
+
+impl<'a> Message for RunTarget<'a> {
+    fn reason(&self) -> &str {
+        "run-target"
+    }
+}
+
+#[derive(Serialize)]
+pub struct TimingInfo<'a> {
+    pub duration: f64,
+    pub jobs: u32,
+    pub max_concurrency: usize,
+    pub average_concurrency: f64,
+    pub idle_parallelism: f64,
+    pub units: Vec<UnitTiming<'a>>,
+    /// Indices in `units` of the units on the critical path, in build order.
+    pub critical_path: Vec<usize>,
+}
+
+#[derive(Serialize)]
+pub struct UnitTiming<'a> {
+    pub package_id: &'a PackageId,
+    pub target: &'a Target,
+    pub fresh: bool,
+    pub start: f64,
+    pub duration: f64,
+    pub rmeta_time: Option<f64>,
+}
+
+impl<'a> Message for TimingInfo<'a> {
+    fn reason(&self) -> &str {
+        "timing-info"
+    }
+}
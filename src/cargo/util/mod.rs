@@ -12,13 +12,15 @@ pub use self::lev_distance::{lev_distance};
 pub use self::paths::{join_paths, path2bytes, bytes2path, dylib_path};
 pub use self::paths::{normalize_path, dylib_path_envvar, without_prefix};
 pub use self::process_builder::{process, ProcessBuilder};
-pub use self::rustc::Rustc;
+pub use self::progress::Progress;
+pub use self::rustc::{Rustc, parse_rust_version, rust_version_compatible};
 pub use self::sha256::Sha256;
 pub use self::to_semver::ToSemver;
 pub use self::to_url::ToUrl;
 pub use self::vcs::{GitRepo, HgRepo, PijulRepo, FossilRepo};
 pub use self::read2::read2;
 
+pub mod cache_usage;
 pub mod config;
 pub mod errors;
 pub mod graph;
@@ -31,6 +33,7 @@ pub mod network;
 pub mod paths;
 pub mod process_builder;
 pub mod profile;
+pub mod progress;
 pub mod to_semver;
 pub mod to_url;
 pub mod toml;
@@ -7,7 +7,10 @@ use util::Config;
 use util::errors::{CargoError, CargoErrorKind, CargoResult};
 
 use git2;
-fn maybe_spurious<E, EKind>(err: &E) -> bool
+
+/// Returns whether `err` is a network error which may go away when the
+/// operation is retried.
+pub fn maybe_spurious<E, EKind>(err: &E) -> bool
     where E: ChainedError<ErrorKind=EKind> + 'static {
     //Error inspection in non-verbose mode requires inspecting the
     //error kind to avoid printing Internal errors. The downcasting
@@ -43,6 +46,8 @@ fn maybe_spurious<E, EKind>(err: &E) -> bool
                 &CargoErrorKind::HttpNot200(code, ref _url) if 500 <= code && code < 600 => {
                     return true
                 }
+                // `git` doesn't tell network errors apart from other ones.
+                &CargoErrorKind::GitCli(_) => return true,
                 _ => ()
             }
         }
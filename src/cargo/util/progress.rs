@@ -0,0 +1,169 @@
+use std::cmp;
+use std::env;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use util::{Config, CargoResult};
+
+/// A progress bar drawn on the last line of the shell's stderr.
+///
+/// The bar is only displayed when stderr is a terminal (or when
+/// `term.progress.when` is `always`), and is erased by the shell before any
+/// other status line or warning is printed, so output keeps interleaving
+/// correctly with it.
+pub struct Progress<'cfg> {
+    state: Option<State<'cfg>>,
+}
+
+struct State<'cfg> {
+    config: &'cfg Config,
+    name: String,
+    width: usize,
+    created: Instant,
+    last_update: Option<Instant>,
+}
+
+impl<'cfg> Progress<'cfg> {
+    /// Creates a progress bar with the status header `name`, or a disabled
+    /// one if the configuration or the terminal don't allow drawing it.
+    pub fn new(name: &str, config: &'cfg Config) -> CargoResult<Progress<'cfg>> {
+        let when = config.get_string("term.progress.when")?;
+        let always = match when.as_ref().map(|w| &w.val[..]) {
+            Some("auto") | None => false,
+            Some("always") => true,
+            Some("never") => return Ok(Progress { state: None }),
+            Some(other) => {
+                bail!("invalid value for `term.progress.when` in {}: \
+                       expected `auto`, `always` or `never`, but found `{}`",
+                      when.as_ref().unwrap().definition, other)
+            }
+        };
+        let width = match config.get_i64("term.progress.width")? {
+            Some(w) => Some(cmp::max(w.val, 0) as usize),
+            None => config.shell().err_width(),
+        };
+
+        // Dumb terminals can't move the cursor back to redraw the bar, and
+        // CI logs would end up with every intermediate state of it.
+        let dumb = env::var("TERM").map(|t| t == "dumb").unwrap_or(false) ||
+                   env::var_os("CI").is_some();
+        let enabled = if always {
+            true
+        } else {
+            config.shell().is_err_tty() && !dumb
+        };
+
+        let state = match width {
+            Some(width) if enabled => Some(State {
+                config: config,
+                name: name.to_string(),
+                width: width,
+                created: Instant::now(),
+                last_update: None,
+            }),
+            _ => None,
+        };
+        Ok(Progress { state: state })
+    }
+
+    /// Updates the bar to show that `cur` out of `max` steps are done, with
+    /// `msg` describing what's currently in progress.
+    ///
+    /// Redrawing is throttled so that quick operations never show a bar and
+    /// long ones don't flood the terminal.
+    pub fn tick(&mut self, cur: usize, max: usize, msg: &str) -> CargoResult<()> {
+        match self.state {
+            Some(ref mut s) => s.tick(cur, max, msg),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the bar is drawn at all, as opposed to being disabled by the
+    /// configuration or because stderr isn't a terminal.
+    pub fn is_enabled(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Erases the bar if it's currently drawn.
+    pub fn clear(&mut self) {
+        if let Some(ref mut s) = self.state {
+            s.clear();
+        }
+    }
+}
+
+impl<'cfg> State<'cfg> {
+    fn tick(&mut self, cur: usize, max: usize, msg: &str) -> CargoResult<()> {
+        let now = Instant::now();
+        match self.last_update {
+            None if now - self.created < Duration::from_millis(500) => return Ok(()),
+            Some(last) if now - last < Duration::from_millis(100) => return Ok(()),
+            _ => {}
+        }
+        self.last_update = Some(now);
+
+        let line = match render(self.width, cur, max, msg) {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        let mut shell = self.config.shell();
+        shell.status_header(&self.name)?;
+        write!(shell.err(), "{}\r", line)?;
+        shell.set_needs_clear(self.width);
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        if self.last_update.is_some() {
+            self.config.shell().err_erase_line();
+        }
+    }
+}
+
+impl<'cfg> Drop for State<'cfg> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Renders everything after the status header, e.g.
+/// `[=====>     ] 12/60: foo, bar`, so that it fits in `width` columns.
+fn render(width: usize, cur: usize, max: usize, msg: &str) -> Option<String> {
+    // The header is right-aligned to 12 columns followed by a space, and the
+    // last column is left empty so the terminal doesn't wrap.
+    let counts = format!(" {}/{}", cur, max);
+    let avail = width.saturating_sub(13 + 1 + 2 + counts.len());
+    let bar_width = cmp::min(avail, 40);
+    if bar_width < 15 {
+        return None
+    }
+
+    let pct = if max == 0 { 0.0 } else { cur as f64 / max as f64 };
+    let done = cmp::min((pct * bar_width as f64) as usize, bar_width);
+    let mut line = String::with_capacity(width);
+    line.push('[');
+    for i in 0..bar_width {
+        line.push(if i < done {
+            '='
+        } else if i == done {
+            '>'
+        } else {
+            ' '
+        });
+    }
+    line.push(']');
+    line.push_str(&counts);
+
+    let room = avail - bar_width;
+    if !msg.is_empty() && room > 5 {
+        line.push_str(": ");
+        let room = room - 2;
+        if msg.chars().count() > room {
+            line.extend(msg.chars().take(room - 3));
+            line.push_str("...");
+        } else {
+            line.push_str(msg);
+        }
+    }
+    Some(line)
+}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use semver::Version;
+
 use util::{self, CargoResult, internal, ProcessBuilder};
 
 pub struct Rustc {
@@ -40,6 +42,24 @@ impl Rustc {
         })
     }
 
+    /// Returns the version of this compiler, as reported on the `release:`
+    /// line of `rustc -vV`.
+    pub fn version(&self) -> CargoResult<Version> {
+        let release = self.verbose_version.lines().find(|l| {
+            l.starts_with("release: ")
+        }).map(|l| &l[9..]).ok_or(internal("rustc -v didn't have a line for `release:`"))?;
+        Version::parse(release).map_err(|_| {
+            internal(format!("rustc -v returned an invalid release: `{}`", release))
+        })
+    }
+
+    /// Returns whether this compiler is at least as new as `rust_version`, a
+    /// package's minimum supported Rust version. Nightly and beta compilers
+    /// count as the release they will become.
+    pub fn supports(&self, rust_version: &Version) -> CargoResult<bool> {
+        Ok(rust_version_compatible(rust_version, &self.version()?))
+    }
+
     pub fn process(&self) -> ProcessBuilder {
         if let Some(ref wrapper) = self.wrapper {
             let mut cmd = util::process(wrapper);
@@ -52,3 +72,22 @@ impl Rustc {
         }
     }
 }
+
+/// Parses the `rust-version` of a package, such as `1.56` or `1.56.1`.
+pub fn parse_rust_version(s: &str) -> CargoResult<Version> {
+    let parts = s.split('.').collect::<Vec<_>>();
+    if parts.len() < 2 || parts.len() > 3 ||
+       parts.iter().any(|p| p.is_empty() || !p.chars().all(|c| c.is_digit(10))) {
+        bail!("`{}` is not a valid rust-version, expected a version like \
+               \"1.56\" or \"1.56.1\"", s)
+    }
+    let patch = if parts.len() == 3 {""} else {".0"};
+    Ok(Version::parse(&format!("{}{}", s, patch))?)
+}
+
+/// Returns whether a compiler of version `rustc` can build a package with
+/// the given `rust_version`, ignoring any pre-release of the compiler.
+pub fn rust_version_compatible(rust_version: &Version, rustc: &Version) -> bool {
+    (rustc.major, rustc.minor, rustc.patch) >=
+        (rust_version.major, rust_version.minor, rust_version.patch)
+}
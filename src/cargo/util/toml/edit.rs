@@ -0,0 +1,467 @@
+//! A minimal editor for TOML documents such as `Cargo.toml`, which changes
+//! individual entries while leaving the formatting and comments of the rest
+//! of the document untouched.
+//!
+//! The manifest types in the parent module can only be deserialized, and
+//! serializing them back would lose all formatting, so commands which edit
+//! manifests work on the lines of the document instead. Only the shapes of
+//! TOML found in manifests are supported: `[table]` headers, `key = value`
+//! entries (whose values may span several lines) and comments.
+
+use std::fmt;
+
+use serde_json;
+use toml;
+
+use util::errors::{CargoResult, CargoResultExt};
+
+pub struct Document {
+    lines: Vec<String>,
+    trailing_newline: bool,
+}
+
+/// A table of the document, with its body on the lines `start..end`.
+struct Table {
+    path: Vec<String>,
+    header: Option<usize>,
+    start: usize,
+    end: usize,
+    array: bool,
+}
+
+/// An entry of a table, on the lines `start..end`, whose value starts at the
+/// byte offset `value` of the first line.
+struct Entry {
+    key: String,
+    start: usize,
+    end: usize,
+    value: usize,
+}
+
+impl Document {
+    pub fn parse(contents: &str) -> CargoResult<Document> {
+        contents.parse::<toml::Value>().chain_err(|| "invalid TOML document")?;
+        Ok(Document {
+            lines: contents.lines().map(|s| s.to_string()).collect(),
+            trailing_newline: contents.is_empty() || contents.ends_with('\n'),
+        })
+    }
+
+    /// Returns the paths of all the (non-array) tables with a header.
+    pub fn table_paths(&self) -> Vec<Vec<String>> {
+        self.tables().into_iter().filter(|t| {
+            t.header.is_some() && !t.array
+        }).map(|t| t.path).collect()
+    }
+
+    pub fn has_table(&self, path: &[&str]) -> bool {
+        self.table(path).is_some()
+    }
+
+    /// Returns the keys of the entries of the table at `path`, in order.
+    pub fn keys(&self, path: &[&str]) -> Vec<String> {
+        match self.table(path) {
+            Some(table) => self.entries(&table).into_iter().map(|e| e.key).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the value of `key` in the table at `path`.
+    pub fn get(&self, path: &[&str], key: &str) -> Option<toml::Value> {
+        let table = match self.table(path) {
+            Some(table) => table,
+            None => return None,
+        };
+        let entry = match self.entries(&table).into_iter().find(|e| e.key == key) {
+            Some(entry) => entry,
+            None => return None,
+        };
+        let mut text = self.lines[entry.start][entry.value..].to_string();
+        for line in self.lines[entry.start + 1..entry.end].iter() {
+            text.push('\n');
+            text.push_str(line);
+        }
+        match format!("value = {}", text).parse::<toml::Value>() {
+            Ok(toml::Value::Table(mut table)) => table.remove("value"),
+            _ => None,
+        }
+    }
+
+    /// Sets `key` in the table at `path` to `value`, which must be a TOML
+    /// value already formatted as it should appear in the document.
+    ///
+    /// An existing entry is replaced in place. Otherwise the entry is added
+    /// at its sorted position if the table is sorted, or after the last
+    /// entry, and the table is created at the end of the document if needed.
+    pub fn set(&mut self, path: &[&str], key: &str, value: &str) {
+        let table = match self.table(path) {
+            Some(table) => table,
+            None => {
+                if self.lines.last().map_or(false, |l| !l.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                let path = path.iter().map(|p| key_to_string(p)).collect::<Vec<_>>();
+                self.lines.push(format!("[{}]", path.join(".")));
+                self.lines.push(format!("{} = {}", key_to_string(key), value));
+                return
+            }
+        };
+        let entries = self.entries(&table);
+        if let Some(entry) = entries.iter().find(|e| e.key == key) {
+            let prefix = self.lines[entry.start][..entry.value].trim_right().to_string();
+            let mut line = format!("{} {}", prefix, value);
+            // Keep the comment following the old value.
+            if let (_, Some(pos)) = scan_value(&self.lines, entry.start) {
+                line.push_str(" ");
+                line.push_str(&self.lines[entry.end - 1][pos..]);
+            }
+            self.lines.drain(entry.start..entry.end);
+            self.lines.insert(entry.start, line);
+            return
+        }
+
+        let line = format!("{} = {}", key_to_string(key), value);
+        let sorted = entries.windows(2).all(|w| w[0].key <= w[1].key);
+        let idx = if sorted {
+            entries.iter().find(|e| e.key.as_str() > key).map(|e| e.start)
+        } else {
+            None
+        };
+        let idx = idx.unwrap_or_else(|| {
+            entries.last().map(|e| e.end).unwrap_or(table.start)
+        });
+        self.lines.insert(idx, line);
+    }
+
+    /// Removes `key` from the table at `path`, returning whether it existed.
+    pub fn remove(&mut self, path: &[&str], key: &str) -> bool {
+        let table = match self.table(path) {
+            Some(table) => table,
+            None => return false,
+        };
+        match self.entries(&table).into_iter().find(|e| e.key == key) {
+            Some(entry) => {
+                self.lines.drain(entry.start..entry.end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the table at `path` along with all its entries, returning
+    /// whether it existed.
+    ///
+    /// Blank lines and comments after the last entry are left in place, as
+    /// they usually belong to the table following it.
+    pub fn remove_table(&mut self, path: &[&str]) -> bool {
+        let table = match self.table(path) {
+            Some(table) => table,
+            None => return false,
+        };
+        let header = match table.header {
+            Some(header) => header,
+            None => return false,
+        };
+        let end = self.entries(&table).last().map(|e| e.end).unwrap_or(table.start);
+        // Drop the blank lines separating the table from the previous one if
+        // the lines after it separate them already, or it's the last one.
+        let mut start = header;
+        let blank_after = self.lines.get(end).map_or(true, |l| l.trim().is_empty());
+        while start > 0 && blank_after && self.lines[start - 1].trim().is_empty() {
+            start -= 1;
+        }
+        self.lines.drain(start..end);
+        true
+    }
+
+    fn table(&self, path: &[&str]) -> Option<Table> {
+        self.tables().into_iter().find(|t| {
+            !t.array && t.path.len() == path.len() &&
+                t.path.iter().zip(path).all(|(a, b)| a == b)
+        })
+    }
+
+    fn tables(&self) -> Vec<Table> {
+        let mut ret = vec![Table {
+            path: Vec::new(),
+            header: None,
+            start: 0,
+            end: self.lines.len(),
+            array: false,
+        }];
+        let mut i = 0;
+        while i < self.lines.len() {
+            if let Some((path, array)) = parse_header(&self.lines[i]) {
+                ret.last_mut().unwrap().end = i;
+                ret.push(Table {
+                    path: path,
+                    header: Some(i),
+                    start: i + 1,
+                    end: self.lines.len(),
+                    array: array,
+                });
+                i += 1;
+                continue
+            }
+            // Skip over values spanning several lines, which may contain
+            // lines looking like headers.
+            i = match parse_key(self.lines[i].trim_left()) {
+                Some(_) => value_end(&self.lines, i),
+                None => i + 1,
+            };
+        }
+        ret
+    }
+
+    fn entries(&self, table: &Table) -> Vec<Entry> {
+        let mut ret = Vec::new();
+        let mut i = table.start;
+        while i < table.end {
+            let line = &self.lines[i];
+            let indent = line.len() - line.trim_left().len();
+            match parse_key(line.trim_left()) {
+                Some((key, value)) => {
+                    let end = value_end(&self.lines, i);
+                    ret.push(Entry {
+                        key: key,
+                        start: i,
+                        end: end,
+                        value: indent + value,
+                    });
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+        ret
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))?;
+        if self.trailing_newline {
+            write!(f, "\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats `key` as a TOML key, quoting it if needed.
+pub fn key_to_string(key: &str) -> String {
+    let bare = !key.is_empty() && key.chars().all(is_bare_key_char);
+    if bare {
+        key.to_string()
+    } else if !key.contains('\'') && !key.contains('\n') {
+        format!("'{}'", key)
+    } else {
+        string_to_string(key)
+    }
+}
+
+/// Formats a string as a TOML basic string.
+pub fn string_to_string(s: &str) -> String {
+    // A JSON string is also a valid TOML basic string.
+    serde_json::to_string(s).unwrap()
+}
+
+/// Formats `value` on a single line, using inline tables for tables.
+pub fn value_to_string(value: &toml::Value) -> String {
+    match *value {
+        toml::Value::String(ref s) => string_to_string(s),
+        toml::Value::Integer(i) => i.to_string(),
+        toml::Value::Float(f) => format!("{:?}", f),
+        toml::Value::Boolean(b) => b.to_string(),
+        toml::Value::Datetime(ref d) => d.to_string(),
+        toml::Value::Array(ref values) => {
+            let values = values.iter().map(value_to_string).collect::<Vec<_>>();
+            format!("[{}]", values.join(", "))
+        }
+        toml::Value::Table(ref table) => {
+            let entries = table.iter().map(|(k, v)| {
+                (k.clone(), v.clone())
+            }).collect::<Vec<_>>();
+            inline_table(&entries)
+        }
+    }
+}
+
+/// Formats the `entries` as an inline table, keeping their order.
+pub fn inline_table(entries: &[(String, toml::Value)]) -> String {
+    if entries.is_empty() {
+        return "{}".to_string()
+    }
+    let entries = entries.iter().map(|&(ref k, ref v)| {
+        format!("{} = {}", key_to_string(k), value_to_string(v))
+    }).collect::<Vec<_>>();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    match c {
+        'a'...'z' | 'A'...'Z' | '0'...'9' | '-' | '_' => true,
+        _ => false,
+    }
+}
+
+/// Parses a `[table]` or `[[array]]` header, returning the path of the
+/// table and whether it's an array of tables.
+fn parse_header(line: &str) -> Option<(Vec<String>, bool)> {
+    let line = line.trim();
+    let (array, rest) = if line.starts_with("[[") {
+        (true, &line[2..])
+    } else if line.starts_with('[') {
+        (false, &line[1..])
+    } else {
+        return None
+    };
+    let mut path = Vec::new();
+    let mut rest = rest;
+    loop {
+        let (part, len) = match parse_simple_key(rest.trim_left()) {
+            Some(key) => key,
+            None => return None,
+        };
+        rest = &rest[rest.len() - rest.trim_left().len() + len..];
+        rest = rest.trim_left();
+        path.push(part);
+        if rest.starts_with('.') {
+            rest = &rest[1..];
+        } else {
+            break
+        }
+    }
+    let close = if array { "]]" } else { "]" };
+    if !rest.starts_with(close) {
+        return None
+    }
+    let rest = rest[close.len()..].trim_left();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return None
+    }
+    Some((path, array))
+}
+
+/// Parses the key at the start of an entry line, returning it along with
+/// the byte offset at which its value starts.
+fn parse_key(line: &str) -> Option<(String, usize)> {
+    let (key, len) = match parse_simple_key(line) {
+        Some(key) => key,
+        None => return None,
+    };
+    let rest = &line[len..];
+    let after_ws = rest.trim_left();
+    if !after_ws.starts_with('=') {
+        return None
+    }
+    let value = &after_ws[1..];
+    let offset = line.len() - value.len() + (value.len() - value.trim_left().len());
+    Some((key, offset))
+}
+
+/// Parses a bare or quoted key, returning it and its length in `s`.
+fn parse_simple_key(s: &str) -> Option<(String, usize)> {
+    if s.starts_with('\'') {
+        return s[1..].find('\'').map(|end| (s[1..end + 1].to_string(), end + 2))
+    }
+    if s.starts_with('"') {
+        let mut key = String::new();
+        let mut chars = s.char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Some((key, i + 1)),
+                '\\' => {
+                    match chars.next() {
+                        Some((_, 'n')) => key.push('\n'),
+                        Some((_, 't')) => key.push('\t'),
+                        Some((_, c)) => key.push(c),
+                        None => return None,
+                    }
+                }
+                c => key.push(c),
+            }
+        }
+        return None
+    }
+    let len = s.find(|c| !is_bare_key_char(c)).unwrap_or(s.len());
+    if len == 0 {
+        None
+    } else {
+        Some((s[..len].to_string(), len))
+    }
+}
+
+/// Returns the index of the line after the end of the value of the entry
+/// starting on line `start`.
+fn value_end(lines: &[String], start: usize) -> usize {
+    scan_value(lines, start).0
+}
+
+/// Scans the value of the entry starting on line `start`, returning the index
+/// of the line after its end and the byte offset of the comment following
+/// the value on its last line, if any.
+fn scan_value(lines: &[String], start: usize) -> (usize, Option<usize>) {
+    let mut depth = 0i32;
+    let mut multiline: Option<&'static str> = None;
+    let mut comment = None;
+    let mut i = start;
+    while i < lines.len() {
+        let line = &lines[i];
+        comment = None;
+        let mut pos = if i == start {
+            line.find('=').map(|p| p + 1).unwrap_or(line.len())
+        } else {
+            0
+        };
+        while pos < line.len() {
+            let rest = &line[pos..];
+            if let Some(delim) = multiline {
+                match rest.find(delim) {
+                    Some(p) => {
+                        multiline = None;
+                        pos += p + 3;
+                    }
+                    None => pos = line.len(),
+                }
+                continue
+            }
+            if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+                multiline = Some(if rest.starts_with('"') {"\"\"\""} else {"'''"});
+                pos += 3;
+                continue
+            }
+            let c = rest.chars().next().unwrap();
+            match c {
+                '#' => {
+                    comment = Some(pos);
+                    break
+                }
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth -= 1,
+                '"' => {
+                    // Skip over the basic string, along with escapes.
+                    let mut escaped = false;
+                    let end = rest[1..].char_indices().find(|&(_, c)| {
+                        let done = !escaped && c == '"';
+                        escaped = !escaped && c == '\\';
+                        done
+                    }).map(|(p, _)| p + 2).unwrap_or(rest.len());
+                    pos += end;
+                    continue
+                }
+                '\'' => {
+                    let end = rest[1..].find('\'').map(|p| p + 2).unwrap_or(rest.len());
+                    pos += end;
+                    continue
+                }
+                _ => {}
+            }
+            pos += c.len_utf8();
+        }
+        i += 1;
+        if depth <= 0 && multiline.is_none() {
+            break
+        }
+    }
+    (i, comment)
+}
@@ -1,6 +1,7 @@
-use std::collections::{HashMap, HashSet, BTreeSet};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet};
 use std::fmt;
 use std::fs;
+use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str;
@@ -8,19 +9,23 @@ use std::str;
 use toml;
 use semver::{self, VersionReq};
 use serde::ser;
-use serde::de::{self, Deserialize};
+use serde::de::{self, Deserialize, IntoDeserializer};
 use serde_ignored;
+use url::Url;
 
 use core::{SourceId, Profiles, PackageIdSpec, GitReference, WorkspaceConfig};
+use core::{CustomProfile, ProfileOverride, ProfilePackageSpec};
 use core::{Summary, Manifest, Target, Dependency, PackageId};
-use core::{EitherManifest, VirtualManifest};
+use core::{EitherManifest, VirtualManifest, Edition, find_workspace_root};
 use core::dependency::{Kind, Platform};
-use core::manifest::{LibKind, Profile, ManifestMetadata};
+use core::resolver::ResolveBehavior;
+use core::manifest::{LibKind, Lints, Profile, ManifestMetadata};
 use sources::CRATES_IO;
 use util::paths;
 use util::{self, ToUrl, Config};
 use util::errors::{CargoError, CargoResult, CargoResultExt};
 
+pub mod edit;
 mod targets;
 use self::targets::targets;
 
@@ -48,11 +53,12 @@ fn do_read_manifest(contents: &str,
     };
 
     let mut unused = BTreeSet::new();
-    let manifest: TomlManifest = serde_ignored::deserialize(toml, |path| {
+    let mut manifest: TomlManifest = serde_ignored::deserialize(toml, |path| {
         let mut key = String::new();
         stringify(&mut key, &path);
         unused.insert(key);
     })?;
+    manifest.inherit_from_workspace(manifest_file, config)?;
 
     let manifest = Rc::new(manifest);
     return match TomlManifest::to_real_manifest(&manifest,
@@ -107,6 +113,42 @@ fn do_read_manifest(contents: &str,
     }
 }
 
+/// Checks the syntax of the `[features]` table. Whether the features and
+/// dependencies referenced actually exist is checked by `Summary::new`.
+fn validate_feature_syntax(features: &HashMap<String, Vec<String>>) -> CargoResult<()> {
+    for (feature, list) in features {
+        if feature.starts_with("dep:") || feature.contains('/') {
+            bail!("feature name `{}` may not start with `dep:` or contain `/`",
+                  feature)
+        }
+        for f in list {
+            if f.starts_with("dep:") && f.contains('/') {
+                bail!("feature `{}` includes `{}`, but `dep:` cannot be used \
+                       to enable features of a dependency", feature, f)
+            }
+            if let Some(i) = f.find('?') {
+                if f[i + 1..].chars().next() != Some('/') || i == 0 {
+                    bail!("feature `{}` includes `{}`, but `?` may only be \
+                           used in the form `dep-name?/feature-name`",
+                          feature, f)
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the `TomlManifest` at `path` as written, without resolving it.
+fn read_toml_manifest(path: &Path, config: &Config) -> CargoResult<TomlManifest> {
+    (|| -> CargoResult<TomlManifest> {
+        let contents = paths::read(path)?;
+        let toml = parse(&contents, path, config)?;
+        Ok(toml.try_into()?)
+    })().chain_err(|| {
+        format!("failed to parse manifest at `{}`", path.display())
+    })
+}
+
 pub fn parse(toml: &str,
              file: &Path,
              config: &Config) -> CargoResult<toml::Value> {
@@ -141,7 +183,7 @@ type TomlExampleTarget = TomlTarget;
 type TomlTestTarget = TomlTarget;
 type TomlBenchTarget = TomlTarget;
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 #[serde(untagged)]
 pub enum TomlDependency {
     Simple(String),
@@ -180,7 +222,7 @@ impl<'de> de::Deserialize<'de> for TomlDependency {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Default)]
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq)]
 pub struct DetailedTomlDependency {
     version: Option<String>,
     path: Option<String>,
@@ -194,6 +236,13 @@ pub struct DetailedTomlDependency {
     default_features: Option<bool>,
     #[serde(rename = "default_features")]
     default_features2: Option<bool>,
+    package: Option<String>,
+    registry: Option<String>,
+    #[serde(rename = "registry-index")]
+    registry_index: Option<String>,
+    workspace: Option<bool>,
+    artifact: Option<String>,
+    target: Option<String>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -218,17 +267,35 @@ pub struct TomlManifest {
     features: Option<HashMap<String, Vec<String>>>,
     target: Option<HashMap<String, TomlPlatform>>,
     replace: Option<HashMap<String, TomlDependency>>,
+    patch: Option<HashMap<String, HashMap<String, TomlDependency>>>,
     workspace: Option<TomlWorkspace>,
     badges: Option<HashMap<String, HashMap<String, String>>>,
+    lints: Option<TomlLints>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Default)]
-pub struct TomlProfiles {
-    test: Option<TomlProfile>,
-    doc: Option<TomlProfile>,
-    bench: Option<TomlProfile>,
-    dev: Option<TomlProfile>,
-    release: Option<TomlProfile>,
+#[derive(Clone, Default)]
+pub struct TomlProfiles(BTreeMap<String, TomlProfile>);
+
+impl TomlProfiles {
+    fn get(&self, name: &str) -> Option<&TomlProfile> {
+        self.0.get(name)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for TomlProfiles {
+    fn deserialize<D>(d: D) -> Result<TomlProfiles, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        BTreeMap::deserialize(d).map(TomlProfiles)
+    }
+}
+
+impl ser::Serialize for TomlProfiles {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer,
+    {
+        self.0.serialize(s)
+    }
 }
 
 #[derive(Clone)]
@@ -337,6 +404,10 @@ pub struct TomlProfile {
     panic: Option<String>,
     #[serde(rename = "overflow-checks")]
     overflow_checks: Option<bool>,
+    package: Option<BTreeMap<String, TomlProfile>>,
+    #[serde(rename = "build-override")]
+    build_override: Option<Box<TomlProfile>>,
+    inherits: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -376,41 +447,320 @@ impl<'de> de::Deserialize<'de> for StringOrBool {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum VecStringOrBool {
+    VecString(Vec<String>),
+    Bool(bool),
+}
+
+impl<'de> de::Deserialize<'de> for VecStringOrBool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = VecStringOrBool;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a boolean or vector of strings")
+            }
+
+            fn visit_seq<V>(self, v: V) -> Result<Self::Value, V::Error>
+                where V: de::SeqAccess<'de>
+            {
+                let seq = de::value::SeqAccessDeserializer::new(v);
+                Vec::deserialize(seq).map(VecStringOrBool::VecString)
+            }
+
+            fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E>
+                where E: de::Error,
+            {
+                Ok(VecStringOrBool::Bool(b))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// A value which is either defined in place or inherited from the workspace
+/// root with `key.workspace = true`.
+#[derive(Clone, Debug)]
+pub enum MaybeWorkspace<T> {
+    Defined(T),
+    Workspace,
+}
+
+#[derive(Deserialize, Serialize)]
+struct TomlWorkspaceField {
+    workspace: bool,
+}
+
+impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for MaybeWorkspace<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+            type Value = MaybeWorkspace<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a value or `{ workspace = true }`")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where E: de::Error,
+            {
+                T::deserialize(s.into_deserializer()).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_bool<E>(self, b: bool) -> Result<Self::Value, E>
+                where E: de::Error,
+            {
+                T::deserialize(b.into_deserializer()).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_seq<V>(self, v: V) -> Result<Self::Value, V::Error>
+                where V: de::SeqAccess<'de>
+            {
+                let seq = de::value::SeqAccessDeserializer::new(v);
+                T::deserialize(seq).map(MaybeWorkspace::Defined)
+            }
+
+            fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+                where V: de::MapAccess<'de>
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                let field = TomlWorkspaceField::deserialize(mvd)?;
+                if field.workspace {
+                    Ok(MaybeWorkspace::Workspace)
+                } else {
+                    Err(de::Error::custom("`workspace` cannot be false"))
+                }
+            }
+        }
+
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
+}
+
+impl<T: ser::Serialize> ser::Serialize for MaybeWorkspace<T> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer,
+    {
+        match *self {
+            MaybeWorkspace::Defined(ref value) => value.serialize(s),
+            MaybeWorkspace::Workspace => {
+                TomlWorkspaceField { workspace: true }.serialize(s)
+            }
+        }
+    }
+}
+
+impl<T> MaybeWorkspace<T> {
+    fn defined(&self) -> Option<&T> {
+        match *self {
+            MaybeWorkspace::Defined(ref value) => Some(value),
+            MaybeWorkspace::Workspace => None,
+        }
+    }
+
+    fn is_workspace(&self) -> bool {
+        match *self {
+            MaybeWorkspace::Defined(..) => false,
+            MaybeWorkspace::Workspace => true,
+        }
+    }
+}
+
+/// The `[lints]` table, either the lint levels for each tool or
+/// `workspace = true` to inherit `[workspace.lints]`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct TomlLints {
+    workspace: Option<bool>,
+    rust: Option<BTreeMap<String, TomlLint>>,
+    rustdoc: Option<BTreeMap<String, TomlLint>>,
+}
+
+/// The level of a single lint, either `name = "deny"` or
+/// `name = { level = "deny", priority = -1 }`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum TomlLint {
+    Level(String),
+    Config(TomlLintConfig),
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TomlLintConfig {
+    level: String,
+    priority: Option<i8>,
+}
+
+impl<'de> de::Deserialize<'de> for TomlLint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = TomlLint;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a lint level or a table with `level` and `priority`")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where E: de::Error,
+            {
+                Ok(TomlLint::Level(s.to_string()))
+            }
+
+            fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+                where V: de::MapAccess<'de>
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                TomlLintConfig::deserialize(mvd).map(TomlLint::Config)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+impl TomlLint {
+    fn level(&self) -> &str {
+        match *self {
+            TomlLint::Level(ref level) => level,
+            TomlLint::Config(ref config) => &config.level,
+        }
+    }
+
+    fn priority(&self) -> i8 {
+        match *self {
+            TomlLint::Level(..) => 0,
+            TomlLint::Config(ref config) => config.priority.unwrap_or(0),
+        }
+    }
+}
+
+impl TomlLints {
+    /// Turns the lint levels into the flags passed to rustc and rustdoc.
+    ///
+    /// Lints are sorted by priority and then by name, so that a lint with a
+    /// higher priority is passed later and overrides lint groups with a
+    /// lower one.
+    fn to_lints(&self) -> CargoResult<Lints> {
+        let empty = BTreeMap::new();
+        let mut rust = Vec::new();
+        let mut rustdoc = Vec::new();
+        for (tool, lints) in vec![("rust", &self.rust), ("rustdoc", &self.rustdoc)] {
+            for (name, lint) in lints.as_ref().unwrap_or(&empty) {
+                let flag = match lint.level() {
+                    "forbid" => "-F",
+                    "deny" => "-D",
+                    "warn" => "-W",
+                    "allow" => "-A",
+                    level => bail!("`lints.{}.{}` has an invalid level `{}`, \
+                                    expected `forbid`, `deny`, `warn` or `allow`",
+                                   tool, name, level),
+                };
+                let lint = (lint.priority(), name.clone(), flag);
+                if tool == "rust" {
+                    rust.push(lint.clone());
+                }
+                rustdoc.push(lint);
+            }
+        }
+        rust.sort();
+        rustdoc.sort();
+        let args = |lints: Vec<(i8, String, &str)>| -> Vec<String> {
+            lints.into_iter().flat_map(|(_, name, flag)| {
+                vec![flag.to_string(), name]
+            }).collect()
+        };
+        Ok(Lints {
+            rustc: args(rust),
+            rustdoc: args(rustdoc),
+        })
+    }
+}
+
+/// Returns the value of an optional field which may have been inherited from
+/// the workspace, once it has been resolved.
+fn defined<T: Clone>(field: &Option<MaybeWorkspace<T>>) -> Option<T> {
+    field.as_ref().and_then(|f| f.defined()).cloned()
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct TomlProject {
     name: String,
-    version: semver::Version,
-    authors: Option<Vec<String>>,
+    version: MaybeWorkspace<semver::Version>,
+    authors: Option<MaybeWorkspace<Vec<String>>>,
     build: Option<StringOrBool>,
     links: Option<String>,
     exclude: Option<Vec<String>>,
     include: Option<Vec<String>>,
-    publish: Option<bool>,
+    publish: Option<MaybeWorkspace<VecStringOrBool>>,
     workspace: Option<String>,
+    resolver: Option<String>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<MaybeWorkspace<String>>,
+    edition: Option<String>,
 
     // package metadata
-    description: Option<String>,
-    homepage: Option<String>,
-    documentation: Option<String>,
+    description: Option<MaybeWorkspace<String>>,
+    homepage: Option<MaybeWorkspace<String>>,
+    documentation: Option<MaybeWorkspace<String>>,
     readme: Option<String>,
-    keywords: Option<Vec<String>>,
-    categories: Option<Vec<String>>,
-    license: Option<String>,
+    keywords: Option<MaybeWorkspace<Vec<String>>>,
+    categories: Option<MaybeWorkspace<Vec<String>>>,
+    license: Option<MaybeWorkspace<String>>,
     #[serde(rename = "license-file")]
     license_file: Option<String>,
-    repository: Option<String>,
+    repository: Option<MaybeWorkspace<String>>,
     metadata: Option<toml::Value>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct TomlWorkspace {
     members: Option<Vec<String>>,
     exclude: Option<Vec<String>>,
+    package: Option<TomlWorkspacePackage>,
+    dependencies: Option<HashMap<String, TomlDependency>>,
+    resolver: Option<String>,
+    lints: Option<TomlLints>,
+}
+
+/// The `[workspace.package]` table, holding the values members may inherit.
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct TomlWorkspacePackage {
+    version: Option<semver::Version>,
+    authors: Option<Vec<String>>,
+    publish: Option<VecStringOrBool>,
+    description: Option<String>,
+    homepage: Option<String>,
+    documentation: Option<String>,
+    keywords: Option<Vec<String>>,
+    categories: Option<Vec<String>>,
+    license: Option<String>,
+    repository: Option<String>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<String>,
 }
 
 impl TomlProject {
     pub fn to_package_id(&self, source_id: &SourceId) -> CargoResult<PackageId> {
-        PackageId::new(&self.name, self.version.clone(), source_id)
+        let version = match self.version {
+            MaybeWorkspace::Defined(ref version) => version.clone(),
+            MaybeWorkspace::Workspace => {
+                bail!("`package.version` was not inherited from the workspace")
+            }
+        };
+        PackageId::new(&self.name, version, source_id)
     }
 }
 
@@ -426,13 +776,13 @@ struct Context<'a, 'b> {
 }
 
 impl TomlManifest {
-    pub fn prepare_for_publish(&self) -> TomlManifest {
+    pub fn prepare_for_publish(&self, config: &Config) -> CargoResult<TomlManifest> {
         let mut package = self.package.as_ref()
                               .or(self.project.as_ref())
                               .unwrap()
                               .clone();
         package.workspace = None;
-        return TomlManifest {
+        return Ok(TomlManifest {
             package: Some(package),
             project: None,
             profile: self.profile.clone(),
@@ -441,56 +791,326 @@ impl TomlManifest {
             example: self.example.clone(),
             test: self.test.clone(),
             bench: self.bench.clone(),
-            dependencies: map_deps(self.dependencies.as_ref()),
-            dev_dependencies: map_deps(self.dev_dependencies.as_ref()
-                                         .or(self.dev_dependencies2.as_ref())),
+            dependencies: map_deps(config, self.dependencies.as_ref())?,
+            dev_dependencies: map_deps(config, self.dev_dependencies.as_ref()
+                                         .or(self.dev_dependencies2.as_ref()))?,
             dev_dependencies2: None,
-            build_dependencies: map_deps(self.build_dependencies.as_ref()
-                                         .or(self.build_dependencies2.as_ref())),
+            build_dependencies: map_deps(config, self.build_dependencies.as_ref()
+                                         .or(self.build_dependencies2.as_ref()))?,
             build_dependencies2: None,
             features: self.features.clone(),
-            target: self.target.as_ref().map(|target_map| {
+            target: match self.target.as_ref().map(|target_map| {
                 target_map.iter().map(|(k, v)| {
-                    (k.clone(), TomlPlatform {
-                        dependencies: map_deps(v.dependencies.as_ref()),
-                        dev_dependencies: map_deps(v.dev_dependencies.as_ref()
-                                                     .or(v.dev_dependencies2.as_ref())),
+                    Ok((k.clone(), TomlPlatform {
+                        dependencies: map_deps(config, v.dependencies.as_ref())?,
+                        dev_dependencies: map_deps(config, v.dev_dependencies.as_ref()
+                                                     .or(v.dev_dependencies2.as_ref()))?,
                         dev_dependencies2: None,
-                        build_dependencies: map_deps(v.build_dependencies.as_ref()
-                                                     .or(v.build_dependencies2.as_ref())),
+                        build_dependencies: map_deps(config, v.build_dependencies.as_ref()
+                                                     .or(v.build_dependencies2.as_ref()))?,
                         build_dependencies2: None,
-                    })
+                    }))
                 }).collect()
-            }),
+            }) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(e)) => return Err(e),
+                None => None,
+            },
             replace: None,
+            patch: None,
             workspace: None,
             badges: self.badges.clone(),
-        };
+        });
 
-        fn map_deps(deps: Option<&HashMap<String, TomlDependency>>)
-                        -> Option<HashMap<String, TomlDependency>>
+        fn map_deps(config: &Config, deps: Option<&HashMap<String, TomlDependency>>)
+                        -> CargoResult<Option<HashMap<String, TomlDependency>>>
         {
             let deps = match deps {
                 Some(deps) => deps,
-                None => return None
+                None => return Ok(None),
             };
-            Some(deps.iter().map(|(k, v)| (k.clone(), map_dependency(v))).collect())
+            let deps = deps.iter()
+                           .map(|(k, v)| Ok((k.clone(), map_dependency(config, v)?)))
+                           .collect::<CargoResult<HashMap<_, _>>>()?;
+            Ok(Some(deps))
         }
 
-        fn map_dependency(dep: &TomlDependency) -> TomlDependency {
+        fn map_dependency(config: &Config, dep: &TomlDependency) -> CargoResult<TomlDependency> {
             match *dep {
                 TomlDependency::Detailed(ref d) => {
                     let mut d = d.clone();
                     d.path.take(); // path dependencies become crates.io deps
-                    TomlDependency::Detailed(d)
+                    // registry specifications are elaborated to the index URL
+                    if let Some(registry) = d.registry.take() {
+                        let src = SourceId::alt_registry(config, &registry)?;
+                        d.registry_index = Some(src.url().to_string());
+                    }
+                    Ok(TomlDependency::Detailed(d))
                 }
                 TomlDependency::Simple(ref s) => {
-                    TomlDependency::Detailed(DetailedTomlDependency {
+                    Ok(TomlDependency::Detailed(DetailedTomlDependency {
                         version: Some(s.clone()),
                         ..Default::default()
-                    })
+                    }))
+                }
+            }
+        }
+    }
+
+    fn workspace_config(&self) -> CargoResult<WorkspaceConfig> {
+        let project = self.project.as_ref().or_else(|| self.package.as_ref());
+        Ok(match (self.workspace.as_ref(),
+                  project.and_then(|p| p.workspace.as_ref())) {
+            (Some(config), None) => {
+                WorkspaceConfig::Root {
+                    members: config.members.clone(),
+                    exclude: config.exclude.clone().unwrap_or(Vec::new()),
                 }
             }
+            (None, root) => {
+                WorkspaceConfig::Member { root: root.cloned() }
+            }
+            (Some(..), Some(..)) => {
+                bail!("cannot configure both `package.workspace` and \
+                       `[workspace]`, only one can be specified")
+            }
+        })
+    }
+
+    /// Returns the feature resolver selected with `package.resolver` or
+    /// `workspace.resolver`.
+    fn resolve_behavior(&self) -> CargoResult<Option<ResolveBehavior>> {
+        let project = self.project.as_ref().or_else(|| self.package.as_ref());
+        let resolver = project.and_then(|p| p.resolver.as_ref()).or_else(|| {
+            self.workspace.as_ref().and_then(|w| w.resolver.as_ref())
+        });
+        match resolver {
+            Some(resolver) => Ok(Some(ResolveBehavior::from_manifest(resolver)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all the tables of dependencies in this manifest.
+    fn dependency_tables_mut(&mut self)
+                             -> Vec<&mut HashMap<String, TomlDependency>> {
+        let mut tables = Vec::new();
+        {
+            let TomlManifest {
+                ref mut dependencies, ref mut dev_dependencies,
+                ref mut dev_dependencies2, ref mut build_dependencies,
+                ref mut build_dependencies2, ..
+            } = *self;
+            tables.extend(dependencies.as_mut());
+            tables.extend(dev_dependencies.as_mut());
+            tables.extend(dev_dependencies2.as_mut());
+            tables.extend(build_dependencies.as_mut());
+            tables.extend(build_dependencies2.as_mut());
+        }
+        for platform in self.target.iter_mut().flat_map(|t| t.values_mut()) {
+            let TomlPlatform {
+                ref mut dependencies, ref mut dev_dependencies,
+                ref mut dev_dependencies2, ref mut build_dependencies,
+                ref mut build_dependencies2,
+            } = *platform;
+            tables.extend(dependencies.as_mut());
+            tables.extend(dev_dependencies.as_mut());
+            tables.extend(dev_dependencies2.as_mut());
+            tables.extend(build_dependencies.as_mut());
+            tables.extend(build_dependencies2.as_mut());
+        }
+        tables
+    }
+
+    fn uses_workspace_inheritance(&mut self) -> bool {
+        let package = {
+            let project = self.project.as_ref().or_else(|| self.package.as_ref());
+            project.map(|p| {
+                p.version.is_workspace() ||
+                    [&p.description, &p.homepage, &p.documentation,
+                     &p.license, &p.repository, &p.rust_version].iter().any(|f| {
+                        f.as_ref().map(|f| f.is_workspace()) == Some(true)
+                    }) ||
+                    [&p.authors, &p.keywords, &p.categories].iter().any(|f| {
+                        f.as_ref().map(|f| f.is_workspace()) == Some(true)
+                    }) ||
+                    p.publish.as_ref().map(|f| f.is_workspace()) == Some(true)
+            }).unwrap_or(false)
+        };
+        let lints = self.lints.as_ref().and_then(|l| l.workspace) == Some(true);
+        package || lints || self.dependency_tables_mut().iter().any(|deps| {
+            deps.values().any(|dep| {
+                match *dep {
+                    TomlDependency::Detailed(ref d) => d.workspace.is_some(),
+                    TomlDependency::Simple(..) => false,
+                }
+            })
+        })
+    }
+
+    /// Replaces all values specified with `workspace = true` with those
+    /// defined in the `[workspace]` table of the workspace root, so that the
+    /// rest of Cargo (and `cargo package`) only ever sees expanded manifests.
+    fn inherit_from_workspace(&mut self, manifest_file: &Path, config: &Config)
+                              -> CargoResult<()> {
+        if !self.uses_workspace_inheritance() {
+            return Ok(())
+        }
+
+        let (ws, ws_root) = match self.workspace {
+            Some(ref ws) => (ws.clone(), manifest_file.parent().unwrap().to_path_buf()),
+            None => {
+                let current = self.workspace_config()?;
+                let root = find_workspace_root(manifest_file, |path| {
+                    if path == manifest_file {
+                        return Ok(current.clone())
+                    }
+                    read_toml_manifest(path, config)?.workspace_config()
+                })?;
+                let root = match root {
+                    Some(root) => root,
+                    None => bail!("failed to find a workspace root to inherit \
+                                   values from with `workspace = true`"),
+                };
+                let ws = match read_toml_manifest(&root, config)?.workspace {
+                    Some(ws) => ws,
+                    None => bail!("workspace root `{}` has no [workspace] table \
+                                   to inherit values from", root.display()),
+                };
+                (ws, root.parent().unwrap().to_path_buf())
+            }
+        };
+
+        let empty = TomlWorkspacePackage::default();
+        let inherited = ws.package.as_ref().unwrap_or(&empty);
+        if let Some(project) = self.package.as_mut().or(self.project.as_mut()) {
+            inherit(&mut project.version, "version", inherited.version.as_ref())?;
+            inherit_opt(&mut project.authors, "authors", inherited.authors.as_ref())?;
+            inherit_opt(&mut project.publish, "publish", inherited.publish.as_ref())?;
+            inherit_opt(&mut project.description, "description",
+                        inherited.description.as_ref())?;
+            inherit_opt(&mut project.homepage, "homepage",
+                        inherited.homepage.as_ref())?;
+            inherit_opt(&mut project.documentation, "documentation",
+                        inherited.documentation.as_ref())?;
+            inherit_opt(&mut project.keywords, "keywords",
+                        inherited.keywords.as_ref())?;
+            inherit_opt(&mut project.categories, "categories",
+                        inherited.categories.as_ref())?;
+            inherit_opt(&mut project.license, "license",
+                        inherited.license.as_ref())?;
+            inherit_opt(&mut project.repository, "repository",
+                        inherited.repository.as_ref())?;
+            inherit_opt(&mut project.rust_version, "rust-version",
+                        inherited.rust_version.as_ref())?;
+        }
+
+        let inherits_lints = self.lints.as_ref().and_then(|l| l.workspace) == Some(true);
+        if inherits_lints {
+            let overridden = self.lints.as_ref().map(|l| {
+                l.rust.is_some() || l.rustdoc.is_some()
+            }) == Some(true);
+            if overridden {
+                bail!("`lints` inherits from the workspace with `workspace = true`, \
+                       so no other lints may be specified")
+            }
+            match ws.lints {
+                Some(ref ws_lints) => self.lints = Some(ws_lints.clone()),
+                None => bail!("`lints` was inherited from the workspace, but \
+                               `workspace.lints` is not defined"),
+            }
+        }
+
+        let empty = HashMap::new();
+        let ws_deps = ws.dependencies.as_ref().unwrap_or(&empty);
+        for deps in self.dependency_tables_mut() {
+            for (name, dep) in deps.iter_mut() {
+                let inherited = inherit_dep(name, dep, ws_deps, &ws_root)?;
+                if let Some(inherited) = inherited {
+                    *dep = TomlDependency::Detailed(inherited);
+                }
+            }
+        }
+        return Ok(());
+
+        fn inherit<T: Clone>(field: &mut MaybeWorkspace<T>,
+                             key: &str,
+                             value: Option<&T>) -> CargoResult<()> {
+            if !field.is_workspace() {
+                return Ok(())
+            }
+            match value {
+                Some(value) => *field = MaybeWorkspace::Defined(value.clone()),
+                None => bail!("`package.{}` was inherited from the workspace, \
+                               but `workspace.package.{0}` is not defined", key),
+            }
+            Ok(())
+        }
+
+        fn inherit_opt<T: Clone>(field: &mut Option<MaybeWorkspace<T>>,
+                                 key: &str,
+                                 value: Option<&T>) -> CargoResult<()> {
+            match *field {
+                Some(ref mut field) => inherit(field, key, value),
+                None => Ok(()),
+            }
+        }
+
+        fn inherit_dep(name: &str,
+                       dep: &TomlDependency,
+                       ws_deps: &HashMap<String, TomlDependency>,
+                       ws_root: &Path)
+                       -> CargoResult<Option<DetailedTomlDependency>> {
+            let member = match *dep {
+                TomlDependency::Detailed(ref d) => d,
+                TomlDependency::Simple(..) => return Ok(None),
+            };
+            match member.workspace {
+                Some(true) => {}
+                Some(false) => bail!("`workspace` cannot be false for \
+                                      dependency `{}`", name),
+                None => return Ok(None),
+            }
+            // Only the keys which can differ between members may be given
+            // alongside `workspace = true`.
+            let DetailedTomlDependency {
+                ref features, optional, ..
+            } = *member;
+            if *member != (DetailedTomlDependency {
+                features: features.clone(),
+                optional: optional,
+                workspace: Some(true),
+                ..DetailedTomlDependency::default()
+            }) {
+                bail!("dependency `{}` inherits from the workspace, so only \
+                       `features` and `optional` may be specified", name)
+            }
+
+            let mut d = match ws_deps.get(name) {
+                Some(&TomlDependency::Simple(ref version)) => {
+                    DetailedTomlDependency {
+                        version: Some(version.clone()),
+                        ..DetailedTomlDependency::default()
+                    }
+                }
+                Some(&TomlDependency::Detailed(ref d)) => d.clone(),
+                None => bail!("dependency `{}` was inherited from the \
+                               workspace, but `workspace.dependencies.{0}` \
+                               is not defined", name),
+            };
+            if d.workspace.is_some() || d.optional.is_some() {
+                bail!("`workspace.dependencies.{}` cannot specify \
+                       `workspace` or `optional`", name)
+            }
+            // Paths are relative to the workspace root, not the member.
+            let path = d.path.as_ref().map(|p| ws_root.join(p).display().to_string());
+            d.path = path;
+            if let Some(ref features) = *features {
+                let mut all = d.features.take().unwrap_or(Vec::new());
+                all.extend(features.iter().cloned());
+                d.features = Some(all);
+            }
+            d.optional = optional;
+            Ok(Some(d))
         }
     }
 
@@ -517,7 +1137,13 @@ impl TomlManifest {
         // If we have no lib at all, use the inferred lib if available
         // If we have a lib with a path, we're done
         // If we have a lib with no path, use the inferred lib or_else package name
-        let targets = targets(me, package_name, package_root, &project.build, &mut warnings)?;
+        let edition = match project.edition {
+            Some(ref edition) => edition.parse()?,
+            None => Edition::Edition2015,
+        };
+
+        let targets = targets(me, package_name, package_root, edition,
+                              &project.build, &mut warnings)?;
 
         if targets.is_empty() {
             debug!("manifest has no build targets");
@@ -530,6 +1156,7 @@ impl TomlManifest {
 
         let mut deps = Vec::new();
         let replace;
+        let patch;
 
         {
 
@@ -585,12 +1212,13 @@ impl TomlManifest {
             }
 
             replace = me.replace(&mut cx)?;
+            patch = me.patch(&mut cx)?;
         }
 
         {
             let mut names_sources = HashMap::new();
             for dep in deps.iter() {
-                let name = dep.name();
+                let name = dep.name_in_toml();
                 let prev = names_sources.insert(name, dep.source_id());
                 if prev.is_some() && prev != Some(dep.source_id()) {
                     bail!("Dependency '{}' has different source paths depending on the build \
@@ -603,40 +1231,46 @@ impl TomlManifest {
         let exclude = project.exclude.clone().unwrap_or(Vec::new());
         let include = project.include.clone().unwrap_or(Vec::new());
 
+        if let Some(ref features) = me.features {
+            validate_feature_syntax(features)?;
+        }
+        let rust_version = match defined(&project.rust_version) {
+            Some(rust_version) => Some(util::parse_rust_version(&rust_version)?),
+            None => None,
+        };
         let summary = Summary::new(pkgid, deps, me.features.clone()
             .unwrap_or_else(HashMap::new))?;
+        let summary = summary.set_rust_version(rust_version);
+        let lints = match me.lints {
+            Some(ref lints) => {
+                if lints.workspace.is_some() {
+                    bail!("`lints.workspace` cannot be false")
+                }
+                lints.to_lints()?
+            }
+            None => Lints::default(),
+        };
         let metadata = ManifestMetadata {
-            description: project.description.clone(),
-            homepage: project.homepage.clone(),
-            documentation: project.documentation.clone(),
+            description: defined(&project.description),
+            homepage: defined(&project.homepage),
+            documentation: defined(&project.documentation),
             readme: project.readme.clone(),
-            authors: project.authors.clone().unwrap_or(Vec::new()),
-            license: project.license.clone(),
+            authors: defined(&project.authors).unwrap_or(Vec::new()),
+            license: defined(&project.license),
             license_file: project.license_file.clone(),
-            repository: project.repository.clone(),
-            keywords: project.keywords.clone().unwrap_or(Vec::new()),
-            categories: project.categories.clone().unwrap_or(Vec::new()),
+            repository: defined(&project.repository),
+            keywords: defined(&project.keywords).unwrap_or(Vec::new()),
+            categories: defined(&project.categories).unwrap_or(Vec::new()),
             badges: me.badges.clone().unwrap_or_else(HashMap::new),
         };
 
-        let workspace_config = match (me.workspace.as_ref(),
-                                      project.workspace.as_ref()) {
-            (Some(config), None) => {
-                WorkspaceConfig::Root {
-                    members: config.members.clone(),
-                    exclude: config.exclude.clone().unwrap_or(Vec::new()),
-                }
-            }
-            (None, root) => {
-                WorkspaceConfig::Member { root: root.cloned() }
-            }
-            (Some(..), Some(..)) => {
-                bail!("cannot configure both `package.workspace` and \
-                       `[workspace]`, only one can be specified")
-            }
+        let workspace_config = me.workspace_config()?;
+        let profiles = build_profiles(&me.profile)?;
+        let publish = match defined(&project.publish) {
+            Some(VecStringOrBool::VecString(vecstring)) => Some(vecstring),
+            Some(VecStringOrBool::Bool(false)) => Some(vec![]),
+            None | Some(VecStringOrBool::Bool(true)) => None,
         };
-        let profiles = build_profiles(&me.profile);
-        let publish = project.publish.unwrap_or(true);
         let mut manifest = Manifest::new(summary,
                                          targets,
                                          exclude,
@@ -646,8 +1280,12 @@ impl TomlManifest {
                                          profiles,
                                          publish,
                                          replace,
+                                         patch,
                                          workspace_config,
-                                         me.clone());
+                                         me.clone(),
+                                         me.resolve_behavior()?,
+                                         edition,
+                                         lints);
         if project.license_file.is_some() && project.license.is_some() {
             manifest.add_warning("only one of `license` or \
                                  `license-file` is necessary".to_string());
@@ -685,21 +1323,28 @@ impl TomlManifest {
         if me.bench.is_some() {
             bail!("virtual manifests do not specifiy [[bench]]");
         }
+        if me.lints.is_some() {
+            bail!("virtual manifests do not specify [lints], \
+                   use [workspace.lints] instead");
+        }
 
         let mut nested_paths = Vec::new();
         let mut warnings = Vec::new();
         let mut deps = Vec::new();
-        let replace = me.replace(&mut Context {
-            pkgid: None,
-            deps: &mut deps,
-            source_id: source_id,
-            nested_paths: &mut nested_paths,
-            config: config,
-            warnings: &mut warnings,
-            platform: None,
-            root: root
-        })?;
-        let profiles = build_profiles(&me.profile);
+        let (replace, patch) = {
+            let mut cx = Context {
+                pkgid: None,
+                deps: &mut deps,
+                source_id: source_id,
+                nested_paths: &mut nested_paths,
+                config: config,
+                warnings: &mut warnings,
+                platform: None,
+                root: root
+            };
+            (me.replace(&mut cx)?, me.patch(&mut cx)?)
+        };
+        let profiles = build_profiles(&me.profile)?;
         let workspace_config = match me.workspace {
             Some(ref config) => {
                 WorkspaceConfig::Root {
@@ -711,7 +1356,10 @@ impl TomlManifest {
                 bail!("virtual manifests must be configured with [workspace]");
             }
         };
-        Ok((VirtualManifest::new(replace, workspace_config, profiles), nested_paths))
+        let resolve_behavior = me.resolve_behavior()?;
+        Ok((VirtualManifest::new(replace, patch, workspace_config, profiles,
+                                 resolve_behavior),
+            nested_paths))
     }
 
     fn replace(&self, cx: &mut Context)
@@ -750,6 +1398,21 @@ impl TomlManifest {
         Ok(replace)
     }
 
+    fn patch(&self, cx: &mut Context)
+             -> CargoResult<HashMap<Url, Vec<Dependency>>> {
+        let mut patch = HashMap::new();
+        for (url, deps) in self.patch.iter().flat_map(|x| x) {
+            let url = match &url[..] {
+                "crates-io" => CRATES_IO.parse().unwrap(),
+                _ => url.to_url()?,
+            };
+            patch.insert(url, deps.iter().map(|(name, dep)| {
+                dep.to_dependency(name, cx, None)
+            }).collect::<CargoResult<Vec<_>>>()?);
+        }
+        Ok(patch)
+    }
+
     fn maybe_custom_build(&self,
                           build: &Option<StringOrBool>,
                           package_root: &Path)
@@ -823,6 +1486,16 @@ impl TomlDependency {
             }
         }
 
+        if details.registry.is_some() && details.registry_index.is_some() {
+            bail!("dependency ({}) specification is ambiguous. \
+                   Only one of `registry` or `registry-index` is allowed.", name);
+        }
+        if (details.registry.is_some() || details.registry_index.is_some()) &&
+           (details.git.is_some() || details.path.is_some()) {
+            bail!("dependency ({}) specification is ambiguous. \
+                   Only one of `git`, `path` or `registry` is allowed.", name);
+        }
+
         let new_source_id = match (details.git.as_ref(), details.path.as_ref()) {
             (Some(git), maybe_path) => {
                 if maybe_path.is_some() {
@@ -869,16 +1542,30 @@ impl TomlDependency {
                     cx.source_id.clone()
                 }
             },
-            (None, None) => SourceId::crates_io(cx.config)?,
+            (None, None) => {
+                match (details.registry.as_ref(), details.registry_index.as_ref()) {
+                    (Some(registry), _) => SourceId::alt_registry(cx.config, registry)?,
+                    (None, Some(index)) => SourceId::for_registry(&index.to_url()?),
+                    (None, None) => SourceId::crates_io(cx.config)?,
+                }
+            }
+        };
+
+        // If the `package` key is given then the dependency is known locally
+        // as `name`, but it's actually the package `package` that we depend
+        // on.
+        let (pkg_name, explicit_name_in_toml) = match details.package {
+            Some(ref s) => (&s[..], Some(name)),
+            None => (name, None),
         };
 
         let version = details.version.as_ref().map(|v| &v[..]);
         let mut dep = match cx.pkgid {
             Some(id) => {
-                Dependency::parse(name, version, &new_source_id,
+                Dependency::parse(pkg_name, version, &new_source_id,
                                   id, cx.config)?
             }
-            None => Dependency::parse_no_deprecated(name, version, &new_source_id)?,
+            None => Dependency::parse_no_deprecated(pkg_name, version, &new_source_id)?,
         };
         dep.set_features(details.features.unwrap_or(Vec::new()))
            .set_default_features(details.default_features
@@ -886,9 +1573,25 @@ impl TomlDependency {
                                         .unwrap_or(true))
            .set_optional(details.optional.unwrap_or(false))
            .set_platform(cx.platform.clone());
+        if let Some(name_in_toml) = explicit_name_in_toml {
+            dep.set_explicit_name_in_toml(name_in_toml);
+        }
         if let Some(kind) = kind {
             dep.set_kind(kind);
         }
+        match (details.artifact, details.target) {
+            (Some(artifact), target) => {
+                let artifact = artifact.parse().chain_err(|| {
+                    format!("failed to parse the `artifact` of dependency `{}`", name)
+                })?;
+                dep.set_artifact(Some(artifact), target);
+            }
+            (None, Some(_)) => {
+                bail!("dependency `{}` specifies `target`, which is only \
+                       allowed for artifact dependencies", name)
+            }
+            (None, None) => {}
+        }
         Ok(dep)
     }
 }
@@ -917,6 +1620,7 @@ struct TomlTarget {
     harness: Option<bool>,
     #[serde(rename = "required-features")]
     required_features: Option<Vec<String>>,
+    edition: Option<String>,
 }
 
 #[derive(Clone)]
@@ -979,27 +1683,157 @@ impl fmt::Debug for PathValue {
     }
 }
 
-fn build_profiles(profiles: &Option<TomlProfiles>) -> Profiles {
+const BUILTIN_PROFILES: &'static [&'static str] = &[
+    "dev", "release", "test", "bench", "doc",
+];
+
+/// Names which can't be used for custom profiles as they would clash with
+/// other directories in the target directory.
+const RESERVED_PROFILE_NAMES: &'static [&'static str] = &[
+    "debug", "build", "deps", "examples", "incremental", "native", "package",
+];
+
+fn build_profiles(profiles: &Option<TomlProfiles>) -> CargoResult<Profiles> {
     let profiles = profiles.as_ref();
+    let get = |name: &str| profiles.and_then(|p| p.get(name));
+    for name in BUILTIN_PROFILES.iter() {
+        if get(*name).and_then(|t| t.inherits.as_ref()).is_some() {
+            bail!("`inherits` may not be specified in the built-in profile `{}`",
+                  name)
+        }
+    }
+    let mut ret = build_profile_set(get("dev"), get("release"), get("test"),
+                                    get("bench"), get("doc"))?;
+
+    let custom = profiles.into_iter().flat_map(|p| p.0.keys()).filter(|name| {
+        !BUILTIN_PROFILES.contains(&&name[..])
+    });
+    for name in custom {
+        if RESERVED_PROFILE_NAMES.contains(&&name[..]) {
+            bail!("profile name `{}` is reserved", name)
+        }
+        if name.is_empty() || !name.chars().all(|c| {
+            c.is_alphanumeric() || c == '-' || c == '_'
+        }) {
+            bail!("invalid profile name `{}`, profile names may only contain \
+                   alphanumeric characters, `-` or `_`", name)
+        }
+
+        // Walk up the `inherits` chain until we hit either `dev` or
+        // `release`, then layer the custom profiles on top of the built-in
+        // profiles it selects.
+        let mut chain = Vec::new();
+        let mut cur = &name[..];
+        let release = loop {
+            match cur {
+                "dev" => break false,
+                "release" => break true,
+                "test" | "bench" | "doc" => {
+                    bail!("profile `{}` must inherit from either `dev` or \
+                           `release`, not `{}`", name, cur)
+                }
+                _ => {}
+            }
+            if chain.contains(&cur) {
+                bail!("profile inheritance loop detected with profile `{}`",
+                      name)
+            }
+            let toml = match get(cur) {
+                Some(toml) => toml,
+                None => {
+                    bail!("profile `{}` inherits from `{}`, but that profile \
+                           is not defined", chain.last().unwrap(), cur)
+                }
+            };
+            chain.push(cur);
+            cur = match toml.inherits {
+                Some(ref parent) => &parent[..],
+                None => {
+                    bail!("profile `{}` is missing an `inherits` directive \
+                           (`inherits` is required for all profiles except \
+                           the built-in ones)", cur)
+                }
+            };
+        };
+        let toml = chain.iter().rev().fold(TomlProfile::default(), |acc, name| {
+            merge_toml(&acc, get(*name).unwrap())
+        });
+        let layer = |base: Option<&TomlProfile>| {
+            merge_toml(&base.cloned().unwrap_or_default(), &toml)
+        };
+        let profiles = if release {
+            build_profile_set(get("dev"), Some(&layer(get("release"))),
+                              get("test"), Some(&layer(get("bench"))),
+                              get("doc"))?
+        } else {
+            build_profile_set(Some(&layer(get("dev"))), get("release"),
+                              Some(&layer(get("test"))), get("bench"),
+                              get("doc"))?
+        };
+        ret.custom.insert(name.clone(), CustomProfile {
+            release: release,
+            profiles: profiles,
+        });
+    }
+    return Ok(ret);
+
+    /// Layers the settings of `toml` on top of `base`.
+    fn merge_toml(base: &TomlProfile, toml: &TomlProfile) -> TomlProfile {
+        let mut package = base.package.clone();
+        if let Some(ref toml) = toml.package {
+            let mut map = package.unwrap_or_default();
+            for (spec, toml) in toml {
+                let merged = match map.get(spec) {
+                    Some(base) => merge_toml(base, toml),
+                    None => toml.clone(),
+                };
+                map.insert(spec.clone(), merged);
+            }
+            package = Some(map);
+        }
+        let build_override = match (&base.build_override, &toml.build_override) {
+            (&Some(ref base), &Some(ref toml)) => {
+                Some(Box::new(merge_toml(base, toml)))
+            }
+            (base, toml) => toml.clone().or(base.clone()),
+        };
+        TomlProfile {
+            opt_level: toml.opt_level.clone().or(base.opt_level.clone()),
+            lto: toml.lto.or(base.lto),
+            codegen_units: toml.codegen_units.or(base.codegen_units),
+            debug: toml.debug.clone().or(base.debug.clone()),
+            debug_assertions: toml.debug_assertions.or(base.debug_assertions),
+            rpath: toml.rpath.or(base.rpath),
+            panic: toml.panic.clone().or(base.panic.clone()),
+            overflow_checks: toml.overflow_checks.or(base.overflow_checks),
+            package: package,
+            build_override: build_override,
+            inherits: None,
+        }
+    }
+}
+
+/// Builds the set of profiles used by a compilation from the `dev`,
+/// `release`, `test`, `bench` and `doc` profiles in the manifest.
+fn build_profile_set(dev_toml: Option<&TomlProfile>,
+                     release_toml: Option<&TomlProfile>,
+                     test_toml: Option<&TomlProfile>,
+                     bench_toml: Option<&TomlProfile>,
+                     doc_toml: Option<&TomlProfile>) -> CargoResult<Profiles> {
     let mut profiles = Profiles {
-        release: merge(Profile::default_release(),
-                       profiles.and_then(|p| p.release.as_ref())),
-        dev: merge(Profile::default_dev(),
-                   profiles.and_then(|p| p.dev.as_ref())),
-        test: merge(Profile::default_test(),
-                    profiles.and_then(|p| p.test.as_ref())),
-        test_deps: merge(Profile::default_dev(),
-                         profiles.and_then(|p| p.dev.as_ref())),
-        bench: merge(Profile::default_bench(),
-                     profiles.and_then(|p| p.bench.as_ref())),
-        bench_deps: merge(Profile::default_release(),
-                          profiles.and_then(|p| p.release.as_ref())),
-        doc: merge(Profile::default_doc(),
-                   profiles.and_then(|p| p.doc.as_ref())),
+        release: merge(Profile::default_release(), release_toml),
+        dev: merge(Profile::default_dev(), dev_toml),
+        test: merge(Profile::default_test(), test_toml),
+        test_deps: merge(Profile::default_dev(), dev_toml),
+        bench: merge(Profile::default_bench(), bench_toml),
+        bench_deps: merge(Profile::default_release(), release_toml),
+        doc: merge(Profile::default_doc(), doc_toml),
         custom_build: Profile::default_custom_build(),
-        check: merge(Profile::default_check(),
-                     profiles.and_then(|p| p.dev.as_ref())),
+        check: merge(Profile::default_check(), dev_toml),
         doctest: Profile::default_doctest(),
+        package_overrides: Vec::new(),
+        build_override: (None, None),
+        custom: BTreeMap::new(),
     };
     // The test/bench targets cannot have panic=abort because they'll all get
     // compiled with --test which requires the unwind runtime currently
@@ -1007,12 +1841,77 @@ fn build_profiles(profiles: &Option<TomlProfiles>) -> Profiles {
     profiles.bench.panic = None;
     profiles.test_deps.panic = None;
     profiles.bench_deps.panic = None;
-    return profiles;
+
+    // Overrides are only supported in the profiles which dependencies are
+    // built with, and are layered on top of those profiles.
+    let mut specs = BTreeSet::new();
+    for toml in dev_toml.iter().chain(release_toml.iter()) {
+        for (spec, toml) in toml.package.iter().flat_map(|p| p) {
+            validate_override(toml, &format!("package.{}", spec))?;
+            specs.insert(&spec[..]);
+        }
+        if let Some(ref toml) = toml.build_override {
+            validate_override(toml, "build-override")?;
+        }
+    }
+    let build_override = (
+        dev_toml.and_then(|t| t.build_override.as_ref()).map(|toml| {
+            merge(profiles.dev.clone(), Some(toml))
+        }),
+        release_toml.and_then(|t| t.build_override.as_ref()).map(|toml| {
+            merge(profiles.release.clone(), Some(toml))
+        }),
+    );
+    let package_overrides = specs.into_iter().map(|spec| {
+        let dev = dev_toml.and_then(|t| t.package.as_ref())
+                          .and_then(|p| p.get(spec));
+        let release = release_toml.and_then(|t| t.package.as_ref())
+                                  .and_then(|p| p.get(spec));
+        let spec = if spec == "*" {
+            ProfilePackageSpec::All
+        } else {
+            ProfilePackageSpec::Spec(PackageIdSpec::parse(spec).chain_err(|| {
+                format!("invalid package spec `{}` in profile overrides", spec)
+            })?)
+        };
+        Ok(ProfileOverride {
+            spec: spec,
+            release: merge(profiles.release.clone(), release),
+            dev: merge(profiles.dev.clone(), dev),
+            test_deps: merge(profiles.test_deps.clone(), dev),
+            bench_deps: merge(profiles.bench_deps.clone(), release),
+            check: merge(profiles.check.clone(), dev),
+            build_override: (
+                build_override.0.clone().map(|profile| merge(profile, dev)),
+                build_override.1.clone().map(|profile| merge(profile, release)),
+            ),
+        })
+    }).collect::<CargoResult<Vec<_>>>()?;
+    profiles.package_overrides = package_overrides;
+    profiles.build_override = build_override;
+    return Ok(profiles);
+
+    fn validate_override(toml: &TomlProfile, name: &str) -> CargoResult<()> {
+        if toml.package.is_some() || toml.build_override.is_some() {
+            bail!("profile overrides cannot be nested (found in `{}`)", name)
+        }
+        if toml.panic.is_some() {
+            bail!("`panic` may not be specified in a profile override (`{}`)", name)
+        }
+        if toml.lto.is_some() {
+            bail!("`lto` may not be specified in a profile override (`{}`)", name)
+        }
+        if toml.rpath.is_some() {
+            bail!("`rpath` may not be specified in a profile override (`{}`)", name)
+        }
+        Ok(())
+    }
 
     fn merge(profile: Profile, toml: Option<&TomlProfile>) -> Profile {
         let &TomlProfile {
             ref opt_level, lto, codegen_units, ref debug, debug_assertions, rpath,
-            ref panic, ref overflow_checks,
+            ref panic, ref overflow_checks, package: _, build_override: _,
+            inherits: _,
         } = match toml {
             Some(toml) => toml,
             None => return profile,
@@ -1026,7 +1925,7 @@ fn build_profiles(profiles: &Option<TomlProfiles>) -> Profiles {
         Profile {
             opt_level: opt_level.clone().unwrap_or(TomlOptLevel(profile.opt_level)).0,
             lto: lto.unwrap_or(profile.lto),
-            codegen_units: codegen_units,
+            codegen_units: codegen_units.or(profile.codegen_units),
             rustc_args: None,
             rustdoc_args: None,
             debuginfo: debug.unwrap_or(profile.debuginfo),
@@ -13,7 +13,7 @@ use std::path::{Path, PathBuf};
 use std::fs::{self, DirEntry};
 use std::collections::HashSet;
 
-use core::Target;
+use core::{Edition, Target};
 use ops::is_bad_artifact_name;
 use util::errors::CargoResult;
 use util::paths::without_prefix;
@@ -24,6 +24,7 @@ use super::{TomlTarget, LibKind, PathValue, TomlManifest, StringOrBool,
 pub fn targets(manifest: &TomlManifest,
                package_name: &str,
                package_root: &Path,
+               edition: Edition,
                custom_build: &Option<StringOrBool>,
                warnings: &mut Vec<String>)
                -> CargoResult<Vec<Target>> {
@@ -31,7 +32,8 @@ pub fn targets(manifest: &TomlManifest,
 
     let has_lib;
 
-    if let Some(target) = clean_lib(manifest.lib.as_ref(), package_root, package_name, warnings)? {
+    if let Some(target) = clean_lib(manifest.lib.as_ref(), package_root, package_name,
+                                        edition, warnings)? {
         targets.push(target);
         has_lib = true;
     } else {
@@ -39,26 +41,29 @@ pub fn targets(manifest: &TomlManifest,
     }
 
     targets.extend(
-        clean_bins(manifest.bin.as_ref(), package_root, package_name, warnings, has_lib)?
+        clean_bins(manifest.bin.as_ref(), package_root, package_name, edition,
+                   warnings, has_lib)?
     );
 
     targets.extend(
-        clean_examples(manifest.example.as_ref(), package_root)?
+        clean_examples(manifest.example.as_ref(), package_root, edition)?
     );
 
     targets.extend(
-        clean_tests(manifest.test.as_ref(), package_root)?
+        clean_tests(manifest.test.as_ref(), package_root, edition)?
     );
 
     targets.extend(
-        clean_benches(manifest.bench.as_ref(), package_root)?
+        clean_benches(manifest.bench.as_ref(), package_root, edition)?
     );
 
     // processing the custom build script
     if let Some(custom_build) = manifest.maybe_custom_build(custom_build, package_root) {
         let name = format!("build-script-{}",
                            custom_build.file_stem().and_then(|s| s.to_str()).unwrap_or(""));
-        targets.push(Target::custom_build_target(&name, package_root.join(custom_build)));
+        let mut target = Target::custom_build_target(&name, package_root.join(custom_build));
+        target.set_edition(edition);
+        targets.push(target);
     }
 
     Ok(targets)
@@ -68,6 +73,7 @@ pub fn targets(manifest: &TomlManifest,
 fn clean_lib(toml_lib: Option<&TomlLibTarget>,
              package_root: &Path,
              package_name: &str,
+             edition: Edition,
              warnings: &mut Vec<String>) -> CargoResult<Option<Target>> {
     let inferred = inferred_lib(package_root);
     let lib = match toml_lib {
@@ -141,13 +147,14 @@ fn clean_lib(toml_lib: Option<&TomlLibTarget>,
     };
 
     let mut target = Target::lib_target(&lib.name(), crate_types, path);
-    configure(lib, &mut target);
+    configure(lib, &mut target, edition)?;
     Ok(Some(target))
 }
 
 fn clean_bins(toml_bins: Option<&Vec<TomlBinTarget>>,
               package_root: &Path,
               package_name: &str,
+              edition: Edition,
               warnings: &mut Vec<String>,
               has_lib: bool) -> CargoResult<Vec<Target>> {
     let inferred = inferred_bins(package_root, package_name);
@@ -198,7 +205,7 @@ fn clean_bins(toml_bins: Option<&Vec<TomlBinTarget>>,
 
         let mut target = Target::bin_target(&bin.name(), path,
                                             bin.required_features.clone());
-        configure(bin, &mut target);
+        configure(bin, &mut target, edition)?;
         result.push(target);
     }
     return Ok(result);
@@ -224,7 +231,8 @@ fn clean_bins(toml_bins: Option<&Vec<TomlBinTarget>>,
 }
 
 fn clean_examples(toml_examples: Option<&Vec<TomlExampleTarget>>,
-                  package_root: &Path)
+                  package_root: &Path,
+                  edition: Edition)
                   -> CargoResult<Vec<Target>> {
     let targets = clean_targets("example", "example",
                                 toml_examples, inferred_examples(package_root),
@@ -239,7 +247,7 @@ fn clean_examples(toml_examples: Option<&Vec<TomlExampleTarget>>,
 
         let mut target = Target::example_target(&toml.name(), crate_types, path,
                                                 toml.required_features.clone());
-        configure(&toml, &mut target);
+        configure(&toml, &mut target, edition)?;
         result.push(target);
     }
 
@@ -247,7 +255,8 @@ fn clean_examples(toml_examples: Option<&Vec<TomlExampleTarget>>,
 }
 
 fn clean_tests(toml_tests: Option<&Vec<TomlTestTarget>>,
-               package_root: &Path) -> CargoResult<Vec<Target>> {
+               package_root: &Path,
+               edition: Edition) -> CargoResult<Vec<Target>> {
     let targets = clean_targets("test", "test",
                                 toml_tests, inferred_tests(package_root),
                                 package_root)?;
@@ -256,14 +265,15 @@ fn clean_tests(toml_tests: Option<&Vec<TomlTestTarget>>,
     for (path, toml) in targets {
         let mut target = Target::test_target(&toml.name(), path,
                                              toml.required_features.clone());
-        configure(&toml, &mut target);
+        configure(&toml, &mut target, edition)?;
         result.push(target);
     }
     Ok(result)
 }
 
 fn clean_benches(toml_benches: Option<&Vec<TomlBenchTarget>>,
-                 package_root: &Path) -> CargoResult<Vec<Target>> {
+                 package_root: &Path,
+                 edition: Edition) -> CargoResult<Vec<Target>> {
     let targets = clean_targets("benchmark", "bench",
                                 toml_benches, inferred_benches(package_root),
                                 package_root)?;
@@ -272,7 +282,7 @@ fn clean_benches(toml_benches: Option<&Vec<TomlBenchTarget>>,
     for (path, toml) in targets {
         let mut target = Target::bench_target(&toml.name(), path,
                                               toml.required_features.clone());
-        configure(&toml, &mut target);
+        configure(&toml, &mut target, edition)?;
         result.push(target);
     }
 
@@ -411,7 +421,9 @@ fn validate_unique_names(targets: &[TomlTarget], target_kind: &str) -> CargoResu
 }
 
 
-fn configure(toml: &TomlTarget, target: &mut Target) {
+fn configure(toml: &TomlTarget,
+             target: &mut Target,
+             edition: Edition) -> CargoResult<()> {
     let t2 = target.clone();
     target.set_tested(toml.test.unwrap_or(t2.tested()))
         .set_doc(toml.doc.unwrap_or(t2.documented()))
@@ -422,7 +434,12 @@ fn configure(toml: &TomlTarget, target: &mut Target) {
             (None, None) => t2.for_host(),
             (Some(true), _) | (_, Some(true)) => true,
             (Some(false), _) | (_, Some(false)) => false,
+        })
+        .set_edition(match toml.edition {
+            Some(ref edition) => edition.parse()?,
+            None => edition,
         });
+    Ok(())
 }
 
 fn target_path(target: &TomlTarget,
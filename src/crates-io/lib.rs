@@ -86,6 +86,7 @@ pub struct NewCrate {
     pub license_file: Option<String>,
     pub repository: Option<String>,
     pub badges: HashMap<String, HashMap<String, String>>,
+    pub rust_version: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -97,6 +98,12 @@ pub struct NewCrateDependency {
     pub version_req: String,
     pub target: Option<String>,
     pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explicit_name_in_toml: Option<String>,
+    /// The index URL of the registry the dependency comes from, if it's not
+    /// the registry the crate is published to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
 }
 
 #[derive(Deserialize)]
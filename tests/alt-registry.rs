@@ -0,0 +1,372 @@
+extern crate cargotest;
+extern crate hamcrest;
+extern crate serde_json;
+
+use std::fs::File;
+use std::io::prelude::*;
+
+use cargotest::support::registry::{self, Package, alt_dl_path};
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn depend_on_alt_registry() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies.bar]
+            version = "0.0.1"
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    assert_that(p.cargo("build"),
+                execs().with_status(0).with_stderr(&format!("\
+[UPDATING] registry `{reg}`
+[DOWNLOADING] bar v0.0.1 (registry `file://[..]`)
+[COMPILING] bar v0.0.1 (registry `file://[..]`)
+[COMPILING] foo v0.0.1 ({dir})
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..] secs
+",
+        dir = p.url(),
+        reg = registry::alt_registry())));
+
+    assert_that(p.cargo("clean"), execs().with_status(0));
+
+    // Don't download a second time
+    assert_that(p.cargo("build"),
+                execs().with_status(0).with_stderr(&format!("\
+[COMPILING] bar v0.0.1 (registry `file://[..]`)
+[COMPILING] foo v0.0.1 ({dir})
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..] secs
+",
+        dir = p.url())));
+}
+
+#[test]
+fn registry_and_crates_io_dependencies() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            crates_io_dep = "0.0.1"
+
+            [dependencies.alt_reg_dep]
+            version = "0.1.0"
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("crates_io_dep", "0.0.1").publish();
+    Package::new("alt_reg_dep", "0.1.0").alternative(true).publish();
+
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_contains(format!("\
+[UPDATING] registry `{}`", registry::alt_registry()))
+                       .with_stderr_contains(&format!("\
+[UPDATING] registry `{}`", registry::registry()))
+                       .with_stderr_contains("\
+[DOWNLOADING] crates_io_dep v0.0.1 (registry `file://[..]`)")
+                       .with_stderr_contains("\
+[DOWNLOADING] alt_reg_dep v0.1.0 (registry `file://[..]`)")
+                       .with_stderr_contains("\
+[COMPILING] alt_reg_dep v0.1.0 (registry `file://[..]`)")
+                       .with_stderr_contains("\
+[COMPILING] crates_io_dep v0.0.1")
+                       .with_stderr_contains(&format!("\
+[COMPILING] foo v0.0.1 ({})", p.url()))
+                       .with_stderr_contains("\
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..] secs"))
+}
+
+#[test]
+fn unknown_registry() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies.bar]
+            version = "0.0.1"
+            registry = "nope"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+[..]no index found for registry: `nope`"));
+}
+
+#[test]
+fn registry_incompatible_with_git() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies.bar]
+            git = ""
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  dependency (bar) specification is ambiguous. Only one of `git`, `path` or \
+`registry` is allowed."));
+}
+
+#[test]
+fn cannot_publish_to_crates_io_with_registry_dependency() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+
+            [dependencies.bar]
+            version = "0.0.1"
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    assert_that(p.cargo("publish").arg("--index").arg(registry::registry().to_string()),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] crates cannot be published to crates.io with dependencies sourced \
+from other registries[..]"));
+}
+
+#[test]
+fn publish_to_alt_registry() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    // Setup the registry by publishing a package
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    // Now perform the actual publish
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--registry").arg("alternative")
+                 .arg("--token").arg("api-token"),
+                execs().with_status(0));
+
+    // Ensure that the crate is uploaded
+    assert!(alt_dl_path().join("api/v1/crates/new").exists());
+}
+
+#[test]
+fn publish_with_crates_io_dep() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+
+            [dependencies.bar]
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").publish();
+
+    // Now perform the actual publish
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--registry").arg("alternative")
+                 .arg("--token").arg("api-token"),
+                execs().with_status(0));
+
+    // The dependency names the registry it comes from, as it's not the
+    // registry the crate was published to.
+    let metadata = upload_metadata();
+    let dep = &metadata["deps"][0];
+    assert_eq!(dep["name"], "bar");
+    let bar_registry = dep["registry"].as_str().unwrap();
+    assert_eq!(bar_registry, "https://github.com/rust-lang/crates.io-index");
+
+    // An index entry written from the upload resolves `bar` from crates.io.
+    Package::new("foo", "0.0.1")
+        .registry_dep("bar", "0.0.1", bar_registry)
+        .alternative(true)
+        .publish();
+    let p2 = project("baz")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "baz"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies.foo]
+            version = "0.0.1"
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p2.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[DOWNLOADING] bar v0.0.1 (registry `file://[..]registry`)")
+                       .with_stderr_contains("\
+[DOWNLOADING] foo v0.0.1 (registry `file://[..]alternative-registry`)"));
+}
+
+/// Reads the JSON metadata of the last crate uploaded to the alternative
+/// registry.
+fn upload_metadata() -> serde_json::Value {
+    let mut f = File::open(&alt_dl_path().join("api/v1/crates/new")).unwrap();
+    let mut sz = [0; 4];
+    assert_eq!(f.read(&mut sz).unwrap(), 4);
+    let sz = ((sz[0] as usize) <<  0) |
+             ((sz[1] as usize) <<  8) |
+             ((sz[2] as usize) << 16) |
+             ((sz[3] as usize) << 24);
+    let mut json = vec![0; sz];
+    f.read_exact(&mut json).unwrap();
+    serde_json::from_slice(&json).unwrap()
+}
+
+#[test]
+fn publish_restricted_to_registries() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+            publish = ["alternative"]
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--index").arg(registry::registry().to_string()),
+                execs().with_status(101).with_stderr("\
+[ERROR] some crates cannot be published.
+`foo` is marked as unpublishable
+"));
+
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--registry").arg("alternative")
+                 .arg("--token").arg("api-token"),
+                execs().with_status(0));
+}
+
+#[test]
+fn publish_list_allows_dependency_registry() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+            publish = ["alternative"]
+
+            [dependencies.bar]
+            version = "0.0.1"
+            registry = "alternative"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    assert_that(p.cargo("package").arg("--no-verify"),
+                execs().with_status(0));
+}
+
+#[test]
+fn publish_list_rejects_dependency_registry() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+            publish = ["alternative"]
+
+            [dependencies.bar]
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").publish();
+
+    assert_that(p.cargo("package").arg("--no-verify"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] `foo` may only be published to `alternative`, but its dependency `bar` \
+comes from registry `crates-io`"));
+
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--registry").arg("alternative")
+                 .arg("--token").arg("api-token"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] `foo` may only be published to `alternative`, but its dependency `bar` \
+comes from registry `crates-io`"));
+}
+
+#[test]
+fn index_and_registry_conflict() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+            description = "foo"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.0.1").alternative(true).publish();
+
+    assert_that(p.cargo("publish").arg("--no-verify")
+                 .arg("--registry").arg("alternative")
+                 .arg("--index").arg(registry::registry().to_string()),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] both `--index` and `--registry` should not be set at the same time"));
+}
@@ -0,0 +1,216 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs, ProjectBuilder};
+use hamcrest::assert_that;
+
+fn bar(p: ProjectBuilder) -> ProjectBuilder {
+    p.file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("bar/src/main.rs", r#"
+            fn main() { println!("hello from bar"); }
+        "#)
+}
+
+#[test]
+fn build_script_runs_bin_artifact() {
+    let p = bar(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            build = "build.rs"
+
+            [build-dependencies]
+            bar = { path = "bar", artifact = "bin" }
+        "#)
+        .file("build.rs", r#"
+            use std::env;
+            use std::process::Command;
+
+            fn main() {
+                let bar = env::var("CARGO_BIN_FILE_BAR_bar").unwrap();
+                let out = Command::new(&bar).output().unwrap();
+                assert!(out.status.success());
+                assert_eq!(String::from_utf8(out.stdout).unwrap(), "hello from bar\n");
+
+                // Also available while the build script is compiled.
+                assert_eq!(bar, env!("CARGO_BIN_FILE_BAR_bar"));
+            }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] bar v0.1.0 ([..])")
+                       .with_stderr_does_not_contain("[..]--extern bar=[..]"));
+}
+
+#[test]
+fn test_uses_bin_artifact() {
+    let p = bar(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dev-dependencies]
+            bar = { path = "bar", artifact = "bin" }
+        "#)
+        .file("src/lib.rs", "")
+        .file("tests/bar.rs", r#"
+            use std::path::Path;
+            use std::process::Command;
+
+            #[test]
+            fn runs_bar() {
+                let bar = env!("CARGO_BIN_FILE_BAR_bar");
+                assert!(Path::new(bar).is_file());
+                assert!(Command::new(bar).status().unwrap().success());
+            }
+        "#);
+
+    assert_that(p.cargo_process("test"), execs().with_status(0));
+}
+
+#[test]
+fn artifact_is_not_linked() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", artifact = "bin" }
+        "#)
+        .file("src/lib.rs", "extern crate bar;")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "")
+        .file("bar/src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101)
+                       .with_stderr_contains("[..]can't find crate for `bar`[..]"));
+}
+
+#[test]
+fn cdylib_artifact() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            build = "build.rs"
+
+            [build-dependencies]
+            bar = { path = "bar", artifact = "cdylib" }
+        "#)
+        .file("build.rs", r#"
+            use std::env;
+            use std::path::Path;
+
+            fn main() {
+                let bar = env::var("CARGO_CDYLIB_FILE_BAR").unwrap();
+                assert!(Path::new(&bar).is_file(), "{} is missing", bar);
+            }
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [lib]
+            crate-type = ["cdylib"]
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+}
+
+#[test]
+fn missing_cdylib_target() {
+    let p = bar(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", artifact = "cdylib" }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] dependency `bar` of package `foo v0.1.0 ([..])` is an artifact \
+dependency on `cdylib`, but package `bar v0.1.0 ([..])` has no cdylib target
+"));
+}
+
+#[test]
+fn invalid_artifact() {
+    let p = bar(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", artifact = "rlib" }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  failed to parse the `artifact` of dependency `bar`
+
+Caused by:
+  `artifact = \"rlib\"` is not valid, valid options are \"bin\", \"cdylib\" \
+or \"staticlib\"
+"));
+}
+
+#[test]
+fn target_without_artifact() {
+    let p = bar(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", target = "target" }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  dependency `bar` specifies `target`, which is only allowed for artifact \
+dependencies
+"));
+}
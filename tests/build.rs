@@ -2667,7 +2667,8 @@ fn compiler_json_error_format() {
             "kind":["lib"],
             "crate_types":["lib"],
             "name":"bar",
-            "src_path":"[..]lib.rs"
+            "src_path":"[..]lib.rs",
+            "edition":"2015"
         },
         "message":"{...}"
     }
@@ -2687,8 +2688,10 @@ fn compiler_json_error_format() {
             "kind":["lib"],
             "crate_types":["lib"],
             "name":"bar",
-            "src_path":"[..]lib.rs"
+            "src_path":"[..]lib.rs",
+            "edition":"2015"
         },
+        "target_triple":"[..]",
         "filenames":["[..].rlib"],
         "fresh": false
     }
@@ -2700,7 +2703,8 @@ fn compiler_json_error_format() {
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..]main.rs"
+            "src_path":"[..]main.rs",
+            "edition":"2015"
         },
         "message":"{...}"
     }
@@ -2712,8 +2716,10 @@ fn compiler_json_error_format() {
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..]main.rs"
+            "src_path":"[..]main.rs",
+            "edition":"2015"
         },
+        "target_triple":"[..]",
         "profile": {
             "debug_assertions": true,
             "debuginfo": 2,
@@ -2747,8 +2753,10 @@ fn compiler_json_error_format() {
             "kind":["lib"],
             "crate_types":["lib"],
             "name":"bar",
-            "src_path":"[..]lib.rs"
+            "src_path":"[..]lib.rs",
+            "edition":"2015"
         },
+        "target_triple":"[..]",
         "filenames":["[..].rlib"],
         "fresh": true
     }
@@ -2760,8 +2768,10 @@ fn compiler_json_error_format() {
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..]main.rs"
+            "src_path":"[..]main.rs",
+            "edition":"2015"
         },
+        "target_triple":"[..]",
         "profile": {
             "debug_assertions": true,
             "debuginfo": 2,
@@ -2805,7 +2815,8 @@ fn message_format_json_forward_stderr() {
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..]"
+            "src_path":"[..]",
+            "edition":"2015"
         },
         "message":"{...}"
     }
@@ -2817,8 +2828,10 @@ fn message_format_json_forward_stderr() {
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..]"
+            "src_path":"[..]",
+            "edition":"2015"
         },
+        "target_triple":"[..]",
         "profile":{
             "debug_assertions":true,
             "debuginfo":2,
@@ -0,0 +1,198 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use cargotest::support::registry::Package;
+use hamcrest::assert_that;
+
+#[test]
+fn add_latest_version() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.3").publish();
+    Package::new("bar", "0.3.0-alpha.1").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+# Keep these sorted.
+[dependencies]
+aaa = "0.1" # local
+zzz = "1"
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("bar"),
+                execs().with_status(0).with_stderr("\
+[UPDATING] registry `[..]`
+[ADDING] bar v0.2.3 to dependencies
+"));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+# Keep these sorted.
+[dependencies]
+aaa = "0.1" # local
+bar = "0.2.3"
+zzz = "1"
+"#);
+}
+
+#[test]
+fn add_version_requirement_and_features() {
+    Package::new("bar", "1.2.0").publish();
+    Package::new("bar", "1.3.1")
+        .feature("std", &[])
+        .feature("derive", &[])
+        .publish();
+    Package::new("bar", "2.0.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("bar@1.2")
+                 .arg("--features").arg("std,derive").arg("--optional"),
+                execs().with_status(0).with_stderr("\
+[UPDATING] registry `[..]`
+[ADDING] bar v1.3.1 to dependencies
+[FEATURES] std, derive
+"));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+bar = { version = "1.2", features = ["std", "derive"], optional = true }
+"#);
+}
+
+#[test]
+fn add_dev_and_target() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("bar").arg("--dev"),
+                execs().with_status(0).with_stderr_contains("\
+[ADDING] bar v0.1.0 to dev-dependencies"));
+    assert_that(p.cargo("add").arg("bar").arg("--target").arg("cfg(unix)"),
+                execs().with_status(0).with_stderr_contains("\
+[ADDING] bar v0.1.0 to target.'cfg(unix)'.dependencies"));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+
+[dev-dependencies]
+bar = "0.1.0"
+
+[target.'cfg(unix)'.dependencies]
+bar = "0.1.0"
+"#);
+    assert_that(p.cargo("build"), execs().with_status(0));
+}
+
+#[test]
+fn add_updates_existing_dependency() {
+    Package::new("bar", "0.2.0").feature("std", &[]).publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies.bar]
+version = "0.1"   # pinned for now
+default-features = false # no_std
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("bar").arg("--features").arg("std"),
+                execs().with_status(0));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies.bar]
+version = "0.2.0" # pinned for now
+default-features = false # no_std
+features = ["std"]
+"#);
+}
+
+#[test]
+fn add_workspace_member() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[workspace]
+members = ["baz"]
+"#)
+        .file("baz/Cargo.toml", r#"[package]
+name = "baz"
+version = "0.0.1"
+authors = []
+"#)
+        .file("baz/src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("bar"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] manifest path `[..]` is a virtual manifest, but this command requires \
+running against an actual package in this workspace"));
+    assert_that(p.cargo("add").arg("bar").arg("-p").arg("baz"),
+                execs().with_status(0));
+    assert_that(p.cargo("add").arg("bar").arg("--dev").arg("-p").arg("baz:0.0.1"),
+                execs().with_status(0));
+    assert_that(p.cargo("add").arg("bar").arg("-p").arg("nope"),
+                execs().with_status(101).with_stderr("\
+[ERROR] package `nope` is not a member of the workspace
+"));
+}
+
+#[test]
+fn add_errors() {
+    Package::new("bar", "0.1.0").feature("std", &[]).publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("add").arg("baz"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] could not find `baz` in `registry [..]`"));
+    assert_that(p.cargo("add").arg("bar@2"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] could not find `bar` in `registry [..]` with version `2`"));
+    assert_that(p.cargo("add").arg("bar").arg("--features").arg("serde"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] package `bar v0.1.0` does not have a feature `serde`"));
+    assert_that(p.cargo("add").arg("bar@x.y"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] the version requirement `x.y` for `bar` is invalid: [..]"));
+}
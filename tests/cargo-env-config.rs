@@ -0,0 +1,167 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn env_basic() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/main.rs", r#"
+            use std::env;
+
+            fn main() {
+                println!("compile-time:{}", env!("ENV_TEST_1233"));
+                println!("run-time:{}", env::var("ENV_TEST_1233").unwrap());
+            }
+        "#)
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_1233 = "Hello"
+        "#);
+
+    assert_that(p.cargo_process("run"),
+                execs().with_status(0)
+                       .with_stdout_contains("compile-time:Hello")
+                       .with_stdout_contains("run-time:Hello"));
+}
+
+#[test]
+fn env_build_script() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            build = "build.rs"
+        "#)
+        .file("build.rs", r#"
+            fn main() {
+                assert_eq!(std::env::var("ENV_TEST_BUILD").unwrap(), "from-config");
+            }
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_BUILD = "from-config"
+        "#);
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+}
+
+#[test]
+fn env_no_override() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/main.rs", r#"
+            fn main() {
+                println!("kept:{}", env!("ENV_TEST_KEPT"));
+                println!("forced:{}", env!("ENV_TEST_FORCED"));
+            }
+        "#)
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_KEPT = "from-config"
+            ENV_TEST_FORCED = { value = "from-config", force = true }
+        "#);
+
+    assert_that(p.cargo_process("run")
+                 .env("ENV_TEST_KEPT", "from-env")
+                 .env("ENV_TEST_FORCED", "from-env"),
+                execs().with_status(0)
+                       .with_stdout_contains("kept:from-env")
+                       .with_stdout_contains("forced:from-config"));
+}
+
+#[test]
+fn env_relative() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/main.rs", r#"
+            use std::path::Path;
+
+            fn main() {
+                assert!(Path::new(env!("ENV_TEST_RELATIVE")).is_absolute());
+                assert!(Path::new(env!("ENV_TEST_RELATIVE")).ends_with("vendor"));
+                assert_eq!(env!("ENV_TEST_ABSOLUTE"), "vendor");
+            }
+        "#)
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_RELATIVE = { value = "vendor", relative = true }
+            ENV_TEST_ABSOLUTE = { value = "vendor", relative = false }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+}
+
+#[test]
+fn env_changes_rebuild() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/main.rs", r#"
+            fn main() { println!("{}", env!("ENV_TEST_CHANGE")); }
+        "#)
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_CHANGE = "one"
+        "#);
+
+    assert_that(p.cargo_process("run"),
+                execs().with_status(0).with_stdout("one\n"));
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[COMPILING] foo [..]"));
+
+    p.change_file(".cargo/config", r#"
+        [env]
+        ENV_TEST_CHANGE = "two"
+    "#);
+    assert_that(p.cargo("run"),
+                execs().with_status(0)
+                       .with_stdout("two\n")
+                       .with_stderr_contains("[COMPILING] foo [..]"));
+}
+
+#[test]
+fn env_missing_value() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [env]
+            ENV_TEST_MISSING = { force = true }
+        "#);
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+[ERROR] `env.ENV_TEST_MISSING` in [..]config is missing a `value`"));
+}
@@ -0,0 +1,152 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn remove_dependency() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+bar = "0.1" # the bar crate
+baz = { version = "0.2", features = [
+    "std",
+] }
+qux = "0.3"
+
+[dev-dependencies]
+bar = "0.1"
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("remove").arg("baz"),
+                execs().with_status(0).with_stderr("\
+[REMOVING] baz from dependencies
+"));
+    assert_that(p.cargo("remove").arg("bar").arg("--dev"),
+                execs().with_status(0).with_stderr("\
+[REMOVING] bar from dev-dependencies
+"));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+bar = "0.1" # the bar crate
+qux = "0.3"
+
+[dev-dependencies]
+"#);
+}
+
+#[test]
+fn remove_dependency_table() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[target.'cfg(unix)'.dependencies.bar]
+version = "0.1"
+default-features = false
+
+# Nothing is enabled by default.
+[features]
+default = []
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("remove").arg("bar")
+                 .arg("--target").arg("cfg(unix)"),
+                execs().with_status(0).with_stderr("\
+[REMOVING] bar from target.'cfg(unix)'.dependencies
+"));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+# Nothing is enabled by default.
+[features]
+default = []
+"#);
+}
+
+#[test]
+fn remove_cleans_up_features() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+bar = { version = "0.1", optional = true }
+baz = { version = "0.2", optional = true }
+qux = { version = "0.3", optional = true }
+
+[build-dependencies]
+baz = { version = "0.2", optional = true }
+
+[features]
+default = ["bar", "baz"]
+std = ["bar/std", "baz/std"]
+serde = ["bar?/serde", "qux?/serde"]
+derive = ["dep:qux"]
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("remove").arg("bar"),
+                execs().with_status(0));
+    assert_that(p.cargo("remove").arg("qux"),
+                execs().with_status(0));
+    // `baz` is still a build dependency, so its features are kept.
+    assert_that(p.cargo("remove").arg("baz"),
+                execs().with_status(0));
+    assert_eq!(p.read_file("Cargo.toml"), r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+
+[build-dependencies]
+baz = { version = "0.2", optional = true }
+
+[features]
+default = ["baz"]
+std = ["baz/std"]
+serde = []
+derive = []
+"#);
+}
+
+#[test]
+fn remove_missing_dependency() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"[package]
+name = "foo"
+version = "0.0.1"
+authors = []
+
+[dependencies]
+bar = "0.1"
+"#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("remove").arg("bar").arg("--build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] the dependency `bar` could not be found in `build-dependencies`
+"));
+    assert_that(p.cargo("remove").arg("baz"),
+                execs().with_status(101).with_stderr("\
+[ERROR] the dependency `baz` could not be found in `dependencies`
+"));
+}
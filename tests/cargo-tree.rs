@@ -0,0 +1,356 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{git, project, execs, ProjectBuilder};
+use cargotest::support::registry::Package;
+use hamcrest::assert_that;
+
+fn simple_project() -> ProjectBuilder {
+    Package::new("d", "1.0.0").publish();
+    Package::new("c", "1.0.0").dep("d", "1.0").publish();
+    Package::new("e", "1.0.0").publish();
+    Package::new("f", "1.0.0").publish();
+
+    project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            a = { path = "a" }
+            b = { path = "b" }
+
+            [build-dependencies]
+            e = "1.0"
+
+            [dev-dependencies]
+            f = "1.0"
+        "#)
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", r#"
+            [package]
+            name = "a"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            c = "1.0"
+        "#)
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", r#"
+            [package]
+            name = "b"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            c = "1.0"
+        "#)
+        .file("b/src/lib.rs", "")
+}
+
+#[test]
+fn simple() {
+    let p = simple_project();
+
+    assert_that(p.cargo_process("tree"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── a v0.0.1 ([..])
+│   └── c v1.0.0
+│       └── d v1.0.0
+└── b v0.0.1 ([..])
+    └── c v1.0.0 (*)
+[build-dependencies]
+└── e v1.0.0
+[dev-dependencies]
+└── f v1.0.0
+"));
+
+    assert_that(p.cargo("tree").arg("-e").arg("normal,build"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── a v0.0.1 ([..])
+│   └── c v1.0.0
+│       └── d v1.0.0
+└── b v0.0.1 ([..])
+    └── c v1.0.0 (*)
+[build-dependencies]
+└── e v1.0.0
+"));
+}
+
+#[test]
+fn depth_and_prune() {
+    let p = simple_project();
+
+    assert_that(p.cargo_process("tree").arg("--depth").arg("1"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── a v0.0.1 ([..])
+└── b v0.0.1 ([..])
+[build-dependencies]
+└── e v1.0.0
+[dev-dependencies]
+└── f v1.0.0
+"));
+
+    assert_that(p.cargo("tree").arg("--prune").arg("c").arg("-e").arg("normal"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── a v0.0.1 ([..])
+└── b v0.0.1 ([..])
+"));
+
+    assert_that(p.cargo("tree").arg("--prune").arg("nope"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] package ID specification `nope` for `--prune` did not match any packages"));
+}
+
+#[test]
+fn invert() {
+    let p = simple_project();
+
+    assert_that(p.cargo_process("tree").arg("--invert").arg("d"),
+                execs().with_status(0).with_stdout("\
+d v1.0.0
+└── c v1.0.0
+    ├── a v0.0.1 ([..])
+    │   └── foo v0.0.1 ([..])
+    └── b v0.0.1 ([..])
+        └── foo v0.0.1 ([..])
+"));
+
+    assert_that(p.cargo("tree").arg("-i").arg("e"),
+                execs().with_status(0).with_stdout("\
+e v1.0.0
+[build-dependencies]
+└── foo v0.0.1 ([..])
+"));
+}
+
+#[test]
+fn same_name_from_different_sources() {
+    Package::new("bar", "1.0.0").publish();
+    let git = git::new("bar", |project| {
+        project.file("Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "1.0.0"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "1.0"
+
+            [build-dependencies]
+            bar = {{ git = '{}' }}
+        "#, git.url()))
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("tree"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+└── bar v1.0.0
+[build-dependencies]
+└── bar v1.0.0 ([..])
+"));
+
+    assert_that(p.cargo("tree").arg("-e").arg("normal"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+└── bar v1.0.0
+"));
+}
+
+#[test]
+fn patched_dependency() {
+    Package::new("bar", "1.0.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "1.0"
+
+            [patch.crates-io]
+            bar = { path = "bar" }
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "1.0.1"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("tree"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+└── bar v1.0.1 ([..]bar)
+"));
+}
+
+#[test]
+fn duplicates() {
+    Package::new("c", "1.0.0").publish();
+    Package::new("c", "2.0.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            a = { path = "a" }
+            c = "1.0"
+        "#)
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", r#"
+            [package]
+            name = "a"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            c = "2.0"
+        "#)
+        .file("a/src/lib.rs", "");
+
+    assert_that(p.cargo_process("tree").arg("--duplicates"),
+                execs().with_status(0).with_stdout("\
+c v1.0.0
+└── foo v0.0.1 ([..])
+
+c v2.0.0
+└── a v0.0.1 ([..])
+    └── foo v0.0.1 ([..])
+"));
+}
+
+#[test]
+fn target_filtering() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [target.'cfg(foobar)'.dependencies]
+            never = { path = "never" }
+
+            [target.'cfg(not(foobar))'.dependencies]
+            always = { path = "always" }
+        "#)
+        .file("src/lib.rs", "")
+        .file("never/Cargo.toml", r#"
+            [package]
+            name = "never"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("never/src/lib.rs", "")
+        .file("always/Cargo.toml", r#"
+            [package]
+            name = "always"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("always/src/lib.rs", "");
+
+    assert_that(p.cargo_process("tree"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+└── always v0.0.1 ([..])
+"));
+
+    assert_that(p.cargo("tree").arg("--target").arg("all"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── always v0.0.1 ([..])
+└── never v0.0.1 ([..])
+"));
+}
+
+#[test]
+fn features_and_format() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", features = ["f1"] }
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+            license = "MIT"
+
+            [dependencies]
+            baz = { path = "../baz", optional = true }
+
+            [features]
+            default = ["f2"]
+            f1 = ["baz"]
+            f2 = []
+            f3 = []
+        "#)
+        .file("bar/src/lib.rs", "")
+        .file("baz/Cargo.toml", r#"
+            [package]
+            name = "baz"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("baz/src/lib.rs", "");
+
+    assert_that(p.cargo_process("tree").arg("-e").arg("features"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])
+├── bar feature \"default\"
+│   ├── bar v0.0.1 ([..])
+│   │   └── baz v0.0.1 ([..])
+│   └── bar feature \"f2\"
+│       └── bar v0.0.1 ([..]) (*)
+└── bar feature \"f1\"
+    ├── bar v0.0.1 ([..]) (*)
+    └── baz v0.0.1 ([..])
+"));
+
+    assert_that(p.cargo("tree").arg("--depth").arg("1")
+                 .arg("--format").arg("{p} {l} [{f}]"),
+                execs().with_status(0).with_stdout("\
+foo v0.0.1 ([..])  []
+└── bar v0.0.1 ([..]) MIT [baz,default,f1,f2]
+"));
+
+    assert_that(p.cargo("tree").arg("--format").arg("{x}"),
+                execs().with_status(101).with_stderr("\
+[ERROR] unsupported pattern `{x}` in format `{x}`
+"));
+}
@@ -20,7 +20,7 @@ extern crate url;
 use std::ffi::OsStr;
 use std::time::Duration;
 
-use cargo::util::Rustc;
+use cargo::util::{Rustc, parse_rust_version};
 use std::path::PathBuf;
 
 pub mod support;
@@ -39,6 +39,12 @@ pub fn is_nightly() -> bool {
     })
 }
 
+/// Returns whether the active rustc is at least `version`, e.g. `"1.31"`.
+pub fn rustc_at_least(version: &str) -> bool {
+    let version = parse_rust_version(version).unwrap();
+    RUSTC.with(|r| r.supports(&version).unwrap())
+}
+
 pub fn process<T: AsRef<OsStr>>(t: T) -> cargo::util::ProcessBuilder {
     _process(t.as_ref())
 }
@@ -186,6 +186,14 @@ impl ProjectBuilder {
         FileBuilder::new(self.root.join(path), body).mk()
     }
 
+    /// Returns the contents of the file at `path` in the project.
+    pub fn read_file(&self, path: &str) -> String {
+        let mut contents = String::new();
+        fs::File::open(self.root.join(path)).unwrap()
+            .read_to_string(&mut contents).unwrap();
+        contents
+    }
+
     pub fn symlink<T: AsRef<Path>>(mut self, dst: T,
                                    src: T) -> ProjectBuilder {
         self.symlinks.push(SymlinkBuilder::new(self.root.join(dst),
@@ -837,6 +845,8 @@ fn substitute_macros(input: &str) -> String {
         ("[UPDATING]",    "    Updating"),
         ("[ADDING]",      "      Adding"),
         ("[REMOVING]",    "    Removing"),
+        ("[REMOVED]",     "     Removed"),
+        ("[FEATURES]",    "    Features"),
         ("[DOCTEST]",     "   Doc-tests"),
         ("[PACKAGING]",   "   Packaging"),
         ("[DOWNLOADING]", " Downloading"),
@@ -846,6 +856,7 @@ fn substitute_macros(input: &str) -> String {
         ("[INSTALLING]",  "  Installing"),
         ("[REPLACING]",   "   Replacing"),
         ("[UNPACKING]",   "   Unpacking"),
+        ("[VENDORING]",   "   Vendoring"),
         ("[EXE]", if cfg!(windows) {".exe"} else {""}),
         ("[/]", if cfg!(windows) {"\\"} else {"/"}),
     ];
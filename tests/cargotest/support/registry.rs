@@ -18,6 +18,10 @@ pub fn registry_path() -> PathBuf { paths::root().join("registry") }
 pub fn registry() -> Url { Url::from_file_path(&*registry_path()).ok().unwrap() }
 pub fn dl_path() -> PathBuf { paths::root().join("dl") }
 pub fn dl_url() -> Url { Url::from_file_path(&*dl_path()).ok().unwrap() }
+pub fn alt_registry_path() -> PathBuf { paths::root().join("alternative-registry") }
+pub fn alt_registry() -> Url { Url::from_file_path(&*alt_registry_path()).ok().unwrap() }
+pub fn alt_dl_path() -> PathBuf { paths::root().join("alt_dl") }
+pub fn alt_dl_url() -> Url { Url::from_file_path(&*alt_dl_path()).ok().unwrap() }
 
 pub struct Package {
     name: String,
@@ -26,7 +30,9 @@ pub struct Package {
     files: Vec<(String, String)>,
     yanked: bool,
     features: HashMap<String, Vec<String>>,
+    rust_version: Option<String>,
     local: bool,
+    alternative: bool,
 }
 
 struct Dependency {
@@ -35,6 +41,7 @@ struct Dependency {
     kind: String,
     target: Option<String>,
     features: Vec<String>,
+    registry: Option<String>,
 }
 
 pub fn init() {
@@ -53,7 +60,10 @@ pub fn init() {
 
         [source.dummy-registry]
         registry = '{reg}'
-    "#, reg = registry()).as_bytes()));
+
+        [registries.alternative]
+        index = '{alt}'
+    "#, reg = registry(), alt = alt_registry()).as_bytes()));
 
     // Init a new registry
     repo(&registry_path())
@@ -62,6 +72,14 @@ pub fn init() {
         "#, dl_url()))
         .build();
     fs::create_dir_all(dl_path().join("api/v1/crates")).unwrap();
+
+    // Init an alt registry
+    repo(&alt_registry_path())
+        .file("config.json", &format!(r#"
+            {{"dl":"{0}","api":"{0}"}}
+        "#, alt_dl_url()))
+        .build();
+    fs::create_dir_all(alt_dl_path().join("api/v1/crates")).unwrap();
 }
 
 impl Package {
@@ -74,7 +92,9 @@ impl Package {
             files: Vec::new(),
             yanked: false,
             features: HashMap::new(),
+            rust_version: None,
             local: false,
+            alternative: false,
         }
     }
 
@@ -83,6 +103,11 @@ impl Package {
         self
     }
 
+    pub fn alternative(&mut self, alternative: bool) -> &mut Package {
+        self.alternative = alternative;
+        self
+    }
+
     pub fn file(&mut self, name: &str, contents: &str) -> &mut Package {
         self.files.push((name.to_string(), contents.to_string()));
         self
@@ -110,6 +135,17 @@ impl Package {
         self.full_dep(name, vers, None, "dev", &[])
     }
 
+    /// Adds a dependency on a package from the registry with the index
+    /// `registry`, rather than the registry this package is published to.
+    pub fn registry_dep(&mut self,
+                        name: &str,
+                        vers: &str,
+                        registry: &str) -> &mut Package {
+        self.full_dep(name, vers, None, "normal", &[]);
+        self.deps.last_mut().unwrap().registry = Some(registry.to_string());
+        self
+    }
+
     fn full_dep(&mut self,
                 name: &str,
                 vers: &str,
@@ -122,15 +158,27 @@ impl Package {
             kind: kind.to_string(),
             target: target.map(|s| s.to_string()),
             features: features.iter().map(|s| s.to_string()).collect(),
+            registry: None,
         });
         self
     }
 
+    pub fn feature(&mut self, name: &str, deps: &[&str]) -> &mut Package {
+        self.features.insert(name.to_string(),
+                             deps.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
     pub fn yanked(&mut self, yanked: bool) -> &mut Package {
         self.yanked = yanked;
         self
     }
 
+    pub fn rust_version(&mut self, rust_version: &str) -> &mut Package {
+        self.rust_version = Some(rust_version.to_string());
+        self
+    }
+
     pub fn publish(&self) -> String {
         self.make_archive();
 
@@ -144,6 +192,7 @@ impl Package {
                 "target": dep.target,
                 "optional": false,
                 "kind": dep.kind,
+                "registry": dep.registry,
             })
         }).collect::<Vec<_>>();
         let cksum = {
@@ -151,14 +200,28 @@ impl Package {
             t!(t!(File::open(&self.archive_dst())).read_to_end(&mut c));
             cksum(&c)
         };
-        let line = json!({
+        // Features using the `dep:` or `pkg?/feat` syntax are not understood
+        // by older versions of Cargo, so they're published in `features2`.
+        let (features, features2): (HashMap<_, _>, HashMap<_, _>) =
+            self.features.iter().partition(|&(_, values)| {
+                !values.iter().any(|v| v.starts_with("dep:") || v.contains("?/"))
+            });
+        let mut line = json!({
             "name": self.name,
             "vers": self.vers,
             "deps": deps,
             "cksum": cksum,
-            "features": self.features,
+            "features": features,
             "yanked": self.yanked,
-        }).to_string();
+        });
+        if !features2.is_empty() {
+            line["features2"] = json!(features2);
+            line["v"] = json!(2);
+        }
+        if let Some(ref rust_version) = self.rust_version {
+            line["rust_version"] = json!(rust_version);
+        }
+        let line = line.to_string();
 
         let file = match self.name.len() {
             1 => format!("1/{}", self.name),
@@ -167,11 +230,17 @@ impl Package {
             _ => format!("{}/{}/{}", &self.name[0..2], &self.name[2..4], self.name),
         };
 
+        let registry_path = if self.alternative {
+            alt_registry_path()
+        } else {
+            registry_path()
+        };
+
         // Write file/line in the index
         let dst = if self.local {
-            registry_path().join("index").join(&file)
+            registry_path.join("index").join(&file)
         } else {
-            registry_path().join(&file)
+            registry_path.join(&file)
         };
         let mut prev = String::new();
         let _ = File::open(&dst).and_then(|mut f| f.read_to_string(&mut prev));
@@ -181,7 +250,7 @@ impl Package {
 
         // Add the new file to the index
         if !self.local {
-            let repo = t!(git2::Repository::open(&registry_path()));
+            let repo = t!(git2::Repository::open(&registry_path));
             let mut index = t!(repo.index());
             t!(index.add_path(Path::new(&file)));
             t!(index.write());
@@ -250,6 +319,8 @@ impl Package {
         if self.local {
             registry_path().join(format!("{}-{}.crate", self.name,
                                          self.vers))
+        } else if self.alternative {
+            alt_dl_path().join(&self.name).join(&self.vers).join("download")
         } else {
             dl_path().join(&self.name).join(&self.vers).join("download")
         }
@@ -1,9 +1,11 @@
 extern crate hamcrest;
+#[macro_use]
 extern crate cargotest;
 
 use std::env;
+use std::fs;
 
-use cargotest::support::{git, project, execs, main_file, basic_bin_manifest};
+use cargotest::support::{git, paths, project, execs, main_file, basic_bin_manifest};
 use cargotest::support::registry::Package;
 use hamcrest::{assert_that, existing_dir, existing_file, is_not};
 
@@ -136,6 +138,44 @@ fn clean_release() {
 "));
 }
 
+#[test]
+fn clean_custom_profile() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            a = { path = "a" }
+
+            [profile.ci]
+            inherits = "dev"
+            opt-level = 1
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file("a/Cargo.toml", r#"
+            [package]
+            name = "a"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("a/src/lib.rs", "");
+    p.build();
+
+    assert_that(p.cargo("build").arg("--profile").arg("ci"),
+                execs().with_status(0));
+
+    assert_that(p.cargo("clean").arg("-p").arg("foo"),
+                execs().with_status(0));
+    assert_that(p.cargo("build").arg("--profile").arg("ci"),
+                execs().with_status(0).with_stderr("\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] ci [optimized + debuginfo] target(s) in [..]
+"));
+}
+
 #[test]
 fn build_script() {
     let p = project("foo")
@@ -234,3 +274,218 @@ fn registry() {
     assert_that(p.cargo("build"),
                 execs().with_status(0));
 }
+
+/// Counts the crates (or checkouts) in the per-source directories of `dir` in
+/// the global package cache.
+fn cache_entries(dir: &str) -> usize {
+    let dir = paths::home().join(".cargo").join(dir);
+    if !dir.is_dir() {
+        return 0
+    }
+    t!(fs::read_dir(&dir)).map(|source| {
+        t!(fs::read_dir(t!(source).path())).filter(|e| {
+            !t!(e.as_ref()).file_name().to_str().unwrap().starts_with('.')
+        }).count()
+    }).sum()
+}
+
+#[test]
+fn gc() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+    p.build();
+
+    Package::new("bar", "0.1.0").publish();
+
+    assert_that(p.cargo("build"),
+                execs().with_status(0));
+    assert_eq!(cache_entries("registry/cache"), 1);
+    assert_eq!(cache_entries("registry/src"), 1);
+
+    // Everything was just used.
+    assert_that(p.cargo("clean").arg("gc").arg("--max-age").arg("30d"),
+                execs().with_status(0)
+                       .with_stderr("\
+[REMOVED] 0 package cache entries, 0 B total
+"));
+    assert_eq!(cache_entries("registry/src"), 1);
+
+    assert_that(p.cargo("clean").arg("gc").arg("--max-size").arg("0"),
+                execs().with_status(0)
+                       .with_stderr("\
+[REMOVED] 2 package cache entries, [..] total
+"));
+    assert_eq!(cache_entries("registry/cache"), 0);
+    assert_eq!(cache_entries("registry/src"), 0);
+
+    // Removed crates are simply downloaded again.
+    assert_that(p.cargo("clean"),
+                execs().with_status(0));
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("[DOWNLOADING] bar v0.1.0 [..]"));
+}
+
+#[test]
+fn gc_git() {
+    let git = git::new("dep", |project| {
+        project.file("Cargo.toml", r#"
+            [project]
+            name = "dep"
+            version = "0.5.0"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            dep = {{ git = '{}' }}
+        "#, git.url()))
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+    assert_eq!(cache_entries("git/checkouts"), 1);
+
+    assert_that(p.cargo("clean").arg("gc").arg("--max-size").arg("0B"),
+                execs().with_status(0)
+                       .with_stderr("\
+[REMOVED] 2 package cache entries, [..] total
+"));
+    assert_that(&paths::home().join(".cargo/git/checkouts"), existing_dir());
+    assert_eq!(t!(fs::read_dir(paths::home().join(".cargo/git/checkouts"))).count(), 0);
+    assert_eq!(t!(fs::read_dir(paths::home().join(".cargo/git/db"))).count(), 0);
+}
+
+#[test]
+fn gc_auto() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", r#"
+            [gc]
+            auto = true
+            max-size = "0"
+        "#);
+    p.build();
+
+    Package::new("bar", "0.1.0").publish();
+
+    // The cache is cleaned once the build is done with it.
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[REMOVED] 2 package cache entries, [..] total"));
+    assert_eq!(cache_entries("registry/src"), 0);
+
+    // But only once a day.
+    assert_that(p.cargo("fetch"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[REMOVED] [..]"));
+    assert_eq!(cache_entries("registry/cache"), 1);
+}
+
+#[test]
+fn gc_auto_only_after_using_cache() {
+    let p = project("foo")
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/foo.rs", &main_file(r#""i am foo""#, &[]))
+        .file(".cargo/config", r#"
+            [gc]
+            auto = true
+        "#);
+
+    // Nothing in the package cache is used to build a package without
+    // dependencies, so it's not cleaned either.
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[REMOVED] [..]"));
+}
+
+#[test]
+fn gc_auto_after_manual_gc() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", r#"
+            [gc]
+            auto = true
+        "#);
+    p.build();
+
+    Package::new("bar", "0.1.0").publish();
+
+    assert_that(p.cargo("clean").arg("gc"),
+                execs().with_status(0)
+                       .with_stderr("\
+[REMOVED] 0 package cache entries, 0 B total
+"));
+
+    // The cache counts as cleaned automatically for the next day.
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[REMOVED] [..]"));
+}
+
+#[test]
+fn gc_bad_limits() {
+    let p = project("foo")
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/foo.rs", &main_file(r#""i am foo""#, &[]));
+
+    assert_that(p.cargo_process("clean").arg("gc").arg("--max-age").arg("30x"),
+                execs().with_status(101)
+                       .with_stderr("\
+[ERROR] invalid age `30x`, expected a number followed by one of `s`, `m`, `h`, `d` or `w`
+"));
+    assert_that(p.cargo("clean").arg("gc").arg("--max-size").arg("lots"),
+                execs().with_status(101)
+                       .with_stderr("\
+[ERROR] `lots` doesn't start with a number
+
+Caused by:
+  cannot parse integer from empty string
+"));
+    assert_that(p.cargo("clean").arg("gc").arg("--max-age").arg("9999999999999999w"),
+                execs().with_status(101)
+                       .with_stderr("\
+[ERROR] age `9999999999999999w` is too large
+"));
+    assert_that(p.cargo("clean").arg("gc").arg("--max-size").arg("99999999999GiB"),
+                execs().with_status(101)
+                       .with_stderr("\
+[ERROR] size `99999999999GiB` is too large
+"));
+}
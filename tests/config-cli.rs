@@ -0,0 +1,269 @@
+extern crate cargo;
+#[macro_use]
+extern crate cargotest;
+extern crate hamcrest;
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+
+use cargotest::support::{project, execs, cargo_exe, ProjectBuilder};
+use cargotest::support::paths;
+use cargo::util::ProcessBuilder;
+use hamcrest::assert_that;
+
+/// Runs `cargo` in `p` with the global `--config` arguments `args`, before
+/// the name of the command is added.
+fn cargo_with_config(p: &ProjectBuilder, args: &[&str]) -> ProcessBuilder {
+    let mut process = p.process(cargo_exe());
+    for arg in args {
+        process.arg(arg);
+    }
+    process
+}
+
+#[test]
+fn cli_overrides_config_file() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [build]
+            jobs = 1
+        "#);
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config", "build.jobs=4"])
+                 .arg("config").arg("get").arg("build.jobs"),
+                execs().with_status(0).with_stdout("build.jobs = 4\n"));
+
+    // Later arguments take precedence.
+    assert_that(cargo_with_config(&p, &["--config=build.jobs=4",
+                                        "--config=build.jobs=8"])
+                 .arg("config").arg("get").arg("build.jobs"),
+                execs().with_status(0).with_stdout("build.jobs = 8\n"));
+}
+
+#[test]
+fn cli_overrides_environment() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config", "term.verbose=true"])
+                 .arg("build")
+                 .env("CARGO_TERM_VERBOSE", "false"),
+                execs().with_status(0)
+                       .with_stderr_contains("[RUNNING] `rustc [..]`"));
+}
+
+#[test]
+fn cli_quoted_keys() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config", "target.'cfg(not(target_os = \"none\"))'.runner = \"nonexistent-runner\""])
+                 .arg("run"),
+                execs().with_stderr_contains("\
+[RUNNING] `nonexistent-runner target[/]debug[/]foo[EXE]`"));
+}
+
+#[test]
+fn cli_config_file() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file("ci/extra.toml", r#"
+            [build]
+            jobs = 3
+        "#);
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config", "ci/extra.toml"])
+                 .arg("config").arg("get").arg("build").arg("--show-origin"),
+                execs().with_status(0).with_stdout("\
+build.jobs = 3 # [..]ci[/]extra.toml
+"));
+
+    assert_that(cargo_with_config(&p, &["--config", "missing.toml"])
+                 .arg("config").arg("get"),
+                execs().with_status(101).with_stderr("\
+[ERROR] config file `[..]missing.toml` passed with `--config` does not exist
+"));
+}
+
+#[test]
+fn bad_cli_value() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config", "build.jobs=four"]).arg("build"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] failed to parse `--config` argument `build.jobs=four`"));
+}
+
+#[test]
+fn get_show_origin() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [build]
+            rustflags = ["-C", "debuginfo=1"]
+            target-dir = "out"
+        "#);
+
+    p.build();
+    assert_that(cargo_with_config(&p, &["--config",
+                                        "build.rustflags=[\"-Cdebug-assertions\"]"])
+                 .arg("config").arg("get").arg("build").arg("--show-origin"),
+                execs().with_status(0).with_stdout("\
+build.rustflags = [
+    \"-Cdebug-assertions\", # --config cli option
+    \"-C\", # [..].cargo[/]config
+    \"debuginfo=1\", # [..].cargo[/]config
+]
+build.target-dir = \"out\" # [..].cargo[/]config
+"));
+}
+
+#[test]
+fn get_json() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [build]
+            jobs = 2
+            target-dir = "out"
+        "#);
+
+    assert_that(p.cargo_process("config").arg("get").arg("build.jobs")
+                 .arg("--format").arg("json"),
+                execs().with_status(0).with_json(r#"
+                    {"build": {"jobs": 2}}
+                "#));
+
+    assert_that(p.cargo("config").arg("get").arg("build.jobs")
+                 .arg("--format").arg("json").arg("--show-origin"),
+                execs().with_status(0).with_json(r#"
+                    {"build": {"jobs": {"value": 2, "origin": "[..]config"}}}
+                "#));
+}
+
+#[test]
+fn get_missing_key() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("config").arg("get").arg("build.not-set"),
+                execs().with_status(101).with_stderr("\
+[ERROR] config value `build.not-set` is not set
+"));
+}
+
+#[test]
+fn config_after_command_is_not_global() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    // It's left to the command, which doesn't know about it.
+    assert_that(p.cargo_process("config").arg("get").arg("build.jobs")
+                 .arg("--config").arg("build.jobs=4"),
+                execs().with_status(1).with_stderr_contains("[..]--config[..]"));
+}
+
+#[test]
+fn get_leaves_out_credentials() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "")
+        .file(".cargo/config", r#"
+            [registry]
+            index = "http://example.com/index"
+
+            [registries.alt]
+            index = "http://example.com/alt"
+            token = "alt-secret"
+        "#);
+    p.build();
+    t!(fs::create_dir_all(paths::home().join(".cargo")));
+    t!(t!(File::create(paths::home().join(".cargo/credentials"))).write_all(br#"
+        [registry]
+        token = "secret"
+    "#));
+
+    assert_that(p.cargo("config").arg("get"),
+                execs().with_status(0)
+                       .with_stdout_contains("\
+registry.index = \"http://example.com/index\"")
+                       .with_stdout_contains("\
+registries.alt.index = \"http://example.com/alt\"")
+                       .with_stdout_does_not_contain("[..]secret[..]"));
+    assert_that(p.cargo("config").arg("get").arg("registries")
+                 .arg("--format").arg("json"),
+                execs().with_status(0)
+                       .with_json(r#"
+                           {"registries": {"alt": {"index": "http://example.com/alt"}}}
+                       "#));
+    assert_that(p.cargo("config").arg("get").arg("registry.token"),
+                execs().with_status(101).with_stderr("\
+[ERROR] config value `registry.token` is a credential and isn't printed
+"));
+}
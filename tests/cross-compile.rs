@@ -3,6 +3,7 @@ extern crate cargotest;
 extern crate hamcrest;
 
 use std::env;
+use std::fs;
 use std::process::Command;
 use std::sync::{Once, ONCE_INIT};
 use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
@@ -1106,3 +1107,131 @@ fn cross_test_dylib() {
                        .with_stdout_contains_n("test foo ... ok", 2));
 
 }
+
+#[test]
+fn multiple_targets() {
+    if disabled() { return }
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.0"
+            authors = []
+            build = "build.rs"
+        "#)
+        .file("build.rs", r#"
+            fn main() {
+                let target = std::env::var("TARGET").unwrap();
+                println!("cargo:rustc-env=BUILT_FOR={}", target);
+            }
+        "#)
+        .file("src/main.rs", r#"
+            fn main() {
+                println!("{}", env!("BUILT_FOR"));
+            }
+        "#);
+
+    let target = alternate();
+    assert_that(p.cargo_process("build")
+                 .arg("--target").arg(&target)
+                 .arg("--target").arg(&host()),
+                execs().with_status(0));
+    assert_that(&p.target_bin(&target, "foo"), existing_file());
+    assert_that(&p.target_bin(&host(), "foo"), existing_file());
+
+    assert_that(process(&p.target_bin(&target, "foo")),
+                execs().with_status(0).with_stdout(&format!("{}\n", target)));
+    assert_that(process(&p.target_bin(&host(), "foo")),
+                execs().with_status(0).with_stdout(&format!("{}\n", host())));
+
+    // The build script itself is only compiled once, for the host.
+    let build_dirs = fs::read_dir(p.root().join("target/debug/build")).unwrap()
+        .filter(|e| {
+            e.as_ref().unwrap().path().join("build-script-build").exists() ||
+            e.as_ref().unwrap().path().join("build-script-build.exe").exists()
+        })
+        .count();
+    assert_eq!(build_dirs, 1);
+}
+
+#[test]
+fn multiple_targets_config() {
+    if disabled() { return }
+
+    let p = project("foo")
+        .file(".cargo/config", &format!(r#"
+            [build]
+            target = ["{}", "{}"]
+        "#, alternate(), host()))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.0"
+            authors = []
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    let target = alternate();
+    assert_that(p.cargo_process("build").arg("--message-format").arg("json"),
+                execs().with_status(0)
+                       .with_stdout_contains(&format!(
+                           "[..]\"target_triple\":\"{}\"[..]", target))
+                       .with_stdout_contains(&format!(
+                           "[..]\"target_triple\":\"{}\"[..]", host())));
+    assert_that(&p.target_bin(&target, "foo"), existing_file());
+    assert_that(&p.target_bin(&host(), "foo"), existing_file());
+}
+
+#[test]
+fn string_target_config_is_one_target() {
+    let p = project("foo")
+        .file(".cargo/config", r#"
+            [build]
+            target = "not a target"
+        "#)
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.0"
+            authors = []
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    // A string isn't split on spaces, multiple targets are given as a list.
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101)
+                       .with_stderr_contains("[..]--target not a target[..]"));
+}
+
+#[test]
+fn multiple_targets_cannot_be_tested() {
+    if disabled() { return }
+
+    let p = project("foo")
+        .file(".cargo/config", &format!(r#"
+            [build]
+            target = ["{}", "{}"]
+        "#, alternate(), host()))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.0"
+            authors = []
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("test"),
+                execs().with_status(101)
+                       .with_stderr(&format!("\
+[ERROR] tests and benchmarks can only be built for a single target, \
+but 2 were requested: {}, {}
+", alternate(), host())));
+
+    assert_that(p.cargo("run"),
+                execs().with_status(101)
+                       .with_stderr(&format!("\
+[ERROR] `cargo run` can only run an executable built for a single target, \
+but 2 were requested: {}, {}
+", alternate(), host())));
+}
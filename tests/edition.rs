@@ -0,0 +1,146 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::rustc_at_least;
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn edition_2015_by_default() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[..]--edition[..]"));
+}
+
+#[test]
+fn edition_passed_to_rustc() {
+    if !rustc_at_least("1.31") {
+        return
+    }
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            edition = "2018"
+        "#)
+        .file("src/lib.rs", r#"
+            pub fn foo() -> u32 { crate::bar() }
+            fn bar() -> u32 { 1 }
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name foo --edition=2018 src[/]lib.rs [..]`"));
+}
+
+#[test]
+fn edition_overridden_per_target() {
+    if !rustc_at_least("1.31") {
+        return
+    }
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            edition = "2018"
+
+            [[bin]]
+            name = "old"
+            path = "src/main.rs"
+            edition = "2015"
+        "#)
+        .file("src/main.rs", r#"
+            fn main() { let async = 1; let _ = async; }
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[..]--edition[..]"));
+}
+
+#[test]
+fn edition_passed_to_doctests() {
+    if !rustc_at_least("1.31") {
+        return
+    }
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            edition = "2018"
+        "#)
+        .file("src/lib.rs", r#"
+            /// ```
+            /// assert_eq!(foo::foo(), 1);
+            /// ```
+            pub fn foo() -> u32 { 1 }
+        "#);
+
+    assert_that(p.cargo_process("test").arg("--doc").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustdoc --test [..]--edition=2018[..]`"));
+}
+
+#[test]
+fn invalid_edition() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            edition = "2017"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  supported edition values are `2015` or `2018`, but `2017` is unknown
+"));
+}
+
+#[test]
+fn edition_in_metadata() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            edition = "2018"
+
+            [[bin]]
+            name = "old"
+            path = "src/main.rs"
+            edition = "2015"
+        "#)
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("metadata").arg("--no-deps"),
+                execs().with_status(0)
+                       .with_stdout_contains("[..]\"name\":\"foo\",\
+\"src_path\":\"[..]lib.rs\",\"edition\":\"2018\"}[..]")
+                       .with_stdout_contains("[..]\"name\":\"old\",\
+\"src_path\":\"[..]main.rs\",\"edition\":\"2015\"}[..]"));
+}
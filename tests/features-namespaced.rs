@@ -0,0 +1,257 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::registry::Package;
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn dep_feature_enables_optional_dep() {
+    Package::new("baz", "0.1.0")
+        .file("src/lib.rs", "pub fn baz() {}")
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", optional = true }
+
+            [features]
+            fancy = ["dep:baz"]
+        "#)
+        .file("src/lib.rs", r#"
+            #[cfg(feature = "fancy")]
+            extern crate baz;
+
+            #[cfg(feature = "baz")]
+            pub fn implicit_feature() { let _x: u32 = "should not exist"; }
+
+            #[cfg(feature = "fancy")]
+            pub fn foo() { baz::baz() }
+        "#);
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[COMPILING] baz [..]"));
+    assert_that(p.cargo("build").arg("--features").arg("fancy"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] baz v0.1.0"));
+}
+
+#[test]
+fn feature_shares_name_with_namespaced_dep() {
+    Package::new("baz", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", optional = true }
+
+            [features]
+            baz = ["dep:baz"]
+        "#)
+        .file("src/lib.rs", r#"
+            #[cfg(feature = "baz")]
+            extern crate baz;
+        "#);
+
+    assert_that(p.cargo_process("build").arg("--features").arg("baz"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] baz v0.1.0"));
+}
+
+#[test]
+fn hidden_dep_cannot_be_enabled_directly() {
+    Package::new("baz", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", optional = true }
+
+            [features]
+            fancy = ["dep:baz"]
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("--features").arg("baz"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+[..]Package `foo v0.0.1 ([..])` does not have feature `baz`. It has an \
+optional dependency with that name, but it is only enabled with `dep:baz`"));
+}
+
+#[test]
+fn invalid_dep_feature() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [features]
+            fancy = ["dep:bar"]
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  Feature `fancy` includes `dep:bar`, but `bar` is not an optional dependency
+"));
+}
+
+#[test]
+fn weak_dep_feature() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar", optional = true }
+
+            [features]
+            extra = ["bar?/extra"]
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+
+            [features]
+            extra = []
+        "#)
+        .file("bar/src/lib.rs", r#"
+            #[cfg(feature = "extra")]
+            pub fn extra() { let _x: u32 = "extra enabled"; }
+        "#);
+
+    // `extra` alone doesn't pull in `bar`.
+    assert_that(p.cargo_process("build").arg("--features").arg("extra"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[COMPILING] bar [..]"));
+
+    // Once `bar` is enabled, its `extra` feature is enabled too.
+    assert_that(p.cargo("build").arg("--features").arg("extra bar"),
+                execs().with_status(101)
+                       .with_stderr_contains("[..]mismatched types[..]"));
+
+    assert_that(p.cargo("build").arg("--features").arg("bar"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] bar v0.0.1 [..]"));
+}
+
+#[test]
+fn invalid_weak_syntax() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [features]
+            extra = ["bar?"]
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  feature `extra` includes `bar?`, but `?` may only be used in the form \
+`dep-name?/feature-name`
+"));
+}
+
+#[test]
+fn registry_features2() {
+    Package::new("baz", "0.1.0")
+        .feature("extra", &[])
+        .file("src/lib.rs", r#"
+            #[cfg(not(feature = "extra"))]
+            pub fn extra() { let _x: u32 = "extra not enabled"; }
+        "#)
+        .publish();
+    Package::new("bar", "0.1.0")
+        .dep("baz", "0.1.0")
+        .feature("extra", &["baz?/extra"])
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { version = "0.1.0", features = ["extra"] }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] baz v0.1.0")
+                       .with_stderr_contains("[COMPILING] bar v0.1.0"));
+}
+
+#[test]
+fn registry_index_from_the_future() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.1.1")
+        .feature("extra", &["dep:baz"])
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+        "#)
+        .file("src/lib.rs", "");
+
+    // `bar 0.1.1` refers to a dependency it doesn't have, so its summary
+    // can't be parsed and the index entry is skipped.
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] bar v0.1.0"));
+}
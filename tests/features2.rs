@@ -0,0 +1,249 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{git, project, execs, ProjectBuilder};
+use hamcrest::assert_that;
+
+fn common(p: ProjectBuilder) -> ProjectBuilder {
+    p.file("common/Cargo.toml", r#"
+            [package]
+            name = "common"
+            version = "0.1.0"
+            authors = []
+
+            [features]
+            f = []
+        "#)
+        .file("common/src/lib.rs", r#"
+            pub fn enabled() -> bool { cfg!(feature = "f") }
+        "#)
+}
+
+#[test]
+fn build_dep_features_are_decoupled() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = { path = "common" }
+
+            [build-dependencies]
+            common = { path = "common", features = ["f"] }
+        "#)
+        .file("build.rs", r#"
+            extern crate common;
+            fn main() { assert!(common::enabled()); }
+        "#)
+        .file("src/main.rs", r#"
+            extern crate common;
+            fn main() { assert!(!common::enabled()); }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+}
+
+#[test]
+fn build_dep_features_unified_by_default() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            common = { path = "common" }
+
+            [build-dependencies]
+            common = { path = "common", features = ["f"] }
+        "#)
+        .file("build.rs", r#"
+            extern crate common;
+            fn main() { assert!(common::enabled()); }
+        "#)
+        .file("src/main.rs", r#"
+            extern crate common;
+            fn main() { assert!(common::enabled()); }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+}
+
+#[test]
+fn dev_dep_features_only_when_needed() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = { path = "common" }
+
+            [dev-dependencies]
+            common = { path = "common", features = ["f"] }
+        "#)
+        .file("src/main.rs", r#"
+            extern crate common;
+
+            fn main() { assert!(!common::enabled()); }
+
+            #[test]
+            fn enabled() { assert!(common::enabled()); }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+    assert_that(p.cargo("test"), execs().with_status(0));
+}
+
+#[test]
+fn other_platform_features_are_ignored() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = { path = "common" }
+
+            [target.not-a-real-target.dependencies]
+            common = { path = "common", features = ["f"] }
+        "#)
+        .file("src/main.rs", r#"
+            extern crate common;
+            fn main() { assert!(!common::enabled()); }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+}
+
+#[test]
+fn resolver_in_virtual_workspace() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+            resolver = "2"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            common = { path = "../common" }
+
+            [build-dependencies]
+            common = { path = "../common", features = ["f"] }
+        "#)
+        .file("bar/build.rs", r#"
+            extern crate common;
+            fn main() { assert!(common::enabled()); }
+        "#)
+        .file("bar/src/main.rs", r#"
+            extern crate common;
+            fn main() { assert!(!common::enabled()); }
+        "#);
+
+    assert_that(p.cargo_process("run").arg("-p").arg("bar"),
+                execs().with_status(0));
+}
+
+#[test]
+fn invalid_resolver() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "3"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `resolver` setting `3` is not valid, valid options are \"1\" or \"2\"
+"));
+}
+
+#[test]
+fn metadata_reports_host_features() {
+    let p = common(project("foo"))
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = { path = "common" }
+
+            [build-dependencies]
+            common = { path = "common", features = ["f"] }
+        "#)
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("metadata"),
+                execs().with_status(0)
+                       .with_stdout_contains("\
+[..]{\"id\":\"common 0.1.0 ([..])\",\"dependencies\":[],\
+\"features\":[],\"host_features\":[\"f\"]}[..]"));
+}
+
+#[test]
+fn same_name_from_different_sources_are_decoupled() {
+    let git_project = git::new("common", |project| {
+        project.file("Cargo.toml", r#"
+            [package]
+            name = "common"
+            version = "0.1.0"
+            authors = []
+
+            [features]
+            f = []
+        "#)
+        .file("src/lib.rs", r#"
+            pub fn git_enabled() -> bool { cfg!(feature = "f") }
+        "#)
+    }).unwrap();
+
+    let p = common(project("foo"))
+        .file("Cargo.toml", &format!(r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            resolver = "2"
+
+            [dependencies]
+            common = {{ path = "common", features = ["f"] }}
+            common-git = {{ git = '{}', package = "common" }}
+        "#, git_project.url()))
+        .file("src/main.rs", r#"
+            extern crate common;
+            extern crate common_git;
+            fn main() {
+                assert!(common::enabled());
+                assert!(!common_git::git_enabled());
+            }
+        "#);
+
+    assert_that(p.cargo_process("run"), execs().with_status(0));
+}
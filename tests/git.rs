@@ -2083,3 +2083,94 @@ fn include_overrides_gitignore() {
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 "));
 }
+
+#[test]
+fn shallow_tag_dep_is_deepened_when_needed() {
+    let git_project = git::new("dep1", |project| {
+        project
+            .file("Cargo.toml", r#"
+                [project]
+                name = "dep1"
+                version = "0.5.0"
+                authors = []
+            "#)
+            .file("src/lib.rs", "pub fn dep() {}")
+    }).unwrap();
+    let repo = git2::Repository::open(&git_project.root()).unwrap();
+    File::create(&git_project.root().join("README")).unwrap();
+    git::add(&repo);
+    git::commit(&repo);
+    git::tag(&repo, "v0.5.0");
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            dep1 = {{ git = '{}', tag = 'v0.5.0' }}
+        "#, git_project.url()))
+        .file("src/lib.rs", "extern crate dep1; pub fn foo() { dep1::dep() }")
+        .file(".cargo/config", r#"
+            [net]
+            git-shallow-deps = true
+        "#);
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+
+    let db = paths::home().join(".cargo/git/db");
+    let db = fs::read_dir(&db).unwrap().next().unwrap().unwrap().path();
+    assert_that(&db.join("shallow"), existing_file());
+
+    // Depending on a branch needs the full history, which unshallows the
+    // database.
+    File::create(&p.root().join("Cargo.toml")).unwrap().write_all(format!(r#"
+        [project]
+        name = "foo"
+        version = "0.5.0"
+        authors = []
+
+        [dependencies]
+        dep1 = {{ git = '{}', branch = 'master' }}
+    "#, git_project.url()).as_bytes()).unwrap();
+
+    assert_that(p.cargo("build"), execs().with_status(0));
+    assert!(!db.join("shallow").exists());
+}
+
+#[test]
+fn git_fetch_with_cli() {
+    let git_project = git::new("dep1", |project| {
+        project
+            .file("Cargo.toml", r#"
+                [project]
+                name = "dep1"
+                version = "0.5.0"
+                authors = []
+            "#)
+            .file("src/lib.rs", "pub fn dep() {}")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies.dep1]
+            git = '{}'
+        "#, git_project.url()))
+        .file("src/lib.rs", "extern crate dep1; pub fn foo() { dep1::dep() }")
+        .file(".cargo/config", r#"
+            [net]
+            git-fetch-with-cli = true
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `git fetch --tags --force --update-head-ok [..]refs/heads/*:refs/heads/*[..]`"));
+}
@@ -0,0 +1,250 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn lints_passed_to_rustc() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "deny"
+        "#)
+        .file("src/lib.rs", r#"
+            pub fn foo() { unsafe {} }
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name foo [..]-D unsafe_code[..]`"));
+}
+
+#[test]
+fn lints_sorted_by_priority() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unused_variables = "allow"
+            warnings = { level = "deny", priority = -1 }
+        "#)
+        .file("src/lib.rs", r#"
+            pub fn foo() { let x = 1; }
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc [..]-D warnings -A unused_variables[..]`"));
+}
+
+#[test]
+fn rustdoc_lints_only_passed_to_rustdoc() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "deny"
+
+            [lints.rustdoc]
+            missing_docs = "warn"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[..]missing_docs[..]"));
+    assert_that(p.cargo("doc").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustdoc [..]-W missing_docs -D unsafe_code[..]`"));
+}
+
+#[test]
+fn lints_passed_to_doctests() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "deny"
+        "#)
+        .file("src/lib.rs", r#"
+            /// ```
+            /// foo::foo();
+            /// ```
+            pub fn foo() {}
+        "#);
+
+    assert_that(p.cargo_process("test").arg("--doc").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustdoc --test [..]-D unsafe_code[..]`"));
+}
+
+#[test]
+fn lints_only_apply_to_members() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+        "#)
+        .file("src/lib.rs", "extern crate bar;")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "deny"
+        "#)
+        .file("bar/src/lib.rs", r#"
+            pub fn bar() { unsafe {} }
+        "#);
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[..]unsafe_code[..]"));
+}
+
+#[test]
+fn inherit_lints_from_workspace() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.lints.rust]
+            unsafe_code = "deny"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+
+            [lints]
+            workspace = true
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name bar [..]-D unsafe_code[..]`"));
+}
+
+#[test]
+fn inherit_lints_and_override() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.lints.rust]
+            unsafe_code = "deny"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+
+            [lints]
+            workspace = true
+
+            [lints.rust]
+            unused = "allow"
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `lints` inherits from the workspace with `workspace = true`, so no other \
+lints may be specified
+"));
+}
+
+#[test]
+fn invalid_lint_level() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "forbidden"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `lints.rust.unsafe_code` has an invalid level `forbidden`, expected \
+`forbid`, `deny`, `warn` or `allow`
+"));
+}
+
+#[test]
+fn changing_lints_rebuilds() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [lints.rust]
+            unsafe_code = "warn"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[COMPILING] foo [..]"));
+
+    p.change_file("Cargo.toml", r#"
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        authors = []
+
+        [lints.rust]
+        unsafe_code = "deny"
+    "#);
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] foo [..]"));
+}
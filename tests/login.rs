@@ -9,7 +9,7 @@ use std::fs::{self, File};
 
 use cargotest::cargo_process;
 use cargotest::support::execs;
-use cargotest::support::registry::registry;
+use cargotest::support::registry::{self, registry};
 use cargotest::install::cargo_home;
 use cargo::util::config::Config;
 use cargo::core::Shell;
@@ -35,19 +35,21 @@ fn setup_new_credentials() {
     "#));
 }
 
-fn check_host_token(toml: toml::Value) -> bool {
-    match toml {
-        toml::Value::Table(table) => match table.get("token") {
-            Some(v) => match v {
-                &toml::Value::String(ref token) => (token.as_str() == TOKEN),
-                _ => false,
-            },
-            None => false,
-        },
+fn check_token(toml: toml::Value, registry: Option<&str>) -> bool {
+    let table = match registry {
+        Some(registry) => toml.get("registries").and_then(|r| r.get(registry)),
+        None => toml.get("registry"),
+    };
+    match table.and_then(|t| t.get("token")) {
+        Some(&toml::Value::String(ref token)) => token.as_str() == TOKEN,
         _ => false,
     }
 }
 
+fn check_host_token(toml: toml::Value) -> bool {
+    check_token(toml, None)
+}
+
 #[test]
 fn login_with_old_credentials() {
     setup_old_credentials();
@@ -126,3 +128,38 @@ fn new_credentials_is_used_instead_old() {
     let token = config.get_string("registry.token").unwrap().map(|p| p.val);
     assert!(token.unwrap() == TOKEN);
 }
+
+#[test]
+fn login_with_old_format_credentials() {
+    setup_new_credentials();
+
+    assert_that(cargo_process().arg("login")
+                .arg("--host").arg(registry().to_string()).arg(TOKEN),
+                execs().with_status(0));
+
+    let credentials = cargo_home().join("credentials");
+    let mut contents = String::new();
+    File::open(&credentials).unwrap().read_to_string(&mut contents).unwrap();
+    let toml: toml::Value = contents.parse().unwrap();
+    assert!(toml.get("token").is_none());
+    assert!(check_host_token(toml));
+}
+
+#[test]
+fn registry_credentials() {
+    registry::init();
+    setup_new_credentials();
+
+    assert_that(cargo_process().arg("login")
+                .arg("--registry").arg("alternative").arg(TOKEN),
+                execs().with_status(0));
+
+    let credentials = cargo_home().join("credentials");
+    let mut contents = String::new();
+    File::open(&credentials).unwrap().read_to_string(&mut contents).unwrap();
+    let toml: toml::Value = contents.parse().unwrap();
+
+    // The default registry's token is left untouched
+    assert!(!check_host_token(toml.clone()));
+    assert!(check_token(toml, Some("alternative")));
+}
@@ -32,7 +32,8 @@ fn cargo_metadata_simple() {
                             "bin"
                         ],
                         "name": "foo",
-                        "src_path": "[..][/]foo[/]src[/]foo.rs"
+                        "src_path": "[..][/]foo[/]src[/]foo.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -105,7 +106,8 @@ crate-type = ["lib", "staticlib"]
                             "staticlib"
                         ],
                         "name": "foo",
-                        "src_path": "[..][/]foo[/]src[/]lib.rs"
+                        "src_path": "[..][/]foo[/]src[/]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -173,7 +175,8 @@ fn cargo_metadata_with_deps_and_version() {
                             "lib"
                         ],
                         "name": "baz",
-                        "src_path": "[..]lib.rs"
+                        "src_path": "[..]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "version": "0.0.1"
@@ -185,6 +188,7 @@ fn cargo_metadata_with_deps_and_version() {
                         "kind": null,
                         "name": "baz",
                         "optional": false,
+                        "rename": null,
                         "req": "^0.0.1",
                         "source": "registry+[..]",
                         "target": null,
@@ -208,7 +212,8 @@ fn cargo_metadata_with_deps_and_version() {
                             "lib"
                         ],
                         "name": "bar",
-                        "src_path": "[..]lib.rs"
+                        "src_path": "[..]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "version": "0.0.1"
@@ -220,6 +225,7 @@ fn cargo_metadata_with_deps_and_version() {
                         "kind": null,
                         "name": "bar",
                         "optional": false,
+                        "rename": null,
                         "req": "*",
                         "source": "registry+[..]",
                         "target": null,
@@ -243,7 +249,8 @@ fn cargo_metadata_with_deps_and_version() {
                             "bin"
                         ],
                         "name": "foo",
-                        "src_path": "[..]foo.rs"
+                        "src_path": "[..]foo.rs",
+                        "edition": "2015"
                     }
                 ],
                 "version": "0.5.0"
@@ -307,13 +314,15 @@ name = "ex"
                         "kind": [ "lib" ],
                         "crate_types": [ "lib" ],
                         "name": "foo",
-                        "src_path": "[..][/]foo[/]src[/]lib.rs"
+                        "src_path": "[..][/]foo[/]src[/]lib.rs",
+                        "edition": "2015"
                     },
                     {
                         "kind": [ "example" ],
                         "crate_types": [ "bin" ],
                         "name": "ex",
-                        "src_path": "[..][/]foo[/]examples[/]ex.rs"
+                        "src_path": "[..][/]foo[/]examples[/]ex.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -369,13 +378,15 @@ crate-type = ["rlib", "dylib"]
                         "kind": [ "lib" ],
                         "crate_types": [ "lib" ],
                         "name": "foo",
-                        "src_path": "[..][/]foo[/]src[/]lib.rs"
+                        "src_path": "[..][/]foo[/]src[/]lib.rs",
+                        "edition": "2015"
                     },
                     {
                         "kind": [ "example" ],
                         "crate_types": [ "rlib", "dylib" ],
                         "name": "ex",
-                        "src_path": "[..][/]foo[/]examples[/]ex.rs"
+                        "src_path": "[..][/]foo[/]examples[/]ex.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -428,7 +439,8 @@ fn workspace_metadata() {
                         "kind": [ "lib" ],
                         "crate_types": [ "lib" ],
                         "name": "bar",
-                        "src_path": "[..]bar[/]src[/]lib.rs"
+                        "src_path": "[..]bar[/]src[/]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -448,7 +460,8 @@ fn workspace_metadata() {
                         "kind": [ "lib" ],
                         "crate_types": [ "lib" ],
                         "name": "baz",
-                        "src_path": "[..]baz[/]src[/]lib.rs"
+                        "src_path": "[..]baz[/]src[/]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -503,7 +516,8 @@ fn workspace_metadata_no_deps() {
                         "kind": [ "lib" ],
                         "crate_types": [ "lib" ],
                         "name": "bar",
-                        "src_path": "[..]bar[/]src[/]lib.rs"
+                        "src_path": "[..]bar[/]src[/]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -523,7 +537,8 @@ fn workspace_metadata_no_deps() {
                         "kind": [ "lib" ],
                         "crate_types": ["lib"],
                         "name": "baz",
-                        "src_path": "[..]baz[/]src[/]lib.rs"
+                        "src_path": "[..]baz[/]src[/]lib.rs",
+                        "edition": "2015"
                     }
                 ],
                 "features": {},
@@ -566,7 +581,8 @@ const MANIFEST_OUTPUT: &'static str=
             "kind":["bin"],
             "crate_types":["bin"],
             "name":"foo",
-            "src_path":"[..][/]foo[/]src[/]foo.rs"
+            "src_path":"[..][/]foo[/]src[/]foo.rs",
+            "edition":"2015"
         }],
         "features":{},
         "manifest_path":"[..]Cargo.toml"
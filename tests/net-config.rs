@@ -54,3 +54,28 @@ fn net_retry_git_outputs_warning() {
                 .with_stderr_contains("\
 [WARNING] spurious network error (1 tries remaining): [..]"));
 }
+
+#[test]
+fn net_retry_git_cli() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies.bar]
+            git = "https://127.0.0.1:11/foo/bar"
+        "#)
+        .file(".cargo/config", r#"
+        [net]
+        retry=1
+        git-fetch-with-cli = true
+         "#)
+        .file("src/main.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(101)
+                .with_stderr_contains("[WARNING] spurious network error \
+(1 tries remaining): process didn't exit successfully: `git fetch [..]`[..]"));
+}
@@ -0,0 +1,150 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn inherits_release() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.release]
+            opt-level = 2
+
+            [profile.release-lto]
+            inherits = "release"
+            lto = true
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build").arg("-v").arg("--profile").arg("release-lto"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name foo src[/]main.rs [..]-C opt-level=2 -C lto [..]`")
+                       .with_stderr_contains("\
+[FINISHED] release-lto [optimized] target(s) in [..]"));
+    assert!(p.root().join("target/release-lto").is_dir());
+    assert!(!p.root().join("target/release").exists());
+}
+
+#[test]
+fn inherits_custom_profile() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.ci]
+            inherits = "dev"
+            opt-level = 1
+
+            [profile.ci-nodebug]
+            inherits = "ci"
+            debug = false
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v").arg("--profile").arg("ci-nodebug"),
+                execs().with_status(0).with_stderr(&format!("\
+[COMPILING] foo v0.0.1 ({url})
+[RUNNING] `rustc --crate-name foo src[/]lib.rs --crate-type lib \
+        --emit=dep-info,link \
+        -C opt-level=1 \
+        -C debug-assertions=on \
+        -C metadata=[..] \
+        --out-dir [..] \
+        -L dependency={dir}[/]target[/]ci-nodebug[/]deps`
+[FINISHED] ci-nodebug [optimized] target(s) in [..]
+",
+dir = p.root().display(),
+url = p.url(),
+)));
+}
+
+#[test]
+fn builtin_profiles_by_name() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("--profile").arg("release"),
+                execs().with_status(0).with_stderr("\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] release [optimized] target(s) in [..]
+"));
+    assert_that(p.cargo("build").arg("--profile").arg("release").arg("--release"),
+                execs().with_status(101).with_stderr("\
+[ERROR] conflicting usage of --profile and --release
+"));
+}
+
+#[test]
+fn undefined_profile() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("--profile").arg("ci"),
+                execs().with_status(101).with_stderr("\
+[ERROR] profile `ci` is not defined
+"));
+}
+
+#[test]
+fn missing_inherits() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.ci]
+            opt-level = 1
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  profile `ci` is missing an `inherits` directive (`inherits` is required for \
+all profiles except the built-in ones)"));
+}
+
+#[test]
+fn inherits_loop() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.a]
+            inherits = "b"
+
+            [profile.b]
+            inherits = "a"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  profile inheritance loop detected with profile `a`"));
+}
@@ -0,0 +1,203 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::registry::Package;
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn override_one_package() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("baz", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+            baz = "0.1.0"
+
+            [profile.dev.package.bar]
+            opt-level = 3
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name bar [..] -C opt-level=3 [..]`")
+                       .with_stderr_does_not_contain("\
+[RUNNING] `rustc --crate-name baz [..] -C opt-level=3 [..]`")
+                       .with_stderr_does_not_contain("\
+[RUNNING] `rustc --crate-name foo [..] -C opt-level=3 [..]`"));
+}
+
+#[test]
+fn override_all_dependencies() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+
+            [profile.dev.package."*"]
+            opt-level = 2
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name bar [..] -C opt-level=2 [..]`")
+                       .with_stderr_does_not_contain("\
+[RUNNING] `rustc --crate-name foo [..] -C opt-level=2 [..]`"));
+}
+
+#[test]
+fn changing_override_rebuilds() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+
+    p.change_file("Cargo.toml", r#"
+        [package]
+        name = "foo"
+        version = "0.0.1"
+        authors = []
+
+        [dependencies]
+        bar = "0.1.0"
+
+        [profile.dev.package.bar]
+        opt-level = 1
+    "#);
+
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] bar v0.1.0"));
+}
+
+#[test]
+fn override_build_script() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            build = "build.rs"
+
+            [profile.dev.build-override]
+            opt-level = 3
+        "#)
+        .file("build.rs", "fn main() {}")
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name build_script_build build.rs [..] -C opt-level=3 [..]`")
+                       .with_stderr_does_not_contain("\
+[RUNNING] `rustc --crate-name foo src[/]lib.rs [..] -C opt-level=3 [..]`"));
+}
+
+#[test]
+fn override_build_script_of_overridden_package() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [profile.dev.build-override]
+            opt-level = 3
+
+            [profile.dev.package.bar]
+            codegen-units = 7
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+            build = "build.rs"
+        "#)
+        .file("bar/build.rs", "fn main() {}")
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build").arg("-v"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[RUNNING] `rustc --crate-name build_script_build bar[/]build.rs [..] \
+-C opt-level=3 [..]-C codegen-units=7 [..]`")
+                       .with_stderr_does_not_contain("\
+[RUNNING] `rustc --crate-name bar bar[/]src[/]lib.rs [..] -C opt-level=3 [..]`"));
+}
+
+#[test]
+fn unused_spec_warns() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.dev.package.bar]
+            opt-level = 3
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0).with_stderr("\
+[WARNING] profile package spec `bar` did not match any packages
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+"));
+}
+
+#[test]
+fn override_rejects_panic() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.dev.package.bar]
+            panic = "abort"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+[..]`panic` may not be specified in a profile override (`package.bar`)"));
+}
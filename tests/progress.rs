@@ -0,0 +1,101 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn bad_progress_config() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file(".cargo/config", r#"
+            [term.progress]
+            when = "sometimes"
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101)
+                       .with_stderr("\
+[ERROR] invalid value for `term.progress.when` in [..]config: expected \
+`auto`, `always` or `never`, but found `sometimes`
+"));
+}
+
+#[test]
+fn progress_always() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            build = "build.rs"
+        "#)
+        .file("build.rs", r#"
+            use std::thread;
+            use std::time::Duration;
+            fn main() { thread::sleep(Duration::from_millis(1000)); }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build")
+                 .env("CARGO_TERM_PROGRESS_WHEN", "always")
+                 .env("CARGO_TERM_PROGRESS_WIDTH", "100"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[..]Building [..]] 2/3: foo[..]"));
+}
+
+#[test]
+fn progress_never() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build")
+                 .env("CARGO_TERM_PROGRESS_WHEN", "never"),
+                execs().with_status(0)
+                       .with_stderr("\
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+"));
+}
+
+#[test]
+fn progress_with_warnings() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            build = "build.rs"
+        "#)
+        .file("build.rs", r#"
+            use std::thread;
+            use std::time::Duration;
+            fn main() { thread::sleep(Duration::from_millis(1000)); }
+        "#)
+        .file("src/lib.rs", "pub fn foo() { let unused = 1; }");
+
+    // Compiler diagnostics are printed by Cargo itself, after erasing the bar
+    // by overwriting it with as many spaces as it's wide.
+    assert_that(p.cargo_process("build")
+                 .env("CARGO_TERM_PROGRESS_WHEN", "always")
+                 .env("CARGO_TERM_PROGRESS_WIDTH", "100"),
+                execs().with_status(0)
+                       .with_stderr_contains(&format!("\
+[..]Building [..]] 2/3: foo[..]{}warning: unused variable: `unused`",
+                                                      " ".repeat(100))));
+}
@@ -18,7 +18,8 @@ static MANIFEST_OUTPUT: &'static str = r#"
         "kind":["bin"],
         "crate_types":["bin"],
         "name":"foo",
-        "src_path":"[..][/]foo[/]src[/]foo.rs"
+        "src_path":"[..][/]foo[/]src[/]foo.rs",
+        "edition":"2015"
     }],
     "features":{},
     "manifest_path":"[..]Cargo.toml"
@@ -1408,3 +1408,146 @@ fn vv_prints_warnings() {
     assert_that(p.cargo("build").arg("-vv"),
                 execs().with_status(0));
 }
+
+#[test]
+fn download_several_at_once() {
+    for name in ["a", "b", "c", "d", "e"].iter() {
+        Package::new(name, "0.1.0").publish();
+    }
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+            b = "0.1"
+            c = "0.1"
+            d = "0.1"
+            e = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", r#"
+            [http]
+            multiplexing = false
+            max-concurrency = 2
+        "#);
+    p.build();
+
+    assert_that(p.cargo("fetch"),
+                execs().with_status(0)
+                       .with_stderr_contains("[DOWNLOADING] a v0.1.0 [..]")
+                       .with_stderr_contains("[DOWNLOADING] b v0.1.0 [..]")
+                       .with_stderr_contains("[DOWNLOADING] c v0.1.0 [..]")
+                       .with_stderr_contains("[DOWNLOADING] d v0.1.0 [..]")
+                       .with_stderr_contains("[DOWNLOADING] e v0.1.0 [..]"));
+
+    // Everything was downloaded, so nothing is downloaded when building.
+    assert_that(p.cargo("build").arg("--frozen"),
+                execs().with_status(0)
+                       .with_stderr_does_not_contain("[DOWNLOADING] [..]"));
+}
+
+#[test]
+fn build_downloads_whole_graph_first() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("bar", "0.1.0").dep("baz", "0.1").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    // Dependencies of dependencies are downloaded along with everything else
+    // before anything is compiled.
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr(&format!("\
+[UPDATING] registry `{reg}`
+[DOWNLOADING] [..] v0.1.0 (registry file://[..])
+[DOWNLOADING] [..] v0.1.0 (registry file://[..])
+[COMPILING] baz v0.1.0
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.5.0 ({dir})
+[FINISHED] [..]
+", reg = registry::registry(), dir = p.url())));
+}
+
+#[test]
+fn build_skips_downloads_for_other_platforms() {
+    Package::new("baz", "0.1.0").publish();
+    Package::new("dev", "0.1.0").publish();
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+
+            [target.'cfg(target_os = "nonexistent")'.dependencies]
+            baz = "0.1"
+
+            [dev-dependencies]
+            dev = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    // Neither the dependency for another platform nor the dev-dependency is
+    // needed to build the binary.
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr(&format!("\
+[UPDATING] registry `{reg}`
+[DOWNLOADING] bar v0.1.0 (registry file://[..])
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.5.0 ({dir})
+[FINISHED] [..]
+", reg = registry::registry(), dir = p.url())));
+
+    assert_that(p.cargo("test").arg("--no-run"),
+                execs().with_status(0)
+                       .with_stderr_contains("[DOWNLOADING] dev v0.1.0 [..]")
+                       .with_stderr_does_not_contain("[DOWNLOADING] baz [..]"));
+}
+
+#[test]
+fn bad_max_concurrency() {
+    Package::new("a", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            a = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", r#"
+            [http]
+            max-concurrency = 0
+        "#);
+    p.build();
+
+    assert_that(p.cargo("fetch"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+[..]http.max-concurrency must be at least 1, but found 0 in [..]"));
+}
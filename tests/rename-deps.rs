@@ -0,0 +1,306 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::git;
+use cargotest::support::registry::Package;
+use cargotest::support::{execs, project};
+use hamcrest::assert_that;
+
+#[test]
+fn rename_dependency() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { version = "0.1.0" }
+            baz = { version = "0.2.0", package = "bar" }
+        "#)
+        .file("src/lib.rs", "
+            extern crate bar;
+            extern crate baz;
+        ");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn rename_with_different_names() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { path = "bar", package = "bar" }
+        "#)
+        .file("src/lib.rs", "
+            extern crate baz;
+        ")
+        .file("bar/Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+
+            [lib]
+            name = "random_name"
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn rename_git_and_registry() {
+    Package::new("bar", "0.1.0").publish();
+
+    let git_project = git::new("bar", |project| {
+        project.file("Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("src/lib.rs", "pub fn bar() {}")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+            bar-git = {{ git = '{}', package = "bar" }}
+        "#, git_project.url()))
+        .file("src/lib.rs", "
+            extern crate bar;
+            extern crate bar_git;
+
+            pub fn foo() {
+                bar_git::bar();
+            }
+        ");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn rename_git_and_registry_are_not_mixed_up() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn registry() {}")
+        .publish();
+
+    let git_project = git::new("bar", |project| {
+        project.file("Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("src/lib.rs", "pub fn git() {}")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar-git = {{ git = '{}', package = "bar" }}
+            bar = "0.1.0"
+        "#, git_project.url()))
+        .file("src/lib.rs", "
+            extern crate bar;
+            extern crate bar_git;
+
+            pub fn foo() {
+                bar::registry();
+                bar_git::git();
+            }
+        ");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn rename_same_package_twice() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", package = "bar" }
+            qux = { version = "0.1.0", package = "bar" }
+        "#)
+        .file("src/lib.rs", "
+            extern crate baz;
+            extern crate qux;
+
+            pub fn foo() {
+                baz::bar();
+                qux::bar();
+            }
+        ");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn rename_optional_dependency() {
+    Package::new("bar", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", package = "bar", optional = true }
+        "#)
+        .file("src/lib.rs", r#"
+            #[cfg(feature = "baz")]
+            extern crate baz;
+
+            #[cfg(not(feature = "baz"))]
+            pub fn foo() {}
+        "#);
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+    assert_that(p.cargo("build").arg("--features").arg("baz"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] bar v0.1.0"));
+}
+
+#[test]
+fn rename_affects_metadata() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { path = "bar", package = "bar" }
+        "#)
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("metadata").arg("--no-deps"),
+                execs().with_status(0)
+                       .with_stdout_contains("[..]\"rename\":\"baz\"[..]"));
+}
+
+#[test]
+fn rename_patched_dependency() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "pub fn registry() {}")
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            baz = { version = "0.1.0", package = "bar" }
+
+            [patch.crates-io]
+            bar = { path = "bar" }
+        "#)
+        .file("src/lib.rs", "
+            extern crate baz;
+
+            pub fn foo() {
+                baz::patched();
+            }
+        ")
+        .file("bar/Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "pub fn patched() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
+
+#[test]
+fn same_name_for_other_platform_is_not_built() {
+    Package::new("bar", "0.1.0")
+        .file("src/lib.rs", "this does not compile")
+        .publish();
+
+    let git_project = git::new("bar", |project| {
+        project.file("Cargo.toml", r#"
+            [project]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("src/lib.rs", "pub fn git() {}")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar-git = {{ git = '{}', package = "bar" }}
+
+            [target.'cfg(target_os = "nonexistent")'.dependencies]
+            bar = "0.1.0"
+        "#, git_project.url()))
+        .file("src/lib.rs", "
+            extern crate bar_git;
+
+            pub fn foo() {
+                bar_git::git();
+            }
+        ");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0));
+}
@@ -0,0 +1,139 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use cargotest::support::registry::Package;
+use cargotest::support::{project, execs};
+use hamcrest::assert_that;
+
+#[test]
+fn rust_version_satisfied() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.0"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+}
+
+#[test]
+fn rust_version_too_new() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "99999.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] package `foo v0.0.1` cannot be built because it requires rustc \
+99999.1.0 or newer, while the currently active rustc version is [..]
+"));
+}
+
+#[test]
+fn rust_version_bad_format() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.x"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr("\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `1.x` is not a valid rust-version, expected a version like \"1.56\" or \"1.56.1\"
+"));
+}
+
+#[test]
+fn dependency_rust_version_too_new() {
+    Package::new("bar", "0.1.0")
+        .rust_version("99999.0")
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] package `bar v0.1.0` cannot be built because it requires rustc \
+99999.0.0 or newer, while the currently active rustc version is [..]
+"));
+}
+
+#[test]
+fn update_prefers_compatible_versions() {
+    Package::new("bar", "0.1.0").rust_version("1.0").publish();
+    Package::new("bar", "0.1.1").rust_version("99999.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("generate-lockfile"), execs().with_status(0));
+    assert_that(p.cargo("update").arg("--rust-version-compatible"),
+                execs().with_status(0)
+                       .with_stderr_contains("[UPDATING] bar v0.1.1 -> v0.1.0"));
+    assert_that(p.cargo("build"), execs().with_status(0));
+
+    // Without the flag the newest version is picked again.
+    assert_that(p.cargo("update"),
+                execs().with_status(0)
+                       .with_stderr_contains("[UPDATING] bar v0.1.0 -> v0.1.1"));
+}
+
+#[test]
+fn update_uses_workspace_rust_version() {
+    Package::new("bar", "0.1.0").rust_version("1.5").publish();
+    Package::new("bar", "0.1.1").rust_version("1.10").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+            rust-version = "1.6"
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}");
+
+    assert_that(p.cargo_process("update").arg("--rust-version-compatible"),
+                execs().with_status(0));
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"bar\"\nversion = \"0.1.0\""));
+}
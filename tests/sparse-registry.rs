@@ -0,0 +1,207 @@
+extern crate bufstream;
+#[macro_use]
+extern crate cargotest;
+extern crate hamcrest;
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use bufstream::BufStream;
+use cargotest::support::{project, execs, ProjectBuilder};
+use cargotest::support::registry::{Package, registry_path, cksum};
+use hamcrest::assert_that;
+
+/// A minimal HTTP server standing in for a sparse registry, serving the files
+/// of the test registry's index and logging each request as `<status> <path>`.
+struct Server {
+    port: u16,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Server {
+    fn new() -> Server {
+        // Make sure the test registry exists before serving it.
+        Package::new("init", "0.0.1");
+        let root = registry_path();
+        let listener = t!(TcpListener::bind("127.0.0.1:0"));
+        let port = t!(listener.local_addr()).port();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log2 = log.clone();
+        thread::spawn(move || {
+            for conn in listener.incoming() {
+                let mut conn = BufStream::new(t!(conn));
+                handle(&mut conn, &root, &log2);
+            }
+        });
+        Server { port: port, log: log }
+    }
+
+    fn url(&self) -> String {
+        format!("sparse+http://127.0.0.1:{}/", self.port)
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.log.lock().unwrap().clone()
+    }
+
+    fn project(&self, deps: &str) -> ProjectBuilder {
+        project("foo")
+            .file("Cargo.toml", &format!(r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies]
+                {}
+            "#, deps))
+            .file("src/lib.rs", "")
+            .file(".cargo/config", &format!(r#"
+                [source.crates-io]
+                replace-with = 'sparse-registry'
+
+                [source.sparse-registry]
+                registry = '{}'
+            "#, self.url()))
+    }
+}
+
+fn handle(conn: &mut BufStream<::std::net::TcpStream>,
+          root: &PathBuf,
+          log: &Mutex<Vec<String>>) {
+    let mut path = String::new();
+    let mut if_none_match = None;
+    loop {
+        let mut line = String::new();
+        t!(conn.read_line(&mut line));
+        let line = line.trim();
+        if line.is_empty() {
+            break
+        }
+        if line.starts_with("GET ") {
+            path = line.split(' ').nth(1).unwrap().to_string();
+        } else if line.to_lowercase().starts_with("if-none-match:") {
+            if_none_match = Some(line[14..].trim().to_string());
+        }
+    }
+
+    let mut contents = Vec::new();
+    let found = File::open(root.join(&path[1..]))
+        .and_then(|mut f| f.read_to_end(&mut contents))
+        .is_ok();
+    let etag = format!("\"{}\"", cksum(&contents));
+    let status = if !found {
+        "404 Not Found"
+    } else if if_none_match.as_ref() == Some(&etag) {
+        "304 Not Modified"
+    } else {
+        "200 OK"
+    };
+    log.lock().unwrap().push(format!("{} {}", &status[..3], path));
+
+    let body = if status.starts_with("200") { &contents[..] } else { &[][..] };
+    t!(write!(conn, "HTTP/1.1 {}\r\n\
+                     ETag: {}\r\n\
+                     Content-Length: {}\r\n\
+                     Connection: close\r\n\
+                     \r\n", status, etag, body.len()));
+    t!(conn.write_all(body));
+    t!(conn.flush());
+}
+
+#[test]
+fn simple() {
+    let server = Server::new();
+    Package::new("bar", "0.1.0").publish();
+    Package::new("unused", "0.1.0").publish();
+
+    let p = server.project("bar = \"0.1\"");
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0)
+                       .with_stderr(&format!("\
+[UPDATING] registry `{}`
+[DOWNLOADING] bar v0.1.0 ([..])
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+", server.url())));
+
+    // Only the index files of crates which are actually needed are fetched.
+    let requests = server.requests();
+    assert!(requests.contains(&"200 /config.json".to_string()));
+    assert!(requests.contains(&"200 /3/b/bar".to_string()));
+    assert!(!requests.iter().any(|r| r.contains("unused")));
+}
+
+#[test]
+fn lockfile_uses_cache() {
+    let server = Server::new();
+    Package::new("bar", "0.1.0").publish();
+
+    let p = server.project("bar = \"0.1\"");
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+    let before = server.requests().len();
+
+    // With a lock file, the cached index is used without any requests.
+    assert_that(p.cargo("clean"), execs().with_status(0));
+    assert_that(p.cargo("build").arg("--frozen"),
+                execs().with_status(0)
+                       .with_stderr("\
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.0.1 ([..])
+[FINISHED] [..]
+"));
+    assert_eq!(server.requests().len(), before);
+}
+
+#[test]
+fn frozen_does_not_fetch() {
+    let server = Server::new();
+    Package::new("bar", "0.1.0").publish();
+
+    let p = server.project("bar = \"0.1\"");
+    assert_that(p.cargo_process("build").arg("--frozen"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+  attempting to update a registry index, but --frozen was specified"));
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn update_revalidates() {
+    let server = Server::new();
+    Package::new("bar", "0.1.0").publish();
+
+    let p = server.project("bar = \"0.1\"");
+    assert_that(p.cargo_process("generate-lockfile"), execs().with_status(0));
+
+    // An unchanged index file is revalidated with its `ETag`.
+    assert_that(p.cargo("update"), execs().with_status(0));
+    assert_eq!(server.requests().iter().filter(|r| *r == "304 /3/b/bar").count(), 1);
+
+    // A new version is picked up once the file changed.
+    Package::new("bar", "0.1.1").publish();
+    assert_that(p.cargo("update"),
+                execs().with_status(0)
+                       .with_stderr(&format!("\
+[UPDATING] registry `{}`
+[UPDATING] bar v0.1.0 -> v0.1.1
+", server.url())));
+    assert_eq!(server.requests().iter().filter(|r| *r == "200 /3/b/bar").count(), 2);
+}
+
+#[test]
+fn not_found() {
+    let server = Server::new();
+
+    let p = server.project("baz = \"0.1\"");
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101)
+                       .with_stderr_contains("\
+[..]no matching package named `baz` found[..]"));
+    assert!(server.requests().contains(&"404 /3/b/baz".to_string()));
+}
@@ -0,0 +1,106 @@
+extern crate cargotest;
+extern crate hamcrest;
+
+use std::fs::File;
+use std::io::Read;
+
+use cargotest::support::{project, execs};
+use hamcrest::{assert_that, existing_file};
+
+#[test]
+fn timings_report() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = { path = "bar" }
+        "#)
+        .file("src/main.rs", r#"
+            extern crate bar;
+            fn main() { bar::bar(); }
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "pub fn bar() {}");
+
+    assert_that(p.cargo_process("build").arg("--timings"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[..]Timing report saved to [..]cargo-timing-[..].html"));
+
+    let report = p.root().join("target/cargo-timings/cargo-timing.html");
+    assert_that(&report, existing_file());
+    let mut html = String::new();
+    File::open(&report).unwrap().read_to_string(&mut html).unwrap();
+    assert!(html.contains("Cargo Build Timings"));
+    assert!(html.contains("foo v0.0.1"));
+    assert!(html.contains("bar v0.0.1"));
+}
+
+#[test]
+fn timings_json() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build")
+                 .arg("--timings").arg("--message-format").arg("json"),
+                execs().with_status(0)
+                       .with_stdout_contains("[..]\"reason\":\"timing-info\"[..]"));
+}
+
+#[test]
+fn no_report_without_flag() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"), execs().with_status(0));
+    assert!(!p.root().join("target/cargo-timings").exists());
+}
+
+#[test]
+fn timings_record_metadata_of_full_builds() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+        "#)
+        .file("src/lib.rs", "pub fn foo() { let unused = 1; }");
+
+    // Diagnostics are still printed the way rustc renders them.
+    assert_that(p.cargo_process("build").arg("--timings"),
+                execs().with_status(0)
+                       .with_stderr_contains("[..]unused variable: `unused`[..]"));
+
+    let report = p.root().join("target/cargo-timings/cargo-timing.html");
+    let mut html = String::new();
+    File::open(&report).unwrap().read_to_string(&mut html).unwrap();
+    assert!(html.contains("class=\"rmeta\"/>"));
+
+    assert_that(p.cargo("build").arg("--release")
+                 .arg("--timings").arg("--message-format").arg("json"),
+                execs().with_status(0)
+                       .with_stdout_contains("[..]\"reason\":\"timing-info\"[..]")
+                       .with_stdout_does_not_contain("[..]\"rmeta_time\":null[..]"));
+}
@@ -169,3 +169,102 @@ fn custom_runner() {
 [RUNNING] `nonexistent-runner -r [..][/]target[/]release[/]deps[/]bench-[..][EXE] --param --bench`
 ", url = foo.url())));
 }
+
+#[test]
+fn cfg_runner() {
+    let foo = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file("tests/test.rs", "")
+        .file(".cargo/config", r#"
+            [target.'cfg(not(target_os = "none"))']
+            runner = "nonexistent-runner -r"
+        "#);
+
+    foo.build();
+
+    assert_that(foo.cargo("run").args(&["--", "--param"]),
+                execs().with_stderr_contains("\
+[RUNNING] `nonexistent-runner -r target[/]debug[/]foo[EXE] --param`
+"));
+
+    assert_that(foo.cargo("test").args(&["--test", "test", "--verbose", "--", "--param"]),
+                execs().with_stderr_contains("\
+[RUNNING] `nonexistent-runner -r [..][/]target[/]debug[/]deps[/]test-[..][EXE] --param`
+"));
+}
+
+#[test]
+fn cfg_runner_conflict() {
+    let foo = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", r#"
+            [target.'cfg(not(target_os = "none"))']
+            runner = "runner-a"
+
+            [target.'cfg(not(target_os = "nothing"))']
+            runner = "runner-b"
+        "#);
+
+    assert_that(foo.cargo_process("run"),
+                execs().with_status(101).with_stderr_contains("\
+[ERROR] several matching instances of `target.'cfg(..)'.runner` in configurations
+"));
+}
+
+#[test]
+fn triple_runner_beats_cfg_runner() {
+    let target = rustc_host();
+
+    let foo = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", &format!(r#"
+            [target.{}]
+            runner = "triple-runner"
+
+            [target.'cfg(not(target_os = "none"))']
+            runner = "cfg-runner"
+        "#, target));
+
+    assert_that(foo.cargo_process("run"),
+                execs().with_stderr_contains("\
+[RUNNING] `triple-runner target[/]debug[/]foo[EXE]`
+"));
+}
+
+#[test]
+fn runner_in_json_messages() {
+    let target = rustc_host();
+
+    let foo = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+        "#)
+        .file("src/main.rs", "fn main() {}")
+        .file(".cargo/config", &format!(r#"
+            [target.{}]
+            runner = "nonexistent-runner -r"
+        "#, target));
+
+    assert_that(foo.cargo_process("run").args(&["--message-format", "json", "--", "--param"]),
+                execs().with_stdout_contains("\
+{\"args\":[\"-r\",\"[..]foo[EXE]\",\"--param\"],\"package_id\":\"foo 0.0.1 ([..])\",\
+\"program\":\"nonexistent-runner\",\"reason\":\"run-target\"}
+"));
+}
@@ -0,0 +1,257 @@
+#[macro_use]
+extern crate cargotest;
+extern crate hamcrest;
+
+use std::fs::{self, File};
+use std::io::prelude::*;
+
+use cargotest::support::{project, execs, git};
+use cargotest::support::registry::Package;
+use hamcrest::{assert_that, existing_file, existing_dir, is_not};
+
+fn vendored_config(p: &cargotest::support::ProjectBuilder) {
+    t!(fs::create_dir_all(p.root().join(".cargo")));
+    t!(t!(File::create(p.root().join(".cargo/config"))).write_all(br#"
+        [source.crates-io]
+        replace-with = 'vendored-sources'
+
+        [source.vendored-sources]
+        directory = 'vendor'
+    "#));
+}
+
+#[test]
+fn vendor_simple() {
+    Package::new("baz", "0.1.0").file("src/lib.rs", "pub fn baz() {}").publish();
+    Package::new("bar", "0.1.0")
+        .dep("baz", "0.1")
+        .file("src/lib.rs", "extern crate baz; pub fn bar() { baz::baz() }")
+        .publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/lib.rs", "extern crate bar; pub fn foo() { bar::bar() }");
+
+    assert_that(p.cargo_process("vendor").arg("--respect-source-config"),
+                execs().with_status(0)
+                       .with_stdout("\
+[source.crates-io]
+replace-with = \"vendored-sources\"
+
+[source.vendored-sources]
+directory = \"vendor\"
+")
+                       .with_stderr_contains("\
+[VENDORING] bar v0.1.0 to vendor[/]bar")
+                       .with_stderr_contains("\
+[VENDORING] baz v0.1.0 to vendor[/]baz"));
+
+    assert_that(&p.root().join("vendor/bar/src/lib.rs"), existing_file());
+    assert_that(&p.root().join("vendor/bar/.cargo-checksum.json"), existing_file());
+    assert_that(&p.root().join("vendor/baz/Cargo.toml"), existing_file());
+    assert_that(&p.root().join("vendor/bar/.cargo-ok"), is_not(existing_file()));
+
+    vendored_config(&p);
+    assert_that(p.cargo("build").arg("--frozen"),
+                execs().with_status(0)
+                       .with_stderr("\
+[COMPILING] baz v0.1.0
+[COMPILING] bar v0.1.0
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] [..]
+"));
+}
+
+#[test]
+fn two_versions() {
+    Package::new("bar", "0.7.0").publish();
+    Package::new("bar", "0.8.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.8"
+            baz = { path = "baz" }
+        "#)
+        .file("src/lib.rs", "")
+        .file("baz/Cargo.toml", r#"
+            [package]
+            name = "baz"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.7"
+        "#)
+        .file("baz/src/lib.rs", "");
+
+    assert_that(p.cargo_process("vendor").arg("--respect-source-config").arg("--versioned-dirs"),
+                execs().with_status(0));
+    assert_that(&p.root().join("vendor/bar-0.7.0"), existing_dir());
+    assert_that(&p.root().join("vendor/bar-0.8.0"), existing_dir());
+
+    t!(fs::remove_dir_all(p.root().join("vendor")));
+    assert_that(p.cargo("vendor").arg("--respect-source-config"), execs().with_status(0));
+    assert_that(&p.root().join("vendor/bar-0.7.0"), existing_dir());
+    assert_that(&p.root().join("vendor/bar"), existing_dir());
+    assert_that(&p.root().join("vendor/baz"), is_not(existing_dir()));
+
+    vendored_config(&p);
+    assert_that(p.cargo("build").arg("--frozen"), execs().with_status(0));
+}
+
+#[test]
+fn delete_old_crates() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("old", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/lib.rs", "")
+        .file("vendor/other/README", "not a vendored crate");
+    p.build();
+
+    let old = project("old")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "old"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            old = "0.1"
+        "#)
+        .file("src/lib.rs", "");
+    old.build();
+
+    assert_that(p.cargo("vendor").arg("--respect-source-config").arg("--sync").arg(old.root().join("Cargo.toml")),
+                execs().with_status(0));
+    assert_that(&p.root().join("vendor/old"), existing_dir());
+
+    assert_that(p.cargo("vendor").arg("--respect-source-config").arg("--no-delete"),
+                execs().with_status(0));
+    assert_that(&p.root().join("vendor/old"), existing_dir());
+
+    assert_that(p.cargo("vendor").arg("--respect-source-config"), execs().with_status(0));
+    assert_that(&p.root().join("vendor/old"), is_not(existing_dir()));
+    assert_that(&p.root().join("vendor/bar"), existing_dir());
+    assert_that(&p.root().join("vendor/other/README"), existing_file());
+}
+
+#[test]
+fn git_dependency() {
+    let git = git::new("dep", |project| {
+        project
+            .file("Cargo.toml", r#"
+                [package]
+                name = "dep"
+                version = "0.5.0"
+                authors = []
+            "#)
+            .file("src/lib.rs", "pub fn dep() {}")
+            .file(".gitignore", "target")
+    }).unwrap();
+
+    let p = project("foo")
+        .file("Cargo.toml", &format!(r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = {{ git = '{}' }}
+        "#, git.url()))
+        .file("src/lib.rs", "extern crate dep; pub fn foo() { dep::dep() }");
+
+    assert_that(p.cargo_process("vendor"),
+                execs().with_status(0)
+                       .with_stdout(&format!("\
+[source.\"{0}\"]
+git = \"{0}\"
+replace-with = \"vendored-sources\"
+
+[source.vendored-sources]
+directory = \"vendor\"
+", git.url())));
+    assert_that(&p.root().join("vendor/dep/src/lib.rs"), existing_file());
+    assert_that(&p.root().join("vendor/dep/.gitignore"), is_not(existing_file()));
+
+    let mut checksum = String::new();
+    t!(t!(File::open(p.root().join("vendor/dep/.cargo-checksum.json")))
+        .read_to_string(&mut checksum));
+    assert!(checksum.contains("\"package\":null"));
+
+    t!(fs::create_dir_all(p.root().join(".cargo")));
+    t!(t!(File::create(p.root().join(".cargo/config"))).write_all(format!(r#"
+        [source."{0}"]
+        git = '{0}'
+        replace-with = 'vendored-sources'
+
+        [source.vendored-sources]
+        directory = 'vendor'
+    "#, git.url()).as_bytes()));
+    t!(fs::remove_dir_all(git.root()));
+
+    assert_that(p.cargo("build"),
+                execs().with_status(0)
+                       .with_stderr("\
+[COMPILING] dep v0.5.0 ([..])
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] [..]
+"));
+}
+
+#[test]
+fn revendor_with_vendored_sources_active() {
+    Package::new("bar", "0.1.0").file("src/lib.rs", "pub fn bar() {}").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1"
+        "#)
+        .file("src/lib.rs", "extern crate bar; pub fn foo() { bar::bar() }");
+
+    assert_that(p.cargo_process("vendor").arg("--respect-source-config"),
+                execs().with_status(0));
+
+    // With the printed configuration active, `bar` is loaded from the vendor
+    // directory it's vendored into again.
+    vendored_config(&p);
+    assert_that(p.cargo("vendor").arg("--respect-source-config"),
+                execs().with_status(0)
+                       .with_stderr_contains("\
+[VENDORING] bar v0.1.0 to vendor[/]bar"));
+    assert_that(&p.root().join("vendor/bar/src/lib.rs"), existing_file());
+    assert_that(&p.root().join("vendor/bar/.cargo-checksum.json"), existing_file());
+
+    assert_that(p.cargo("build").arg("--frozen"),
+                execs().with_status(0));
+}
@@ -0,0 +1,234 @@
+extern crate cargotest;
+extern crate flate2;
+extern crate hamcrest;
+extern crate tar;
+
+use std::fs::File;
+use std::io::prelude::*;
+
+use cargotest::support::registry::Package;
+use cargotest::support::{project, execs};
+use flate2::read::GzDecoder;
+use hamcrest::{assert_that, equal_to};
+use tar::Archive;
+
+#[test]
+fn inherit_package_metadata() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            authors = ["Rustaceans"]
+            license = "MIT"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = { workspace = true }
+            license = { workspace = true }
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(0).with_stderr("\
+[COMPILING] bar v1.2.3 ([..])
+[FINISHED] [..]
+"));
+}
+
+#[test]
+fn inherit_dependencies() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = { path = "dep", features = ["a"] }
+            baz = { path = "baz" }
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = { workspace = true, features = ["b"] }
+
+            [dev-dependencies]
+            baz = { workspace = true }
+        "#)
+        .file("bar/src/lib.rs", r#"
+            extern crate dep;
+            pub fn bar() { dep::a(); dep::b(); }
+        "#)
+        .file("dep/Cargo.toml", r#"
+            [package]
+            name = "dep"
+            version = "0.1.0"
+            authors = []
+
+            [features]
+            a = []
+            b = []
+        "#)
+        .file("dep/src/lib.rs", r#"
+            #[cfg(feature = "a")] pub fn a() {}
+            #[cfg(feature = "b")] pub fn b() {}
+        "#)
+        .file("baz/Cargo.toml", r#"
+            [package]
+            name = "baz"
+            version = "0.1.0"
+            authors = []
+        "#)
+        .file("baz/src/lib.rs", "");
+
+    assert_that(p.cargo_process("test").arg("--no-run").arg("-p").arg("bar"),
+                execs().with_status(0)
+                       .with_stderr_contains("[COMPILING] dep v0.1.0 ([..])")
+                       .with_stderr_contains("[COMPILING] baz v0.1.0 ([..])"));
+}
+
+#[test]
+fn inherited_dependency_extra_keys() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.dependencies]
+            dep = "0.1"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = { workspace = true, version = "0.2" }
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  dependency `dep` inherits from the workspace, so only `features` and \
+`optional` may be specified"));
+}
+
+#[test]
+fn undefined_inherited_key() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = []
+        "#)
+        .file("bar/src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  `package.version` was inherited from the workspace, but \
+`workspace.package.version` is not defined"));
+}
+
+#[test]
+fn inherit_without_workspace() {
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            dep = { workspace = true }
+        "#)
+        .file("src/lib.rs", "");
+
+    assert_that(p.cargo_process("build"),
+                execs().with_status(101).with_stderr_contains("\
+  failed to find a workspace root to inherit values from with \
+`workspace = true`"));
+}
+
+#[test]
+fn package_expands_inherited_values() {
+    Package::new("dep", "0.1.0").publish();
+
+    let p = project("foo")
+        .file("Cargo.toml", r#"
+            [workspace]
+            members = ["bar"]
+
+            [workspace.package]
+            version = "1.2.3"
+            authors = []
+            license = "MIT"
+            description = "bar"
+
+            [workspace.dependencies]
+            dep = "0.1"
+        "#)
+        .file("bar/Cargo.toml", r#"
+            [package]
+            name = "bar"
+            version = { workspace = true }
+            authors = { workspace = true }
+            license = { workspace = true }
+            description = { workspace = true }
+
+            [dependencies]
+            dep = { workspace = true }
+        "#)
+        .file("bar/src/lib.rs", "");
+    p.build();
+
+    assert_that(p.cargo("package").arg("--no-verify").cwd(p.root().join("bar")),
+                execs().with_status(0));
+
+    let f = File::open(&p.root().join("target/package/bar-1.2.3.crate")).unwrap();
+    let mut rdr = GzDecoder::new(f).unwrap();
+    let mut contents = Vec::new();
+    rdr.read_to_end(&mut contents).unwrap();
+    let mut ar = Archive::new(&contents[..]);
+    let mut entry = ar.entries().unwrap()
+                        .map(|f| f.unwrap())
+                        .find(|e| e.path().unwrap().ends_with("Cargo.toml"))
+                        .unwrap();
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).unwrap();
+    assert_that(&contents[..], equal_to(
+r#"# THIS FILE IS AUTOMATICALLY GENERATED BY CARGO
+#
+# When uploading crates to the registry Cargo will automatically
+# "normalize" Cargo.toml files for maximal compatibility
+# with all versions of Cargo and also rewrite `path` dependencies
+# to registry (e.g. crates.io) dependencies
+#
+# If you believe there's an error in this file please file an
+# issue against the rust-lang/cargo repository. If you're
+# editing this file be aware that the upstream Cargo.toml
+# will likely look very different (and much more reasonable)
+
+[package]
+name = "bar"
+version = "1.2.3"
+authors = []
+description = "bar"
+license = "MIT"
+[dependencies.dep]
+version = "0.1"
+"#));
+}